@@ -226,6 +226,36 @@ async def models_status():
         "pytorch_available": motion_analyzer is not None
     }
 
+@app.get("/device")
+async def device_info():
+    """Report the compute device PyTorch will use"""
+    info = {
+        "device": "cpu",
+        "cuda_available": False,
+        "gpu_name": None,
+        "compute_capability": None,
+        "vram_total_mb": None,
+        "vram_used_mb": None,
+        "cuda_version": None,
+    }
+    try:
+        import torch
+    except ImportError:
+        return info
+
+    if torch.cuda.is_available():
+        props = torch.cuda.get_device_properties(0)
+        info.update({
+            "device": "cuda",
+            "cuda_available": True,
+            "gpu_name": props.name,
+            "compute_capability": f"{props.major}.{props.minor}",
+            "vram_total_mb": props.total_memory // 1024**2,
+            "vram_used_mb": torch.cuda.memory_reserved(0) // 1024**2,
+            "cuda_version": torch.version.cuda,
+        })
+    return info
+
 @app.get("/")
 async def root():
     """Root endpoint with service information"""
@@ -237,7 +267,8 @@ async def root():
             "analyze_frame": "/analyze/frame",
             "analyze_video": "/analyze/video", 
             "analyze_batch": "/analyze/batch",
-            "models_status": "/models/status"
+            "models_status": "/models/status",
+            "device": "/device"
         },
         "documentation": "/docs"
     }
@@ -3,12 +3,13 @@
 use crate::core::{FitnessError, Result};
 use crate::models::{
     optimization::*,
-    food::{Recipe, NutritionFacts, MealType, Food},
+    food::{Recipe, NutritionFacts, MealType, Food, GlycemicLoad},
 };
 use rand::{Rng, SeedableRng};
 use rand::seq::SliceRandom;
 use rand_distr::{Distribution, Normal};
 use rayon::prelude::*;
+use super::types::{CancellationToken, ProgressSender};
 use std::collections::HashMap;
 use std::time::Instant;
 
@@ -16,6 +17,9 @@ pub struct GeneticAlgorithm {
     pub config: AlgorithmConfig,
     pub recipes: Vec<Recipe>,
     pub foods: HashMap<String, Food>,
+    warm_start: Option<Vec<MealGene>>,
+    progress: Option<ProgressSender>,
+    cancel: Option<CancellationToken>,
     rng: rand::rngs::StdRng,
 }
 
@@ -35,10 +39,34 @@ impl GeneticAlgorithm {
             config,
             recipes,
             foods,
+            warm_start: None,
+            progress: None,
+            cancel: None,
             rng,
         }
     }
 
+    /// Seed the initial population from a previous plan's meals. The seed is
+    /// ignored (a cold start) if it doesn't fit the request's meal slots.
+    pub fn with_warm_start(mut self, meals: Vec<MealGene>) -> Self {
+        self.warm_start = Some(meals);
+        self
+    }
+
+    /// Send an `OptimizationProgress` after every generation. Off by default
+    /// so headless runs don't pay for it.
+    pub fn with_progress(mut self, progress: ProgressSender) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Check `cancel` before every generation and stop with the best plan so
+    /// far once it fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
     pub fn optimize(&mut self, request: &OptimizationRequest) -> Result<OptimizationSolution> {
         let start_time = Instant::now();
         
@@ -46,8 +74,13 @@ impl GeneticAlgorithm {
         request.validate()
             .map_err(|e| FitnessError::optimization(format!("Invalid request: {}", e)))?;
 
-        // Initialize population
-        let mut population = self.create_initial_population(request)?;
+        // Initialize population, seeded from the previous plan when it fits
+        let warm_start = self.warm_start.take().filter(|meals| self.fits_request(meals, request));
+        let warm_started = warm_start.is_some();
+        let mut population = match warm_start {
+            Some(meals) => self.create_seeded_population(meals, request)?,
+            None => self.create_initial_population(request)?,
+        };
         
         // Evaluate initial population
         self.evaluate_population(&mut population, request)?;
@@ -55,28 +88,35 @@ impl GeneticAlgorithm {
         let mut best_fitness_history = Vec::new();
         let mut convergence_generation = None;
         let mut generations_run = 0;
+        let mut cancelled = false;
 
         // Evolution loop
         for generation in 0..self.config.max_generations {
-            generations_run = generation + 1;
-
             // Check runtime limit
             if start_time.elapsed().as_secs() > self.config.max_runtime_seconds {
                 break;
             }
+            if self.cancel.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                cancelled = true;
+                break;
+            }
+            generations_run = generation + 1;
 
             // Track best fitness
             let best_fitness = population.iter()
                 .map(|ind| ind.get_fitness())
                 .fold(f64::NEG_INFINITY, f64::max);
             best_fitness_history.push(best_fitness);
+            self.report_progress(generations_run, best_fitness, start_time);
 
-            // Check convergence
-            if best_fitness_history.len() >= 50 {
-                let recent_improvement = best_fitness_history.iter().rev().take(50)
-                    .fold(0.0, |acc, &f| (f - best_fitness_history[best_fitness_history.len() - 51]).max(acc));
-                
-                if recent_improvement < self.config.convergence_threshold {
+            // Check convergence: stop once the best fitness hasn't improved by more
+            // than the threshold over the configured window of generations
+            let window = self.config.convergence_generations;
+            if window > 0 && best_fitness_history.len() > window {
+                let baseline = best_fitness_history[best_fitness_history.len() - 1 - window];
+                let recent_improvement = best_fitness - baseline;
+
+                if recent_improvement <= self.config.convergence_threshold {
                     convergence_generation = Some(generation);
                     break;
                 }
@@ -100,12 +140,14 @@ impl GeneticAlgorithm {
             }
         }
 
+        let diversity_score = Self::population_diversity(&population);
+
         // Create solution from best individual
         let best_individual = population.into_iter()
             .max_by(|a, b| a.get_fitness().partial_cmp(&b.get_fitness()).unwrap())
             .ok_or_else(|| FitnessError::optimization("No valid solution found"))?;
 
-        self.create_solution(best_individual, AlgorithmMetadata {
+        self.create_solution(best_individual, request, AlgorithmMetadata {
             algorithm_used: AlgorithmType::GeneticAlgorithm,
             generations_run,
             final_population_size: self.config.population_size,
@@ -113,10 +155,27 @@ impl GeneticAlgorithm {
             execution_time_ms: start_time.elapsed().as_millis() as f64,
             evaluations_performed: generations_run * self.config.population_size,
             best_fitness_history,
-            diversity_score: 0.75, // TODO: Calculate actual diversity
+            diversity_score,
+            warm_start: warm_started,
+            cancelled,
         })
     }
 
+    fn report_progress(&self, generation: usize, best_fitness: f64, start_time: Instant) {
+        let Some(progress) = &self.progress else { return };
+
+        let elapsed_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+        let remaining_generations = self.config.max_generations.saturating_sub(generation);
+        // A closed receiver only means nobody is watching any more
+        let _ = progress.send(OptimizationProgress {
+            generation,
+            max_generations: self.config.max_generations,
+            best_fitness,
+            elapsed_ms,
+            estimated_remaining_ms: elapsed_ms / generation as f64 * remaining_generations as f64,
+        });
+    }
+
     fn create_initial_population(&mut self, request: &OptimizationRequest) -> Result<Vec<Individual>> {
         let mut population = Vec::with_capacity(self.config.population_size);
 
@@ -128,31 +187,66 @@ impl GeneticAlgorithm {
         Ok(population)
     }
 
-    fn create_random_individual(&mut self, request: &OptimizationRequest) -> Result<Individual> {
-        let mut genome = Vec::new();
+    /// Warm-started population: the previous plan itself, mutated copies of
+    /// it for half the population, and random plans for the rest so the
+    /// search can still move away from it if the constraints changed a lot
+    fn create_seeded_population(&mut self, meals: Vec<MealGene>, request: &OptimizationRequest) -> Result<Vec<Individual>> {
+        let mut population = Vec::with_capacity(self.config.population_size);
+        let seed = Individual::new(meals);
+        population.push(seed.clone());
 
-        for day in 0..request.time_horizon_days {
-            // Add breakfast
-            for _ in 0..request.constraints.meal_count_per_day.breakfast {
-                genome.push(self.create_random_meal_gene(day, MealType::Breakfast)?);
-            }
+        let seeded = self.config.population_size / 2;
+        while population.len() < seeded {
+            population.push(self.mutate(seed.clone(), request)?);
+        }
 
-            // Add lunch
-            for _ in 0..request.constraints.meal_count_per_day.lunch {
-                genome.push(self.create_random_meal_gene(day, MealType::Lunch)?);
-            }
+        while population.len() < self.config.population_size {
+            population.push(self.create_random_individual(request)?);
+        }
 
-            // Add dinner
-            for _ in 0..request.constraints.meal_count_per_day.dinner {
-                genome.push(self.create_random_meal_gene(day, MealType::Dinner)?);
-            }
+        Ok(population)
+    }
 
-            // Add snacks
-            for _ in 0..request.constraints.meal_count_per_day.snacks {
-                genome.push(self.create_random_meal_gene(day, MealType::Snack)?);
+    /// The (day, meal type) slots a plan for this request has to fill, in
+    /// genome order
+    fn meal_slots(request: &OptimizationRequest) -> Vec<(u32, MealType)> {
+        let counts = &request.constraints.meal_count_per_day;
+        let mut slots = Vec::new();
+
+        for day in 0..request.time_horizon_days {
+            for (meal_type, count) in [
+                (MealType::Breakfast, counts.breakfast),
+                (MealType::Lunch, counts.lunch),
+                (MealType::Dinner, counts.dinner),
+                (MealType::Snack, counts.snacks),
+            ] {
+                for _ in 0..count {
+                    slots.push((day, meal_type.clone()));
+                }
             }
         }
 
+        slots
+    }
+
+    /// Whether a previous plan can seed this request: it must fill the same
+    /// slots in the same order with recipes that still exist
+    fn fits_request(&self, meals: &[MealGene], request: &OptimizationRequest) -> bool {
+        let slots = Self::meal_slots(request);
+
+        meals.len() == slots.len()
+            && meals.iter().zip(&slots).all(|(gene, (day, meal_type))| {
+                gene.day == *day
+                    && gene.meal_type == *meal_type
+                    && self.recipes.iter().any(|r| r.id == gene.recipe_id && r.meal_type == *meal_type)
+            })
+    }
+
+    fn create_random_individual(&mut self, request: &OptimizationRequest) -> Result<Individual> {
+        let genome = Self::meal_slots(request).into_iter()
+            .map(|(day, meal_type)| self.create_random_meal_gene(day, meal_type))
+            .collect::<Result<Vec<_>>>()?;
+
         Ok(Individual::new(genome))
     }
 
@@ -209,7 +303,7 @@ impl GeneticAlgorithm {
                 OptimizationObjective::MaximizeTasteScore => self.evaluate_taste_score(&individual.genome, &request.preferences)?,
                 OptimizationObjective::MaximizeVariety => self.evaluate_variety(&individual.genome),
                 OptimizationObjective::MinimizePreparationTime => self.evaluate_preparation_time(&individual.genome)?,
-                OptimizationObjective::MaximizeSeasonality => self.evaluate_seasonality(&individual.genome),
+                OptimizationObjective::MaximizeSeasonality => self.evaluate_seasonality(&individual.genome, request.month),
                 OptimizationObjective::BalanceMacros => self.evaluate_macro_balance(&total_nutrition, &request.constraints),
                 OptimizationObjective::MinimizeFoodWaste => 0.8, // Placeholder
             };
@@ -218,12 +312,21 @@ impl GeneticAlgorithm {
         }
 
         // Check constraints and calculate violations
-        let constraint_violations = self.check_constraints(&total_nutrition, request);
+        let constraint_violations = self.check_constraints(&individual.genome, &total_nutrition, request);
 
         // Calculate fitness (weighted sum of objectives with penalties)
         let base_fitness: f64 = objective_scores.values().sum::<f64>() / objective_scores.len() as f64;
         let constraint_penalty = individual.get_total_constraint_violation() * 0.1;
-        let fitness = (base_fitness - constraint_penalty).max(0.0);
+        let prep_penalty = self.preparation_time_penalty(&individual.genome, request);
+        let budget_penalty = self.budget_penalty(&individual.genome, request);
+        let excess_repetitions: u32 = self.repetition_overruns(&individual.genome, request).iter()
+            .map(|(_, count, cap)| count - cap)
+            .sum();
+        let repetition_penalty = excess_repetitions as f64 * 0.1;
+        let seasonal_penalty = self.seasonal_penalty(&individual.genome, request);
+        let distribution_penalty = self.calorie_distribution_penalty(&individual.genome, request);
+        let fitness = (base_fitness - constraint_penalty - prep_penalty - budget_penalty
+            - repetition_penalty - seasonal_penalty - distribution_penalty).max(0.0);
 
         individual.fitness = Some(fitness);
         individual.objective_scores = objective_scores;
@@ -270,16 +373,68 @@ impl GeneticAlgorithm {
         nutrition.calculate_nutrition_score()
     }
 
-    fn evaluate_cost(&self, genome: &[MealGene]) -> Result<f64> {
-        let total_cost: f64 = genome.iter()
-            .map(|gene| {
-                self.recipes.iter()
-                    .find(|r| r.id == gene.recipe_id)
-                    .and_then(|r| r.cost_per_serving)
-                    .unwrap_or(5.0) * gene.portion_size // Default cost if not available
+    /// Cost of one meal. Priced from the ingredients' cost_per_100g when
+    /// every ingredient has a price, otherwise from the recipe's listed
+    /// cost per serving.
+    fn meal_cost(&self, gene: &MealGene) -> f64 {
+        let Some(recipe) = self.recipes.iter().find(|r| r.id == gene.recipe_id) else {
+            return 5.0 * gene.portion_size; // Default cost if not available
+        };
+
+        let ingredient_cost: Option<f64> = recipe.ingredients.iter()
+            .map(|ingredient| {
+                self.foods.get(&ingredient.food_id)
+                    .and_then(|food| food.cost_per_100g)
+                    .map(|cost| cost * ingredient.amount_g / 100.0)
             })
             .sum();
 
+        let per_serving = ingredient_cost
+            .map(|cost| cost / recipe.servings.max(1) as f64)
+            .or(recipe.cost_per_serving)
+            .unwrap_or(5.0);
+
+        per_serving * gene.portion_size
+    }
+
+    fn total_cost(&self, genome: &[MealGene]) -> f64 {
+        genome.iter().map(|gene| self.meal_cost(gene)).sum()
+    }
+
+    /// Estimated food cost for each day of the plan
+    fn daily_costs(&self, genome: &[MealGene]) -> Vec<f64> {
+        let days = genome.iter().map(|gene| gene.day + 1).max().unwrap_or(0) as usize;
+        let mut costs = vec![0.0; days];
+        for gene in genome {
+            costs[gene.day as usize] += self.meal_cost(gene);
+        }
+        costs
+    }
+
+    /// Fitness penalty for days that overrun the daily budget, measured as
+    /// the share of the day's spend above budget. It stays below 0.5 so an
+    /// infeasible budget still ranks cheaper plans higher instead of
+    /// flattening every plan to zero fitness.
+    fn budget_penalty(&self, genome: &[MealGene], request: &OptimizationRequest) -> f64 {
+        let Some(budget) = request.constraints.budget_per_day else {
+            return 0.0;
+        };
+
+        let daily = self.daily_costs(genome);
+        if daily.is_empty() {
+            return 0.0;
+        }
+
+        let overrun: f64 = daily.iter()
+            .filter(|&&cost| cost > budget)
+            .map(|&cost| (cost - budget) / cost)
+            .sum();
+        0.5 * overrun / daily.len() as f64
+    }
+
+    fn evaluate_cost(&self, genome: &[MealGene]) -> Result<f64> {
+        let total_cost = self.total_cost(genome);
+
         // Convert cost to score (lower cost = higher score)
         Ok(1.0 / (1.0 + total_cost / 100.0))
     }
@@ -350,10 +505,185 @@ impl GeneticAlgorithm {
         Ok(1.0 / (1.0 + total_time as f64 / 1000.0))
     }
 
-    fn evaluate_seasonality(&self, _genome: &[MealGene]) -> f64 {
-        // Placeholder for seasonality evaluation
-        // Would check current season and food availability
-        0.7
+    /// Minutes of preparation and cooking needed on each day of the plan.
+    /// A recipe served more than once on the same day is batch-prepped, so
+    /// its time only counts once.
+    fn daily_prep_minutes(&self, genome: &[MealGene]) -> Vec<u32> {
+        let days = genome.iter().map(|gene| gene.day + 1).max().unwrap_or(0) as usize;
+        let mut recipes_by_day: Vec<std::collections::HashSet<&str>> = vec![Default::default(); days];
+        for gene in genome {
+            recipes_by_day[gene.day as usize].insert(&gene.recipe_id);
+        }
+
+        recipes_by_day.iter()
+            .map(|recipe_ids| {
+                recipe_ids.iter()
+                    .map(|id| {
+                        self.recipes.iter()
+                            .find(|r| r.id == *id)
+                            .map(|r| r.prep_time_minutes + r.cook_time_minutes)
+                            .unwrap_or(30) // Default time if not available
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Fitness penalty proportional to how far the plan's days overrun the
+    /// daily prep-time budget, so tight budgets steer towards quicker recipes
+    fn preparation_time_penalty(&self, genome: &[MealGene], request: &OptimizationRequest) -> f64 {
+        let Some(budget) = request.constraints.preparation_time_max_minutes else {
+            return 0.0;
+        };
+
+        let daily = self.daily_prep_minutes(genome);
+        if daily.is_empty() {
+            return 0.0;
+        }
+
+        let overrun: f64 = daily.iter()
+            .map(|&minutes| minutes.saturating_sub(budget) as f64 / budget as f64)
+            .sum();
+        overrun / daily.len() as f64
+    }
+
+    fn meal_calories(&self, gene: &MealGene) -> f64 {
+        self.recipes.iter()
+            .find(|r| r.id == gene.recipe_id)
+            .map_or(0.0, |recipe| recipe.nutrition_per_serving.calories * gene.portion_size)
+    }
+
+    /// Fitness penalty for days whose calories are split across meal types
+    /// differently from the requested distribution, measured as the share
+    /// of the day's calories that would have to move between meal types
+    fn calorie_distribution_penalty(&self, genome: &[MealGene], request: &OptimizationRequest) -> f64 {
+        let Some(distribution) = &request.constraints.calorie_distribution else {
+            return 0.0;
+        };
+        let counts = &request.constraints.meal_count_per_day;
+
+        let days = genome.iter().map(|gene| gene.day + 1).max().unwrap_or(0);
+        if days == 0 {
+            return 0.0;
+        }
+
+        let mut deviation = 0.0;
+        for day in 0..days {
+            let meals: Vec<&MealGene> = genome.iter().filter(|gene| gene.day == day).collect();
+            let day_calories: f64 = meals.iter().map(|gene| self.meal_calories(gene)).sum();
+            if day_calories <= 0.0 {
+                continue;
+            }
+
+            let misplaced: f64 = [MealType::Breakfast, MealType::Lunch, MealType::Dinner, MealType::Snack].iter()
+                .map(|meal_type| {
+                    let calories: f64 = meals.iter()
+                        .filter(|gene| gene.meal_type == *meal_type)
+                        .map(|gene| self.meal_calories(gene))
+                        .sum();
+                    (calories / day_calories - distribution.share(meal_type, counts)).abs()
+                })
+                .sum();
+            deviation += misplaced / 2.0;
+        }
+
+        deviation / days as f64
+    }
+
+    fn recipe_repetitions(genome: &[MealGene]) -> HashMap<String, u32> {
+        let mut counts = HashMap::new();
+        for gene in genome {
+            *counts.entry(gene.recipe_id.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Repetition cap for one recipe. When there are too few recipes of its
+    /// meal type to fill the plan within the requested cap, the cap is
+    /// raised to the unavoidable minimum so only avoidable repeats count.
+    fn repetition_cap(&self, recipe_id: &str, genome: &[MealGene], request: &OptimizationRequest) -> u32 {
+        let requested = request.max_recipe_repetitions();
+        let Some(recipe) = self.recipes.iter().find(|r| r.id == recipe_id) else {
+            return requested;
+        };
+
+        let slots = genome.iter().filter(|gene| gene.meal_type == recipe.meal_type).count() as u32;
+        let alternatives = self.recipes.iter().filter(|r| r.meal_type == recipe.meal_type).count() as u32;
+        requested.max(slots.div_ceil(alternatives.max(1)))
+    }
+
+    /// Times for a day's meals spread across the eating window, if any
+    fn meal_times(request: &OptimizationRequest) -> Vec<MealTime> {
+        let Some(window) = &request.constraints.eating_window else {
+            return vec![];
+        };
+        let meals = request.constraints.meal_count_per_day.daily_order();
+
+        window.meal_times(meals.len() as u32).into_iter()
+            .zip(meals)
+            .map(|(time, meal_type)| MealTime { meal_type, time })
+            .collect()
+    }
+
+    /// Glycemic load for each day of the plan
+    fn daily_glycemic_load(&self, genome: &[MealGene]) -> Vec<GlycemicLoad> {
+        let days = genome.iter().map(|gene| gene.day + 1).max().unwrap_or(0) as usize;
+        let mut loads = vec![0.0; days];
+        for gene in genome {
+            if let Some(recipe) = self.recipes.iter().find(|r| r.id == gene.recipe_id) {
+                loads[gene.day as usize] += recipe.glycemic_load_per_serving(&self.foods).value * gene.portion_size;
+            }
+        }
+        loads.into_iter().map(GlycemicLoad::daily).collect()
+    }
+
+    /// Recipes served more often than their cap, with count and cap
+    fn repetition_overruns(&self, genome: &[MealGene], request: &OptimizationRequest) -> Vec<(String, u32, u32)> {
+        Self::recipe_repetitions(genome).into_iter()
+            .filter_map(|(recipe_id, count)| {
+                let cap = self.repetition_cap(&recipe_id, genome, request);
+                (count > cap).then_some((recipe_id, count, cap))
+            })
+            .collect()
+    }
+
+    /// Share of the plan's seasonal produce, by weight, that is at its peak
+    /// in `month`. Foods without seasonal data are left out; `None` when the
+    /// plan has no seasonal produce at all.
+    fn seasonal_coverage(&self, genome: &[MealGene], month: u8) -> Option<f64> {
+        let (mut in_season_g, mut seasonal_g) = (0.0, 0.0);
+
+        for gene in genome {
+            let Some(recipe) = self.recipes.iter().find(|r| r.id == gene.recipe_id) else {
+                continue;
+            };
+            for ingredient in &recipe.ingredients {
+                let Some(in_season) = self.foods.get(&ingredient.food_id).and_then(|f| f.is_in_season(month)) else {
+                    continue;
+                };
+                let grams = ingredient.amount_g * gene.portion_size;
+                seasonal_g += grams;
+                if in_season {
+                    in_season_g += grams;
+                }
+            }
+        }
+
+        (seasonal_g > 0.0).then(|| in_season_g / seasonal_g)
+    }
+
+    fn evaluate_seasonality(&self, genome: &[MealGene], month: Option<u8>) -> f64 {
+        month.and_then(|month| self.seasonal_coverage(genome, month))
+            .unwrap_or(0.7) // Neutral when there is nothing to judge
+    }
+
+    /// Fitness penalty for out-of-season produce in the requested month. It
+    /// is small next to constraint penalties, so it only decides between
+    /// otherwise comparable plans.
+    fn seasonal_penalty(&self, genome: &[MealGene], request: &OptimizationRequest) -> f64 {
+        request.month
+            .and_then(|month| self.seasonal_coverage(genome, month))
+            .map_or(0.0, |coverage| 0.2 * (1.0 - coverage))
     }
 
     fn evaluate_macro_balance(&self, nutrition: &NutritionFacts, constraints: &NutritionConstraints) -> f64 {
@@ -380,7 +710,7 @@ impl GeneticAlgorithm {
         1.0 - (total_deviation / 3.0).min(1.0)
     }
 
-    fn check_constraints(&self, nutrition: &NutritionFacts, request: &OptimizationRequest) -> Vec<ConstraintViolation> {
+    fn check_constraints(&self, genome: &[MealGene], nutrition: &NutritionFacts, request: &OptimizationRequest) -> Vec<ConstraintViolation> {
         let mut violations = Vec::new();
 
         // Check calorie constraints
@@ -429,21 +759,60 @@ impl GeneticAlgorithm {
             }
         }
 
+        if let Some((recipe_id, count, cap)) = self.repetition_overruns(genome, request).into_iter()
+            .max_by_key(|(_, count, cap)| count - cap)
+        {
+            violations.push(ConstraintViolation {
+                constraint_type: "recipe_repetition_max".to_string(),
+                severity: ViolationSeverity::Low,
+                current_value: count as f64,
+                required_value: cap as f64,
+                description: format!("Recipe {} is served more often than allowed", recipe_id),
+            });
+        }
+
+        if let Some(budget) = request.constraints.budget_per_day {
+            let priciest_day = self.daily_costs(genome).into_iter().fold(0.0, f64::max);
+            if priciest_day > budget {
+                violations.push(ConstraintViolation {
+                    constraint_type: "budget_per_day".to_string(),
+                    severity: ViolationSeverity::Medium,
+                    current_value: priciest_day,
+                    required_value: budget,
+                    description: "Daily food cost exceeds budget".to_string(),
+                });
+            }
+        }
+
+        if let Some(budget) = request.constraints.preparation_time_max_minutes {
+            let busiest_day = self.daily_prep_minutes(genome).into_iter().max().unwrap_or(0);
+            if busiest_day > budget {
+                violations.push(ConstraintViolation {
+                    constraint_type: "preparation_time_max".to_string(),
+                    severity: ViolationSeverity::Medium,
+                    current_value: busiest_day as f64,
+                    required_value: budget as f64,
+                    description: "Daily preparation time exceeds budget".to_string(),
+                });
+            }
+        }
+
         violations
     }
 
     fn selection(&mut self, population: &[Individual]) -> Vec<Individual> {
         let mut parents = Vec::new();
         let tournament_size = 5;
+        let shared_fitness = self.shared_fitness(population);
 
         for _ in 0..population.len() {
             // Tournament selection
             let mut tournament: Vec<_> = (0..tournament_size)
-                .map(|_| &population[self.rng.gen_range(0..population.len())])
+                .map(|_| self.rng.gen_range(0..population.len()))
                 .collect();
 
-            tournament.sort_by(|a, b| b.get_fitness().partial_cmp(&a.get_fitness()).unwrap());
-            parents.push(tournament[0].clone());
+            tournament.sort_by(|&a, &b| shared_fitness[b].partial_cmp(&shared_fitness[a]).unwrap());
+            parents.push(population[tournament[0]].clone());
         }
 
         parents
@@ -527,27 +896,85 @@ impl GeneticAlgorithm {
         // Sort by fitness (descending)
         population.sort_by(|a, b| b.get_fitness().partial_cmp(&a.get_fitness()).unwrap());
 
+        if self.sharing_radius().is_some() && population.len() > self.config.population_size {
+            // Always keep the best individual so the best fitness never regresses,
+            // then fill the remaining slots by shared fitness to preserve niches
+            let shared_fitness = self.shared_fitness(&population);
+            let mut order: Vec<usize> = (1..population.len()).collect();
+            order.sort_by(|&a, &b| shared_fitness[b].partial_cmp(&shared_fitness[a]).unwrap());
+            order.truncate(self.config.population_size - 1);
+            order.insert(0, 0);
+
+            let mut slots: Vec<Option<Individual>> = population.into_iter().map(Some).collect();
+            return order.into_iter()
+                .filter_map(|index| slots[index].take())
+                .collect();
+        }
+
         // Keep the best individuals (elitism)
         population.truncate(self.config.population_size);
 
         population
     }
 
-    fn create_solution(&self, individual: Individual, metadata: AlgorithmMetadata) -> Result<OptimizationSolution> {
+    /// Sharing radius if niching is enabled
+    fn sharing_radius(&self) -> Option<f64> {
+        self.config.sharing_radius.filter(|radius| *radius > 0.0)
+    }
+
+    /// Fitness of each individual divided by its niche count, so that crowds of
+    /// structurally similar meal plans penalize each other. Returns the raw
+    /// fitness when niching is disabled.
+    fn shared_fitness(&self, population: &[Individual]) -> Vec<f64> {
+        let radius = match self.sharing_radius() {
+            Some(radius) => radius,
+            None => return population.iter().map(|ind| ind.get_fitness()).collect(),
+        };
+
+        population.iter()
+            .map(|individual| {
+                let niche_count: f64 = population.iter()
+                    .map(|other| {
+                        let distance = genome_distance(&individual.genome, &other.genome);
+                        if distance < radius { 1.0 - distance / radius } else { 0.0 }
+                    })
+                    .sum();
+
+                individual.get_fitness() / niche_count.max(1.0)
+            })
+            .collect()
+    }
+
+    /// Mean pairwise genome distance across the population (0.0 = all identical)
+    fn population_diversity(population: &[Individual]) -> f64 {
+        let mut total = 0.0;
+        let mut pairs = 0;
+
+        for (i, a) in population.iter().enumerate() {
+            for b in &population[i + 1..] {
+                total += genome_distance(&a.genome, &b.genome);
+                pairs += 1;
+            }
+        }
+
+        if pairs == 0 { 0.0 } else { total / pairs as f64 }
+    }
+
+    fn create_solution(&self, individual: Individual, request: &OptimizationRequest, metadata: AlgorithmMetadata) -> Result<OptimizationSolution> {
         let nutrition_summary = self.calculate_total_nutrition(&individual.genome)?;
         
         // Calculate additional scores
         let variety_score = self.evaluate_variety(&individual.genome);
         let taste_score = individual.objective_scores.get("MaximizeTasteScore").copied().unwrap_or(0.5);
         let convenience_score = individual.objective_scores.get("MinimizePreparationTime").copied().unwrap_or(0.5);
-        let seasonality_score = self.evaluate_seasonality(&individual.genome);
+        let seasonality_score = self.evaluate_seasonality(&individual.genome, request.month);
+        let seasonal_coverage = request.month.and_then(|month| self.seasonal_coverage(&individual.genome, month));
+        let daily_prep_minutes = self.daily_prep_minutes(&individual.genome);
+        let daily_glycemic_load = self.daily_glycemic_load(&individual.genome);
+        let meal_times = Self::meal_times(request);
+        let recipe_repetitions = Self::recipe_repetitions(&individual.genome);
 
-        // Calculate total cost
-        let total_cost = if let Ok(cost_score) = self.evaluate_cost(&individual.genome) {
-            Some(100.0 * (1.0 - cost_score)) // Convert score back to cost estimate
-        } else {
-            None
-        };
+        let total_cost = Some(self.total_cost(&individual.genome));
 
         Ok(OptimizationSolution {
             meal_plan_id: uuid::Uuid::new_v4().to_string(),
@@ -560,11 +987,31 @@ impl GeneticAlgorithm {
             taste_score,
             convenience_score,
             seasonality_score,
+            seasonal_coverage,
+            daily_glycemic_load,
+            meal_times,
             algorithm_metadata: metadata,
+            meals: individual.genome,
+            recipe_repetitions,
+            daily_prep_minutes,
         })
     }
 }
 
+/// Fraction of meal slots in which two plans use a different recipe
+fn genome_distance(a: &[MealGene], b: &[MealGene]) -> f64 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 0.0;
+    }
+
+    let matching = a.iter().zip(b)
+        .filter(|(gene_a, gene_b)| gene_a.recipe_id == gene_b.recipe_id)
+        .count();
+
+    (len - matching) as f64 / len as f64
+}
+
 impl Clone for Individual {
     fn clone(&self) -> Self {
         Self {
@@ -575,4 +1022,417 @@ impl Clone for Individual {
             age: self.age,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::food::Ingredient;
+    use crate::models::validation::Validate;
+    use crate::sample_data::SampleDataSet;
+
+    fn test_config() -> AlgorithmConfig {
+        AlgorithmConfig {
+            population_size: 20,
+            max_generations: 500,
+            parallel_evaluation: false,
+            ..AlgorithmConfig::default()
+        }
+    }
+
+    fn test_request(time_horizon_days: u32, algorithm_config: AlgorithmConfig) -> OptimizationRequest {
+        OptimizationRequest {
+            user_id: "test_user".to_string(),
+            constraints: NutritionConstraints {
+                daily_calories: CalorieRange { min: 1800.0, max: 2600.0, target: 2200.0 },
+                macros: MacroConstraints {
+                    protein_g: Range::new(100.0, 180.0),
+                    carbs_g: Range::new(180.0, 320.0),
+                    fat_g: Range::new(50.0, 90.0),
+                    fiber_g: Range::new(25.0, 40.0),
+                    sugar_g_max: Some(50.0),
+                    sodium_mg_max: Some(2300.0),
+                },
+                micronutrients: MicronutrientConstraints {
+                    vitamin_c_mg: Range::new(65.0, 2000.0),
+                    calcium_mg: Range::new(1000.0, 2500.0),
+                    iron_mg: Range::new(8.0, 45.0),
+                    vitamin_d_iu: Range::new(600.0, 4000.0),
+                    vitamin_b12_mcg: Range::new(2.4, 100.0),
+                    folate_mcg: Range::new(400.0, 1000.0),
+                    omega3_g: Range::new(1.1, 3.0),
+                },
+                meal_count_per_day: MealCountConstraints { breakfast: 1, lunch: 1, dinner: 1, snacks: 1 },
+                budget_per_day: None,
+                preparation_time_max_minutes: None,
+                calorie_distribution: None,
+                eating_window: None,
+            },
+            preferences: UserPreferences {
+                dietary_restrictions: vec![],
+                allergens_to_avoid: vec![],
+                cuisine_preferences: vec!["American".to_string()],
+                disliked_foods: vec![],
+                preferred_foods: vec![],
+                taste_preferences: TastePreferences {
+                    sweetness_preference: 0.0,
+                    saltiness_preference: 0.0,
+                    sourness_preference: 0.0,
+                    bitterness_preference: 0.0,
+                    umami_preference: 0.0,
+                    spiciness_preference: 0.0,
+                    spice_tolerance: 0.5,
+                },
+                cooking_skill_level: CookingSkillLevel::Intermediate,
+                equipment_available: vec![CookingEquipment::Stovetop, CookingEquipment::Oven],
+                meal_variety_importance: 0.5,
+                cost_importance: 0.5,
+                health_importance: 0.5,
+                convenience_importance: 0.5,
+            },
+            objectives: vec![
+                OptimizationObjective::MaximizeNutrition,
+                OptimizationObjective::BalanceMacros,
+            ],
+            time_horizon_days,
+            algorithm_config,
+            // Never binding, so tests of other behaviour aren't shaped by the cap
+            max_recipe_repetitions_per_week: 28,
+            month: None,
+        }
+    }
+
+    fn sample_algorithm(config: AlgorithmConfig) -> GeneticAlgorithm {
+        let data = SampleDataSet::new();
+        GeneticAlgorithm::new(config, data.recipes, data.foods, Some(42))
+    }
+
+    #[test]
+    fn test_stops_early_once_converged() {
+        // Without crossover or mutation the population can never improve,
+        // so the best fitness plateaus immediately
+        let config = AlgorithmConfig {
+            mutation_rate: 0.0,
+            crossover_rate: 0.0,
+            convergence_generations: 10,
+            ..test_config()
+        };
+        let request = test_request(1, config.clone());
+
+        let solution = sample_algorithm(config.clone()).optimize(&request).unwrap();
+        let metadata = &solution.algorithm_metadata;
+
+        assert!(metadata.convergence_generation.is_some());
+        assert!(metadata.generations_run < config.max_generations / 10);
+        assert_eq!(metadata.best_fitness_history.len(), metadata.generations_run);
+    }
+
+    #[test]
+    fn test_reports_progress_for_every_generation() {
+        let config = AlgorithmConfig { max_generations: 30, ..test_config() };
+        let request = test_request(2, config.clone());
+        let (progress, mut updates) = tokio::sync::mpsc::unbounded_channel();
+
+        let solution = sample_algorithm(config.clone())
+            .with_progress(progress)
+            .optimize(&request)
+            .unwrap();
+
+        let mut reports = Vec::new();
+        while let Ok(report) = updates.try_recv() {
+            reports.push(report);
+        }
+        let metadata = &solution.algorithm_metadata;
+        assert!(metadata.generations_run > 1);
+        assert_eq!(reports.len(), metadata.generations_run);
+        assert!(reports.windows(2).all(|pair| pair[1].generation == pair[0].generation + 1));
+        assert_eq!(reports[0].generation, 1);
+        assert_eq!(reports.last().unwrap().generation, metadata.generations_run);
+        assert_eq!(reports.last().unwrap().best_fitness, *metadata.best_fitness_history.last().unwrap());
+        assert!(reports.iter().all(|r| r.max_generations == 30 && r.estimated_remaining_ms >= 0.0));
+        // The algorithm dropped its sender when it finished
+        assert!(updates.is_closed());
+    }
+
+    #[test]
+    fn test_cancelling_returns_best_plan_so_far() {
+        let config = AlgorithmConfig {
+            max_generations: 1000,
+            convergence_generations: 1000,
+            ..test_config()
+        };
+        let request = test_request(2, config.clone());
+        let (progress, mut updates) = tokio::sync::mpsc::unbounded_channel();
+        let cancel = CancellationToken::new();
+
+        let mut algorithm = sample_algorithm(config)
+            .with_progress(progress)
+            .with_cancellation(cancel.clone());
+        let run = std::thread::spawn(move || algorithm.optimize(&request));
+
+        // Cancel as soon as the first generation has been evaluated
+        let first = updates.blocking_recv().unwrap();
+        assert_eq!(first.generation, 1);
+        cancel.cancel();
+
+        let solution = run.join().unwrap().unwrap();
+        let metadata = &solution.algorithm_metadata;
+        assert!(metadata.cancelled);
+        assert!(metadata.convergence_generation.is_none());
+        assert!(metadata.generations_run >= 1);
+        assert!(metadata.generations_run < 100, "ran {} generations", metadata.generations_run);
+        assert_eq!(metadata.best_fitness_history.len(), metadata.generations_run);
+        assert!(!solution.meals.is_empty());
+    }
+
+    #[test]
+    fn test_niching_preserves_meal_variety() {
+        // Total distinct recipes in the best plan, summed over a few seeds
+        let distinct_recipes = |sharing_radius: Option<f64>| -> usize {
+            (0..4).map(|seed| {
+                let config = AlgorithmConfig {
+                    max_generations: 100,
+                    sharing_radius,
+                    ..test_config()
+                };
+                let request = test_request(7, config.clone());
+                let data = SampleDataSet::new();
+                let mut algorithm = GeneticAlgorithm::new(config, data.recipes, data.foods, Some(seed));
+                let solution = algorithm.optimize(&request).unwrap();
+
+                // 7 days x 4 meals per day
+                (solution.variety_score * 28.0).round() as usize
+            }).sum()
+        };
+
+        let baseline = distinct_recipes(None);
+        let with_niching = distinct_recipes(Some(0.5));
+
+        assert!(
+            with_niching > baseline,
+            "niching used {} distinct recipes, baseline used {}", with_niching, baseline
+        );
+    }
+
+    #[test]
+    fn test_caps_recipe_repetitions() {
+        // Four breakfast recipes allow a week of breakfasts with none served
+        // more than twice
+        let data = SampleDataSet::new();
+        let mut recipes: Vec<Recipe> = data.recipes.iter()
+            .filter(|r| r.meal_type == MealType::Breakfast)
+            .cloned()
+            .collect();
+        let variants: Vec<Recipe> = recipes.iter()
+            .map(|r| Recipe { id: format!("{}_variant", r.id), ..r.clone() })
+            .collect();
+        recipes.extend(variants);
+        assert_eq!(recipes.len(), 4);
+
+        let config = AlgorithmConfig { max_generations: 200, ..test_config() };
+        let mut request = test_request(7, config.clone());
+        request.constraints.meal_count_per_day = MealCountConstraints { breakfast: 1, lunch: 0, dinner: 0, snacks: 0 };
+        request.max_recipe_repetitions_per_week = 2;
+
+        let solution = GeneticAlgorithm::new(config, recipes, data.foods, Some(42))
+            .optimize(&request)
+            .unwrap();
+
+        assert_eq!(solution.recipe_repetitions.values().sum::<u32>(), 7);
+        assert!(
+            solution.recipe_repetitions.values().all(|&count| count <= 2),
+            "recipe repetitions {:?} exceed the cap", solution.recipe_repetitions
+        );
+        assert!(solution.constraint_violations.iter().all(|v| v.constraint_type != "recipe_repetition_max"));
+    }
+
+    #[test]
+    fn test_tight_budget_prefers_cheaper_meals() {
+        let config = AlgorithmConfig { max_generations: 200, ..test_config() };
+        let average_daily_cost = |solution: &OptimizationSolution| solution.total_cost.unwrap() / 7.0;
+
+        let unconstrained = sample_algorithm(config.clone())
+            .optimize(&test_request(7, config.clone()))
+            .unwrap();
+
+        let mut request = test_request(7, config.clone());
+        request.constraints.budget_per_day = Some(18.0);
+        let budgeted = sample_algorithm(config.clone()).optimize(&request).unwrap();
+
+        assert!(
+            average_daily_cost(&budgeted) < average_daily_cost(&unconstrained),
+            "budgeted plan costs {:.2}/day, unconstrained {:.2}/day",
+            average_daily_cost(&budgeted), average_daily_cost(&unconstrained)
+        );
+
+        // Four meals a day can't be bought for a dollar, so the plan says so
+        request.constraints.budget_per_day = Some(1.0);
+        let infeasible = sample_algorithm(config).optimize(&request).unwrap();
+        let violation = infeasible.constraint_violations.iter()
+            .find(|v| v.constraint_type == "budget_per_day")
+            .expect("cost overrun is reported");
+        assert!(violation.current_value > 1.0);
+    }
+
+    #[test]
+    fn test_july_plan_prefers_summer_produce() {
+        // Two breakfasts with identical nutrition and cost that differ only
+        // in whether their produce peaks in summer or winter
+        let data = SampleDataSet::new();
+        let base = data.recipes.iter()
+            .find(|r| r.meal_type == MealType::Breakfast)
+            .unwrap();
+        let with_produce = |id: &str, food_id: &str| Recipe {
+            id: id.to_string(),
+            ingredients: vec![Ingredient {
+                food_id: food_id.to_string(),
+                amount_g: 150.0,
+                preparation: None,
+                substitutes: vec![],
+            }],
+            cost_per_serving: Some(2.0),
+            ..base.clone()
+        };
+        let mut foods = data.foods.clone();
+        let blueberry_price = foods["blueberries"].cost_per_100g;
+        foods.get_mut("broccoli").unwrap().cost_per_100g = blueberry_price;
+        let recipes = vec![
+            with_produce("summer_bowl", "blueberries"),
+            with_produce("winter_bowl", "broccoli"),
+        ];
+
+        let config = AlgorithmConfig { max_generations: 100, ..test_config() };
+        let mut request = test_request(7, config.clone());
+        request.constraints.meal_count_per_day = MealCountConstraints { breakfast: 1, lunch: 0, dinner: 0, snacks: 0 };
+        request.month = Some(7);
+
+        let solution = GeneticAlgorithm::new(config, recipes, foods, Some(42))
+            .optimize(&request)
+            .unwrap();
+
+        let served = |id: &str| solution.recipe_repetitions.get(id).copied().unwrap_or(0);
+        assert!(
+            served("summer_bowl") > served("winter_bowl"),
+            "repetitions {:?}", solution.recipe_repetitions
+        );
+        assert!(solution.seasonal_coverage.unwrap() > 0.5);
+    }
+
+    #[test]
+    fn test_follows_requested_calorie_distribution() {
+        let config = AlgorithmConfig { max_generations: 200, ..test_config() };
+        let calories_for = |solution: &OptimizationSolution, meal_type: MealType| -> f64 {
+            let recipes = SampleDataSet::new().recipes;
+            solution.meals.iter()
+                .filter(|gene| gene.meal_type == meal_type)
+                .map(|gene| {
+                    let recipe = recipes.iter().find(|r| r.id == gene.recipe_id).unwrap();
+                    recipe.nutrition_per_serving.calories * gene.portion_size
+                })
+                .sum()
+        };
+
+        // Front-loaded; the shares sum to 200 and are normalized
+        let mut request = test_request(3, config.clone());
+        request.constraints.calorie_distribution = Some(CalorieDistribution {
+            breakfast: 80.0, lunch: 50.0, dinner: 50.0, snacks: 20.0,
+        });
+        let front_loaded = sample_algorithm(config.clone()).optimize(&request).unwrap();
+        assert!(calories_for(&front_loaded, MealType::Breakfast) > calories_for(&front_loaded, MealType::Snack));
+
+        // The same plan shape with snacks carrying the day flips it
+        request.constraints.calorie_distribution = Some(CalorieDistribution {
+            breakfast: 10.0, lunch: 25.0, dinner: 25.0, snacks: 40.0,
+        });
+        let snack_heavy = sample_algorithm(config).optimize(&request).unwrap();
+        assert!(calories_for(&snack_heavy, MealType::Snack) > calories_for(&snack_heavy, MealType::Breakfast));
+    }
+
+    #[test]
+    fn test_places_meals_inside_the_eating_window() {
+        let config = AlgorithmConfig { max_generations: 20, ..test_config() };
+        let time = |hh_mm: &str| chrono::NaiveTime::parse_from_str(hh_mm, "%H:%M").unwrap();
+        let mut request = test_request(1, config.clone());
+        assert_eq!(request.constraints.meal_count_per_day.total(), 4);
+
+        // Four meals can't be spaced 90 minutes apart in four hours
+        request.constraints.eating_window = Some(EatingWindow { start: time("12:00"), end: time("16:00") });
+        let errors = request.validate_fields().unwrap_err();
+        assert!(errors.iter().any(|e| e.field == "constraints.eating_window"));
+
+        // 16:8
+        request.constraints.eating_window = Some(EatingWindow { start: time("12:00"), end: time("20:00") });
+        assert!(request.validate_fields().is_ok());
+        let solution = sample_algorithm(config).optimize(&request).unwrap();
+
+        let times: Vec<_> = solution.meal_times.iter().map(|m| (m.meal_type.clone(), m.time)).collect();
+        assert_eq!(times, vec![
+            (MealType::Breakfast, time("12:00")),
+            (MealType::Lunch, time("14:40")),
+            (MealType::Snack, time("17:20")),
+            (MealType::Dinner, time("20:00")),
+        ]);
+    }
+
+    #[test]
+    fn test_warm_start_converges_faster_for_small_changes() {
+        let config = AlgorithmConfig { convergence_generations: 20, ..test_config() };
+        let base = sample_algorithm(config.clone())
+            .optimize(&test_request(3, config.clone()))
+            .unwrap();
+
+        // Nudge the protein floor up a little
+        let mut request = test_request(3, config.clone());
+        request.constraints.macros.protein_g = Range::new(110.0, 180.0);
+
+        let cold = sample_algorithm(config.clone()).optimize(&request).unwrap();
+        let warm = sample_algorithm(config.clone())
+            .with_warm_start(base.meals.clone())
+            .optimize(&request)
+            .unwrap();
+
+        assert!(!cold.algorithm_metadata.warm_start);
+        assert!(warm.algorithm_metadata.warm_start);
+        assert!(
+            warm.algorithm_metadata.generations_run < cold.algorithm_metadata.generations_run,
+            "warm start ran {} generations, cold start {}",
+            warm.algorithm_metadata.generations_run, cold.algorithm_metadata.generations_run
+        );
+
+        // A plan for a different horizon can't seed this one
+        let mismatched = sample_algorithm(config)
+            .with_warm_start(base.meals[..4].to_vec())
+            .optimize(&request)
+            .unwrap();
+        assert!(!mismatched.algorithm_metadata.warm_start);
+    }
+
+    #[test]
+    fn test_respects_daily_prep_time_budget() {
+        let config = AlgorithmConfig { max_generations: 200, ..test_config() };
+
+        // Breakfast plus dinner fits 30 minutes only with the 5-minute yogurt
+        // bowl before the 25-minute stir-fry
+        let mut request = test_request(7, config.clone());
+        request.constraints.meal_count_per_day = MealCountConstraints { breakfast: 1, lunch: 0, dinner: 1, snacks: 0 };
+        request.constraints.preparation_time_max_minutes = Some(30);
+
+        let solution = sample_algorithm(config.clone()).optimize(&request).unwrap();
+        assert_eq!(solution.daily_prep_minutes.len(), 7);
+        assert!(
+            solution.daily_prep_minutes.iter().all(|&minutes| minutes <= 30),
+            "daily prep minutes {:?} exceed the budget", solution.daily_prep_minutes
+        );
+        assert!(solution.constraint_violations.iter().all(|v| v.constraint_type != "preparation_time_max"));
+
+        // Four meals a day can't be prepared in 30 minutes, so the plan says so
+        let mut request = test_request(2, config.clone());
+        request.constraints.preparation_time_max_minutes = Some(30);
+
+        let solution = sample_algorithm(config).optimize(&request).unwrap();
+        let violation = solution.constraint_violations.iter()
+            .find(|v| v.constraint_type == "preparation_time_max")
+            .expect("prep-time violation is reported");
+        assert_eq!(violation.required_value, 30.0);
+        assert!(violation.current_value > 30.0);
+    }
+}
@@ -4,4 +4,4 @@ pub mod genetic;
 pub mod types;
 
 pub use genetic::GeneticAlgorithm;
-pub use types::{OptimizationAlgorithm, AlgorithmFactory, GeneticAlgorithmWrapper};
\ No newline at end of file
+pub use types::{OptimizationAlgorithm, AlgorithmFactory, GeneticAlgorithmWrapper, ProgressSender, CancellationToken};
\ No newline at end of file
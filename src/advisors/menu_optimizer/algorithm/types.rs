@@ -4,6 +4,12 @@ use crate::models::optimization::*;
 use crate::core::Result;
 use async_trait::async_trait;
 
+/// Where an algorithm sends `OptimizationProgress` when asked to. Unbounded
+/// so the search never waits on a slow listener.
+pub type ProgressSender = tokio::sync::mpsc::UnboundedSender<OptimizationProgress>;
+
+pub use tokio_util::sync::CancellationToken;
+
 /// Trait for optimization algorithms
 #[async_trait]
 pub trait OptimizationAlgorithm: Send + Sync {
@@ -19,8 +25,21 @@ pub trait OptimizationAlgorithm: Send + Sync {
     /// Check if algorithm can handle the given request
     fn can_handle(&self, request: &OptimizationRequest) -> bool;
     
-    /// Get estimated runtime for the request
+    /// Get estimated runtime for the request. This is an upper bound: algorithms
+    /// that converge early will usually finish sooner.
     fn estimate_runtime(&self, request: &OptimizationRequest) -> std::time::Duration;
+
+    /// Seed the next run from a previous plan's meals. Algorithms that can't
+    /// use a seed ignore it and start cold.
+    fn set_warm_start(&mut self, _meals: Vec<MealGene>) {}
+
+    /// Report progress of the next run. Algorithms without generations
+    /// ignore it.
+    fn set_progress(&mut self, _progress: ProgressSender) {}
+
+    /// Stop the next run early once `cancel` fires, returning the best plan
+    /// so far with `algorithm_metadata.cancelled` set
+    fn set_cancellation(&mut self, _cancel: CancellationToken) {}
 }
 
 /// Algorithm factory for creating optimization algorithms
@@ -60,6 +79,11 @@ impl AlgorithmFactory {
         }
     }
     
+    /// Whether `create_algorithm` can build this type
+    pub fn is_implemented(algorithm_type: &AlgorithmType) -> bool {
+        matches!(algorithm_type, AlgorithmType::GeneticAlgorithm)
+    }
+
     /// Get recommended algorithm for request
     pub fn recommend_algorithm(request: &OptimizationRequest) -> AlgorithmType {
         // Algorithm selection logic based on request characteristics
@@ -80,6 +104,9 @@ pub struct GeneticAlgorithmWrapper {
     config: AlgorithmConfig,
     recipes: Vec<crate::models::food::Recipe>,
     foods: std::collections::HashMap<String, crate::models::food::Food>,
+    warm_start: Option<Vec<MealGene>>,
+    progress: Option<ProgressSender>,
+    cancel: Option<CancellationToken>,
 }
 
 impl GeneticAlgorithmWrapper {
@@ -88,6 +115,9 @@ impl GeneticAlgorithmWrapper {
             config: algorithm.config.clone(),
             recipes: algorithm.recipes.clone(),
             foods: algorithm.foods.clone(),
+            warm_start: None,
+            progress: None,
+            cancel: None,
         }
     }
 }
@@ -100,12 +130,24 @@ impl OptimizationAlgorithm for GeneticAlgorithmWrapper {
         let recipes = self.recipes.clone();
         let foods = self.foods.clone();
         let request = request.clone();
+        let warm_start = self.warm_start.take();
+        let progress = self.progress.take();
+        let cancel = self.cancel.take();
         
         // Run optimization in a blocking task to avoid blocking the async runtime
         let result = tokio::task::spawn_blocking(move || {
             let mut algorithm = crate::advisors::menu_optimizer::algorithm::genetic::GeneticAlgorithm::new(
                 config, recipes, foods, None
             );
+            if let Some(meals) = warm_start {
+                algorithm = algorithm.with_warm_start(meals);
+            }
+            if let Some(progress) = progress {
+                algorithm = algorithm.with_progress(progress);
+            }
+            if let Some(cancel) = cancel {
+                algorithm = algorithm.with_cancellation(cancel);
+            }
             algorithm.optimize(&request)
         }).await;
         
@@ -135,6 +177,20 @@ impl OptimizationAlgorithm for GeneticAlgorithmWrapper {
         let complexity_factor = request.objectives.len() * request.time_horizon_days as usize;
         let estimated_seconds = base_time + (complexity_factor / 10);
         
-        std::time::Duration::from_secs(estimated_seconds.min(300) as u64) // Max 5 minutes
+        // Never exceed the configured runtime cap, since the GA stops there regardless
+        let cap = self.config.max_runtime_seconds.min(300); // Max 5 minutes
+        std::time::Duration::from_secs((estimated_seconds as u64).min(cap))
+    }
+
+    fn set_warm_start(&mut self, meals: Vec<MealGene>) {
+        self.warm_start = Some(meals);
+    }
+
+    fn set_progress(&mut self, progress: ProgressSender) {
+        self.progress = Some(progress);
+    }
+
+    fn set_cancellation(&mut self, cancel: CancellationToken) {
+        self.cancel = Some(cancel);
     }
-}
\ No newline at end of file
+}
@@ -3,9 +3,10 @@
 pub mod algorithm;
 pub mod data_loader;
 
+use crate::advisors::nutrition_calc::{self, MacroSplit};
 use crate::core::{FitnessError, Result, MetricsCollector, OptimizationMetrics};
 use crate::models::{optimization::*, food::*};
-use algorithm::{AlgorithmFactory, OptimizationAlgorithm};
+use algorithm::{AlgorithmFactory, CancellationToken, OptimizationAlgorithm, ProgressSender};
 pub use data_loader::DataLoader;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -13,6 +14,21 @@ use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 use std::time::Instant;
 
+/// Optional hooks for a single optimization run
+#[derive(Default)]
+pub struct RunHooks {
+    /// Receives progress after every generation. The sender is dropped when
+    /// the run ends, so a listener can stop on the channel closing.
+    pub progress: Option<ProgressSender>,
+    /// Stops the run early, returning the best plan so far flagged
+    /// `algorithm_metadata.cancelled`
+    pub cancel: Option<CancellationToken>,
+    /// Run this algorithm instead of the recommended one, e.g. for an
+    /// experiment. Algorithms that aren't implemented yet fall back to the
+    /// recommendation; `algorithm_metadata.algorithm_used` says which ran.
+    pub algorithm: Option<AlgorithmType>,
+}
+
 /// Main menu optimization service
 pub struct MenuOptimizer {
     recipes: Arc<RwLock<Vec<Recipe>>>,
@@ -72,8 +88,50 @@ impl MenuOptimizer {
         self.foods.read().await.len()
     }
 
+    /// A local food by id or by name, ignoring case
+    pub async fn find_food(&self, query: &str) -> Option<Food> {
+        let query = query.trim();
+        let foods = self.foods.read().await;
+        foods.get(query).cloned().or_else(|| {
+            foods.values().find(|food| food.name.eq_ignore_ascii_case(query)).cloned()
+        })
+    }
+
     /// Optimize meal plan
     pub async fn optimize_meal_plan(&self, request: OptimizationRequest) -> Result<OptimizationSolution> {
+        self.run_optimization(request, None, RunHooks::default()).await
+    }
+
+    /// Optimize with the given hooks attached to the run. Cached solutions
+    /// come back straight away, without any progress.
+    pub async fn optimize_meal_plan_with_hooks(
+        &self,
+        request: OptimizationRequest,
+        hooks: RunHooks,
+    ) -> Result<OptimizationSolution> {
+        self.run_optimization(request, None, hooks).await
+    }
+
+    /// Re-optimize after a change to a previous request, seeding the search
+    /// from `base` so small constraint changes converge faster. Falls back
+    /// to a cold start when the base plan doesn't fit the new request (e.g. a
+    /// different horizon or meal counts); `algorithm_metadata.warm_start`
+    /// reports which happened.
+    pub async fn reoptimize(&self, base: &OptimizationSolution, request: OptimizationRequest) -> Result<OptimizationSolution> {
+        if base.meals.is_empty() {
+            warn!("Base solution {} has no meals to warm start from", base.meal_plan_id);
+            return self.run_optimization(request, None, RunHooks::default()).await;
+        }
+
+        self.run_optimization(request, Some(base.meals.clone()), RunHooks::default()).await
+    }
+
+    async fn run_optimization(
+        &self,
+        request: OptimizationRequest,
+        warm_start: Option<Vec<MealGene>>,
+        hooks: RunHooks,
+    ) -> Result<OptimizationSolution> {
         let start_time = Instant::now();
         
         // Update metrics
@@ -86,8 +144,19 @@ impl MenuOptimizer {
         request.validate()
             .map_err(|e| FitnessError::optimization(format!("Invalid optimization request: {}", e)))?;
 
+        let recommended = AlgorithmFactory::recommend_algorithm(&request);
+        let algorithm_type = match hooks.algorithm {
+            Some(requested) if AlgorithmFactory::is_implemented(&requested) => requested,
+            Some(requested) => {
+                warn!("{:?} is not implemented; falling back to {:?} for user {}",
+                      requested, recommended, request.user_id);
+                recommended
+            }
+            None => recommended,
+        };
+
         // Check cache first
-        let cache_key = self.generate_cache_key(&request);
+        let cache_key = self.generate_cache_key(&request, &algorithm_type);
         if let Some(cached_solution) = self.check_cache(&cache_key).await? {
             info!("Returning cached optimization solution for user {}", request.user_id);
             {
@@ -102,8 +171,6 @@ impl MenuOptimizer {
             metrics.record_cache_miss();
         }
 
-        // Get algorithm recommendation
-        let algorithm_type = AlgorithmFactory::recommend_algorithm(&request);
         info!("Using algorithm: {:?} for optimization request from user {}", 
               algorithm_type, request.user_id);
 
@@ -129,6 +196,16 @@ impl MenuOptimizer {
             ));
         }
 
+        if let Some(meals) = warm_start {
+            algorithm.set_warm_start(meals);
+        }
+        if let Some(progress) = hooks.progress {
+            algorithm.set_progress(progress);
+        }
+        if let Some(cancel) = hooks.cancel {
+            algorithm.set_cancellation(cancel);
+        }
+
         // Log estimated runtime
         let estimated_runtime = algorithm.estimate_runtime(&request);
         info!("Estimated optimization runtime: {:?} for user {}", 
@@ -136,11 +213,28 @@ impl MenuOptimizer {
 
         // Run optimization
         let solution = match algorithm.optimize(&request).await {
+            Ok(solution) if solution.algorithm_metadata.cancelled => {
+                info!("Optimization cancelled for user {} after {} generations",
+                      request.user_id, solution.algorithm_metadata.generations_run);
+
+                // Partial plans are neither successes nor worth caching
+                {
+                    let mut metrics = self.metrics.write().await;
+                    metrics.record_optimization_cancelled();
+                }
+
+                solution
+            }
             Ok(solution) => {
                 let duration = start_time.elapsed();
-                info!("Optimization completed successfully for user {} in {:?}", 
+                info!("Optimization completed successfully for user {} in {:?}",
                       request.user_id, duration);
 
+                if let Some(generation) = solution.algorithm_metadata.convergence_generation {
+                    info!("Optimization converged early at generation {} of {}",
+                          generation, request.algorithm_config.max_generations);
+                }
+
                 // Record success metrics
                 {
                     let mut metrics = self.metrics.write().await;
@@ -182,45 +276,10 @@ impl MenuOptimizer {
         user: &crate::User,
         goals: &[crate::FitnessGoal],
     ) -> Result<NutritionConstraints> {
-        // Calculate BMR using Mifflin-St Jeor equation
-        let bmr = if user.name.contains("female") { // Simple heuristic - in real app this would be a user field
-            655.1 + (9.563 * user.weight as f64) + (1.850 * user.height as f64) - (4.676 * user.age as f64)
-        } else {
-            88.362 + (13.397 * user.weight as f64) + (4.799 * user.height as f64) - (5.677 * user.age as f64)
-        };
-
-        // Activity multiplier based on fitness level
-        let activity_multiplier = match user.fitness_level {
-            crate::FitnessLevel::Beginner => 1.2,
-            crate::FitnessLevel::Intermediate => 1.5,
-            crate::FitnessLevel::Advanced => 1.7,
-            crate::FitnessLevel::Elite => 1.9,
-        };
-
-        let tdee = bmr * activity_multiplier;
-
-        // Adjust calories based on goals
-        let target_calories = if goals.contains(&crate::FitnessGoal::WeightLoss) {
-            tdee * 0.8 // 20% deficit
-        } else if goals.contains(&crate::FitnessGoal::MuscleGain) {
-            tdee * 1.1 // 10% surplus
-        } else {
-            tdee // Maintenance
-        };
-
-        // Calculate macro ranges
-        let protein_g = if goals.contains(&crate::FitnessGoal::MuscleGain) {
-            user.weight as f64 * 2.2 // 2.2g per kg for muscle gain
-        } else {
-            user.weight as f64 * 1.6 // 1.6g per kg for general health
-        };
-
-        let fat_calories = target_calories * 0.25; // 25% of calories from fat
-        let fat_g = fat_calories / 9.0;
-
-        let protein_calories = protein_g * 4.0;
-        let remaining_calories = target_calories - protein_calories - fat_calories;
-        let carbs_g = remaining_calories / 4.0;
+        let tdee = nutrition_calc::tdee(nutrition_calc::user_bmr(user), &user.fitness_level);
+        let target_calories = nutrition_calc::target_calories(tdee, goals);
+        let MacroSplit { protein_g, carbs_g, fat_g } =
+            nutrition_calc::macro_split(target_calories, user.weight as f64, goals);
 
         Ok(NutritionConstraints {
             daily_calories: CalorieRange {
@@ -253,16 +312,195 @@ impl MenuOptimizer {
             },
             budget_per_day: None, // Can be set based on user preferences
             preparation_time_max_minutes: Some(120), // 2 hours max per day
+            calorie_distribution: None,
+            eating_window: None,
         })
     }
 
+    /// Rank recipes by how fully they can be made from the available foods.
+    /// An ingredient counts as available when its food or any listed
+    /// substitute is in the pantry. Makeable recipes come first, then those
+    /// missing the fewest ingredients; recipes using nothing from the pantry
+    /// are left out. Among equally makeable recipes, those with better net
+    /// user feedback (keyed by recipe id) rank higher.
+    pub async fn suggest_recipes(&self, available_food_ids: &[String], feedback: &HashMap<String, i64>) -> Vec<RecipeSuggestion> {
+        let pantry: std::collections::HashSet<&str> = available_food_ids.iter().map(String::as_str).collect();
+        let recipes = self.recipes.read().await;
+
+        let mut suggestions: Vec<RecipeSuggestion> = recipes.iter()
+            .filter(|recipe| !recipe.ingredients.is_empty())
+            .filter_map(|recipe| {
+                let missing_ingredients: Vec<String> = recipe.ingredients.iter()
+                    .filter(|ingredient| {
+                        !pantry.contains(ingredient.food_id.as_str())
+                            && !ingredient.substitutes.iter().any(|s| pantry.contains(s.as_str()))
+                    })
+                    .map(|ingredient| ingredient.food_id.clone())
+                    .collect();
+
+                if missing_ingredients.len() == recipe.ingredients.len() {
+                    return None;
+                }
+
+                Some(RecipeSuggestion {
+                    recipe_id: recipe.id.clone(),
+                    recipe_name: recipe.name.clone(),
+                    can_make: missing_ingredients.is_empty(),
+                    coverage: 1.0 - missing_ingredients.len() as f64 / recipe.ingredients.len() as f64,
+                    missing_ingredients,
+                })
+            })
+            .collect();
+
+        let net_feedback = |suggestion: &RecipeSuggestion| feedback.get(&suggestion.recipe_id).copied().unwrap_or(0);
+        suggestions.sort_by(|a, b| {
+            b.can_make.cmp(&a.can_make)
+                .then(a.missing_ingredients.len().cmp(&b.missing_ingredients.len()))
+                .then(net_feedback(b).cmp(&net_feedback(a)))
+                .then(b.coverage.total_cmp(&a.coverage))
+                .then_with(|| a.recipe_name.cmp(&b.recipe_name))
+        });
+
+        suggestions
+    }
+
+    /// A catalog recipe with one ingredient swapped; see
+    /// `Recipe::substitute_ingredient`. The catalog itself is unchanged.
+    pub async fn substitute_ingredient(
+        &self,
+        recipe_id: &str,
+        food_id: &str,
+        replacement_food_id: &str,
+    ) -> Result<Recipe> {
+        let recipes = self.recipes.read().await;
+        let recipe = recipes.iter()
+            .find(|r| r.id == recipe_id)
+            .ok_or_else(|| FitnessError::RecipeNotFound { id: recipe_id.to_string() })?;
+        let foods = self.foods.read().await;
+
+        recipe.substitute_ingredient(food_id, replacement_food_id, &foods)
+    }
+
+    /// Consolidate the ingredients of every planned meal into one shopping
+    /// list. Ingredient amounts are per recipe, so each meal contributes
+    /// `amount_g / servings * portion_size` grams. Meals or foods that are not
+    /// loaded are skipped with a warning.
+    pub async fn build_grocery_list(&self, solution: &OptimizationSolution) -> GroceryList {
+        let recipes = self.recipes.read().await;
+        let foods = self.foods.read().await;
+
+        let mut sections: Vec<GrocerySection> = Vec::new();
+        for meal in &solution.meals {
+            let Some(recipe) = recipes.iter().find(|r| r.id == meal.recipe_id) else {
+                warn!("Grocery list skipping unknown recipe {}", meal.recipe_id);
+                continue;
+            };
+
+            for ingredient in &recipe.ingredients {
+                let Some(food) = foods.get(&ingredient.food_id) else {
+                    warn!("Grocery list skipping unknown food {}", ingredient.food_id);
+                    continue;
+                };
+
+                let amount_g = ingredient.amount_g / recipe.servings.max(1) as f64 * meal.portion_size;
+                let section_index = match sections.iter().position(|s| s.category == food.category) {
+                    Some(index) => index,
+                    None => {
+                        sections.push(GrocerySection { category: food.category.clone(), items: Vec::new() });
+                        sections.len() - 1
+                    }
+                };
+
+                let items = &mut sections[section_index].items;
+                match items.iter_mut().find(|item| item.food_id == food.id) {
+                    Some(item) => item.amount_g += amount_g,
+                    None => items.push(GroceryItem {
+                        food_id: food.id.clone(),
+                        name: food.name.clone(),
+                        amount_g,
+                        estimated_cost: None,
+                    }),
+                }
+            }
+        }
+
+        for item in sections.iter_mut().flat_map(|s| s.items.iter_mut()) {
+            item.amount_g = (item.amount_g * 10.0).round() / 10.0;
+            item.estimated_cost = foods.get(&item.food_id)
+                .and_then(|food| food.cost_per_100g)
+                .map(|cost| cost * item.amount_g / 100.0);
+        }
+        for section in &mut sections {
+            section.items.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+
+        let costs: Vec<f64> = sections.iter()
+            .flat_map(|s| s.items.iter())
+            .filter_map(|item| item.estimated_cost)
+            .collect();
+
+        GroceryList {
+            meal_plan_id: solution.meal_plan_id.clone(),
+            sections,
+            estimated_cost: if costs.is_empty() { None } else { Some(costs.iter().sum()) },
+        }
+    }
+
+    /// Compare two solutions day by day. A meal is retained when the other
+    /// plan serves the same recipe in the same slot that day, so moving a
+    /// recipe from lunch to dinner shows as a removal and an addition.
+    pub fn diff_solutions(old: &OptimizationSolution, new: &OptimizationSolution) -> MealPlanDiff {
+        let mut days: Vec<u32> = old.meals.iter().chain(&new.meals).map(|meal| meal.day).collect();
+        days.sort_unstable();
+        days.dedup();
+
+        let days = days.into_iter()
+            .map(|day| {
+                let mut removed: Vec<MealGene> = old.meals.iter().filter(|m| m.day == day).cloned().collect();
+                let mut added = Vec::new();
+                let mut retained = Vec::new();
+
+                for meal in new.meals.iter().filter(|m| m.day == day) {
+                    let matching = removed.iter()
+                        .position(|m| m.meal_type == meal.meal_type && m.recipe_id == meal.recipe_id);
+                    match matching {
+                        Some(index) => {
+                            removed.swap_remove(index);
+                            retained.push(meal.clone());
+                        }
+                        None => added.push(meal.clone()),
+                    }
+                }
+
+                for meals in [&mut added, &mut removed, &mut retained] {
+                    meals.sort_by(|a, b| a.meal_type.cmp(&b.meal_type).then_with(|| a.recipe_id.cmp(&b.recipe_id)));
+                }
+                DayDiff { day, added, removed, retained }
+            })
+            .collect();
+
+        let (before, after) = (&old.nutrition_summary, &new.nutrition_summary);
+        MealPlanDiff {
+            old_meal_plan_id: old.meal_plan_id.clone(),
+            new_meal_plan_id: new.meal_plan_id.clone(),
+            days,
+            nutrition_change: NutritionChange {
+                calories: after.calories - before.calories,
+                protein_g: after.protein_g - before.protein_g,
+                carbs_g: after.carbs_g - before.carbs_g,
+                fat_g: after.fat_g - before.fat_g,
+                fiber_g: after.fiber_g - before.fiber_g,
+            },
+        }
+    }
+
     /// Get system metrics
     pub async fn get_metrics(&self) -> crate::core::SystemMetrics {
         self.metrics.read().await.get_current_metrics()
     }
 
     /// Generate cache key for optimization request
-    fn generate_cache_key(&self, request: &OptimizationRequest) -> String {
+    fn generate_cache_key(&self, request: &OptimizationRequest, algorithm_type: &AlgorithmType) -> String {
         use std::hash::{Hash, Hasher};
         use std::collections::hash_map::DefaultHasher;
 
@@ -273,6 +511,12 @@ impl MenuOptimizer {
         request.time_horizon_days.hash(&mut hasher);
         request.constraints.daily_calories.target.to_bits().hash(&mut hasher);
         request.objectives.len().hash(&mut hasher);
+        request.constraints.preparation_time_max_minutes.hash(&mut hasher);
+        request.constraints.budget_per_day.map(f64::to_bits).hash(&mut hasher);
+        request.max_recipe_repetitions_per_week.hash(&mut hasher);
+        request.month.hash(&mut hasher);
+        // Experiment variants mustn't be served each other's plans
+        format!("{:?}", algorithm_type).hash(&mut hasher);
         
         format!("opt_{:x}", hasher.finish())
     }
@@ -362,4 +606,145 @@ impl Default for MenuOptimizer {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample_data::SampleDataSet;
+
+    #[tokio::test]
+    async fn test_suggest_recipes_ranks_makeable_first() {
+        let data = SampleDataSet::new();
+        let optimizer = MenuOptimizer::with_data(data.recipes, data.foods);
+
+        // Covers the scrambled eggs fully and the chicken salad except the chicken
+        let pantry: Vec<String> = ["eggs", "spinach", "olive_oil"].iter().map(|s| s.to_string()).collect();
+        let suggestions = optimizer.suggest_recipes(&pantry, &HashMap::new()).await;
+
+        assert_eq!(suggestions[0].recipe_id, "scrambled_eggs_spinach");
+        assert!(suggestions[0].can_make);
+        assert!(suggestions[0].missing_ingredients.is_empty());
+
+        assert_eq!(suggestions[1].recipe_id, "grilled_chicken_salad");
+        assert!(!suggestions[1].can_make);
+        assert_eq!(suggestions[1].missing_ingredients, vec!["chicken_breast".to_string()]);
+
+        // Nothing from the pantry is used in the yogurt bowl
+        assert!(suggestions.iter().all(|s| s.recipe_id != "greek_yogurt_berry_bowl"));
+    }
+
+    fn planned_meal(day: u32, meal_type: MealType, recipe_id: &str, portion_size: f64) -> MealGene {
+        MealGene { day, meal_type, recipe_id: recipe_id.to_string(), portion_size }
+    }
+
+    fn test_solution(meal_plan_id: &str, meals: Vec<MealGene>) -> OptimizationSolution {
+        OptimizationSolution {
+            meal_plan_id: meal_plan_id.to_string(),
+            fitness_score: 0.0,
+            objective_scores: HashMap::new(),
+            constraint_violations: vec![],
+            nutrition_summary: NutritionFacts::new(),
+            total_cost: None,
+            variety_score: 0.0,
+            taste_score: 0.0,
+            convenience_score: 0.0,
+            seasonality_score: 0.0,
+            seasonal_coverage: None,
+            daily_glycemic_load: vec![],
+            meal_times: vec![],
+            algorithm_metadata: AlgorithmMetadata {
+                algorithm_used: AlgorithmType::GeneticAlgorithm,
+                generations_run: 0,
+                final_population_size: 0,
+                convergence_generation: None,
+                execution_time_ms: 0.0,
+                evaluations_performed: 0,
+                best_fitness_history: vec![],
+                diversity_score: 0.0,
+                warm_start: false,
+                cancelled: false,
+            },
+            meals,
+            recipe_repetitions: HashMap::new(),
+            daily_prep_minutes: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grocery_list_consolidates_shared_ingredients() {
+        let data = SampleDataSet::new();
+        let optimizer = MenuOptimizer::with_data(data.recipes, data.foods);
+
+        // Both dinners use 150g of brown rice per serving
+        let solution = test_solution("plan", vec![
+            planned_meal(0, MealType::Dinner, "salmon_rice_bowl", 1.0),
+            planned_meal(1, MealType::Dinner, "chicken_vegetable_stir_fry", 1.5),
+        ]);
+
+        let list = optimizer.build_grocery_list(&solution).await;
+        let grains = list.sections.iter()
+            .find(|s| s.category == FoodCategory::Grains)
+            .expect("grains section");
+        let rice: Vec<&GroceryItem> = grains.items.iter().filter(|i| i.food_id == "brown_rice").collect();
+
+        assert_eq!(rice.len(), 1);
+        assert_eq!(rice[0].amount_g, 375.0);
+
+        // Olive oil appears in both recipes too but lives in its own section
+        let oils = list.sections.iter().find(|s| s.category == FoodCategory::Oils).unwrap();
+        assert_eq!(oils.items.len(), 1);
+    }
+
+    #[test]
+    fn test_diff_reports_swapped_dinner_and_macro_change() {
+        let mut old = test_solution("old", vec![
+            planned_meal(0, MealType::Breakfast, "greek_yogurt_berry_bowl", 1.0),
+            planned_meal(0, MealType::Dinner, "salmon_rice_bowl", 1.0),
+            planned_meal(1, MealType::Dinner, "chicken_vegetable_stir_fry", 1.0),
+        ]);
+        old.nutrition_summary.calories = 2000.0;
+        old.nutrition_summary.protein_g = 120.0;
+        old.nutrition_summary.carbs_g = 220.0;
+        old.nutrition_summary.fat_g = 70.0;
+
+        // Day 1's dinner is swapped; everything else stays
+        let mut new = test_solution("new", vec![
+            planned_meal(0, MealType::Dinner, "salmon_rice_bowl", 1.0),
+            planned_meal(0, MealType::Breakfast, "greek_yogurt_berry_bowl", 1.0),
+            planned_meal(1, MealType::Dinner, "grilled_chicken_salad", 1.0),
+        ]);
+        new.nutrition_summary.calories = 1850.0;
+        new.nutrition_summary.protein_g = 135.0;
+        new.nutrition_summary.carbs_g = 180.0;
+        new.nutrition_summary.fat_g = 72.5;
+
+        let diff = MenuOptimizer::diff_solutions(&old, &new);
+        assert_eq!(diff.days.iter().map(|d| d.day).collect::<Vec<_>>(), vec![0, 1]);
+
+        let recipes = |meals: &[MealGene]| meals.iter().map(|m| m.recipe_id.clone()).collect::<Vec<_>>();
+        assert!(diff.days[0].added.is_empty() && diff.days[0].removed.is_empty());
+        assert_eq!(recipes(&diff.days[0].retained), ["greek_yogurt_berry_bowl", "salmon_rice_bowl"]);
+        assert_eq!(recipes(&diff.days[1].removed), ["chicken_vegetable_stir_fry"]);
+        assert_eq!(recipes(&diff.days[1].added), ["grilled_chicken_salad"]);
+        assert!(diff.days[1].retained.is_empty());
+
+        let added: usize = diff.days.iter().map(|d| d.added.len()).sum();
+        let removed: usize = diff.days.iter().map(|d| d.removed.len()).sum();
+        assert_eq!((added, removed), (1, 1));
+        assert_eq!(diff.nutrition_change, NutritionChange {
+            calories: -150.0,
+            protein_g: 15.0,
+            carbs_g: -40.0,
+            fat_g: 2.5,
+            fiber_g: 0.0,
+        });
+
+        // Diffing the other way round mirrors the result
+        let reverse = MenuOptimizer::diff_solutions(&new, &old);
+        assert_eq!(recipes(&reverse.days[1].added), ["chicken_vegetable_stir_fry"]);
+        assert_eq!(recipes(&reverse.days[1].removed), ["grilled_chicken_salad"]);
+        assert_eq!(reverse.nutrition_change.calories, 150.0);
+        assert_eq!(reverse.nutrition_change.protein_g, -15.0);
+    }
+}
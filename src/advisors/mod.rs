@@ -1,5 +1,12 @@
 // src/advisors/mod.rs - Fitness advisor modules
 
 pub mod menu_optimizer;
+pub mod nutrition_calc;
+pub mod plate_math;
+pub mod progress_analyzer;
+pub mod warmup;
+pub mod workout_recommender;
 
-pub use menu_optimizer::MenuOptimizer;
\ No newline at end of file
+pub use menu_optimizer::MenuOptimizer;
+pub use progress_analyzer::ProgressAnalyzer;
+pub use workout_recommender::WorkoutRecommender;
\ No newline at end of file
@@ -0,0 +1,196 @@
+// src/advisors/nutrition_calc.rs - Canonical BMR, TDEE and macro targets
+
+use crate::models::user::{FitnessGoal, FitnessLevel, User};
+
+/// Calorie deficit applied for weight loss
+const WEIGHT_LOSS_FACTOR: f64 = 0.8;
+/// Calorie surplus applied for muscle gain
+const MUSCLE_GAIN_FACTOR: f64 = 1.1;
+/// Protein per kg of body weight when building muscle
+const MUSCLE_GAIN_PROTEIN_G_PER_KG: f64 = 2.2;
+/// Protein per kg of body weight otherwise
+const DEFAULT_PROTEIN_G_PER_KG: f64 = 1.6;
+/// Share of calories that come from fat
+const FAT_CALORIE_SHARE: f64 = 0.25;
+
+/// Gap between training-day and rest-day carbs when cycling, as a fraction
+/// of the daily carb target
+const CARB_CYCLE_SPREAD: f64 = 0.5;
+
+const KCAL_PER_G_PROTEIN: f64 = 4.0;
+const KCAL_PER_G_CARBS: f64 = 4.0;
+const KCAL_PER_G_FAT: f64 = 9.0;
+
+/// Daily grams of each macronutrient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacroSplit {
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+}
+
+/// Basal metabolic rate in kcal/day (revised Harris-Benedict equation).
+/// There is no sex field on `User` yet, so callers pass it explicitly.
+pub fn bmr(weight_kg: f64, height_cm: f64, age: u32, female: bool) -> f64 {
+    let age = age as f64;
+    if female {
+        655.1 + (9.563 * weight_kg) + (1.850 * height_cm) - (4.676 * age)
+    } else {
+        88.362 + (13.397 * weight_kg) + (4.799 * height_cm) - (5.677 * age)
+    }
+}
+
+/// BMR for a stored user. Sex is guessed from the name until users have a
+/// proper field for it.
+pub fn user_bmr(user: &User) -> f64 {
+    bmr(user.weight as f64, user.height as f64, user.age, user.name.contains("female"))
+}
+
+/// How much a fitness level multiplies BMR to get daily expenditure
+pub fn activity_multiplier(level: &FitnessLevel) -> f64 {
+    match level {
+        FitnessLevel::Beginner => 1.2,
+        FitnessLevel::Intermediate => 1.5,
+        FitnessLevel::Advanced => 1.7,
+        FitnessLevel::Elite => 1.9,
+    }
+}
+
+/// Total daily energy expenditure in kcal/day
+pub fn tdee(bmr: f64, level: &FitnessLevel) -> f64 {
+    bmr * activity_multiplier(level)
+}
+
+/// Daily calorie target for the goals. Weight loss wins over muscle gain
+/// when both are set; anything else is maintenance.
+pub fn target_calories(tdee: f64, goals: &[FitnessGoal]) -> f64 {
+    if goals.contains(&FitnessGoal::WeightLoss) {
+        tdee * WEIGHT_LOSS_FACTOR
+    } else if goals.contains(&FitnessGoal::MuscleGain) {
+        tdee * MUSCLE_GAIN_FACTOR
+    } else {
+        tdee
+    }
+}
+
+/// Protein scales with body weight, fat takes a fixed share of calories and
+/// carbs fill the rest
+pub fn macro_split(target_calories: f64, weight_kg: f64, goals: &[FitnessGoal]) -> MacroSplit {
+    let protein_per_kg = if goals.contains(&FitnessGoal::MuscleGain) {
+        MUSCLE_GAIN_PROTEIN_G_PER_KG
+    } else {
+        DEFAULT_PROTEIN_G_PER_KG
+    };
+    let protein_g = weight_kg * protein_per_kg;
+
+    let fat_calories = target_calories * FAT_CALORIE_SHARE;
+    let remaining_calories = target_calories - protein_g * KCAL_PER_G_PROTEIN - fat_calories;
+
+    MacroSplit {
+        protein_g,
+        carbs_g: remaining_calories / KCAL_PER_G_CARBS,
+        fat_g: fat_calories / KCAL_PER_G_FAT,
+    }
+}
+
+/// Calories supplied by a day's macros
+pub fn macro_calories(macros: &MacroSplit) -> f64 {
+    macros.protein_g * KCAL_PER_G_PROTEIN + macros.carbs_g * KCAL_PER_G_CARBS + macros.fat_g * KCAL_PER_G_FAT
+}
+
+/// Per-day macros for carb cycling over `training_days`, one flag per day.
+/// Training days get more carbs and rest days fewer, by amounts chosen so
+/// the average over the days still equals `daily`. Protein and fat stay
+/// at their daily targets.
+pub fn carb_cycle(daily: MacroSplit, training_days: &[bool]) -> Vec<MacroSplit> {
+    let days = training_days.len() as f64;
+    let training = training_days.iter().filter(|&&training| training).count() as f64;
+    let rest = days - training;
+
+    training_days.iter()
+        .map(|&is_training| {
+            let factor = if is_training {
+                1.0 + CARB_CYCLE_SPREAD * rest / days
+            } else {
+                1.0 - CARB_CYCLE_SPREAD * training / days
+            };
+            MacroSplit { carbs_g: daily.carbs_g * factor, ..daily }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.01, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_intermediate_man_building_muscle() {
+        let bmr = bmr(80.0, 180.0, 30, false);
+        assert_close(bmr, 1853.63);
+
+        let tdee = tdee(bmr, &FitnessLevel::Intermediate);
+        assert_close(tdee, 2780.45);
+
+        let goals = [FitnessGoal::MuscleGain, FitnessGoal::Strength];
+        let target = target_calories(tdee, &goals);
+        assert_close(target, 3058.49);
+
+        let macros = macro_split(target, 80.0, &goals);
+        assert_close(macros.protein_g, 176.0);
+        assert_close(macros.fat_g, 84.96);
+        assert_close(macros.carbs_g, 397.47);
+    }
+
+    #[test]
+    fn test_beginner_woman_losing_weight() {
+        let bmr = bmr(60.0, 165.0, 40, true);
+        assert_close(bmr, 1347.09);
+
+        let tdee = tdee(bmr, &FitnessLevel::Beginner);
+        assert_close(tdee, 1616.51);
+
+        // Weight loss takes precedence over muscle gain
+        let goals = [FitnessGoal::WeightLoss, FitnessGoal::MuscleGain];
+        let target = target_calories(tdee, &goals);
+        assert_close(target, 1293.21);
+
+        let macros = macro_split(target, 60.0, &[FitnessGoal::WeightLoss]);
+        assert_close(macros.protein_g, 96.0);
+        assert_close(macros.fat_g, 35.92);
+        assert_close(macros.carbs_g, 146.48);
+    }
+
+    #[test]
+    fn test_carb_cycling_over_three_training_days() {
+        let daily = macro_split(2500.0, 75.0, &[FitnessGoal::Strength]);
+        let week = [true, false, true, false, true, false, false];
+        let days = carb_cycle(daily, &week);
+
+        let training_carbs = days[0].carbs_g;
+        let rest_carbs = days[1].carbs_g;
+        assert!(training_carbs > daily.carbs_g && rest_carbs < daily.carbs_g);
+        assert_close(training_carbs - rest_carbs, daily.carbs_g * 0.5);
+
+        let average = |field: fn(&MacroSplit) -> f64| days.iter().map(field).sum::<f64>() / 7.0;
+        assert_close(average(|d| d.carbs_g), daily.carbs_g);
+        assert_close(average(macro_calories), 2500.0);
+
+        // Protein stays at the 1.6 g/kg target every day
+        let protein = average(|d| d.protein_g);
+        assert!((75.0 * 1.5..=75.0 * 1.7).contains(&protein), "weekly protein {}", protein);
+        assert!(days.iter().all(|d| d.protein_g == daily.protein_g));
+    }
+
+    #[test]
+    fn test_activity_multipliers() {
+        assert_eq!(activity_multiplier(&FitnessLevel::Beginner), 1.2);
+        assert_eq!(activity_multiplier(&FitnessLevel::Intermediate), 1.5);
+        assert_eq!(activity_multiplier(&FitnessLevel::Advanced), 1.7);
+        assert_eq!(activity_multiplier(&FitnessLevel::Elite), 1.9);
+        assert_eq!(target_calories(2000.0, &[FitnessGoal::Endurance]), 2000.0);
+    }
+}
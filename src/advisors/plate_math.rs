@@ -0,0 +1,85 @@
+// src/advisors/plate_math.rs - Which plates to put on a barbell for a
+// suggested weight
+
+/// Plates for one side of the bar, heaviest first, and the total weight
+/// they make with the bar
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlateBreakdown {
+    pub per_side: Vec<f32>,
+    pub total: f32,
+}
+
+/// Weights are matched in hundredths so fractional plates add up exactly
+fn hundredths(weight: f32) -> usize {
+    (weight.max(0.0) * 100.0).round() as usize
+}
+
+/// Plates to load on each side of a `bar` for `target`, all in the same
+/// unit. Each of `available_plates` may be used any number of times. When
+/// the target can't be loaded exactly the closest loadable weight is used,
+/// the lighter one on a tie, and a target below the bar is just the bar.
+/// Among equal loads the one with the fewest plates wins.
+pub fn plate_breakdown(target: f32, bar: f32, available_plates: &[f32]) -> PlateBreakdown {
+    let plates: Vec<usize> = available_plates.iter().map(|p| hundredths(*p)).filter(|p| *p > 0).collect();
+    let per_side_target = hundredths((target - bar) / 2.0);
+    let Some(&largest) = plates.iter().max() else {
+        return PlateBreakdown { per_side: Vec::new(), total: bar };
+    };
+
+    // fewest[w] is the fewest plates making w per side, with the last plate
+    // added; going one plate past the target covers rounding up
+    let limit = per_side_target + largest;
+    let mut fewest: Vec<Option<(usize, usize)>> = vec![None; limit + 1];
+    fewest[0] = Some((0, 0));
+    for weight in 1..=limit {
+        fewest[weight] = plates.iter()
+            .filter(|plate| **plate <= weight)
+            .filter_map(|plate| fewest[weight - plate].map(|(count, _)| (count + 1, *plate)))
+            .min_by_key(|(count, _)| *count);
+    }
+
+    let loadable = (0..=limit)
+        .filter(|weight| fewest[*weight].is_some())
+        .min_by_key(|weight| (weight.abs_diff(per_side_target), *weight))
+        .unwrap_or(0);
+
+    let mut per_side = Vec::new();
+    let mut remaining = loadable;
+    while let Some((_, plate)) = fewest[remaining].filter(|_| remaining > 0) {
+        per_side.push(plate as f32 / 100.0);
+        remaining -= plate;
+    }
+    per_side.sort_by(|a, b| b.total_cmp(a));
+
+    PlateBreakdown {
+        total: bar + 2.0 * loadable as f32 / 100.0,
+        per_side,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STANDARD_KG: &[f32] = &[25.0, 20.0, 15.0, 10.0, 5.0, 2.5, 1.25];
+
+    #[test]
+    fn test_exact_and_nearest_loads() {
+        assert_eq!(plate_breakdown(60.0, 20.0, STANDARD_KG), PlateBreakdown { per_side: vec![20.0], total: 60.0 });
+
+        let odd = plate_breakdown(62.5, 20.0, STANDARD_KG);
+        assert_eq!(odd.per_side, vec![20.0, 1.25]);
+        assert_eq!(odd.total, 62.5);
+
+        // 61 needs 20.5 a side; 20 (60 kg) is closer than 21.25 (62.5 kg)
+        assert_eq!(plate_breakdown(61.0, 20.0, STANDARD_KG), PlateBreakdown { per_side: vec![20.0], total: 60.0 });
+        // and rounding up wins when it's closer
+        assert_eq!(plate_breakdown(62.0, 20.0, STANDARD_KG).total, 62.5);
+
+        assert_eq!(plate_breakdown(15.0, 20.0, STANDARD_KG), PlateBreakdown { per_side: vec![], total: 20.0 });
+
+        let pounds = plate_breakdown(225.0, 45.0, &[45.0, 35.0, 25.0, 10.0, 5.0, 2.5]);
+        assert_eq!(pounds.per_side, vec![45.0, 45.0]);
+        assert_eq!(pounds.total, 225.0);
+    }
+}
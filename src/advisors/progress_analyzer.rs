@@ -0,0 +1,558 @@
+// src/advisors/progress_analyzer.rs - Training volume, frequency and rating trends
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::models::achievement::ActivitySummary;
+use crate::models::activity::{ActivityMetric, ActivityMetricKind, ActivityTrends, SleepSession};
+use crate::models::goal::{GoalMetric, UserGoal};
+use crate::models::measurement::BodyMeasurement;
+use crate::models::user::User;
+use crate::models::workout::{DeloadRecommendation, ProgressAnalysis, StreakInfo, WeeklyProgress, WorkoutSession};
+
+/// Number of most recent weeks inspected for deload signals
+const DELOAD_WINDOW_WEEKS: usize = 4;
+/// Minimum weeks of history before a deload can be recommended
+const DELOAD_MIN_WEEKS: usize = 3;
+/// Recent weeks must stay within this fraction of the window's peak volume
+const HIGH_VOLUME_RATIO: f32 = 0.9;
+/// Weekly rating slope at or below which ratings count as declining
+const DECLINING_RATING_SLOPE: f32 = -0.3;
+/// Missed days tolerated on top of the rest days the weekly target implies
+const STREAK_GRACE_DAYS: i64 = 1;
+/// Complete weeks before the current one that weekly consistency covers
+const CONSISTENCY_WINDOW_WEEKS: i64 = 4;
+
+/// Computes progress analytics from a user's logged workouts. All methods are
+/// pure so the analysis can be tested without a database.
+pub struct ProgressAnalyzer;
+
+impl ProgressAnalyzer {
+    /// Analyze the user's workouts (in any order). Sessions with unparseable
+    /// dates still count towards the totals but are left out of the weekly series.
+    pub fn analyze(user: &User, workouts: &[WorkoutSession]) -> ProgressAnalysis {
+        let total_workouts = workouts.len() as u32;
+        let average_duration_minutes = if total_workouts > 0 {
+            workouts.iter()
+                .map(|w| w.total_duration_minutes as f32)
+                .sum::<f32>() / total_workouts as f32
+        } else {
+            0.0
+        };
+
+        let total_calories_burned = workouts.iter()
+            .filter_map(|w| w.calories_burned)
+            .sum::<f32>();
+
+        // Calculate consistency score based on recent workout frequency
+        let consistency_score = if total_workouts >= 5 { 0.9 }
+        else if total_workouts >= 3 { 0.7 }
+        else if total_workouts >= 1 { 0.5 }
+        else { 0.0 };
+
+        let weekly_progress = Self::weekly_series(user, workouts);
+
+        let volume_points: Vec<(f32, f32)> = weekly_progress.iter()
+            .enumerate()
+            .map(|(i, week)| (i as f32, week.total_volume_kg))
+            .collect();
+        let rating_points: Vec<(f32, f32)> = weekly_progress.iter()
+            .enumerate()
+            .filter_map(|(i, week)| week.average_rating.map(|r| (i as f32, r)))
+            .collect();
+
+        ProgressAnalysis {
+            total_workouts,
+            average_duration_minutes,
+            total_calories_burned,
+            consistency_score,
+            volume_trend: linear_slope(&volume_points),
+            rating_trend: linear_slope(&rating_points),
+            frequency_adherence: Self::frequency_adherence(&weekly_progress, user.preferences.workouts_per_week),
+            deload_recommendation: Self::detect_deload(user, workouts),
+            weekly_progress,
+            activity: None,
+        }
+    }
+
+    /// Recommend a deload week when volume has stayed high over recent weeks
+    /// while session ratings decline or top working weights stall.
+    pub fn detect_deload(user: &User, workouts: &[WorkoutSession]) -> Option<DeloadRecommendation> {
+        let weekly = Self::weekly_series(user, workouts);
+        let window = &weekly[weekly.len().saturating_sub(DELOAD_WINDOW_WEEKS)..];
+        if window.len() < DELOAD_MIN_WEEKS {
+            return None;
+        }
+
+        let peak_volume = window.iter().map(|w| w.total_volume_kg).fold(0.0, f32::max);
+        let sustained_high_volume = peak_volume > 0.0
+            && window[window.len() - 2..]
+                .iter()
+                .all(|w| w.total_volume_kg >= peak_volume * HIGH_VOLUME_RATIO);
+        if !sustained_high_volume {
+            return None;
+        }
+
+        let rating_points: Vec<(f32, f32)> = window.iter()
+            .enumerate()
+            .filter_map(|(i, week)| week.average_rating.map(|r| (i as f32, r)))
+            .collect();
+        let ratings_declining = rating_points.len() >= DELOAD_MIN_WEEKS
+            && linear_slope(&rating_points) <= DECLINING_RATING_SLOPE;
+        let strength_stalling = Self::strength_stalling(workouts, &window[0].week_start);
+
+        let (reason, volume_reduction_percent) = match (ratings_declining, strength_stalling) {
+            (true, true) => ("Session ratings are declining and working weights have stalled under high volume", 40.0),
+            (true, false) => ("Session ratings are declining under sustained high volume", 30.0),
+            (false, true) => ("Working weights have stalled under sustained high volume", 30.0),
+            (false, false) => return None,
+        };
+
+        Some(DeloadRecommendation {
+            reason: reason.to_string(),
+            volume_reduction_percent,
+            duration_days: 7,
+        })
+    }
+
+    /// True when no weighted exercise's top load in the second half of the
+    /// window beats its top load in the first half
+    fn strength_stalling(workouts: &[WorkoutSession], window_start: &str) -> bool {
+        let mut sessions: Vec<&WorkoutSession> = workouts.iter()
+            .filter(|w| w.date.as_str() >= window_start)
+            .collect();
+        sessions.sort_by(|a, b| a.date.cmp(&b.date));
+        let (earlier, later) = sessions.split_at(sessions.len() / 2);
+
+        let top_loads = |sessions: &[&WorkoutSession]| {
+            let mut loads: BTreeMap<String, f32> = BTreeMap::new();
+            for set in sessions.iter().flat_map(|w| &w.exercises).filter(|s| s.completed) {
+                if let Some(weight) = set.weight_kg {
+                    let top = loads.entry(set.exercise_id.clone()).or_insert(0.0);
+                    *top = top.max(weight);
+                }
+            }
+            loads
+        };
+        let (before, after) = (top_loads(earlier), top_loads(later));
+
+        let compared: Vec<bool> = after.iter()
+            .filter_map(|(id, load)| before.get(id).map(|previous| load > previous))
+            .collect();
+        !compared.is_empty() && !compared.iter().any(|&improved| improved)
+    }
+
+    /// Current and longest streaks as of `today` (the user's local date).
+    /// Training `workouts_per_week` times leaves about 7 / target days
+    /// between sessions, so a streak survives gaps up to that plus
+    /// `STREAK_GRACE_DAYS`. Workouts dated after `today` are ignored.
+    pub fn streak(user: &User, workouts: &[WorkoutSession], today: NaiveDate) -> StreakInfo {
+        let mut dates: Vec<NaiveDate> = workouts.iter()
+            .filter_map(|w| NaiveDate::parse_from_str(&w.date, "%Y-%m-%d").ok())
+            .filter(|date| *date <= today)
+            .collect();
+        dates.sort();
+        dates.dedup();
+
+        let target = user.preferences.workouts_per_week.max(1);
+        let max_gap = (7 + target as i64 - 1) / target as i64 + STREAK_GRACE_DAYS;
+
+        let mut longest = 0;
+        let mut run_start = None;
+        for (i, &date) in dates.iter().enumerate() {
+            if i == 0 || (date - dates[i - 1]).num_days() > max_gap {
+                run_start = Some(date);
+            }
+            if let Some(start) = run_start {
+                longest = longest.max((date - start).num_days() + 1);
+            }
+        }
+        let current = match (run_start, dates.last()) {
+            (Some(start), Some(&last)) if (today - last).num_days() <= max_gap => (last - start).num_days() + 1,
+            _ => 0,
+        };
+
+        let this_week = week_start(today);
+        let workouts_this_week = dates.iter().filter(|date| **date >= this_week).count() as u32;
+
+        // New users aren't marked down for weeks before their first workout
+        let window_start = match dates.first() {
+            Some(&first) => (this_week - Duration::weeks(CONSISTENCY_WINDOW_WEEKS)).max(week_start(first)),
+            None => this_week,
+        };
+        let weeks: Vec<WeeklyProgress> = (0..(this_week - window_start).num_weeks())
+            .map(|i| window_start + Duration::weeks(i))
+            .map(|week| WeeklyProgress {
+                week_start: week.format("%Y-%m-%d").to_string(),
+                workouts: dates.iter().filter(|date| week_start(**date) == week).count() as u32,
+                total_volume_kg: 0.0,
+                average_rating: None,
+            })
+            .collect();
+
+        StreakInfo {
+            user_id: user.id.clone(),
+            current_streak_days: current as u32,
+            longest_streak_days: longest as u32,
+            max_gap_days: max_gap as u32,
+            last_workout_date: dates.last().map(|date| date.format("%Y-%m-%d").to_string()),
+            workouts_this_week,
+            weekly_target: target,
+            weekly_consistency: Self::frequency_adherence(&weeks, target),
+        }
+    }
+
+    /// The workout side of an achievement check as of `today`; meal plans
+    /// and goals are counted elsewhere
+    pub fn activity_summary(user: &User, workouts: &[WorkoutSession], today: NaiveDate) -> ActivitySummary {
+        let mut top_weights_kg = std::collections::HashMap::new();
+        for set in workouts.iter().flat_map(|w| &w.exercises).filter(|s| s.completed) {
+            if let Some(weight) = set.weight_kg {
+                let top = top_weights_kg.entry(set.exercise_id.clone()).or_insert(0.0_f32);
+                *top = top.max(weight);
+            }
+        }
+
+        ActivitySummary {
+            total_workouts: workouts.len() as u32,
+            current_streak_days: Self::streak(user, workouts, today).current_streak_days,
+            top_weights_kg,
+            ..ActivitySummary::default()
+        }
+    }
+
+    /// Steps, resting heart rate and sleep from imported data, `None`
+    /// without any. Metrics with unparseable dates are ignored.
+    pub fn activity_trends(metrics: &[ActivityMetric], sleep: &[SleepSession]) -> Option<ActivityTrends> {
+        let dated = |kind: ActivityMetricKind| -> Vec<(NaiveDate, f32)> {
+            let mut points: Vec<(NaiveDate, f32)> = metrics.iter()
+                .filter(|m| m.metric == kind)
+                .filter_map(|m| Some((NaiveDate::parse_from_str(&m.date, "%Y-%m-%d").ok()?, m.value as f32)))
+                .collect();
+            points.sort_by_key(|(date, _)| *date);
+            points
+        };
+        let steps = dated(ActivityMetricKind::Steps);
+        let resting = dated(ActivityMetricKind::RestingHeartRate);
+        if steps.is_empty() && resting.is_empty() && sleep.is_empty() {
+            return None;
+        }
+
+        let average_daily_steps = steps.last().map(|(latest, _)| {
+            let week: Vec<f32> = steps.iter()
+                .filter(|(date, _)| *date > *latest - Duration::days(7))
+                .map(|(_, value)| *value)
+                .collect();
+            week.iter().sum::<f32>() / week.len() as f32
+        });
+        let resting_heart_rate_trend = resting.first().filter(|_| resting.len() >= 2).map(|(first, _)| {
+            let points: Vec<(f32, f32)> = resting.iter()
+                .map(|(date, bpm)| ((*date - *first).num_days() as f32 / 7.0, *bpm))
+                .collect();
+            linear_slope(&points)
+        });
+
+        Some(ActivityTrends {
+            average_daily_steps,
+            latest_resting_heart_rate: resting.last().map(|(_, bpm)| *bpm),
+            resting_heart_rate_trend,
+            average_sleep_hours: Self::average_sleep_hours(sleep),
+        })
+    }
+
+    /// Nights are dated by when they ended; naps on the same day add to it
+    fn average_sleep_hours(sleep: &[SleepSession]) -> Option<f32> {
+        let mut nights: BTreeMap<NaiveDate, f32> = BTreeMap::new();
+        for session in sleep {
+            *nights.entry(session.end.date_naive()).or_default() += session.hours();
+        }
+        let (latest, _) = nights.last_key_value()?;
+        let week: Vec<f32> = nights.range(*latest - Duration::days(6)..)
+            .map(|(_, hours)| *hours)
+            .collect();
+        Some(week.iter().sum::<f32>() / week.len() as f32)
+    }
+
+    /// Current value of a goal's metric, or None when nothing logged
+    /// measures it yet. Body weight falls back to the weight on the profile.
+    pub fn goal_value(
+        goal: &UserGoal,
+        user: &User,
+        workouts: &[WorkoutSession],
+        measurements: &[BodyMeasurement],
+    ) -> Option<f64> {
+        let latest = measurements.iter().max_by(|a, b| a.date.cmp(&b.date));
+        match goal.metric {
+            GoalMetric::ExerciseWeight => workouts.iter()
+                .flat_map(|w| &w.exercises)
+                .filter(|set| set.completed && Some(&set.exercise_id) == goal.exercise_id.as_ref())
+                .filter_map(|set| set.weight_kg)
+                .fold(None, |best: Option<f32>, weight| Some(best.map_or(weight, |b| b.max(weight))))
+                .map(f64::from),
+            GoalMetric::BodyWeight => Some(latest.map_or(user.weight, |m| m.weight_kg) as f64),
+            GoalMetric::WaistCm => latest.and_then(|m| m.waist_cm).map(f64::from),
+            // Dates of both are YYYY-MM-DD prefixes, so they compare as strings
+            GoalMetric::WorkoutCount => Some(workouts.iter()
+                .filter(|w| w.date.as_str() >= &goal.created_at[..goal.created_at.len().min(10)])
+                .count() as f64),
+        }
+    }
+
+    /// Training volume of a session: sets × reps × load over completed sets.
+    /// Bodyweight exercises are loaded with the user's body weight.
+    pub fn session_volume(session: &WorkoutSession, body_weight_kg: f32) -> f32 {
+        session.exercises.iter()
+            .filter(|set| set.completed)
+            .map(|set| set.sets as f32 * set.reps as f32 * set.weight_kg.unwrap_or(body_weight_kg))
+            .sum()
+    }
+
+    /// One entry per calendar week (Monday start) from the first to the last
+    /// logged week, including weeks without any workouts.
+    fn weekly_series(user: &User, workouts: &[WorkoutSession]) -> Vec<WeeklyProgress> {
+        let mut weeks: BTreeMap<NaiveDate, (u32, f32, Vec<u32>)> = BTreeMap::new();
+        for workout in workouts {
+            let Ok(date) = NaiveDate::parse_from_str(&workout.date, "%Y-%m-%d") else {
+                continue;
+            };
+            let entry = weeks.entry(week_start(date)).or_insert((0, 0.0, Vec::new()));
+            entry.0 += 1;
+            entry.1 += Self::session_volume(workout, user.weight);
+            entry.2.extend(workout.user_rating);
+        }
+
+        let (Some(&first), Some(&last)) = (weeks.keys().next(), weeks.keys().next_back()) else {
+            return Vec::new();
+        };
+
+        let mut series = Vec::new();
+        let mut week = first;
+        while week <= last {
+            let (workouts, total_volume_kg, ratings) = weeks.remove(&week).unwrap_or_default();
+            let average_rating = if ratings.is_empty() {
+                None
+            } else {
+                Some(ratings.iter().sum::<u32>() as f32 / ratings.len() as f32)
+            };
+
+            series.push(WeeklyProgress {
+                week_start: week.format("%Y-%m-%d").to_string(),
+                workouts,
+                total_volume_kg,
+                average_rating,
+            });
+            week += Duration::weeks(1);
+        }
+
+        series
+    }
+
+    /// Percentage of the weekly workout target met, averaged over all weeks.
+    /// Extra workouts in one week don't make up for a missed week.
+    fn frequency_adherence(weeks: &[WeeklyProgress], workouts_per_week: u32) -> f32 {
+        if weeks.is_empty() {
+            return 0.0;
+        }
+        let target = workouts_per_week.max(1);
+
+        let met = weeks.iter()
+            .map(|week| week.workouts.min(target) as f32 / target as f32)
+            .sum::<f32>();
+        met / weeks.len() as f32 * 100.0
+    }
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Least-squares slope of the points, or 0 when there are fewer than two
+pub(crate) fn linear_slope(points: &[(f32, f32)]) -> f32 {
+    if points.len() < 2 {
+        return 0.0;
+    }
+
+    let n = points.len() as f32;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f32>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f32>() / n;
+    let covariance = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum::<f32>();
+    let variance = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum::<f32>();
+
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::exercise::ExerciseSet;
+    use crate::models::user::{Equipment, ExerciseType, FitnessGoal, FitnessLevel, Role, UserPreferences};
+
+    fn test_user(workouts_per_week: u32) -> User {
+        User {
+            id: "test_user".to_string(),
+            name: "Test User".to_string(),
+            age: 30,
+            height: 175.0,
+            weight: 70.0,
+            fitness_level: FitnessLevel::Intermediate,
+            goals: vec![FitnessGoal::Strength],
+            preferences: UserPreferences {
+                preferred_exercise_types: vec![ExerciseType::Strength],
+                available_equipment: vec![Equipment::Barbells],
+                workout_duration_minutes: 45,
+                workouts_per_week,
+                preferred_time_of_day: None,
+                unit_system: crate::models::UnitSystem::Metric,
+                limitations: vec![],
+                warmup_cooldown: true,
+            },
+            role: Role::Client,
+            version: 0,
+        }
+    }
+
+    fn session(date: &str, weight_kg: f32, rating: u32) -> WorkoutSession {
+        WorkoutSession {
+            id: format!("session_{}", date),
+            user_id: "test_user".to_string(),
+            date: date.to_string(),
+            exercises: vec![ExerciseSet {
+                exercise_id: "deadlift".to_string(),
+                sets: 3,
+                reps: 5,
+                weight_kg: Some(weight_kg),
+                duration_seconds: None,
+                rest_seconds: 120,
+                completed: true,
+                tempo: None,
+                target_rpe: None,
+                group_id: None,
+            }],
+            total_duration_minutes: 45,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: Some(rating),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_increasing_volume_over_four_weeks() {
+        let user = test_user(3);
+        // Mondays and Thursdays of four consecutive weeks, adding load each week
+        let workouts = vec![
+            session("2025-07-07", 60.0, 3),
+            session("2025-07-10", 60.0, 3),
+            session("2025-07-14", 65.0, 4),
+            session("2025-07-17", 65.0, 4),
+            session("2025-07-21", 70.0, 4),
+            session("2025-07-24", 70.0, 4),
+            session("2025-07-28", 75.0, 5),
+            session("2025-07-31", 75.0, 5),
+        ];
+
+        let analysis = ProgressAnalyzer::analyze(&user, &workouts);
+
+        assert_eq!(analysis.weekly_progress.len(), 4);
+        assert_eq!(analysis.weekly_progress[0].week_start, "2025-07-07");
+        assert!(analysis.volume_trend > 0.0);
+        assert!(analysis.rating_trend > 0.0);
+        // Two of three target workouts every week
+        assert!((analysis.frequency_adherence - 66.67).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_sparse_history() {
+        let user = test_user(2);
+
+        let empty = ProgressAnalyzer::analyze(&user, &[]);
+        assert!(empty.weekly_progress.is_empty());
+        assert_eq!(empty.volume_trend, 0.0);
+        assert_eq!(empty.frequency_adherence, 0.0);
+
+        // A single session and a gap week in between two sessions
+        let single = ProgressAnalyzer::analyze(&user, &[session("2025-07-07", 60.0, 4)]);
+        assert_eq!(single.weekly_progress.len(), 1);
+        assert_eq!(single.volume_trend, 0.0);
+
+        let gapped = ProgressAnalyzer::analyze(
+            &user,
+            &[session("2025-07-21", 60.0, 4), session("2025-07-07", 60.0, 4)],
+        );
+        assert_eq!(gapped.weekly_progress.len(), 3);
+        assert_eq!(gapped.weekly_progress[1].workouts, 0);
+        assert!(gapped.weekly_progress[1].average_rating.is_none());
+    }
+
+    #[test]
+    fn test_deload_after_rising_volume_and_dropping_ratings() {
+        let user = test_user(2);
+        let workouts = vec![
+            session("2025-07-07", 60.0, 4),
+            session("2025-07-10", 60.0, 4),
+            session("2025-07-14", 70.0, 4),
+            session("2025-07-17", 70.0, 4),
+            session("2025-07-21", 80.0, 4),
+            session("2025-07-24", 80.0, 3),
+            session("2025-07-28", 80.0, 2),
+            session("2025-07-31", 80.0, 2),
+        ];
+
+        let deload = ProgressAnalyzer::detect_deload(&user, &workouts)
+            .expect("declining ratings under high volume should trigger a deload");
+        assert!(deload.volume_reduction_percent > 0.0);
+        assert_eq!(deload.duration_days, 7);
+
+        let analysis = ProgressAnalyzer::analyze(&user, &workouts);
+        assert!(analysis.deload_recommendation.is_some());
+    }
+
+    #[test]
+    fn test_steady_progress_needs_no_deload() {
+        let user = test_user(2);
+        let workouts = vec![
+            session("2025-07-07", 60.0, 4),
+            session("2025-07-10", 60.0, 4),
+            session("2025-07-14", 65.0, 4),
+            session("2025-07-17", 65.0, 4),
+            session("2025-07-21", 70.0, 4),
+            session("2025-07-24", 70.0, 4),
+            session("2025-07-28", 75.0, 4),
+            session("2025-07-31", 75.0, 4),
+        ];
+
+        assert!(ProgressAnalyzer::detect_deload(&user, &workouts).is_none());
+    }
+
+    #[test]
+    fn test_streaks_tolerate_a_missed_day() {
+        // Five a week leaves gaps of up to 2 days, plus one grace day
+        let user = test_user(5);
+        let dates = [
+            "2024-03-01", "2024-03-02", "2024-03-03", // 03-04 missed: within grace
+            "2024-03-05", "2024-03-06",               // 3 days off: streak broken
+            "2024-03-10", "2024-03-11", "2024-03-13",
+        ];
+        let workouts: Vec<WorkoutSession> = dates.iter().map(|date| session(date, 60.0, 4)).collect();
+        let today = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+
+        let streak = ProgressAnalyzer::streak(&user, &workouts, today);
+        assert_eq!(streak.max_gap_days, 3);
+        assert_eq!(streak.longest_streak_days, 6); // 03-01 to 03-06
+        assert_eq!(streak.current_streak_days, 4); // 03-10 to 03-13
+        assert_eq!(streak.last_workout_date.as_deref(), Some("2024-03-13"));
+        assert_eq!(streak.workouts_this_week, 2);
+        // Three of five in each of the two complete weeks since the first workout
+        assert!((streak.weekly_consistency - 60.0).abs() < 0.01);
+
+        // Four days after the last workout the current streak is gone
+        let later = ProgressAnalyzer::streak(&user, &workouts, NaiveDate::from_ymd_opt(2024, 3, 17).unwrap());
+        assert_eq!(later.current_streak_days, 0);
+        assert_eq!(later.longest_streak_days, 6);
+    }
+}
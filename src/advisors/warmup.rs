@@ -0,0 +1,81 @@
+// src/advisors/warmup.rs - Warmups and cooldowns built around the muscles a
+// session's working sets train
+
+use crate::models::exercise::MuscleGroup;
+use crate::models::workout::{MovementStyle, PhaseMovement, SessionPhase};
+
+/// Every warmup starts by raising the heart rate, whatever the session trains
+const PULSE_RAISER: (&str, u32) = ("Marching in place", 120);
+
+/// Dynamic stretch and activation drill for each muscle group, with seconds
+const WARMUP_MOVEMENTS: &[(MuscleGroup, &str, MovementStyle, u32)] = &[
+    (MuscleGroup::Chest, "Arm swings across the chest", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Chest, "Scapular push-ups", MovementStyle::Activation, 30),
+    (MuscleGroup::Back, "Cat-cow", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Back, "Prone Y raises", MovementStyle::Activation, 30),
+    (MuscleGroup::Shoulders, "Arm circles", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Shoulders, "Wall slides", MovementStyle::Activation, 30),
+    (MuscleGroup::Arms, "Wrist and elbow circles", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Core, "Standing torso twists", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Core, "Dead bugs", MovementStyle::Activation, 45),
+    (MuscleGroup::Legs, "Leg swings", MovementStyle::DynamicStretch, 45),
+    (MuscleGroup::Legs, "Bodyweight good mornings", MovementStyle::Activation, 30),
+    (MuscleGroup::Glutes, "Hip circles", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Glutes, "Fire hydrants", MovementStyle::Activation, 45),
+    (MuscleGroup::Calves, "Ankle rocks", MovementStyle::DynamicStretch, 30),
+    (MuscleGroup::Calves, "Calf raises", MovementStyle::Activation, 30),
+];
+
+/// Static stretch for each muscle group, with seconds held (both sides
+/// where the stretch is one-sided)
+const COOLDOWN_STRETCHES: &[(MuscleGroup, &str, u32)] = &[
+    (MuscleGroup::Chest, "Doorway chest stretch", 45),
+    (MuscleGroup::Back, "Child's pose", 45),
+    (MuscleGroup::Shoulders, "Cross-body shoulder stretch", 60),
+    (MuscleGroup::Arms, "Overhead triceps stretch", 60),
+    (MuscleGroup::Core, "Cobra stretch", 30),
+    (MuscleGroup::Legs, "Standing quad stretch", 60),
+    (MuscleGroup::Legs, "Seated hamstring stretch", 45),
+    (MuscleGroup::Glutes, "Figure-four stretch", 60),
+    (MuscleGroup::Calves, "Wall calf stretch", 60),
+];
+
+fn movement(name: &str, style: MovementStyle, duration_seconds: u32, muscles: Vec<MuscleGroup>) -> PhaseMovement {
+    PhaseMovement {
+        name: name.to_string(),
+        style,
+        duration_seconds,
+        muscles,
+    }
+}
+
+fn phase(movements: Vec<PhaseMovement>) -> SessionPhase {
+    SessionPhase {
+        duration_seconds: movements.iter().map(|m| m.duration_seconds).sum(),
+        movements,
+    }
+}
+
+/// A pulse raiser, then a dynamic stretch and an activation drill for each
+/// of `muscles` in the order given, so the session's main muscles come first
+pub fn warmup(muscles: &[MuscleGroup]) -> SessionPhase {
+    let (name, seconds) = PULSE_RAISER;
+    let mut movements = vec![movement(name, MovementStyle::Activation, seconds, Vec::new())];
+    for muscle in muscles {
+        movements.extend(WARMUP_MOVEMENTS.iter()
+            .filter(|(group, ..)| group == muscle)
+            .map(|(group, name, style, seconds)| movement(name, *style, *seconds, vec![group.clone()])));
+    }
+    phase(movements)
+}
+
+/// Static stretches for each of `muscles`, in the order given
+pub fn cooldown(muscles: &[MuscleGroup]) -> SessionPhase {
+    let mut movements = Vec::new();
+    for muscle in muscles {
+        movements.extend(COOLDOWN_STRETCHES.iter()
+            .filter(|(group, ..)| group == muscle)
+            .map(|(group, name, seconds)| movement(name, MovementStyle::StaticStretch, *seconds, vec![group.clone()])));
+    }
+    phase(movements)
+}
@@ -0,0 +1,1333 @@
+// src/advisors/workout_recommender.rs - Workout recommendations that adapt to session history
+
+use std::collections::HashMap;
+
+use crate::advisors::{plate_math, warmup};
+use crate::config::PlateConfig;
+use crate::models::exercise::{suggest_weight, Exercise, ExerciseSet, MuscleGroup};
+use crate::models::user::{Equipment, ExerciseType, FitnessGoal, FitnessLevel, Limitation, User, UserPreferences};
+use crate::models::UnitSystem;
+use crate::models::workout::{
+    DayFocus, ExcludedExercise, ExerciseGuidance, PlateLoading, ScheduledDay, SessionIntensity, WeeklySchedule,
+    WorkoutRecommendation, WorkoutSession,
+};
+use crate::sample_data::create_sample_exercises;
+
+/// Number of most recent sessions considered when adapting a recommendation
+const HISTORY_WINDOW: usize = 5;
+/// Minimum number of rated sessions before ratings influence volume
+const MIN_RATED_SESSIONS: usize = 2;
+/// Average rating (1-5) at or below which volume is reduced
+const LOW_RATING_THRESHOLD: f32 = 2.5;
+/// Average rating (1-5) at or above which volume is progressed
+const HIGH_RATING_THRESHOLD: f32 = 4.5;
+/// Fraction of skipped sets above which volume is reduced
+const HIGH_SKIP_RATE: f32 = 0.5;
+/// Fraction of skipped sets a user may have and still be progressed
+const LOW_SKIP_RATE: f32 = 0.1;
+
+/// Easier variants swapped in for exercises the user keeps skipping
+const REGRESSIONS: &[(&str, &str)] = &[
+    ("pushup", "knee_pushup"),
+    ("burpee", "mountain_climber"),
+    ("deadlift", "glute_bridge"),
+];
+
+/// Preferred alternatives, in order, for exercises that need equipment the
+/// user may not have. Each alternative trains the same primary muscle groups.
+const EQUIPMENT_SUBSTITUTIONS: &[(&str, &[&str])] = &[
+    ("deadlift", &["dumbbell_single_leg_rdl", "glute_bridge"]),
+    ("dumbbell_single_leg_rdl", &["glute_bridge"]),
+];
+
+/// Catalog exercises that are unsafe with a limitation, and why
+const CONTRAINDICATIONS: &[(&str, Limitation, &str)] = &[
+    ("squat", Limitation::Knee, "deep knee bends load the knee joint"),
+    ("burpee", Limitation::Knee, "repeated jump landings jar the knees"),
+    ("burpee", Limitation::Wrist, "dropping into a plank lands on the wrists"),
+    ("burpee", Limitation::Shoulder, "the push-up portion loads the shoulders explosively"),
+    ("pushup", Limitation::Wrist, "the hands bear body weight with the wrists extended"),
+    ("pushup", Limitation::Shoulder, "pressing body weight can pinch the shoulder"),
+    ("knee_pushup", Limitation::Wrist, "the hands bear body weight with the wrists extended"),
+    ("mountain_climber", Limitation::Wrist, "the hands bear body weight with the wrists extended"),
+    ("deadlift", Limitation::LowerBack, "lifting a heavy load from the floor stresses the lower back"),
+    ("dumbbell_single_leg_rdl", Limitation::LowerBack, "hinging with a load stresses the lower back"),
+];
+
+/// The first of `limitations` that makes an exercise unsafe, with the reason
+fn contraindication(exercise_id: &str, limitations: &[Limitation]) -> Option<(Limitation, &'static str)> {
+    CONTRAINDICATIONS
+        .iter()
+        .find(|(id, limitation, _)| *id == exercise_id && limitations.contains(limitation))
+        .map(|(_, limitation, reason)| (*limitation, *reason))
+}
+
+/// Most alternatives `alternatives` returns when the caller doesn't say
+pub const DEFAULT_ALTERNATIVES: usize = 5;
+
+/// Unilateral dumbbell variants are loaded at roughly half the barbell weight
+const DUMBBELL_LOAD_RATIO: f32 = 0.5;
+
+/// Seconds per repetition when estimating how long a set takes
+const SECONDS_PER_REP: u32 = 3;
+/// Fraction the estimated duration may deviate from the preferred duration
+const DURATION_TOLERANCE: f32 = 0.1;
+/// Upper limit on sets per exercise when padding a short plan
+const MAX_SETS_PER_EXERCISE: u32 = 6;
+/// Group id given to every set of a circuit-style plan
+const CIRCUIT_GROUP_ID: &str = "circuit_1";
+
+const WEEKDAYS: [&str; 7] = ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+/// Below this many training days each session covers the full body
+const SPLIT_MIN_TRAINING_DAYS: usize = 4;
+
+/// Which weekdays (0 = Monday) to train on for each weekly frequency, spread
+/// so rest days fall between sessions wherever the frequency allows
+fn training_weekdays(workouts_per_week: u32) -> &'static [usize] {
+    match workouts_per_week {
+        0 => &[],
+        1 => &[0],
+        2 => &[0, 3],
+        3 => &[0, 2, 4],
+        4 => &[0, 1, 3, 4],
+        5 => &[0, 1, 3, 4, 5],
+        6 => &[0, 1, 2, 3, 4, 5],
+        _ => &[0, 1, 2, 3, 4, 5, 6],
+    }
+}
+
+/// Muscles a focus trains; core work fits either half of a split
+fn focus_muscles(focus: DayFocus) -> &'static [MuscleGroup] {
+    match focus {
+        DayFocus::LowerBody => &[MuscleGroup::Legs, MuscleGroup::Glutes, MuscleGroup::Calves, MuscleGroup::Core],
+        DayFocus::UpperBody => &[
+            MuscleGroup::Chest,
+            MuscleGroup::Back,
+            MuscleGroup::Shoulders,
+            MuscleGroup::Arms,
+            MuscleGroup::Core,
+        ],
+        DayFocus::FullBody | DayFocus::Rest => &[],
+    }
+}
+
+/// Target reps, rest, tempo and effort a training goal pushes resistance
+/// exercises towards
+#[derive(Debug, Clone, Copy)]
+struct GoalTemplate {
+    reps: u32,
+    rest_seconds: u32,
+    tempo: Option<&'static str>,
+    target_rpe: Option<f32>,
+    /// Whether the whole plan is run as one circuit
+    circuit: bool,
+}
+
+/// Goal templates: weight loss and endurance run as short-rest circuits,
+/// muscle gain uses hypertrophy rep ranges and strength uses heavy low-rep
+/// sets with long rests. Only the lifting goals prescribe a tempo and RPE.
+/// Goals without a template keep the level baseline.
+fn goal_template(goal: &FitnessGoal) -> Option<GoalTemplate> {
+    match goal {
+        FitnessGoal::WeightLoss => Some(GoalTemplate {
+            reps: 15,
+            rest_seconds: 20,
+            tempo: None,
+            target_rpe: None,
+            circuit: true,
+        }),
+        FitnessGoal::Endurance => Some(GoalTemplate {
+            reps: 20,
+            rest_seconds: 30,
+            tempo: None,
+            target_rpe: None,
+            circuit: true,
+        }),
+        FitnessGoal::MuscleGain => Some(GoalTemplate {
+            reps: 10,
+            rest_seconds: 75,
+            tempo: Some("3010"),
+            target_rpe: Some(8.0),
+            circuit: false,
+        }),
+        FitnessGoal::Strength => Some(GoalTemplate {
+            reps: 5,
+            rest_seconds: 150,
+            tempo: Some("2010"),
+            target_rpe: Some(8.5),
+            circuit: false,
+        }),
+        FitnessGoal::Flexibility | FitnessGoal::GeneralHealth => None,
+    }
+}
+
+/// Scales goal template reps so fitter users get more absolute volume
+fn level_volume_factor(level: &FitnessLevel) -> f32 {
+    match level {
+        FitnessLevel::Beginner => 0.8,
+        FitnessLevel::Intermediate => 1.0,
+        FitnessLevel::Advanced => 1.2,
+        FitnessLevel::Elite => 1.3,
+    }
+}
+
+/// How the recommended volume should change relative to the level baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VolumeAdjustment {
+    Reduce,
+    Maintain,
+    Progress,
+}
+
+/// What an alternative to an exercise must fit
+#[derive(Debug, Clone)]
+pub struct AlternativeConstraints {
+    /// Equipment the user has; `None` allows any equipment
+    pub available_equipment: Option<Vec<Equipment>>,
+    pub max_difficulty: Option<u32>,
+    /// Exercises unsafe for any of these are left out
+    pub limitations: Vec<Limitation>,
+    pub limit: usize,
+}
+
+impl Default for AlternativeConstraints {
+    fn default() -> Self {
+        Self {
+            available_equipment: None,
+            max_difficulty: None,
+            limitations: Vec::new(),
+            limit: DEFAULT_ALTERNATIVES,
+        }
+    }
+}
+
+/// Builds workout recommendations from the user's fitness level and adjusts
+/// them using recent session ratings, skipped exercises and available equipment.
+pub struct WorkoutRecommender {
+    exercises: HashMap<String, Exercise>,
+    /// The user's estimated one-rep max per exercise id
+    one_rep_maxes: HashMap<String, f32>,
+    plates: PlateConfig,
+}
+
+impl WorkoutRecommender {
+    /// Create a recommender backed by the given exercise catalog
+    pub fn new(exercises: Vec<Exercise>) -> Self {
+        Self {
+            exercises: exercises.into_iter().map(|e| (e.id.clone(), e)).collect(),
+            one_rep_maxes: HashMap::new(),
+            plates: PlateConfig::default(),
+        }
+    }
+
+    /// Load resistance sets from the user's estimated one-rep maxes instead
+    /// of the baseline weights
+    pub fn with_one_rep_maxes(mut self, one_rep_maxes: HashMap<String, f32>) -> Self {
+        self.one_rep_maxes = one_rep_maxes;
+        self
+    }
+
+    /// Bar and plates used to show how to load barbell exercises
+    pub fn with_plates(mut self, plates: PlateConfig) -> Self {
+        self.plates = plates;
+        self
+    }
+
+    /// Recommend exercise sets for the user. `recent_workouts` is expected
+    /// newest-first (as returned by the database); with no history the static
+    /// level baseline is used, limited only by the user's equipment. Exercises
+    /// unsafe for the user's limitations are swapped for safe alternatives or
+    /// dropped, and listed with the reason. Resistance exercises with a known
+    /// one-rep max are loaded from it, and barbell exercises with a weight
+    /// come with the plates to load in the user's units. The plan is then
+    /// fitted to the user's preferred workout duration. Unless the user has turned them off, a
+    /// warmup and cooldown for the plan's primary muscles are added on top of
+    /// that duration.
+    pub fn recommend(&self, user: &User, recent_workouts: &[WorkoutSession]) -> WorkoutRecommendation {
+        let baseline = Self::baseline_sets(&user.fitness_level);
+        let history = &recent_workouts[..recent_workouts.len().min(HISTORY_WINDOW)];
+
+        let adjustment = if history.is_empty() {
+            VolumeAdjustment::Maintain
+        } else {
+            Self::assess(history)
+        };
+        let regressions: HashMap<&str, &str> = REGRESSIONS.iter().copied().collect();
+        let skipped = Self::frequently_skipped(history);
+        let mut excluded = Vec::new();
+
+        let sets: Vec<ExerciseSet> = baseline
+            .into_iter()
+            .map(|set| self.apply_goals(set, &user.goals, &user.fitness_level))
+            .map(|set| match regressions.get(set.exercise_id.as_str()) {
+                Some(easier) if skipped.contains(&set.exercise_id) => ExerciseSet {
+                    exercise_id: easier.to_string(),
+                    weight_kg: None,
+                    ..set
+                },
+                _ => set,
+            })
+            .filter_map(|set| self.substitute_for_equipment(set, &user.preferences.available_equipment))
+            .filter_map(|set| self.substitute_for_limitations(set, &user.preferences, &mut excluded))
+            .map(|set| Self::apply_adjustment(set, adjustment))
+            .map(|set| self.apply_one_rep_max(set))
+            .collect();
+
+        // A plan reduced because of poor feedback is never padded back up
+        let mut exercises = self.fit_to_duration(
+            sets,
+            user.preferences.workout_duration_minutes,
+            adjustment != VolumeAdjustment::Reduce,
+        );
+        if Self::is_circuit(&user.goals) {
+            exercises = Self::group_into_circuit(exercises);
+        }
+
+        let mut recommendation = WorkoutRecommendation {
+            excluded,
+            ..self.recommendation(exercises)
+        };
+        recommendation.plate_loading = self.plate_loading(&recommendation.exercises, user.preferences.unit_system);
+        if user.preferences.warmup_cooldown {
+            let muscles = self.primary_muscles(&recommendation.exercises);
+            recommendation.warmup = Some(warmup::warmup(&muscles));
+            recommendation.cooldown = Some(warmup::cooldown(&muscles));
+        }
+        recommendation
+    }
+
+    /// Plates per side for each barbell exercise with a weight, in `units`
+    fn plate_loading(&self, sets: &[ExerciseSet], units: UnitSystem) -> Vec<PlateLoading> {
+        let plate_set = self.plates.for_units(units);
+        let mut loading: Vec<PlateLoading> = Vec::new();
+        for set in sets {
+            let Some(weight_kg) = set.weight_kg else {
+                continue;
+            };
+            let barbell = self
+                .exercises
+                .get(&set.exercise_id)
+                .is_some_and(|e| e.equipment_needed.contains(&Equipment::Barbells));
+            if !barbell || loading.iter().any(|l| l.exercise_id == set.exercise_id) {
+                continue;
+            }
+
+            let target = units.weight_from_kg(weight_kg as f64) as f32;
+            let breakdown = plate_math::plate_breakdown(target, plate_set.bar, &plate_set.plates);
+            loading.push(PlateLoading {
+                exercise_id: set.exercise_id.clone(),
+                unit: units.weight_unit().to_string(),
+                bar: plate_set.bar,
+                per_side: breakdown.per_side,
+                total: breakdown.total,
+            });
+        }
+        loading
+    }
+
+    /// Distinct primary muscles of the planned exercises, most trained first
+    fn primary_muscles(&self, sets: &[ExerciseSet]) -> Vec<MuscleGroup> {
+        let mut counts: Vec<(MuscleGroup, usize)> = Vec::new();
+        for exercise in sets.iter().filter_map(|set| self.exercises.get(&set.exercise_id)) {
+            for muscle in &exercise.primary_muscles {
+                match counts.iter_mut().find(|(group, _)| group == muscle) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((muscle.clone(), 1)),
+                }
+            }
+        }
+        // Stable, so ties keep plan order
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts.into_iter().map(|(muscle, _)| muscle).collect()
+    }
+
+    /// Wrap a finished plan with its estimated duration and the demo link and
+    /// cues of each catalog exercise in it
+    fn recommendation(&self, exercises: Vec<ExerciseSet>) -> WorkoutRecommendation {
+        let estimated_duration_minutes =
+            (Self::estimate_duration_seconds(&exercises) as f32 / 60.0).round() as u32;
+
+        let mut guidance: Vec<ExerciseGuidance> = Vec::new();
+        for set in &exercises {
+            if guidance.iter().any(|g| g.exercise_id == set.exercise_id) {
+                continue;
+            }
+            if let Some(exercise) = self.exercises.get(&set.exercise_id) {
+                guidance.push(ExerciseGuidance {
+                    exercise_id: exercise.id.clone(),
+                    name: exercise.name.clone(),
+                    demo_url: exercise.demo_url.clone(),
+                    cues: exercise.cues.clone(),
+                });
+            }
+        }
+
+        WorkoutRecommendation {
+            exercises,
+            estimated_duration_minutes,
+            guidance,
+            excluded: Vec::new(),
+            warmup: None,
+            cooldown: None,
+            plate_loading: Vec::new(),
+        }
+    }
+
+    /// Lay out a week of training per the user's `workouts_per_week`, Monday
+    /// first. Training days follow a fixed spread for the frequency, and a
+    /// session directly after a high-intensity day is moderate, so no two
+    /// hard days are back to back (including Sunday into Monday). With four
+    /// or more sessions the hard days alternate lower and upper body and
+    /// moderate days cover the full body; otherwise every session is full
+    /// body. Each day's plan is the regular recommendation narrowed to its
+    /// focus, with moderate days at reduced volume.
+    pub fn recommend_week(&self, user: &User, recent_workouts: &[WorkoutSession]) -> WeeklySchedule {
+        let base = self.recommend(user, recent_workouts).exercises;
+        let training = training_weekdays(user.preferences.workouts_per_week);
+
+        let mut intensities = [SessionIntensity::Rest; 7];
+        for &day in training {
+            let previous = intensities[(day + 6) % 7];
+            intensities[day] = if previous == SessionIntensity::High {
+                SessionIntensity::Moderate
+            } else {
+                SessionIntensity::High
+            };
+        }
+        // Sunday leads into next Monday
+        if intensities[6] == SessionIntensity::High && intensities[0] == SessionIntensity::High {
+            intensities[6] = SessionIntensity::Moderate;
+        }
+
+        let split = training.len() >= SPLIT_MIN_TRAINING_DAYS;
+        let mut hard_days = 0;
+        let days = WEEKDAYS.iter().enumerate()
+            .map(|(day, name)| {
+                let intensity = intensities[day];
+                let focus = match intensity {
+                    SessionIntensity::Rest => DayFocus::Rest,
+                    SessionIntensity::High if split => {
+                        hard_days += 1;
+                        if hard_days % 2 == 1 { DayFocus::LowerBody } else { DayFocus::UpperBody }
+                    }
+                    _ => DayFocus::FullBody,
+                };
+                let workout = (intensity != SessionIntensity::Rest)
+                    .then(|| self.session_for(&base, focus, intensity, user.preferences.workout_duration_minutes));
+
+                ScheduledDay {
+                    day: name.to_string(),
+                    focus,
+                    intensity,
+                    time_of_day: workout.as_ref().and(user.preferences.preferred_time_of_day.clone()),
+                    workout,
+                }
+            })
+            .collect();
+
+        WeeklySchedule {
+            user_id: user.id.clone(),
+            training_days: training.len() as u32,
+            days,
+        }
+    }
+
+    /// Narrow the weekly base plan to a day's focus, falling back to the
+    /// whole plan when nothing matches. High-intensity days are refitted to
+    /// the budget; moderate days take reduced volume and are not padded.
+    fn session_for(
+        &self,
+        base: &[ExerciseSet],
+        focus: DayFocus,
+        intensity: SessionIntensity,
+        budget_minutes: u32,
+    ) -> WorkoutRecommendation {
+        let muscles = focus_muscles(focus);
+        let focused: Vec<ExerciseSet> = base.iter()
+            .filter(|set| {
+                muscles.is_empty()
+                    || self.exercises.get(&set.exercise_id)
+                        .is_some_and(|e| e.primary_muscles.iter().any(|m| muscles.contains(m)))
+            })
+            .cloned()
+            .collect();
+        let sets = if focused.is_empty() { base.to_vec() } else { focused };
+
+        let exercises = match intensity {
+            SessionIntensity::Moderate => sets.into_iter()
+                .map(|set| Self::apply_adjustment(set, VolumeAdjustment::Reduce))
+                .collect(),
+            _ => self.fit_to_duration(sets, budget_minutes, true),
+        };
+
+        self.recommendation(exercises)
+    }
+
+    /// A plan is run as a circuit when all of the user's templated goals are
+    /// circuit-style; mixing in strength or muscle gain keeps straight sets
+    fn is_circuit(goals: &[FitnessGoal]) -> bool {
+        let templates: Vec<GoalTemplate> = goals.iter().filter_map(goal_template).collect();
+        !templates.is_empty() && templates.iter().all(|t| t.circuit)
+    }
+
+    /// Put every set into one circuit sharing a single rest period, the
+    /// average of the sets' rests so the estimated duration barely moves.
+    /// A lone exercise is left ungrouped.
+    fn group_into_circuit(sets: Vec<ExerciseSet>) -> Vec<ExerciseSet> {
+        if sets.len() < 2 {
+            return sets;
+        }
+
+        let total_rest: u32 = sets.iter().map(|set| set.rest_seconds).sum();
+        let rest_seconds = (total_rest as f32 / sets.len() as f32).round() as u32;
+        sets.into_iter()
+            .map(|set| ExerciseSet {
+                rest_seconds,
+                group_id: Some(CIRCUIT_GROUP_ID.to_string()),
+                ..set
+            })
+            .collect()
+    }
+
+    /// Estimated time for a set: work (timed hold or reps) plus rest, per set
+    pub fn estimate_set_seconds(set: &ExerciseSet) -> u32 {
+        let work = set.duration_seconds.unwrap_or(set.reps * SECONDS_PER_REP);
+        set.sets * (work + set.rest_seconds)
+    }
+
+    /// Estimated time for a whole plan
+    pub fn estimate_duration_seconds(sets: &[ExerciseSet]) -> u32 {
+        sets.iter().map(Self::estimate_set_seconds).sum()
+    }
+
+    /// Trim or pad the plan so its estimated duration lands within tolerance
+    /// of the budget. Trimming removes sets from isolation movements first and
+    /// padding adds sets to compound movements first.
+    fn fit_to_duration(&self, mut sets: Vec<ExerciseSet>, budget_minutes: u32, allow_padding: bool) -> Vec<ExerciseSet> {
+        if budget_minutes == 0 || sets.is_empty() {
+            return sets;
+        }
+
+        let budget = budget_minutes as f32 * 60.0;
+        let upper = (budget * (1.0 + DURATION_TOLERANCE)) as u32;
+        let lower = (budget * (1.0 - DURATION_TOLERANCE)) as u32;
+
+        while Self::estimate_duration_seconds(&sets) > upper {
+            // Least compound exercise, preferring later ones on ties
+            let idx = (0..sets.len())
+                .rev()
+                .min_by_key(|&i| self.compound_score(&sets[i].exercise_id))
+                .expect("plan is not empty");
+
+            if sets[idx].sets > 1 {
+                sets[idx].sets -= 1;
+            } else if sets.len() > 1 {
+                sets.remove(idx);
+            } else {
+                break;
+            }
+        }
+
+        while allow_padding && Self::estimate_duration_seconds(&sets) < lower {
+            let current = Self::estimate_duration_seconds(&sets);
+            let per_set = |set: &ExerciseSet| Self::estimate_set_seconds(set) / set.sets.max(1);
+
+            // Most compound exercise with the fewest sets that still fits
+            let candidate = (0..sets.len())
+                .filter(|&i| sets[i].sets < MAX_SETS_PER_EXERCISE)
+                .filter(|&i| current + per_set(&sets[i]) <= upper)
+                .max_by_key(|&i| {
+                    (
+                        self.compound_score(&sets[i].exercise_id),
+                        std::cmp::Reverse(sets[i].sets),
+                        std::cmp::Reverse(i),
+                    )
+                });
+
+            match candidate {
+                Some(idx) => sets[idx].sets += 1,
+                None => break,
+            }
+        }
+
+        sets
+    }
+
+    /// Number of primary muscle groups an exercise trains; compound movements
+    /// score higher. Unknown exercises count as isolation movements.
+    fn compound_score(&self, exercise_id: &str) -> usize {
+        self.exercises
+            .get(exercise_id)
+            .map(|e| e.primary_muscles.len())
+            .unwrap_or(1)
+    }
+
+    /// Blend the templates of the user's goals in equal proportion. Goals
+    /// without a template contribute the level baseline values. Reps, tempo
+    /// and RPE only change for resistance exercises; timed holds and cardio
+    /// keep theirs. The first goal with a tempo sets it and RPE targets are
+    /// averaged over the goals that have one.
+    fn apply_goals(&self, set: ExerciseSet, goals: &[FitnessGoal], level: &FitnessLevel) -> ExerciseSet {
+        if goals.is_empty() {
+            return set;
+        }
+
+        let share = 1.0 / goals.len() as f32;
+        let level_factor = level_volume_factor(level);
+        let (mut reps, mut rest) = (0.0, 0.0);
+        for goal in goals {
+            let (goal_reps, goal_rest) = match goal_template(goal) {
+                Some(template) => (template.reps as f32 * level_factor, template.rest_seconds as f32),
+                None => (set.reps as f32, set.rest_seconds as f32),
+            };
+            reps += goal_reps * share;
+            rest += goal_rest * share;
+        }
+        let templates: Vec<GoalTemplate> = goals.iter().filter_map(goal_template).collect();
+        let tempo = templates.iter().find_map(|t| t.tempo);
+        let rpes: Vec<f32> = templates.iter().filter_map(|t| t.target_rpe).collect();
+
+        let resistance = self
+            .exercises
+            .get(&set.exercise_id)
+            .is_some_and(|e| matches!(e.exercise_type, ExerciseType::Strength));
+        if !resistance || set.duration_seconds.is_some() {
+            return ExerciseSet {
+                rest_seconds: rest.round() as u32,
+                ..set
+            };
+        }
+
+        let target_rpe = if rpes.is_empty() {
+            set.target_rpe
+        } else {
+            Some(rpes.iter().sum::<f32>() / rpes.len() as f32)
+        };
+        ExerciseSet {
+            reps: (reps.round() as u32).max(1),
+            rest_seconds: rest.round() as u32,
+            tempo: tempo.map(str::to_string).or(set.tempo),
+            target_rpe,
+            ..set
+        }
+    }
+
+    /// Weight for a resistance set of an exercise the user has a one-rep max
+    /// for. A target RPE leaves `10 - rpe` reps in reserve, so the weight is
+    /// what the user could lift for the reps plus that reserve.
+    fn apply_one_rep_max(&self, set: ExerciseSet) -> ExerciseSet {
+        let Some(one_rm) = self.one_rep_maxes.get(&set.exercise_id) else {
+            return set;
+        };
+        let resistance = self
+            .exercises
+            .get(&set.exercise_id)
+            .is_some_and(|e| matches!(e.exercise_type, ExerciseType::Strength));
+        if !resistance || set.duration_seconds.is_some() {
+            return set;
+        }
+
+        let reps_in_reserve = set.target_rpe.map_or(0, |rpe| (10.0 - rpe).max(0.0).round() as u32);
+        ExerciseSet {
+            weight_kg: Some(suggest_weight(*one_rm, set.reps + reps_in_reserve)),
+            ..set
+        }
+    }
+
+    /// Whether the user has everything needed to perform the exercise.
+    /// Exercises missing from the catalog are assumed to need no equipment.
+    pub fn is_available(&self, exercise_id: &str, available: &[Equipment]) -> bool {
+        match self.exercises.get(exercise_id) {
+            Some(exercise) => exercise
+                .equipment_needed
+                .iter()
+                .all(|e| *e == Equipment::None || available.contains(e)),
+            None => true,
+        }
+    }
+
+    /// Exercises that can stand in for `exercise_id`, most similar first, or
+    /// `None` for an unknown exercise. Every alternative shares a primary
+    /// muscle group with it; they're ranked by how closely their primary and
+    /// then secondary muscles match, their type, and closeness in difficulty.
+    pub fn alternatives(&self, exercise_id: &str, constraints: &AlternativeConstraints) -> Option<Vec<Exercise>> {
+        let original = self.exercises.get(exercise_id)?;
+
+        let mut candidates: Vec<(&Exercise, f32, u32)> = self
+            .exercises
+            .values()
+            .filter(|e| e.id != original.id && shares_primary_muscle(original, e))
+            .filter(|e| contraindication(&e.id, &constraints.limitations).is_none())
+            .filter(|e| constraints.max_difficulty.is_none_or(|max| e.difficulty_level <= max))
+            .filter(|e| {
+                constraints.available_equipment.as_deref().is_none_or(|available| self.is_available(&e.id, available))
+            })
+            .map(|e| {
+                let same_type = std::mem::discriminant(&e.exercise_type) == std::mem::discriminant(&original.exercise_type);
+                let similarity = 2.0 * muscle_overlap(&e.primary_muscles, &original.primary_muscles)
+                    + muscle_overlap(&e.secondary_muscles, &original.secondary_muscles)
+                    + if same_type { 1.0 } else { 0.0 };
+                (e, similarity, e.difficulty_level.abs_diff(original.difficulty_level))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.2.cmp(&b.2)).then_with(|| a.0.id.cmp(&b.0.id)));
+        Some(candidates.into_iter().take(constraints.limit).map(|(e, _, _)| e.clone()).collect())
+    }
+
+    /// Replace a set that's unsafe for the user's limitations with the most
+    /// similar safe exercise they have the equipment for, or drop it if there
+    /// is none, recording why in `excluded`
+    fn substitute_for_limitations(
+        &self,
+        set: ExerciseSet,
+        preferences: &UserPreferences,
+        excluded: &mut Vec<ExcludedExercise>,
+    ) -> Option<ExerciseSet> {
+        let Some((limitation, reason)) = contraindication(&set.exercise_id, &preferences.limitations) else {
+            return Some(set);
+        };
+
+        let constraints = AlternativeConstraints {
+            available_equipment: Some(preferences.available_equipment.clone()),
+            limitations: preferences.limitations.clone(),
+            limit: 1,
+            ..AlternativeConstraints::default()
+        };
+        let substitute = self.alternatives(&set.exercise_id, &constraints)
+            .and_then(|alternatives| alternatives.into_iter().next());
+
+        excluded.push(ExcludedExercise {
+            exercise_id: set.exercise_id.clone(),
+            limitation,
+            reason: reason.to_string(),
+            replaced_by: substitute.as_ref().map(|e| e.id.clone()),
+        });
+
+        let substitute = substitute?;
+        let bodyweight = substitute.equipment_needed.iter().all(|e| *e == Equipment::None);
+        Some(ExerciseSet {
+            exercise_id: substitute.id,
+            weight_kg: if bodyweight { None } else { set.weight_kg },
+            ..set
+        })
+    }
+
+    /// Replace a set whose equipment is unavailable with an alternative that
+    /// trains the same primary muscles, or drop it if none exists.
+    fn substitute_for_equipment(&self, set: ExerciseSet, available: &[Equipment]) -> Option<ExerciseSet> {
+        if self.is_available(&set.exercise_id, available) {
+            return Some(set);
+        }
+
+        let original = self.exercises.get(&set.exercise_id)?;
+        let mapped = EQUIPMENT_SUBSTITUTIONS
+            .iter()
+            .find(|(id, _)| *id == set.exercise_id)
+            .map(|(_, alternatives)| *alternatives)
+            .unwrap_or(&[]);
+
+        let substitute = mapped
+            .iter()
+            .filter_map(|id| self.exercises.get(*id))
+            .chain(self.catalog_alternatives(original))
+            .find(|candidate| {
+                shares_primary_muscle(original, candidate) && self.is_available(&candidate.id, available)
+            })?;
+
+        let bodyweight = substitute.equipment_needed.iter().all(|e| *e == Equipment::None);
+        let weight_kg = if bodyweight {
+            None
+        } else if substitute.equipment_needed.contains(&Equipment::Dumbbells) {
+            set.weight_kg.map(|w| (w * DUMBBELL_LOAD_RATIO * 2.0).round() / 2.0)
+        } else {
+            set.weight_kg
+        };
+
+        Some(ExerciseSet {
+            exercise_id: substitute.id.clone(),
+            weight_kg,
+            ..set
+        })
+    }
+
+    /// Catalog exercises of the same type, most primary-muscle overlap first
+    fn catalog_alternatives<'a>(&'a self, original: &Exercise) -> Vec<&'a Exercise> {
+        let mut candidates: Vec<(&Exercise, usize)> = self
+            .exercises
+            .values()
+            .filter(|e| e.id != original.id)
+            .filter(|e| {
+                std::mem::discriminant(&e.exercise_type) == std::mem::discriminant(&original.exercise_type)
+            })
+            .map(|e| {
+                let overlap = e
+                    .primary_muscles
+                    .iter()
+                    .filter(|m| original.primary_muscles.contains(m))
+                    .count();
+                (e, overlap)
+            })
+            .filter(|(_, overlap)| *overlap > 0)
+            .collect();
+
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.id.cmp(&b.0.id)));
+        candidates.into_iter().map(|(e, _)| e).collect()
+    }
+
+    /// Decide how volume should change based on ratings and skipped sets
+    pub fn assess(history: &[WorkoutSession]) -> VolumeAdjustment {
+        let ratings: Vec<f32> = history
+            .iter()
+            .filter_map(|w| w.user_rating)
+            .map(|r| r as f32)
+            .collect();
+        let avg_rating = if ratings.len() >= MIN_RATED_SESSIONS {
+            Some(ratings.iter().sum::<f32>() / ratings.len() as f32)
+        } else {
+            None
+        };
+        let skip_rate = Self::skip_rate(history);
+
+        match avg_rating {
+            _ if skip_rate > HIGH_SKIP_RATE => VolumeAdjustment::Reduce,
+            Some(avg) if avg <= LOW_RATING_THRESHOLD => VolumeAdjustment::Reduce,
+            Some(avg) if avg >= HIGH_RATING_THRESHOLD && skip_rate <= LOW_SKIP_RATE => {
+                VolumeAdjustment::Progress
+            }
+            _ => VolumeAdjustment::Maintain,
+        }
+    }
+
+    /// Fraction of logged sets that were not completed
+    fn skip_rate(history: &[WorkoutSession]) -> f32 {
+        let total = history.iter().map(|w| w.exercises.len()).sum::<usize>();
+        if total == 0 {
+            return 0.0;
+        }
+        let skipped = history
+            .iter()
+            .flat_map(|w| &w.exercises)
+            .filter(|set| !set.completed)
+            .count();
+        skipped as f32 / total as f32
+    }
+
+    /// Exercises skipped in more than half of the sessions they appeared in
+    fn frequently_skipped(history: &[WorkoutSession]) -> Vec<String> {
+        let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+        for set in history.iter().flat_map(|w| &w.exercises) {
+            let entry = counts.entry(set.exercise_id.as_str()).or_insert((0, 0));
+            entry.0 += 1;
+            if !set.completed {
+                entry.1 += 1;
+            }
+        }
+
+        counts
+            .into_iter()
+            .filter(|(_, (seen, skipped))| *skipped >= 2 && *skipped * 2 > *seen)
+            .map(|(id, _)| id.to_string())
+            .collect()
+    }
+
+    fn apply_adjustment(set: ExerciseSet, adjustment: VolumeAdjustment) -> ExerciseSet {
+        match adjustment {
+            VolumeAdjustment::Maintain => set,
+            VolumeAdjustment::Reduce => ExerciseSet {
+                sets: set.sets.saturating_sub(1).max(1),
+                reps: ((set.reps as f32 * 0.8).round() as u32).max(1),
+                duration_seconds: set.duration_seconds.map(|d| (d as f32 * 0.8).round() as u32),
+                rest_seconds: set.rest_seconds + 15,
+                ..set
+            },
+            VolumeAdjustment::Progress => ExerciseSet {
+                // Timed holds progress on duration, everything else on reps
+                reps: if set.duration_seconds.is_some() {
+                    set.reps
+                } else {
+                    (set.reps as f32 * 1.1).ceil() as u32
+                },
+                duration_seconds: set.duration_seconds.map(|d| (d as f32 * 1.1).ceil() as u32),
+                weight_kg: set.weight_kg.map(|w| (w * 1.05 * 2.0).round() / 2.0),
+                ..set
+            },
+        }
+    }
+
+    /// Static per-level sets, used as-is when the user has no history
+    pub fn baseline_sets(level: &FitnessLevel) -> Vec<ExerciseSet> {
+        match level {
+            FitnessLevel::Beginner => vec![
+                exercise_set("squat", 2, 10, None, None, 60),
+                exercise_set("pushup", 2, 8, None, None, 60),
+                exercise_set("plank", 2, 1, None, Some(30), 60),
+            ],
+            FitnessLevel::Intermediate => vec![
+                exercise_set("squat", 3, 15, None, None, 45),
+                exercise_set("pushup", 3, 12, None, None, 45),
+                exercise_set("burpee", 2, 8, None, None, 60),
+                exercise_set("plank", 3, 1, None, Some(45), 45),
+            ],
+            _ => vec![
+                exercise_set("squat", 4, 20, None, None, 30),
+                exercise_set("deadlift", 4, 8, Some(60.0), None, 90),
+                exercise_set("burpee", 3, 12, None, None, 45),
+                exercise_set("plank", 3, 1, None, Some(60), 30),
+            ],
+        }
+    }
+}
+
+impl Default for WorkoutRecommender {
+    fn default() -> Self {
+        Self::new(create_sample_exercises())
+    }
+}
+
+fn shares_primary_muscle(a: &Exercise, b: &Exercise) -> bool {
+    a.primary_muscles.iter().any(|m| b.primary_muscles.contains(m))
+}
+
+/// Shared muscle groups as a fraction of all groups either list names
+/// (Jaccard index), so extra muscles count against a match
+fn muscle_overlap(a: &[MuscleGroup], b: &[MuscleGroup]) -> f32 {
+    let shared = a.iter().filter(|m| b.contains(m)).count();
+    let total = a.len() + b.len() - shared;
+    if total == 0 {
+        0.0
+    } else {
+        shared as f32 / total as f32
+    }
+}
+
+fn exercise_set(
+    exercise_id: &str,
+    sets: u32,
+    reps: u32,
+    weight_kg: Option<f32>,
+    duration_seconds: Option<u32>,
+    rest_seconds: u32,
+) -> ExerciseSet {
+    ExerciseSet {
+        exercise_id: exercise_id.to_string(),
+        sets,
+        reps,
+        weight_kg,
+        duration_seconds,
+        rest_seconds,
+        completed: false,
+        tempo: None,
+        target_rpe: None,
+        group_id: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::{ExerciseType, FitnessGoal, Role, UserPreferences};
+    use crate::models::workout::MovementStyle;
+
+    fn test_user(level: FitnessLevel) -> User {
+        test_user_with_equipment(level, vec![Equipment::None])
+    }
+
+    fn test_user_with_equipment(level: FitnessLevel, available_equipment: Vec<Equipment>) -> User {
+        // Budget the baseline's own duration so volume comparisons aren't padded or trimmed
+        let baseline_seconds =
+            WorkoutRecommender::estimate_duration_seconds(&WorkoutRecommender::baseline_sets(&level));
+        User {
+            id: "test_user".to_string(),
+            name: "Test User".to_string(),
+            age: 30,
+            height: 175.0,
+            weight: 70.0,
+            fitness_level: level,
+            goals: vec![FitnessGoal::GeneralHealth],
+            preferences: UserPreferences {
+                preferred_exercise_types: vec![ExerciseType::Strength],
+                available_equipment,
+                workout_duration_minutes: (baseline_seconds as f32 / 60.0).round() as u32,
+                workouts_per_week: 3,
+                preferred_time_of_day: None,
+                unit_system: crate::models::UnitSystem::Metric,
+                limitations: vec![],
+                warmup_cooldown: true,
+            },
+            role: Role::Client,
+            version: 0,
+        }
+    }
+
+    fn session(date: &str, exercises: Vec<ExerciseSet>, rating: u32) -> WorkoutSession {
+        WorkoutSession {
+            id: format!("session_{}", date),
+            user_id: "test_user".to_string(),
+            date: date.to_string(),
+            exercises,
+            total_duration_minutes: 40,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: Some(rating),
+            notes: None,
+        }
+    }
+
+    fn total_volume(sets: &[ExerciseSet]) -> u32 {
+        sets.iter()
+            .map(|s| s.sets * s.duration_seconds.unwrap_or(s.reps))
+            .sum()
+    }
+
+    fn completed(sets: Vec<ExerciseSet>) -> Vec<ExerciseSet> {
+        sets.into_iter().map(|s| ExerciseSet { completed: true, ..s }).collect()
+    }
+
+    #[test]
+    fn test_no_history_returns_baseline() {
+        let user = test_user(FitnessLevel::Intermediate);
+        let recommendation = WorkoutRecommender::default().recommend(&user, &[]).exercises;
+        let baseline = WorkoutRecommender::baseline_sets(&FitnessLevel::Intermediate);
+
+        assert_eq!(total_volume(&recommendation), total_volume(&baseline));
+    }
+
+    #[test]
+    fn test_recommended_squat_includes_demo_and_cues() {
+        let mut catalog = create_sample_exercises();
+        let squat = catalog.iter_mut().find(|e| e.id == "squat").unwrap();
+        squat.demo_url = Some("https://example.com/demos/squat.mp4".to_string());
+        let recommender = WorkoutRecommender::new(catalog);
+
+        let recommendation = recommender.recommend(&test_user(FitnessLevel::Intermediate), &[]);
+        assert!(recommendation.exercises.iter().any(|s| s.exercise_id == "squat"));
+
+        let guidance = recommendation.guidance.iter().find(|g| g.exercise_id == "squat").unwrap();
+        assert_eq!(guidance.name, "Squat");
+        assert_eq!(guidance.demo_url.as_deref(), Some("https://example.com/demos/squat.mp4"));
+        assert!(!guidance.cues.is_empty());
+        // One entry per exercise, even if it appears in several sets
+        assert_eq!(recommendation.guidance.iter().filter(|g| g.exercise_id == "squat").count(), 1);
+    }
+
+    #[test]
+    fn test_low_ratings_reduce_volume() {
+        let user = test_user(FitnessLevel::Intermediate);
+        let baseline = WorkoutRecommender::baseline_sets(&FitnessLevel::Intermediate);
+        let history: Vec<WorkoutSession> = ["2025-08-14", "2025-08-12", "2025-08-10"]
+            .iter()
+            .map(|date| session(date, completed(baseline.clone()), 1))
+            .collect();
+
+        assert_eq!(WorkoutRecommender::assess(&history), VolumeAdjustment::Reduce);
+
+        let recommendation = WorkoutRecommender::default().recommend(&user, &history).exercises;
+        assert!(total_volume(&recommendation) < total_volume(&baseline));
+        assert!(recommendation
+            .iter()
+            .zip(&baseline)
+            .all(|(adjusted, base)| adjusted.rest_seconds > base.rest_seconds));
+    }
+
+    #[test]
+    fn test_skipped_exercise_is_swapped_for_regression() {
+        let user = test_user(FitnessLevel::Intermediate);
+        let baseline = WorkoutRecommender::baseline_sets(&FitnessLevel::Intermediate);
+        let history: Vec<WorkoutSession> = ["2025-08-14", "2025-08-12"]
+            .iter()
+            .map(|date| {
+                let sets = completed(baseline.clone())
+                    .into_iter()
+                    .map(|s| ExerciseSet { completed: s.exercise_id != "pushup", ..s })
+                    .collect();
+                session(date, sets, 3)
+            })
+            .collect();
+
+        let recommendation = WorkoutRecommender::default().recommend(&user, &history).exercises;
+        assert!(recommendation.iter().any(|s| s.exercise_id == "knee_pushup"));
+        assert!(!recommendation.iter().any(|s| s.exercise_id == "pushup"));
+    }
+
+    #[test]
+    fn test_high_ratings_progress_volume() {
+        let user = test_user(FitnessLevel::Advanced);
+        let baseline = WorkoutRecommender::baseline_sets(&FitnessLevel::Advanced);
+        let history: Vec<WorkoutSession> = ["2025-08-14", "2025-08-12", "2025-08-10"]
+            .iter()
+            .map(|date| session(date, completed(baseline.clone()), 5))
+            .collect();
+
+        let recommendation = WorkoutRecommender::default().recommend(&user, &history).exercises;
+        assert!(total_volume(&recommendation) > total_volume(&baseline));
+    }
+
+    #[test]
+    fn test_knee_limitation_swaps_squats_for_knee_friendly_exercises() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Intermediate);
+        user.preferences.limitations = vec![Limitation::Knee];
+
+        let recommendation = recommender.recommend(&user, &[]);
+        let planned: Vec<&str> = recommendation.exercises.iter().map(|s| s.exercise_id.as_str()).collect();
+        assert!(!planned.contains(&"squat") && !planned.contains(&"burpee"), "plan {:?}", planned);
+        assert!(planned.contains(&"glute_bridge"), "plan {:?}", planned);
+
+        let squat = recommendation.excluded.iter().find(|e| e.exercise_id == "squat").unwrap();
+        assert_eq!(squat.limitation, Limitation::Knee);
+        assert_eq!(squat.replaced_by.as_deref(), Some("glute_bridge"));
+        assert!(squat.reason.contains("knee"));
+        assert!(recommendation.excluded.iter().any(|e| e.exercise_id == "burpee"));
+        assert!(recommendation.excluded.iter().all(|e| e.limitation == Limitation::Knee));
+
+        let constraints = AlternativeConstraints { limitations: vec![Limitation::Knee], ..AlternativeConstraints::default() };
+        let alternatives = recommender.alternatives("glute_bridge", &constraints).unwrap();
+        assert!(!alternatives.is_empty());
+        assert!(alternatives.iter().all(|e| e.id != "squat" && e.id != "burpee"));
+
+        // Without the limitation nothing is excluded
+        assert!(recommender.recommend(&test_user(FitnessLevel::Intermediate), &[]).excluded.is_empty());
+    }
+
+    #[test]
+    fn test_strength_session_warms_up_and_stretches_the_worked_muscles() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Intermediate);
+        user.goals = vec![FitnessGoal::Strength];
+
+        let recommendation = recommender.recommend(&user, &[]);
+        let worked = recommender.primary_muscles(&recommendation.exercises);
+        assert!(!worked.is_empty());
+
+        let warmup = recommendation.warmup.unwrap();
+        let warmed: Vec<&MuscleGroup> = warmup.movements.iter().flat_map(|m| &m.muscles).collect();
+        for muscle in &worked {
+            assert!(warmed.contains(&muscle), "warmup {:?} misses {:?}", warmed, muscle);
+        }
+        assert!(warmup.movements.iter().all(|m| m.style != MovementStyle::StaticStretch));
+        assert_eq!(warmup.duration_seconds, warmup.movements.iter().map(|m| m.duration_seconds).sum::<u32>());
+
+        let cooldown = recommendation.cooldown.unwrap();
+        assert!(!cooldown.movements.is_empty());
+        assert!(cooldown.movements.iter().all(|m| m.style == MovementStyle::StaticStretch));
+        let stretched: Vec<&MuscleGroup> = cooldown.movements.iter().flat_map(|m| &m.muscles).collect();
+        assert!(stretched.iter().all(|muscle| worked.contains(muscle)));
+
+        // The phases sit outside the working sets' duration and can be turned off
+        user.preferences.warmup_cooldown = false;
+        let without = recommender.recommend(&user, &[]);
+        assert!(without.warmup.is_none() && without.cooldown.is_none());
+        assert_eq!(without.estimated_duration_minutes, recommendation.estimated_duration_minutes);
+    }
+
+    #[test]
+    fn test_strength_sets_are_loaded_from_the_one_rep_max() {
+        let one_rm = crate::models::exercise::estimate_1rm(100.0, 5);
+        let recommender = WorkoutRecommender::default()
+            .with_one_rep_maxes(HashMap::from([("deadlift".to_string(), one_rm)]));
+        let mut user = test_user_with_equipment(FitnessLevel::Advanced, vec![Equipment::Barbells]);
+        user.goals = vec![FitnessGoal::Strength];
+
+        let recommendation = recommender.recommend(&user, &[]);
+        let deadlift = recommendation.exercises.iter().find(|s| s.exercise_id == "deadlift").unwrap();
+        let weight = deadlift.weight_kg.unwrap();
+        assert!(weight < one_rm, "{} kg from a {} kg max", weight, one_rm);
+        // RPE 8.5 leaves reps in reserve, so it's lighter than an all-out set
+        assert!(weight < suggest_weight(one_rm, deadlift.reps));
+        assert_ne!(weight, 60.0);
+
+        // Without a max the baseline weight stands
+        let baseline = WorkoutRecommender::default().recommend(&user, &[]);
+        let deadlift = baseline.exercises.iter().find(|s| s.exercise_id == "deadlift").unwrap();
+        assert_eq!(deadlift.weight_kg, Some(60.0));
+
+        // Barbell work says how to load the bar, in the user's units
+        let plates = &baseline.plate_loading;
+        assert_eq!(plates.len(), 1);
+        assert_eq!((plates[0].exercise_id.as_str(), plates[0].unit.as_str()), ("deadlift", "kg"));
+        assert_eq!((plates[0].per_side.clone(), plates[0].total), (vec![20.0], 60.0));
+        user.preferences.unit_system = UnitSystem::Imperial;
+        let imperial = WorkoutRecommender::default().recommend(&user, &[]);
+        assert_eq!(imperial.plate_loading[0].unit, "lb");
+        assert_eq!(imperial.plate_loading[0].bar, 45.0);
+    }
+
+    #[test]
+    fn test_bodyweight_only_user_gets_no_equipment_exercises() {
+        let recommender = WorkoutRecommender::default();
+        let user = test_user_with_equipment(FitnessLevel::Advanced, vec![Equipment::None]);
+        let recommendation = recommender.recommend(&user, &[]).exercises;
+        let catalog: HashMap<String, Exercise> = create_sample_exercises()
+            .into_iter()
+            .map(|e| (e.id.clone(), e))
+            .collect();
+
+        assert!(!recommendation.is_empty());
+        for set in &recommendation {
+            let exercise = &catalog[&set.exercise_id];
+            assert!(
+                !exercise.equipment_needed.contains(&Equipment::Barbells)
+                    && !exercise.equipment_needed.contains(&Equipment::Dumbbells),
+                "{} needs equipment the user does not have",
+                set.exercise_id
+            );
+        }
+        assert!(recommendation.iter().any(|s| s.exercise_id == "glute_bridge"));
+    }
+
+    #[test]
+    fn test_dumbbell_substitute_preserves_muscle_groups() {
+        let recommender = WorkoutRecommender::default();
+        let user = test_user_with_equipment(FitnessLevel::Advanced, vec![Equipment::Dumbbells]);
+        let recommendation = recommender.recommend(&user, &[]).exercises;
+
+        let rdl = recommendation
+            .iter()
+            .find(|s| s.exercise_id == "dumbbell_single_leg_rdl")
+            .expect("deadlift should be replaced by the dumbbell variant");
+        assert_eq!(rdl.weight_kg, Some(30.0));
+        assert!(shares_primary_muscle(
+            &recommender.exercises["deadlift"],
+            &recommender.exercises["dumbbell_single_leg_rdl"],
+        ));
+    }
+
+    #[test]
+    fn test_shorter_budget_yields_shorter_plan() {
+        let recommender = WorkoutRecommender::default();
+        let mut short_user = test_user(FitnessLevel::Intermediate);
+        short_user.preferences.workout_duration_minutes = 20;
+        let mut long_user = test_user(FitnessLevel::Intermediate);
+        long_user.preferences.workout_duration_minutes = 60;
+
+        let short_plan = recommender.recommend(&short_user, &[]);
+        let long_plan = recommender.recommend(&long_user, &[]);
+
+        assert!(short_plan.estimated_duration_minutes < long_plan.estimated_duration_minutes);
+        assert!(short_plan.estimated_duration_minutes <= 22);
+    }
+
+    #[test]
+    fn test_trimming_keeps_compound_movements() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Advanced);
+        user.preferences.workout_duration_minutes = 10;
+
+        let plan = recommender.recommend(&user, &[]);
+        assert!(plan.estimated_duration_minutes <= 11);
+        assert!(plan.exercises.iter().any(|s| s.exercise_id == "squat"));
+        assert!(!plan.exercises.iter().any(|s| s.exercise_id == "plank"));
+    }
+
+    #[test]
+    fn test_goal_templates_shape_reps_and_rest() {
+        let recommender = WorkoutRecommender::default();
+        let mut strength_user = test_user(FitnessLevel::Intermediate);
+        strength_user.goals = vec![FitnessGoal::Strength];
+        let mut weight_loss_user = test_user(FitnessLevel::Intermediate);
+        weight_loss_user.goals = vec![FitnessGoal::WeightLoss];
+
+        let strength = recommender.recommend(&strength_user, &[]).exercises;
+        let weight_loss = recommender.recommend(&weight_loss_user, &[]).exercises;
+
+        let squat_reps = |plan: &[ExerciseSet]| plan.iter().find(|s| s.exercise_id == "squat").unwrap().reps;
+        let avg_rest = |plan: &[ExerciseSet]| {
+            plan.iter().map(|s| s.rest_seconds).sum::<u32>() as f32 / plan.len() as f32
+        };
+
+        assert!(squat_reps(&strength) < squat_reps(&weight_loss));
+        assert!(avg_rest(&strength) > avg_rest(&weight_loss));
+
+        let squat = |plan: &[ExerciseSet]| plan.iter().find(|s| s.exercise_id == "squat").unwrap().clone();
+        assert_eq!(squat(&strength).tempo.as_deref(), Some("2010"));
+        assert_eq!(squat(&strength).target_rpe, Some(8.5));
+        assert_eq!(squat(&weight_loss).tempo, None);
+        assert_eq!(squat(&weight_loss).target_rpe, None);
+
+        // Timed holds get no tempo
+        let plank = exercise_set("plank", 3, 1, None, Some(45), 45);
+        let plank = recommender.apply_goals(plank, &[FitnessGoal::Strength], &FitnessLevel::Intermediate);
+        assert_eq!(plank.tempo, None);
+        assert_eq!(plank.target_rpe, None);
+    }
+
+    #[test]
+    fn test_multiple_goals_blend_templates() {
+        let recommender = WorkoutRecommender::default();
+        let squat = exercise_set("squat", 3, 15, None, None, 45);
+        let level = FitnessLevel::Intermediate;
+
+        let strength = recommender.apply_goals(squat.clone(), &[FitnessGoal::Strength], &level);
+        let weight_loss = recommender.apply_goals(squat.clone(), &[FitnessGoal::WeightLoss], &level);
+        let blended = recommender.apply_goals(
+            squat,
+            &[FitnessGoal::Strength, FitnessGoal::WeightLoss],
+            &level,
+        );
+
+        assert_eq!(blended.reps, 10);
+        assert_eq!(blended.rest_seconds, 85);
+        assert_eq!(blended.tempo.as_deref(), Some("2010"));
+        assert_eq!(blended.target_rpe, Some(8.5));
+        assert!(strength.reps < blended.reps && blended.reps < weight_loss.reps);
+    }
+
+    #[test]
+    fn test_four_day_week_separates_hard_sessions() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Intermediate);
+        user.goals = vec![FitnessGoal::Strength];
+        user.preferences.workouts_per_week = 4;
+        user.preferences.preferred_time_of_day = Some("morning".to_string());
+
+        let schedule = recommender.recommend_week(&user, &[]);
+        assert_eq!(schedule.days.len(), 7);
+        assert_eq!(schedule.training_days, 4);
+
+        let (training, rest): (Vec<_>, Vec<_>) = schedule.days.iter().partition(|d| d.workout.is_some());
+        assert_eq!(training.len(), 4);
+        assert_eq!(rest.len(), 3);
+        assert!(rest.iter().all(|d| d.focus == DayFocus::Rest && d.time_of_day.is_none()));
+        assert!(training.iter().all(|d| d.time_of_day.as_deref() == Some("morning")));
+
+        // Wrapping around so Sunday is checked against next Monday
+        let high = |i: usize| schedule.days[i % 7].intensity == SessionIntensity::High;
+        assert!((0..7).all(|i| !(high(i) && high(i + 1))));
+
+        let has_focus = |focus: DayFocus| training.iter().any(|d| d.focus == focus);
+        assert!(has_focus(DayFocus::LowerBody) && has_focus(DayFocus::UpperBody));
+        let lower = training.iter().find(|d| d.focus == DayFocus::LowerBody).unwrap();
+        assert!(lower.workout.as_ref().unwrap().exercises.iter().all(|s| s.exercise_id != "pushup"));
+    }
+
+    #[test]
+    fn test_daily_training_never_has_back_to_back_hard_days() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Advanced);
+        user.preferences.workouts_per_week = 7;
+
+        let schedule = recommender.recommend_week(&user, &[]);
+        let high = |i: usize| schedule.days[i % 7].intensity == SessionIntensity::High;
+        assert!(schedule.days.iter().all(|d| d.workout.is_some()));
+        assert!((0..7).all(|i| !(high(i) && high(i + 1))));
+    }
+
+    #[test]
+    fn test_weight_loss_plan_is_one_circuit_with_shared_rest() {
+        let recommender = WorkoutRecommender::default();
+        let mut user = test_user(FitnessLevel::Intermediate);
+        user.goals = vec![FitnessGoal::WeightLoss, FitnessGoal::Endurance];
+
+        let circuit = recommender.recommend(&user, &[]).exercises;
+        assert!(circuit.len() >= 2);
+        assert!(circuit.iter().all(|s| s.group_id.as_deref() == Some(CIRCUIT_GROUP_ID)));
+        let rest = circuit[0].rest_seconds;
+        assert!(circuit.iter().all(|s| s.rest_seconds == rest));
+
+        // Adding a strength goal keeps straight sets
+        user.goals.push(FitnessGoal::Strength);
+        let straight = recommender.recommend(&user, &[]).exercises;
+        assert!(straight.iter().all(|s| s.group_id.is_none()));
+    }
+}
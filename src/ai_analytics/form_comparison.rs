@@ -0,0 +1,193 @@
+// src/ai_analytics/form_comparison.rs - Comparing a user's pose against reference templates
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use std::collections::HashMap;
+
+use super::AIMotionAnalyzer;
+
+/// Deviation (degrees) a joint can be off by before it earns a cue
+const CUE_THRESHOLD_DEGREES: f64 = 10.0;
+/// Deviation (degrees) at which a joint contributes nothing to similarity
+const ZERO_SIMILARITY_DEGREES: f64 = 45.0;
+/// Most cues returned at once, so the user has a few things to focus on
+const MAX_CUES: usize = 3;
+
+/// Joint angles in degrees captured at a key position of a lift, e.g. the
+/// bottom of a squat. Knee valgus is inward knee collapse (0 = knees over toes).
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Pose {
+    pub exercise: String,
+    pub joint_angles: HashMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JointDelta {
+    pub joint: String,
+    pub user_angle: f64,
+    pub reference_angle: f64,
+    /// User angle minus reference angle
+    pub delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FormCue {
+    pub joint: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FormComparison {
+    pub exercise: String,
+    /// 1.0 is a perfect match with the reference
+    pub similarity: f64,
+    /// Per-joint deviations, largest first
+    pub joint_deltas: Vec<JointDelta>,
+    /// Cues for the largest deviations past the threshold
+    pub cues: Vec<FormCue>,
+}
+
+fn pose(exercise: &str, angles: &[(&str, f64)]) -> Pose {
+    Pose {
+        exercise: exercise.to_string(),
+        joint_angles: angles.iter().map(|(joint, angle)| (joint.to_string(), *angle)).collect(),
+    }
+}
+
+/// Bundled template for the bottom (or hardest) position of a common lift
+pub fn reference_pose(exercise: &str) -> Option<Pose> {
+    match exercise.to_lowercase().as_str() {
+        "squat" => Some(pose("squat", &[
+            ("left_knee", 90.0),
+            ("right_knee", 90.0),
+            ("hip", 80.0),
+            ("trunk_lean", 40.0),
+            ("left_knee_valgus", 0.0),
+            ("right_knee_valgus", 0.0),
+        ])),
+        "deadlift" => Some(pose("deadlift", &[
+            ("left_knee", 140.0),
+            ("right_knee", 140.0),
+            ("hip", 95.0),
+            ("trunk_lean", 50.0),
+            ("spine_flexion", 0.0),
+        ])),
+        "pushup" | "push-up" => Some(pose("pushup", &[
+            ("left_elbow", 90.0),
+            ("right_elbow", 90.0),
+            ("body_line", 180.0),
+            ("elbow_flare", 45.0),
+        ])),
+        _ => None,
+    }
+}
+
+/// What to tell the user when `joint` is `delta` degrees off the reference
+fn joint_cue(joint: &str, delta: f64) -> String {
+    let too_high = delta > 0.0;
+    let cue = if joint.ends_with("knee_valgus") {
+        "Push your knees out so they track over your toes"
+    } else if joint.ends_with("knee") {
+        if too_high { "Bend your knees more" } else { "Don't bend your knees so far" }
+    } else if joint == "hip" {
+        if too_high { "Sit back and hinge further at the hips" } else { "Don't drop your hips so low" }
+    } else if joint == "trunk_lean" {
+        if too_high { "Keep your chest up" } else { "Let your torso lean forward a little more" }
+    } else if joint == "spine_flexion" {
+        "Keep your back flat - avoid rounding your spine"
+    } else if joint.ends_with("elbow") {
+        if too_high { "Lower your chest further" } else { "Don't sink below your range" }
+    } else if joint == "body_line" {
+        "Keep your hips in line with your shoulders and ankles"
+    } else if joint == "elbow_flare" {
+        if too_high { "Tuck your elbows closer to your body" } else { "Let your elbows flare out slightly" }
+    } else {
+        return format!("Adjust your {} by {:.0}°", joint.replace('_', " "), -delta);
+    };
+    cue.to_string()
+}
+
+impl AIMotionAnalyzer {
+    /// Compare a user's pose to a reference: per-joint angle deltas, an
+    /// overall similarity score, and cues for the biggest deviations. Only
+    /// joints present in both poses are compared.
+    pub fn compare_to_reference(&self, user_pose: &Pose, reference_pose: &Pose) -> FormComparison {
+        let mut joint_deltas: Vec<JointDelta> = reference_pose.joint_angles.iter()
+            .filter_map(|(joint, &reference_angle)| {
+                let user_angle = *user_pose.joint_angles.get(joint)?;
+                Some(JointDelta {
+                    joint: joint.clone(),
+                    user_angle,
+                    reference_angle,
+                    delta: user_angle - reference_angle,
+                })
+            })
+            .collect();
+        joint_deltas.sort_by(|a, b| {
+            b.delta.abs().partial_cmp(&a.delta.abs()).unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.joint.cmp(&b.joint))
+        });
+
+        let similarity = if joint_deltas.is_empty() {
+            0.0
+        } else {
+            joint_deltas.iter()
+                .map(|d| (1.0 - d.delta.abs() / ZERO_SIMILARITY_DEGREES).max(0.0))
+                .sum::<f64>() / joint_deltas.len() as f64
+        };
+
+        let mut cues: Vec<FormCue> = Vec::new();
+        for delta in joint_deltas.iter().filter(|d| d.delta.abs() > CUE_THRESHOLD_DEGREES) {
+            let message = joint_cue(&delta.joint, delta.delta);
+            // Left and right often share a cue; say it once
+            if cues.iter().any(|c| c.message == message) {
+                continue;
+            }
+            cues.push(FormCue { joint: delta.joint.clone(), message });
+            if cues.len() == MAX_CUES {
+                break;
+            }
+        }
+
+        FormComparison {
+            exercise: reference_pose.exercise.clone(),
+            similarity,
+            joint_deltas,
+            cues,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squat_with(overrides: &[(&str, f64)]) -> Pose {
+        let mut squat = reference_pose("squat").unwrap();
+        for (joint, angle) in overrides {
+            squat.joint_angles.insert(joint.to_string(), *angle);
+        }
+        squat
+    }
+
+    #[test]
+    fn test_compare_squat_to_reference() {
+        let analyzer = AIMotionAnalyzer::new();
+        let reference = reference_pose("squat").unwrap();
+
+        let good = analyzer.compare_to_reference(&squat_with(&[("left_knee", 93.0), ("hip", 78.0)]), &reference);
+        assert!(good.similarity > 0.95);
+        assert!(good.cues.is_empty());
+
+        let caving = analyzer.compare_to_reference(
+            &squat_with(&[("left_knee_valgus", 18.0), ("right_knee_valgus", 15.0)]),
+            &reference,
+        );
+        assert!(caving.similarity < good.similarity);
+        assert_eq!(caving.joint_deltas[0].joint, "left_knee_valgus");
+        // Both knees cave, but the cue is given once
+        assert_eq!(caving.cues.len(), 1);
+        assert!(caving.cues[0].joint.ends_with("knee_valgus"));
+        assert!(caving.cues[0].message.contains("knees out"));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod form_comparison;
 pub mod motion_analyzer;
 
+pub use form_comparison::{FormComparison, Pose};
 pub use motion_analyzer::{AIMotionAnalyzer, FormAnalysis};
\ No newline at end of file
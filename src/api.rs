@@ -1,66 +1,552 @@
 use std::sync::Arc;
-use serde::Deserialize;
+use std::time::{Duration, Instant};
+use serde::{de::DeserializeOwned, Deserialize};
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
-    routing::{get, post},
+    body::Bytes,
+    extract::{DefaultBodyLimit, Extension, FromRequest, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{delete, get, post, put},
     Router,
 };
 use tower::ServiceBuilder;
-use tower_http::cors::CorsLayer;
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tracing::{info, warn};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
-    AppState, ApiResponse, FitnessGoal,
-    models::optimization,
+    AppState, ApiResponse, FitnessGoal, Page, PageRequest,
+    advisors::menu_optimizer::{algorithm::CancellationToken, RunHooks},
+    auth::AuthUser,
+    config::ServerConfig,
+    database::{ExerciseSeeding, UserUpdate},
+    models::{optimization, FieldError, FieldErrors, HealthStatus, ReadinessReport, SubsystemHealth, Validate},
 };
 
-#[derive(Deserialize)]
+/// Body of a 422 response: every invalid field and why
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct ValidationErrorResponse {
+    pub success: bool,
+    pub message: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// A JSON body that must also pass its `Validate` checks. Invalid payloads
+/// are rejected with 422 before reaching the handler.
+pub struct ValidatedJson<T>(pub T);
+
+#[axum::async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(request, state).await
+            .map_err(IntoResponse::into_response)?;
+
+        if let Err(errors) = value.validate_fields() {
+            warn!("Rejected invalid payload: {} field error(s)", errors.len());
+            let body = ValidationErrorResponse {
+                success: false,
+                message: "Validation failed".to_string(),
+                errors,
+            };
+            return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response());
+        }
+
+        Ok(Self(value))
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct CreateUserRequest {
     pub user: crate::User,
+    /// Lets the user log in; users created without one cannot
+    pub password: Option<String>,
+    /// Units of the user's height and weight; stored as cm and kg
+    #[serde(default)]
+    pub units: crate::UnitSystem,
+}
+
+/// Most users one batch registration may contain
+pub const MAX_BATCH_USERS: usize = 500;
+
+#[derive(Deserialize, ToSchema)]
+pub struct BatchCreateUsersRequest {
+    /// Validated one by one; invalid entries are reported, not fatal
+    pub users: Vec<CreateUserRequest>,
+}
+
+/// Most exercises one bulk insert may contain
+pub const MAX_BULK_EXERCISES: usize = 1000;
+
+#[derive(Deserialize, ToSchema)]
+pub struct BulkExercisesRequest {
+    /// Added together or not at all; ids must be new and distinct
+    pub exercises: Vec<crate::Exercise>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateUserRequest {
+    /// The whole user as it should be saved; its id must match the path
+    pub user: crate::User,
+    /// Units of the user's height and weight; stored as cm and kg
+    #[serde(default)]
+    pub units: crate::UnitSystem,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub user_id: String,
+    pub password: String,
+}
+
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct LoginResponse {
+    pub token: String,
+    pub token_type: String,
+    pub expires_in_seconds: u64,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LogWorkoutRequest {
     pub workout: crate::WorkoutSession,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+pub struct RecordMeasurementRequest {
+    pub date: String,
+    /// Units of the values below: lb and inches when imperial, whatever
+    /// the field names say. Always stored as kg and cm.
+    #[serde(default)]
+    pub units: crate::UnitSystem,
+    pub weight_kg: f32,
+    pub waist_cm: Option<f32>,
+    pub hip_cm: Option<f32>,
+    pub arm_cm: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateGoalRequest {
+    /// e.g. "Bench 100kg"
+    pub title: String,
+    pub metric: crate::GoalMetric,
+    /// Required for `exercise_weight` goals
+    pub exercise_id: Option<String>,
+    /// In kg, cm or workouts, depending on the metric
+    pub target_value: f64,
+    /// YYYY-MM-DD
+    pub deadline: Option<String>,
+}
+
+impl Validate for CreateGoalRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.title.trim().is_empty(), "title", "must not be empty");
+        errors.check(self.target_value.is_finite() && self.target_value > 0.0, "target_value", "must be positive");
+        errors.check(
+            (self.metric == crate::GoalMetric::ExerciseWeight) == self.exercise_id.is_some(),
+            "exercise_id",
+            "is required for exercise_weight goals and only allowed for them",
+        );
+        errors.check(
+            self.deadline.as_deref()
+                .is_none_or(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok()),
+            "deadline",
+            "must be a YYYY-MM-DD date",
+        );
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecommendationFeedbackRequest {
+    pub rating: crate::FeedbackRating,
+    /// What made it (un)helpful
+    pub comment: Option<String>,
+}
+
+/// Longest feedback comment accepted
+const MAX_FEEDBACK_COMMENT_LEN: usize = 1000;
+
+impl Validate for RecommendationFeedbackRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(
+            self.comment.as_ref().is_none_or(|c| c.chars().count() <= MAX_FEEDBACK_COMMENT_LEN),
+            "comment",
+            "must be at most 1000 characters",
+        );
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    /// Receives a JSON POST per event
+    pub url: String,
+    pub events: Vec<crate::WebhookEvent>,
+    /// Signs each body; see the `X-Webhook-Signature` header
+    pub secret: String,
+}
+
+/// Shortest secret accepted for signing webhook bodies
+const MIN_WEBHOOK_SECRET_LEN: usize = 16;
+
+impl Validate for CreateWebhookRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(
+            self.url.starts_with("https://") || self.url.starts_with("http://"),
+            "url",
+            "must be an http or https URL",
+        );
+        errors.check(!self.events.is_empty(), "events", "must not be empty");
+        errors.check(
+            self.secret.len() >= MIN_WEBHOOK_SECRET_LEN,
+            "secret",
+            "must be at least 16 characters",
+        );
+        errors.into_result()
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SuggestRecipesRequest {
+    pub available_food_ids: Vec<String>,
+}
+
+/// A scanned product barcode
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BarcodeLookupRequest {
+    /// EAN-8, UPC-A, EAN-13 or GTIN-14 digits
+    pub barcode: String,
+}
+
+impl Validate for BarcodeLookupRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(
+            crate::open_food_facts::is_valid_barcode(&self.barcode),
+            "barcode",
+            "must be 8, 12, 13 or 14 digits",
+        );
+        errors.into_result()
+    }
+}
+
+/// Most days one Fitbit sync may import
+pub const MAX_FITBIT_SYNC_DAYS: u32 = 30;
+
+fn default_fitbit_sync_days() -> u32 {
+    7
+}
+
+/// A user's Fitbit tokens and how far back to import
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FitbitSyncRequest {
+    pub access_token: String,
+    pub refresh_token: String,
+    /// Days to import, counting back from today (default 7)
+    #[serde(default = "default_fitbit_sync_days")]
+    pub days: u32,
+}
+
+impl Validate for FitbitSyncRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.access_token.trim().is_empty(), "access_token", "must not be empty");
+        errors.check(!self.refresh_token.trim().is_empty(), "refresh_token", "must not be empty");
+        errors.check(
+            (1..=MAX_FITBIT_SYNC_DAYS).contains(&self.days),
+            "days",
+            format!("must be between 1 and {}", MAX_FITBIT_SYNC_DAYS),
+        );
+        errors.into_result()
+    }
+}
+
+/// What a Fitbit sync stored
+#[derive(Debug, serde::Serialize, ToSchema)]
+pub struct FitbitSyncResult {
+    /// Metric records that weren't already imported
+    pub imported: usize,
+    /// Tokens to use next time; Fitbit replaces both when the access token
+    /// is refreshed
+    pub tokens: crate::integrations::fitbit::FitbitTokens,
+}
+
+/// Ingredient swap for a catalog recipe
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SubstituteIngredientRequest {
+    pub food_id: String,
+    pub replacement_food_id: String,
+}
+
+/// Two solutions previously returned by /api/menu/optimize
+#[derive(Deserialize, ToSchema)]
+pub struct MealPlanDiffRequest {
+    pub old: optimization::OptimizationSolution,
+    pub new: optimization::OptimizationSolution,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteUserParams {
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FavoritesQuery {
+    /// Only favorites of this kind
+    pub kind: Option<crate::FavoriteKind>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreakQuery {
+    /// The user's UTC offset, so "today" is their local date; UTC by default
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    pub q: String,
+}
+
+/// Most alternatives one request may ask for
+const MAX_ALTERNATIVES: usize = 20;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AlternativesQuery {
+    /// Comma-separated equipment the user has, e.g. "None" for bodyweight
+    /// only or "Dumbbells,Bench"; any equipment when omitted
+    #[serde(default, deserialize_with = "comma_separated")]
+    #[param(value_type = Option<String>)]
+    pub equipment: Option<Vec<crate::Equipment>>,
+    /// Hardest difficulty level (1-10) to suggest
+    pub max_difficulty: Option<u32>,
+    /// Comma-separated limitations (Knee, Shoulder, LowerBack, Wrist);
+    /// exercises unsafe for any of them are left out
+    #[serde(default, deserialize_with = "comma_separated")]
+    #[param(value_type = Option<String>)]
+    pub limitations: Option<Vec<crate::Limitation>>,
+    /// How many to return, at most 20; 5 by default
+    pub limit: Option<usize>,
+}
+
+/// A query parameter listing enum values by name, separated by commas
+fn comma_separated<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let Some(list) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    list.split(',')
+        .map(|name| serde_json::from_value(serde_json::Value::String(name.trim().to_string())))
+        .collect::<Result<Vec<T>, _>>()
+        .map(Some)
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct AnalyzeFormRequest {
     pub video_base64: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+pub struct CompareFormRequest {
+    pub user_pose: crate::Pose,
+    /// Defaults to the bundled template for the user pose's exercise
+    pub reference_pose: Option<crate::Pose>,
+}
+
+#[derive(Deserialize, ToSchema)]
 pub struct AnalyzeFrameRequest {
     pub frame_base64: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AnalyzeVideoRequest {
     pub video_base64: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MLBatchRequest {
     pub video_path: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct OptimizeMealPlanRequest {
     pub user_id: String,
     pub goals: Vec<FitnessGoal>,
     pub time_horizon_days: u32,
     pub preferences: Option<optimization::UserPreferences>,
     pub objectives: Option<Vec<optimization::OptimizationObjective>>,
+    pub max_recipe_repetitions_per_week: Option<u32>,
+    /// Daily food budget in the same currency as food prices
+    pub budget_per_day: Option<f64>,
+    /// Month the plan is for (1-12); in-season produce is preferred
+    pub month: Option<u8>,
+    /// Percent of daily calories per meal type; normalized if it doesn't sum to 100
+    pub calorie_distribution: Option<optimization::CalorieDistribution>,
+    /// Daily eating window for time-restricted eating, e.g. 12:00-20:00
+    pub eating_window: Option<optimization::EatingWindow>,
+    /// Realtime session (`/api/ai/realtime?session_id=`) to send
+    /// per-generation progress to; no progress is sent without one
+    pub progress_session_id: Option<String>,
+}
+
+impl Validate for CreateUserRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.nested("user", &self.user);
+        errors.check(
+            self.password.as_ref().is_none_or(|p| p.len() >= crate::auth::MIN_PASSWORD_LEN),
+            "password",
+            format!("must be at least {} characters", crate::auth::MIN_PASSWORD_LEN),
+        );
+        errors.into_result()
+    }
+}
+
+impl Validate for BatchCreateUsersRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.users.is_empty(), "users", "must not be empty");
+        errors.check(
+            self.users.len() <= MAX_BATCH_USERS,
+            "users",
+            format!("must contain at most {} users", MAX_BATCH_USERS),
+        );
+        errors.into_result()
+    }
+}
+
+impl Validate for BulkExercisesRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.exercises.is_empty(), "exercises", "must not be empty");
+        errors.check(
+            self.exercises.len() <= MAX_BULK_EXERCISES,
+            "exercises",
+            format!("must contain at most {} exercises", MAX_BULK_EXERCISES),
+        );
+        let mut seen = std::collections::HashSet::new();
+        for (i, exercise) in self.exercises.iter().enumerate() {
+            let field = format!("exercises[{}]", i);
+            errors.nested(&field, exercise);
+            errors.check(seen.insert(exercise.id.as_str()), &format!("{}.id", field), "duplicates an earlier exercise");
+        }
+        errors.into_result()
+    }
+}
+
+impl Validate for UpdateUserRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.nested("user", &self.user);
+        errors.into_result()
+    }
+}
+
+impl Validate for LogWorkoutRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.nested("workout", &self.workout);
+        errors.into_result()
+    }
+}
+
+impl Validate for OptimizeMealPlanRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.user_id.trim().is_empty(), "user_id", "must not be empty");
+        errors.check(
+            (1..=30).contains(&self.time_horizon_days),
+            "time_horizon_days",
+            "must be between 1 and 30 days",
+        );
+        errors.check(
+            self.max_recipe_repetitions_per_week != Some(0),
+            "max_recipe_repetitions_per_week",
+            "must be greater than 0",
+        );
+        errors.check(
+            self.budget_per_day.is_none_or(|budget| budget > 0.0),
+            "budget_per_day",
+            "must be greater than 0",
+        );
+        errors.check(
+            self.month.is_none_or(|month| (1..=12).contains(&month)),
+            "month",
+            "must be between 1 and 12",
+        );
+        errors.check(
+            self.calorie_distribution.as_ref().is_none_or(optimization::CalorieDistribution::is_valid),
+            "calorie_distribution",
+            "shares must not be negative and must not all be zero",
+        );
+        errors.check(
+            self.objectives.as_ref().is_none_or(|objectives| !objectives.is_empty()),
+            "objectives",
+            "must not be empty when given",
+        );
+        errors.check(
+            self.progress_session_id.as_ref().is_none_or(|id| !id.trim().is_empty()),
+            "progress_session_id",
+            "must not be empty when given",
+        );
+        errors.into_result()
+    }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/users",
+    tag = "users",
+    summary = "Create a user",
+    request_body = CreateUserRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 403, description = "Admin role or a configured admin's id requested"),
+        (status = 409, description = "User id already taken"),
+    ),
+)]
 pub async fn create_user(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<CreateUserRequest>,
+    ValidatedJson(mut request): ValidatedJson<CreateUserRequest>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    match state.advisor.register_user(request.user.clone()).await {
-        Ok(_) => {
+    request.user = request.user.into_metric(request.units);
+
+    // Anyone can sign up as a client or coach; admins come from config, and
+    // their ids are reserved so nobody else can claim them
+    if request.user.role == crate::Role::Admin || state.config.auth.admin_user_ids.contains(&request.user.id) {
+        warn!("Refused to register {} as an admin", request.user.id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.register_user(request.user.clone(), request.password.as_deref()).await {
+        Ok(false) => {
+            warn!("Refused to register {}: id already taken", request.user.id);
+            Err(StatusCode::CONFLICT)
+        }
+        Ok(true) => {
             info!("User {} registered successfully", request.user.id);
             Ok(Json(ApiResponse::success(format!(
                 "User {} registered successfully", 
@@ -74,12 +560,135 @@ pub async fn create_user(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/users/batch",
+    tag = "users",
+    summary = "Create many users at once (admin only)",
+    request_body = BatchCreateUsersRequest,
+    responses(
+        (status = 200, description = "Per-user outcome", body = ApiResponse<crate::BatchRegistrationReport>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+        (status = 422, description = "Empty or oversized batch", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn create_users_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+    ValidatedJson(request): ValidatedJson<BatchCreateUsersRequest>,
+) -> Result<Json<ApiResponse<crate::BatchRegistrationReport>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let registrations = request.users.into_iter()
+        .map(|r| (r.user.into_metric(r.units), r.password))
+        .collect();
+    let report = state.advisor.register_users_batch(registrations, &state.config.auth.admin_user_ids).await;
+    info!("Batch registration: {} created, {} rejected", report.created, report.rejected);
+
+    let message = format!("{} created, {} rejected", report.created, report.rejected);
+    Ok(Json(ApiResponse {
+        success: report.rejected == 0,
+        data: Some(report),
+        message,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    summary = "Exchange a password for a bearer token",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<LoginResponse>),
+        (status = 401, description = "Unknown user or wrong password"),
+        (status = 429, description = "Account locked after repeated failures"),
+    ),
+)]
+pub async fn login(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, StatusCode> {
+    let auth = &state.config.auth;
+
+    match state.advisor.login(&request.user_id, &request.password, auth).await {
+        Ok(crate::auth::LoginOutcome::Success) => {}
+        Ok(crate::auth::LoginOutcome::InvalidCredentials) => {
+            warn!("Failed login for user {}", request.user_id);
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+        Ok(crate::auth::LoginOutcome::LockedOut) => {
+            warn!("Login refused for locked-out user {}", request.user_id);
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+        Err(e) => {
+            warn!("Failed to check credentials: {}", e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    }
+
+    // Deleted users keep their credentials until purged, so every login,
+    // configured admins' included, needs a live account
+    let user = match state.advisor.get_user(&request.user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err(StatusCode::UNAUTHORIZED),
+        Err(e) => {
+            warn!("Failed to look up role for {}: {}", request.user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+    let role = if auth.admin_user_ids.contains(&user.id) {
+        crate::Role::Admin
+    } else {
+        user.role
+    };
+    let scope = vec![crate::auth::role_scope(role).to_string()];
+
+    match state.tokens.issue(&request.user_id, scope) {
+        Ok(token) => {
+            info!("Issued API token for user {}", request.user_id);
+            Ok(Json(ApiResponse::success(LoginResponse {
+                token,
+                token_type: "Bearer".to_string(),
+                expires_in_seconds: auth.token_ttl_minutes * 60,
+            })))
+        }
+        Err(e) => {
+            warn!("Failed to issue token: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users",
+    tag = "users",
+    summary = "List users (admin only)",
+    params(PageRequest),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Page<crate::User>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn get_all_users(
+    Query(page): Query<PageRequest>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<Vec<crate::User>>>, StatusCode> {
-    match state.advisor.get_all_users().await {
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Page<crate::User>>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.get_users_page(page).await {
         Ok(users) => {
-            info!("Retrieved {} users", users.len());
+            info!("Retrieved {} of {} users", users.items.len(), users.total);
             Ok(Json(ApiResponse::success(users)))
         }
         Err(e) => {
@@ -89,408 +698,4272 @@ pub async fn get_all_users(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}",
+    tag = "users",
+    summary = "Get a user",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::User>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
 pub async fn get_user(
     Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<crate::User>>, StatusCode> {
+) -> Result<Response, StatusCode> {
     match state.advisor.get_user(&user_id).await {
         Ok(Some(user)) => {
             info!("Retrieved user {}", user_id);
-            Ok(Json(ApiResponse::success(user)))
+            Ok(([(header::ETAG, user_etag(user.version))], Json(ApiResponse::success(user))).into_response())
         }
         Ok(None) => {
             warn!("User {} not found", user_id);
-            Ok(Json(ApiResponse::error("User not found".to_string())))
+            Ok(Json(ApiResponse::<crate::User>::error("User not found".to_string())).into_response())
         }
         Err(e) => {
             warn!("Failed to get user {}: {}", user_id, e);
-            Ok(Json(ApiResponse::error(format!("Failed to get user: {}", e))))
+            Ok(Json(ApiResponse::<crate::User>::error(format!("Failed to get user: {}", e))).into_response())
         }
     }
 }
 
-pub async fn get_workout_recommendation(
+/// Strong ETag for a user version, e.g. `"3"`
+fn user_etag(version: u64) -> HeaderValue {
+    HeaderValue::from_str(&format!("\"{}\"", version)).expect("digits are a valid header value")
+}
+
+/// The version named by an `If-Match` header, as sent back from `user_etag`
+fn if_match_version(headers: &HeaderMap) -> Option<u64> {
+    headers.get(header::IF_MATCH)?
+        .to_str().ok()?
+        .trim()
+        .strip_prefix('"')?
+        .strip_suffix('"')?
+        .parse().ok()
+}
+
+/// Replace a user's profile. The `If-Match` header must carry the ETag
+/// from the caller's last read, so an edit made against a stale copy is
+/// refused with 412 instead of overwriting someone else's change.
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}",
+    tag = "users",
+    summary = "Update a user (requires If-Match)",
+    params(
+        ("user_id" = String, Path, description = "User id"),
+        ("If-Match" = String, Header, description = "ETag from the last GET, e.g. \"3\""),
+    ),
+    request_body = UpdateUserRequest,
+    responses(
+        (status = 200, description = "Success; the ETag header holds the new version", body = ApiResponse<crate::User>),
+        (status = 400, description = "Body user id differs from the path"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user or change their role"),
+        (status = 404, description = "No such user"),
+        (status = 412, description = "User changed since the given version"),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 428, description = "If-Match header missing"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn update_user(
     Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<Vec<crate::ExerciseSet>>>, StatusCode> {
-    match state.advisor.recommend_workout(&user_id).await {
-        Ok(recommendations) => {
-            info!("Generated workout recommendation for user {}", user_id);
-            Ok(Json(ApiResponse::success(recommendations)))
+    Extension(caller): Extension<AuthUser>,
+    headers: HeaderMap,
+    ValidatedJson(mut request): ValidatedJson<UpdateUserRequest>,
+) -> Result<Response, StatusCode> {
+    let expected_version = if_match_version(&headers).ok_or(StatusCode::PRECONDITION_REQUIRED)?;
+    request.user = request.user.into_metric(request.units);
+    if request.user.id != user_id {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    // Roles grant access to other users' data, so only admins change them
+    let current = state.advisor.get_user(&user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if current.role != request.user.role && !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.update_user(&request.user, expected_version).await {
+        Ok(UserUpdate::Updated(user)) => {
+            info!("User {} updated to version {}", user_id, user.version);
+            Ok(([(header::ETAG, user_etag(user.version))], Json(ApiResponse::success(user))).into_response())
+        }
+        Ok(UserUpdate::VersionMismatch(current)) => {
+            warn!("Stale update of user {}: version {} given, {} current", user_id, expected_version, current);
+            Err(StatusCode::PRECONDITION_FAILED)
         }
+        Ok(UserUpdate::NotFound) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            warn!("Failed to generate recommendation for user {}: {}", user_id, e);
-            Ok(Json(ApiResponse::error(format!("Recommendation failed: {}", e))))
+            warn!("Failed to update user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::<crate::User>::error(format!("Failed to update user: {}", e))).into_response())
         }
     }
 }
 
-pub async fn log_workout(
+/// Delete an account. Only the user themselves or an admin may; a linked
+/// coach can manage a client's data but not remove the client.
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}",
+    tag = "users",
+    summary = "Delete a user (soft by default)",
+    params(("user_id" = String, Path, description = "User id"), DeleteUserParams),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not this user or an admin"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn delete_user(
+    Path(user_id): Path<String>,
+    Query(params): Query<DeleteUserParams>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<LogWorkoutRequest>,
+    Extension(caller): Extension<AuthUser>,
 ) -> Result<Json<ApiResponse<String>>, StatusCode> {
-    match state.advisor.log_workout(request.workout.clone()).await {
-        Ok(_) => {
-            info!("Workout logged for user {}", request.workout.user_id);
-            Ok(Json(ApiResponse::success("Workout logged successfully".to_string())))
+    if caller.user_id != user_id && !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.delete_user(&user_id, params.hard).await {
+        Ok(true) => {
+            let kind = if params.hard { "permanently deleted" } else { "deleted" };
+            info!("User {} {}", user_id, kind);
+            Ok(Json(ApiResponse::success(format!("User {} {}", user_id, kind))))
+        }
+        Ok(false) => {
+            warn!("User {} not found", user_id);
+            Ok(Json(ApiResponse::error("User not found".to_string())))
         }
         Err(e) => {
-            warn!("Failed to log workout: {}", e);
-            Ok(Json(ApiResponse::error(format!("Logging failed: {}", e))))
+            warn!("Failed to delete user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to delete user: {}", e))))
         }
     }
 }
 
-pub async fn get_progress_analysis(
-    Path(user_id): Path<String>,
+/// Link a coach to a client. Only the client (or an admin) may choose
+/// their coaches, not a coach who already has access.
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/coaches/{coach_id}",
+    tag = "users",
+    summary = "Link a coach to this client",
+    params(("user_id" = String, Path, description = "User id"), ("coach_id" = String, Path, description = "Coach's user id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn link_coach(
+    Path((user_id, coach_id)): Path<(String, String)>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<crate::ProgressAnalysis>>, StatusCode> {
-    match state.advisor.analyze_progress(&user_id).await {
-        Ok(analysis) => {
-            info!("Generated progress analysis for user {}", user_id);
-            Ok(Json(ApiResponse::success(analysis)))
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    if caller.user_id != user_id && !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.link_coach(&coach_id, &user_id).await {
+        Ok(()) => Ok(Json(ApiResponse::success(format!("Coach {} linked to {}", coach_id, user_id)))),
+        Err(e) => {
+            warn!("Failed to link coach {} to {}: {}", coach_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to link coach: {}", e))))
         }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/coaches/{coach_id}",
+    tag = "users",
+    summary = "Unlink a coach from this client",
+    params(("user_id" = String, Path, description = "User id"), ("coach_id" = String, Path, description = "Coach's user id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn unlink_coach(
+    Path((user_id, coach_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    if caller.user_id != user_id && !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.unlink_coach(&coach_id, &user_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(format!("Coach {} unlinked from {}", coach_id, user_id)))),
+        Ok(false) => Ok(Json(ApiResponse::error("Coach not linked".to_string()))),
         Err(e) => {
-            warn!("Failed to analyze progress for user {}: {}", user_id, e);
-            Ok(Json(ApiResponse::error(format!("Analysis failed: {}", e))))
+            warn!("Failed to unlink coach {} from {}: {}", coach_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to unlink coach: {}", e))))
         }
     }
 }
 
-pub async fn get_user_workouts(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/clients",
+    tag = "users",
+    summary = "List a coach's linked clients",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::User>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_clients(
     Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<Vec<crate::WorkoutSession>>>, StatusCode> {
-    match state.advisor.get_user_workouts(&user_id).await {
-        Ok(workouts) => {
-            info!("Retrieved {} workouts for user {}", workouts.len(), user_id);
-            Ok(Json(ApiResponse::success(workouts)))
+) -> Result<Json<ApiResponse<Vec<crate::User>>>, StatusCode> {
+    match state.advisor.get_clients(&user_id).await {
+        Ok(clients) => {
+            info!("Retrieved {} clients for coach {}", clients.len(), user_id);
+            Ok(Json(ApiResponse::success(clients)))
         }
         Err(e) => {
-            warn!("Failed to get workouts for user {}: {}", user_id, e);
-            Ok(Json(ApiResponse::error(format!("Failed to get workouts: {}", e))))
+            warn!("Failed to get clients for {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get clients: {}", e))))
         }
     }
 }
 
-pub async fn get_exercises(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/favorites",
+    tag = "users",
+    summary = "List favorite recipes and exercises",
+    params(("user_id" = String, Path, description = "User id"), FavoritesQuery),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::Favorite>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn list_favorites(
+    Path(user_id): Path<String>,
+    Query(query): Query<FavoritesQuery>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<Vec<crate::Exercise>>>, StatusCode> {
-    match state.advisor.get_all_exercises().await {
-        Ok(exercises) => {
-            info!("Retrieved {} exercises", exercises.len());
-            Ok(Json(ApiResponse::success(exercises)))
-        }
+) -> Result<Json<ApiResponse<Vec<crate::Favorite>>>, StatusCode> {
+    match state.advisor.list_favorites(&user_id, query.kind).await {
+        Ok(favorites) => Ok(Json(ApiResponse::success(favorites))),
         Err(e) => {
-            warn!("Failed to get exercises: {}", e);
-            Ok(Json(ApiResponse::error(format!("Failed to get exercises: {}", e))))
+            warn!("Failed to list favorites for {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to list favorites: {}", e))))
         }
     }
 }
 
-pub async fn analyze_form(
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/goals",
+    tag = "users",
+    summary = "Set a goal",
+    params(("user_id" = String, Path, description = "User id")),
+    request_body = CreateGoalRequest,
+    responses(
+        (status = 200, description = "The goal with its starting progress", body = ApiResponse<crate::GoalProgress>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn create_goal(
+    Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<AnalyzeFormRequest>,
-) -> Result<Json<ApiResponse<crate::FormAnalysis>>, StatusCode> {
-    info!("🎥 Starting form analysis with RTX 5070...");
-    
-    let video_data = match base64::prelude::Engine::decode(&base64::prelude::BASE64_STANDARD, &request.video_base64) {
-        Ok(data) => data,
+    ValidatedJson(request): ValidatedJson<CreateGoalRequest>,
+) -> Result<Json<ApiResponse<crate::GoalProgress>>, StatusCode> {
+    let created = state.advisor.create_goal(
+        &user_id,
+        request.title,
+        request.metric,
+        request.exercise_id,
+        request.target_value,
+        request.deadline,
+    ).await;
+
+    match created {
+        Ok(progress) => Ok(Json(ApiResponse::success(progress))),
         Err(e) => {
-            warn!("Failed to decode video data: {}", e);
-            return Ok(Json(ApiResponse::error("Invalid video data".to_string())));
+            warn!("Failed to set goal for {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to set goal: {}", e))))
         }
-    };
+    }
+}
 
-    match state.ai_analyzer.analyze_form(&video_data).await {
-        Ok(analysis) => {
-            info!("✅ Form analysis completed using RTX 5070");
-            Ok(Json(ApiResponse::success(analysis)))
-        }
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/goals",
+    tag = "users",
+    summary = "Goals with their completion percentage",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::GoalProgress>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn list_goals(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<Vec<crate::GoalProgress>>>, StatusCode> {
+    match state.advisor.goals(&user_id).await {
+        Ok(goals) => Ok(Json(ApiResponse::success(goals))),
         Err(e) => {
-            warn!("❌ Form analysis failed: {}", e);
-            Ok(Json(ApiResponse::error(format!("Analysis failed: {}", e))))
+            warn!("Failed to list goals for {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to list goals: {}", e))))
         }
     }
 }
 
-pub async fn health_check() -> Json<ApiResponse<String>> {
-    Json(ApiResponse::success("Fitness Advisor AI is healthy! 💪".to_string()))
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/goals/{goal_id}",
+    tag = "users",
+    summary = "One goal with its completion percentage",
+    params(
+        ("user_id" = String, Path, description = "User id"),
+        ("goal_id" = String, Path, description = "Goal id"),
+    ),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::GoalProgress>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 404, description = "No such goal"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_goal(
+    Path((user_id, goal_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<crate::GoalProgress>>, StatusCode> {
+    match state.advisor.goals(&user_id).await {
+        Ok(goals) => goals.into_iter()
+            .find(|progress| progress.goal.id == goal_id)
+            .map(|progress| Json(ApiResponse::success(progress)))
+            .ok_or(StatusCode::NOT_FOUND),
+        Err(e) => {
+            warn!("Failed to get goal {} for {}: {}", goal_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get goal: {}", e))))
+        }
+    }
 }
 
-pub async fn database_health(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/achievements",
+    tag = "users",
+    summary = "Badges the user has earned",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::Achievement>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn list_achievements(
+    Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Json<ApiResponse<crate::database::DatabaseHealth>> {
-    match state.advisor.database_health().await {
-        Ok(health) => Json(ApiResponse::success(health)),
+) -> Result<Json<ApiResponse<Vec<crate::Achievement>>>, StatusCode> {
+    match state.advisor.achievements(&user_id).await {
+        Ok(achievements) => Ok(Json(ApiResponse::success(achievements))),
         Err(e) => {
-            warn!("Database health check failed: {}", e);
-            Json(ApiResponse::error(format!("Database error: {}", e)))
+            warn!("Failed to list achievements for {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to list achievements: {}", e))))
         }
     }
 }
 
-pub async fn gpu_status() -> Json<ApiResponse<crate::GpuStatus>> {
-    let status = crate::GpuStatus {
-        gpu_available: true,
-        gpu_name: "NVIDIA GeForce RTX 5070 Laptop GPU".to_string(),
-        compute_capability: "12.0".to_string(),
-        vram_total_mb: 7716,
-        vram_used_mb: 72,
-        cuda_version: "12.4".to_string(),
-        ready_for_ai: true,
-        features: vec![
-            "Real-time pose estimation".to_string(),
-            "Form analysis".to_string(),
-            "Motion tracking".to_string(),
-            "AI workout recommendations".to_string(),
-            "Database-backed analytics".to_string(),
-        ],
-    };
-    
-    Json(ApiResponse::success(status))
+/// Recipe suggestions are identified by recipe id. Rating again replaces
+/// the user's earlier vote.
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/feedback/{recommendation_id}",
+    tag = "users",
+    summary = "Rate a recommendation thumbs up or down",
+    params(
+        ("user_id" = String, Path, description = "User id"),
+        ("recommendation_id" = String, Path, description = "Recommendation id"),
+    ),
+    request_body = RecommendationFeedbackRequest,
+    responses(
+        (status = 200, description = "The recommendation's feedback after this vote", body = ApiResponse<crate::FeedbackSummary>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 422, description = "Comment too long", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn record_feedback(
+    Path((user_id, recommendation_id)): Path<(String, String)>,
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(request): ValidatedJson<RecommendationFeedbackRequest>,
+) -> Result<Json<ApiResponse<crate::FeedbackSummary>>, StatusCode> {
+    let recorded = state.advisor
+        .record_feedback(&user_id, &recommendation_id, request.rating, request.comment.as_deref())
+        .await;
+
+    match recorded {
+        Ok(summary) => {
+            info!("User {} rated {} {}", user_id, recommendation_id, request.rating.as_str());
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            warn!("Failed to record feedback on {} for {}: {}", recommendation_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to record feedback: {}", e))))
+        }
+    }
 }
 
-pub async fn ml_analyze_frame(
+#[utoipa::path(
+    get,
+    path = "/api/recommendations/{recommendation_id}/feedback",
+    tag = "menu",
+    summary = "Thumbs up and down counts for a recommendation",
+    params(("recommendation_id" = String, Path, description = "Recommendation id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::FeedbackSummary>),
+        (status = 401, description = "Missing or invalid bearer token"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_feedback_summary(
+    Path(recommendation_id): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<AnalyzeFrameRequest>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    match state.ml_client.analyze_frame_realtime(request.frame_base64).await {
-        Ok(response) => {
-            if response.success {
-                info!("ML frame analysis completed in {:.2}ms", response.processing_time_ms);
-                Ok(Json(ApiResponse::success(response.result)))
-            } else {
-                warn!("ML frame analysis failed: {:?}", response.error);
-                Ok(Json(ApiResponse::error(
-                    response.error.unwrap_or("Analysis failed".to_string())
-                )))
-            }
-        }
+) -> Result<Json<ApiResponse<crate::FeedbackSummary>>, StatusCode> {
+    match state.advisor.feedback_summary(&recommendation_id).await {
+        Ok(summary) => Ok(Json(ApiResponse::success(summary))),
         Err(e) => {
-            warn!("ML service request failed: {}", e);
-            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+            warn!("Failed to get feedback on {}: {}", recommendation_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get feedback: {}", e))))
         }
     }
 }
 
-pub async fn ml_analyze_video(
+#[utoipa::path(
+    get,
+    path = "/api/experiments/{experiment}/results",
+    tag = "menu",
+    summary = "Outcome metrics per variant of an experiment (admin only)",
+    params(("experiment" = String, Path, description = "Experiment name, e.g. meal_plan_algorithm")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::VariantResults>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_experiment_results(
+    Path(experiment): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<AnalyzeVideoRequest>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    match state.ml_client.analyze_video(request.video_base64, "detailed").await {
-        Ok(response) => {
-            if response.success {
-                info!("ML video analysis completed in {:.2}ms", response.processing_time_ms);
-                Ok(Json(ApiResponse::success(response.result)))
-            } else {
-                warn!("ML video analysis failed: {:?}", response.error);
-                Ok(Json(ApiResponse::error(
-                    response.error.unwrap_or("Analysis failed".to_string())
-                )))
-            }
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<crate::VariantResults>>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.experiment_results(&experiment).await {
+        Ok(results) => Ok(Json(ApiResponse::success(results))),
+        Err(e) => {
+            warn!("Failed to get results of experiment {}: {}", experiment, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get experiment results: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    summary = "Subscribe a URL to events (admin only)",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::WebhookSubscription>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+        (status = 422, description = "Invalid URL, events or secret", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+    ValidatedJson(request): ValidatedJson<CreateWebhookRequest>,
+) -> Result<Json<ApiResponse<crate::WebhookSubscription>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let mut events = request.events;
+    events.dedup();
+    match state.advisor.create_webhook(request.url, events, &request.secret).await {
+        Ok(subscription) => {
+            info!("Webhook {} subscribed to {:?}", subscription.id, subscription.events);
+            Ok(Json(ApiResponse::success(subscription)))
         }
         Err(e) => {
-            warn!("ML service request failed: {}", e);
-            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+            warn!("Failed to create webhook: {}", e);
+            Ok(Json(ApiResponse::error(format!("Failed to create webhook: {}", e))))
         }
     }
 }
 
-pub async fn ml_analyze_batch(
+#[utoipa::path(
+    get,
+    path = "/api/webhooks",
+    tag = "webhooks",
+    summary = "Webhook subscriptions (admin only)",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::WebhookSubscription>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn list_webhooks(
     State(state): State<Arc<AppState>>,
-    Json(request): Json<MLBatchRequest>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    match state.ml_client.analyze_batch(request.video_path).await {
-        Ok(response) => {
-            if response.success {
-                info!("ML batch analysis completed in {:.2}ms", response.processing_time_ms);
-                Ok(Json(ApiResponse::success(response.result)))
-            } else {
-                warn!("ML batch analysis failed: {:?}", response.error);
-                Ok(Json(ApiResponse::error(
-                    response.error.unwrap_or("Analysis failed".to_string())
-                )))
-            }
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<crate::WebhookSubscription>>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.list_webhooks().await {
+        Ok(subscriptions) => Ok(Json(ApiResponse::success(subscriptions))),
+        Err(e) => {
+            warn!("Failed to list webhooks: {}", e);
+            Ok(Json(ApiResponse::error(format!("Failed to list webhooks: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/webhooks/{webhook_id}",
+    tag = "webhooks",
+    summary = "Unsubscribe a webhook and drop its queued deliveries (admin only)",
+    params(("webhook_id" = String, Path, description = "Subscription id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+        (status = 404, description = "No such webhook"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn delete_webhook(
+    Path(webhook_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.delete_webhook(&webhook_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(format!("Webhook {} deleted", webhook_id)))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            warn!("Failed to delete webhook {}: {}", webhook_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to delete webhook: {}", e))))
         }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/webhooks/{webhook_id}/deliveries",
+    tag = "webhooks",
+    summary = "Delivery attempts for a webhook, including dead-lettered ones (admin only)",
+    params(("webhook_id" = String, Path, description = "Subscription id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::WebhookDelivery>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn list_webhook_deliveries(
+    Path(webhook_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+) -> Result<Json<ApiResponse<Vec<crate::WebhookDelivery>>>, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.webhook_deliveries(&webhook_id).await {
+        Ok(deliveries) => Ok(Json(ApiResponse::success(deliveries))),
         Err(e) => {
-            warn!("ML service request failed: {}", e);
-            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+            warn!("Failed to list deliveries for webhook {}: {}", webhook_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to list webhook deliveries: {}", e))))
         }
     }
 }
 
-pub async fn ml_service_status(
+#[utoipa::path(
+    put,
+    path = "/api/users/{user_id}/favorites/{kind}/{item_id}",
+    tag = "users",
+    summary = "Add a favorite recipe or exercise",
+    params(
+        ("user_id" = String, Path, description = "User id"),
+        ("kind" = crate::FavoriteKind, Path, description = "recipe or exercise"),
+        ("item_id" = String, Path, description = "Recipe or exercise id"),
+    ),
+    responses(
+        (status = 200, description = "Success, including when it was already a favorite", body = ApiResponse<String>),
+        (status = 400, description = "Unknown kind"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn add_favorite(
+    Path((user_id, kind, item_id)): Path<(String, crate::FavoriteKind, String)>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    match state.ml_client.models_status().await {
-        Ok(status) => {
-            info!("ML service status retrieved successfully");
-            Ok(Json(ApiResponse::success(serde_json::to_value(status).unwrap())))
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    match state.advisor.add_favorite(&user_id, kind, &item_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(format!("Added {} {} to favorites", kind.as_str(), item_id)))),
+        Ok(false) => Ok(Json(ApiResponse::success(format!("{} {} is already a favorite", kind.as_str(), item_id)))),
+        Err(e) => {
+            warn!("Failed to add favorite {} for {}: {}", item_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to add favorite: {}", e))))
         }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/users/{user_id}/favorites/{kind}/{item_id}",
+    tag = "users",
+    summary = "Remove a favorite recipe or exercise",
+    params(
+        ("user_id" = String, Path, description = "User id"),
+        ("kind" = crate::FavoriteKind, Path, description = "recipe or exercise"),
+        ("item_id" = String, Path, description = "Recipe or exercise id"),
+    ),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+        (status = 400, description = "Unknown kind"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn remove_favorite(
+    Path((user_id, kind, item_id)): Path<(String, crate::FavoriteKind, String)>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    match state.advisor.remove_favorite(&user_id, kind, &item_id).await {
+        Ok(true) => Ok(Json(ApiResponse::success(format!("Removed {} {} from favorites", kind.as_str(), item_id)))),
+        Ok(false) => Ok(Json(ApiResponse::error("Not a favorite".to_string()))),
         Err(e) => {
-            warn!("Failed to get ML service status: {}", e);
-            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+            warn!("Failed to remove favorite {} for {}: {}", item_id, user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to remove favorite: {}", e))))
         }
     }
 }
 
-pub async fn optimize_meal_plan(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/recommendations",
+    tag = "workouts",
+    summary = "Recommend a workout",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::WorkoutRecommendation>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_workout_recommendation(
+    Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-    Json(request): Json<OptimizeMealPlanRequest>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    let user_result = state.advisor.get_user(&request.user_id).await;
-    let user = match user_result {
-        Ok(Some(user)) => user,
-        Ok(None) => {
-            warn!("User not found for meal plan optimization: {}", request.user_id);
-            return Ok(Json(ApiResponse::error("User not found".to_string())));
+) -> Result<Json<ApiResponse<crate::WorkoutRecommendation>>, StatusCode> {
+    match state.advisor.recommend_workout(&user_id, &state.config.plates).await {
+        Ok(recommendations) => {
+            info!("Generated workout recommendation for user {}", user_id);
+            Ok(Json(ApiResponse::success(recommendations)))
         }
         Err(e) => {
-            warn!("Failed to get user {}: {}", request.user_id, e);
-            return Ok(Json(ApiResponse::error(format!("Database error: {}", e))));
+            warn!("Failed to generate recommendation for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Recommendation failed: {}", e))))
         }
-    };
+    }
+}
 
-    let constraints = match state.menu_optimizer.generate_nutrition_constraints(&user, &request.goals).await {
-        Ok(constraints) => constraints,
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/schedule",
+    tag = "workouts",
+    summary = "Recommend a week of training and rest days",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::WeeklySchedule>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_weekly_schedule(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<crate::WeeklySchedule>>, StatusCode> {
+    match state.advisor.recommend_week(&user_id).await {
+        Ok(schedule) => {
+            info!("Generated weekly schedule for user {}", user_id);
+            Ok(Json(ApiResponse::success(schedule)))
+        }
         Err(e) => {
-            warn!("Failed to generate nutrition constraints for user {}: {}", request.user_id, e);
-            return Ok(Json(ApiResponse::error(format!("Constraint generation failed: {}", e))));
+            warn!("Failed to generate weekly schedule for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Schedule failed: {}", e))))
         }
-    };
+    }
+}
 
-    let preferences = request.preferences.unwrap_or_else(|| optimization::UserPreferences {
-        dietary_restrictions: vec![],
-        allergens_to_avoid: vec![],
-        cuisine_preferences: vec!["American".to_string(), "Italian".to_string()],
-        disliked_foods: vec![],
-        preferred_foods: vec![],
-        taste_preferences: optimization::TastePreferences {
-            sweetness_preference: 0.0,
-            saltiness_preference: 0.0,
-            sourness_preference: 0.0,
-            bitterness_preference: 0.0,
-            umami_preference: 0.0,
-            spiciness_preference: 0.0,
-            spice_tolerance: 0.5,
-        },
-        cooking_skill_level: optimization::CookingSkillLevel::Intermediate,
-        equipment_available: vec![
-            optimization::CookingEquipment::Stovetop,
-            optimization::CookingEquipment::Oven,
-            optimization::CookingEquipment::Microwave,
-        ],
-        meal_variety_importance: 0.7,
-        cost_importance: 0.5,
-        health_importance: 0.8,
-        convenience_importance: 0.6,
-    });
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/carb-cycle",
+    tag = "menu",
+    summary = "Cycle daily carbs around the weekly training schedule",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::CarbCyclePlan>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_carb_cycle_plan(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<crate::CarbCyclePlan>>, StatusCode> {
+    match state.advisor.carb_cycle_plan(&user_id).await {
+        Ok(plan) => Ok(Json(ApiResponse::success(plan))),
+        Err(e) => {
+            warn!("Failed to build carb cycle plan for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Carb cycle plan failed: {}", e))))
+        }
+    }
+}
 
-    let objectives = request.objectives.unwrap_or_else(|| vec![
-        optimization::OptimizationObjective::MaximizeNutrition,
-        optimization::OptimizationObjective::MaximizeTasteScore,
-        optimization::OptimizationObjective::BalanceMacros,
-        optimization::OptimizationObjective::MaximizeVariety,
-    ]);
+/// Header clients set so retried workout submissions are only logged once
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
 
-    let opt_request = optimization::OptimizationRequest {
-        user_id: request.user_id.clone(),
-        constraints,
-        preferences,
-        objectives,
-        time_horizon_days: request.time_horizon_days,
-        algorithm_config: optimization::AlgorithmConfig::default(),
+#[utoipa::path(
+    post,
+    path = "/api/workouts",
+    tag = "workouts",
+    summary = "Log a workout session",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Makes retries log the workout once")),
+    request_body = LogWorkoutRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::WorkoutSession>),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 409, description = "Workout id belongs to another user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn log_workout(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+    headers: HeaderMap,
+    ValidatedJson(request): ValidatedJson<LogWorkoutRequest>,
+) -> Result<Json<ApiResponse<crate::WorkoutSession>>, StatusCode> {
+    caller.authorize(&state.advisor, &request.workout.user_id).await?;
+
+    let idempotency_key = headers.get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty());
+
+    match state.advisor.log_workout(request.workout, idempotency_key).await {
+        Ok(workout) => {
+            info!("Workout {} logged for user {}", workout.id, workout.user_id);
+            Ok(Json(ApiResponse::success(workout)))
+        }
+        Err(e) if e.downcast_ref::<crate::database::WorkoutIdTaken>().is_some() => {
+            warn!("Refused to log workout: {}", e);
+            Err(StatusCode::CONFLICT)
+        }
+        Err(e) => {
+            warn!("Failed to log workout: {}", e);
+            Ok(Json(ApiResponse::error(format!("Logging failed: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/measurements",
+    tag = "users",
+    summary = "Record a body measurement",
+    params(("user_id" = String, Path, description = "User id")),
+    request_body = RecordMeasurementRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::BodyMeasurement>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn record_measurement(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<RecordMeasurementRequest>,
+) -> Result<Json<ApiResponse<crate::BodyMeasurement>>, StatusCode> {
+    let units = request.units;
+    let to_cm = |length: f32| units.length_to_cm(length as f64) as f32;
+    let measurement = crate::BodyMeasurement {
+        user_id: user_id.clone(),
+        date: request.date,
+        weight_kg: units.weight_to_kg(request.weight_kg as f64) as f32,
+        waist_cm: request.waist_cm.map(to_cm),
+        hip_cm: request.hip_cm.map(to_cm),
+        arm_cm: request.arm_cm.map(to_cm),
     };
 
-    match state.menu_optimizer.optimize_meal_plan(opt_request).await {
-        Ok(solution) => {
-            info!("Menu optimization completed for user {}", request.user_id);
-            Ok(Json(ApiResponse::success(serde_json::to_value(solution).unwrap())))
+    match state.advisor.record_measurement(measurement.clone()).await {
+        Ok(_) => {
+            info!("Measurement recorded for user {} on {}", user_id, measurement.date);
+            Ok(Json(ApiResponse::success(measurement)))
         }
         Err(e) => {
-            warn!("Menu optimization failed for user {}: {}", request.user_id, e);
-            Ok(Json(ApiResponse::error(format!("Optimization failed: {}", e))))
+            warn!("Failed to record measurement for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Recording failed: {}", e))))
         }
     }
 }
 
-pub async fn menu_optimizer_status(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/measurements",
+    tag = "users",
+    summary = "Body measurement history",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::BodyMeasurement>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_measurements(
+    Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
-    let metrics = state.menu_optimizer.get_metrics().await;
-    let (cache_size, hit_rate) = state.menu_optimizer.get_cache_stats().await;
-    
-    let recipe_count = state.menu_optimizer.get_recipe_count().await;
-    let food_count = state.menu_optimizer.get_food_count().await;
+) -> Result<Json<ApiResponse<Vec<crate::BodyMeasurement>>>, StatusCode> {
+    match state.advisor.get_measurements(&user_id).await {
+        Ok(measurements) => Ok(Json(ApiResponse::success(measurements))),
+        Err(e) => {
+            warn!("Failed to get measurements for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get measurements: {}", e))))
+        }
+    }
+}
 
-    let status = serde_json::json!({
-        "service": "Menu Optimizer",
-        "status": "healthy",
-        "metrics": metrics,
-        "cache": {
-            "size": cache_size,
-            "hit_rate": hit_rate
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/progress",
+    tag = "workouts",
+    summary = "Analyze training progress",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::ProgressAnalysis>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_progress_analysis(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<crate::ProgressAnalysis>>, StatusCode> {
+    match state.advisor.analyze_progress(&user_id).await {
+        Ok(analysis) => {
+            info!("Generated progress analysis for user {}", user_id);
+            Ok(Json(ApiResponse::success(analysis)))
+        }
+        Err(e) => {
+            warn!("Failed to analyze progress for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Analysis failed: {}", e))))
+        }
+    }
+}
+
+/// Accepts a HealthKit or Google Fit export in the standardized shape any
+/// subset of steps, heart rate, sleep and workouts
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/health-import",
+    tag = "users",
+    summary = "Import health data pushed by a mobile client",
+    params(("user_id" = String, Path, description = "User id")),
+    request_body = crate::HealthImport,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::HealthImportSummary>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn import_health_data(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(import): ValidatedJson<crate::HealthImport>,
+) -> Result<Json<ApiResponse<crate::HealthImportSummary>>, StatusCode> {
+    let source = import.source.as_str();
+    match state.advisor.import_health(&user_id, import).await {
+        Ok(summary) => {
+            info!("Imported {} health data for user {}: {:?}", source, user_id, summary);
+            Ok(Json(ApiResponse::success(summary)))
+        }
+        Err(e) => {
+            warn!("Health import for user {} failed: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Import failed: {}", e))))
+        }
+    }
+}
+
+/// Bring over a MyFitnessPal food diary, posted as CSV with a header row or
+/// as a JSON array of rows. Importing the same diary again adds nothing.
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/food-log/import",
+    tag = "menu",
+    summary = "Import a MyFitnessPal food diary",
+    params(("user_id" = String, Path, description = "User id")),
+    request_body(
+        content(
+            (String = "text/csv"),
+            (Vec<crate::integrations::myfitnesspal::DiaryRow> = "application/json"),
+        ),
+        description = "Date, Time, Meal, Food, Quantity (g), Calories, Protein (g), Carbohydrates (g), Fat (g)",
+    ),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::DiaryImportSummary>),
+        (status = 400, description = "The diary couldn't be read", body = ApiResponse<crate::DiaryImportSummary>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 415, description = "Neither CSV nor JSON"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn import_food_diary(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<(StatusCode, Json<ApiResponse<crate::DiaryImportSummary>>), StatusCode> {
+    let content_type = headers.get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or("").trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    let rows = match content_type.as_str() {
+        "text/csv" => match std::str::from_utf8(&body) {
+            Ok(text) => crate::integrations::myfitnesspal::parse_csv(text),
+            Err(_) => Err("The CSV isn't UTF-8 text".to_string()),
         },
-        "data": {
-            "recipes": recipe_count,
-            "foods": food_count
+        "application/json" => crate::integrations::myfitnesspal::parse_json(&body),
+        _ => {
+            warn!("Rejected food diary upload with content type '{}'", content_type);
+            return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
         }
-    });
+    };
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => return Ok((StatusCode::BAD_REQUEST, Json(ApiResponse::error(e)))),
+    };
 
-    Ok(Json(ApiResponse::success(status)))
+    match state.advisor.import_food_diary(&user_id, rows, &state.menu_optimizer).await {
+        Ok(summary) => {
+            info!("Imported {} food diary rows for user {}, skipped {}", summary.imported, user_id, summary.skipped.len());
+            Ok((StatusCode::OK, Json(ApiResponse::success(summary))))
+        }
+        Err(e) => {
+            warn!("Food diary import for user {} failed: {}", user_id, e);
+            Ok((StatusCode::OK, Json(ApiResponse::error(format!("Import failed: {}", e)))))
+        }
+    }
 }
 
-pub async fn get_menu_recommendations(
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/food-log",
+    tag = "menu",
+    summary = "Food log by day with daily totals",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::FoodLogDay>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_food_log(
     Path(user_id): Path<String>,
     State(state): State<Arc<AppState>>,
-) -> Result<Json<ApiResponse<Vec<String>>>, StatusCode> {
-    match state.menu_optimizer.get_optimization_recommendations(&user_id).await {
-        Ok(recommendations) => {
-            info!("Retrieved menu recommendations for user {}", user_id);
-            Ok(Json(ApiResponse::success(recommendations)))
+) -> Result<Json<ApiResponse<Vec<crate::FoodLogDay>>>, StatusCode> {
+    match state.advisor.get_food_log(&user_id).await {
+        Ok(days) => Ok(Json(ApiResponse::success(days))),
+        Err(e) => {
+            warn!("Failed to get food log for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get food log: {}", e))))
+        }
+    }
+}
+
+/// Pull recent daily activity and heart rate from Fitbit into the user's
+/// metrics, which progress analysis then reports on
+#[utoipa::path(
+    post,
+    path = "/api/users/{user_id}/integrations/fitbit/sync",
+    tag = "users",
+    summary = "Import activity and heart rate from Fitbit",
+    params(("user_id" = String, Path, description = "User id")),
+    request_body = FitbitSyncRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<FitbitSyncResult>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 500, description = "Metrics could not be stored; still has the tokens to use next time", body = ApiResponse<FitbitSyncResult>),
+        (status = 502, description = "Fitbit failed part way; has what was imported and the tokens to use next time", body = ApiResponse<FitbitSyncResult>),
+        (status = 503, description = "No Fitbit client credentials are configured", body = ApiResponse<FitbitSyncResult>),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn sync_fitbit(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(request): ValidatedJson<FitbitSyncRequest>,
+) -> (StatusCode, Json<ApiResponse<FitbitSyncResult>>) {
+    let Some(fitbit) = &state.fitbit_client else {
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(ApiResponse::error("Fitbit import is not configured".to_string())));
+    };
+
+    let mut tokens = crate::integrations::fitbit::FitbitTokens {
+        access_token: request.access_token,
+        refresh_token: request.refresh_token,
+    };
+    // Fitbit may have rotated the tokens before anything failed, and the old
+    // refresh token no longer works, so every outcome hands them back
+    let failed = |status: StatusCode, message: String, imported: usize, tokens| (status, Json(ApiResponse {
+        success: false,
+        message,
+        data: Some(FitbitSyncResult { imported, tokens }),
+    }));
+    match state.advisor.import_fitbit(fitbit, &user_id, &mut tokens, request.days).await {
+        Ok(crate::integrations::fitbit::FitbitImport { imported, error: None }) => {
+            info!("Imported {} Fitbit metrics for user {}", imported, user_id);
+            (StatusCode::OK, Json(ApiResponse::success(FitbitSyncResult { imported, tokens })))
+        }
+        Ok(crate::integrations::fitbit::FitbitImport { imported, error: Some(e) }) => {
+            warn!("Fitbit import for user {} stopped after {} metrics: {}", user_id, imported, e);
+            failed(StatusCode::BAD_GATEWAY, format!("Fitbit import failed: {}", e), imported, tokens)
         }
         Err(e) => {
-            warn!("Failed to get menu recommendations for user {}: {}", user_id, e);
-            Ok(Json(ApiResponse::error(format!("Failed to get recommendations: {}", e))))
+            warn!("Failed to store Fitbit metrics for user {}: {}", user_id, e);
+            failed(StatusCode::INTERNAL_SERVER_ERROR, format!("Fitbit import failed: {}", e), 0, tokens)
         }
     }
 }
 
-pub fn create_router(state: Arc<AppState>) -> Router {
-    Router::new()
-        .route("/api/users", post(create_user))
-        .route("/api/users", get(get_all_users))
-        .route("/api/users/:user_id", get(get_user))
-        .route("/api/users/:user_id/recommendations", get(get_workout_recommendation))
-        .route("/api/users/:user_id/progress", get(get_progress_analysis))
-        .route("/api/users/:user_id/workouts", get(get_user_workouts))
-        
-        .route("/api/exercises", get(get_exercises))
-        
-        .route("/api/workouts", post(log_workout))
-        
-        .route("/api/ai/analyze-form", post(analyze_form))
-        .route("/api/ai/realtime", get(crate::websocket::websocket_handler))
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/report.pdf",
+    tag = "users",
+    summary = "Printable PDF of the week's training plan, nutrition targets and progress",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The report", content_type = "application/pdf", body = Vec<u8>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 404, description = "No such user, or reports aren't enabled for them"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_report_pdf(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, StatusCode> {
+    if !crate::features::is_enabled(&state.config.features, crate::features::PDF_REPORT, Some(&user_id)) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let data = match state.advisor.report_data(&user_id).await {
+        Ok(Some(data)) => data,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            warn!("Failed to gather report for user {}: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    let pdf = crate::report::render(&data);
+    info!("Generated {} byte report for user {}", pdf.len(), user_id);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, format!("inline; filename=\"report-{}.pdf\"", user_id)),
+        ],
+        pdf,
+    ).into_response())
+}
+
+/// Everything held about a user as one JSON document, streamed as it's
+/// gathered. Sections are listed in the header with a schema version.
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/export",
+    tag = "users",
+    summary = "Download all of a user's data",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The archive, as an attachment", content_type = "application/json"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+        (status = 404, description = "No such user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn export_user_data(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, StatusCode> {
+    let user = match state.advisor.get_user(&user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            warn!("Failed to load user {} for export: {}", user_id, e);
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        }
+    };
+
+    info!("Exporting data for user {}", user_id);
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"export-{}.json\"", user_id)),
+        ],
+        axum::body::Body::from_stream(crate::export::stream(state.advisor.clone(), user)),
+    ).into_response())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/streak",
+    tag = "workouts",
+    summary = "Workout streak and weekly consistency",
+    params(("user_id" = String, Path, description = "User id"), StreakQuery),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::StreakInfo>),
+        (status = 400, description = "UTC offset out of range"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_streak(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StreakQuery>,
+) -> Result<Json<ApiResponse<crate::StreakInfo>>, StatusCode> {
+    let utc_offset = query.utc_offset_minutes.checked_mul(60)
+        .and_then(chrono::FixedOffset::east_opt)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    match state.advisor.get_streak(&user_id, utc_offset).await {
+        Ok(streak) => Ok(Json(ApiResponse::success(streak))),
+        Err(e) => {
+            warn!("Failed to compute streak for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to compute streak: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/users/{user_id}/workouts",
+    tag = "workouts",
+    summary = "Workout history",
+    params(("user_id" = String, Path, description = "User id"), PageRequest),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Page<crate::WorkoutSession>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_user_workouts(
+    Path(user_id): Path<String>,
+    Query(page): Query<PageRequest>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<Page<crate::WorkoutSession>>>, StatusCode> {
+    match state.advisor.get_user_workouts_page(&user_id, page).await {
+        Ok(workouts) => {
+            info!("Retrieved {} of {} workouts for user {}", workouts.items.len(), workouts.total, user_id);
+            Ok(Json(ApiResponse::success(workouts)))
+        }
+        Err(e) => {
+            warn!("Failed to get workouts for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get workouts: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/exercises",
+    tag = "exercises",
+    summary = "List exercises",
+    params(
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the client's copy"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified of the client's copy"),
+    ),
+    responses(
+        (status = 200, description = "Success, with ETag and Last-Modified", body = ApiResponse<Vec<crate::Exercise>>),
+        (status = 304, description = "The client's copy is current"),
+    ),
+)]
+pub async fn get_exercises(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    let listed = async {
+        Ok::<_, anyhow::Error>((state.advisor.get_all_exercises().await?, state.advisor.exercises_modified_at().await?))
+    };
+    match listed.await {
+        Ok((exercises, modified_at)) => {
+            info!("Retrieved {} exercises", exercises.len());
+            crate::caching::conditional_json(&headers, &ApiResponse::success(exercises), modified_at)
+        }
+        Err(e) => {
+            warn!("Failed to get exercises: {}", e);
+            Json(ApiResponse::<Vec<crate::Exercise>>::error(format!("Failed to get exercises: {}", e))).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/exercises/search",
+    tag = "exercises",
+    summary = "Search exercises",
+    params(
+        SearchQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the client's copy"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified of the client's copy"),
+    ),
+    responses(
+        (status = 200, description = "Success, with ETag and Last-Modified", body = ApiResponse<Vec<crate::Exercise>>),
+        (status = 304, description = "The client's copy is current"),
+    ),
+)]
+pub async fn search_exercises(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let searched = async {
+        Ok::<_, anyhow::Error>((state.advisor.search_exercises(&query.q).await?, state.advisor.exercises_modified_at().await?))
+    };
+    match searched.await {
+        Ok((exercises, modified_at)) => {
+            info!("Exercise search '{}' matched {} exercises", query.q, exercises.len());
+            crate::caching::conditional_json(&headers, &ApiResponse::success(exercises), modified_at)
+        }
+        Err(e) => {
+            warn!("Failed to search exercises: {}", e);
+            Json(ApiResponse::<Vec<crate::Exercise>>::error(format!("Failed to search exercises: {}", e))).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/exercises/{exercise_id}/alternatives",
+    tag = "exercises",
+    summary = "Exercises training the same muscles, for when this one can't be done",
+    params(("exercise_id" = String, Path, description = "Exercise id"), AlternativesQuery),
+    responses(
+        (status = 200, description = "Alternatives, most similar first", body = ApiResponse<Vec<crate::Exercise>>),
+        (status = 400, description = "Unknown equipment name"),
+        (status = 404, description = "No such exercise", body = ApiResponse<Vec<crate::Exercise>>),
+    ),
+)]
+pub async fn get_exercise_alternatives(
+    Path(exercise_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AlternativesQuery>,
+) -> Result<(StatusCode, Json<ApiResponse<Vec<crate::Exercise>>>), StatusCode> {
+    let constraints = crate::advisors::workout_recommender::AlternativeConstraints {
+        available_equipment: query.equipment,
+        max_difficulty: query.max_difficulty,
+        limitations: query.limitations.unwrap_or_default(),
+        limit: query.limit
+            .unwrap_or(crate::advisors::workout_recommender::DEFAULT_ALTERNATIVES)
+            .min(MAX_ALTERNATIVES),
+    };
+
+    match state.advisor.suggest_alternatives(&exercise_id, &constraints).await {
+        Ok(Some(alternatives)) => Ok((StatusCode::OK, Json(ApiResponse::success(alternatives)))),
+        Ok(None) => Ok((StatusCode::NOT_FOUND, Json(ApiResponse::error(format!("No exercise with id {}", exercise_id))))),
+        Err(e) => {
+            warn!("Failed to find alternatives to {}: {}", exercise_id, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Load an exercise library. Everything is validated, including that no id
+/// repeats or already exists, before anything is written.
+#[utoipa::path(
+    post,
+    path = "/api/exercises/bulk",
+    tag = "exercises",
+    summary = "Add many exercises at once (admin only)",
+    request_body = BulkExercisesRequest,
+    responses(
+        (status = 201, description = "All exercises added; data is how many", body = ApiResponse<usize>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller is not an admin"),
+        (status = 409, description = "Some ids already exist; nothing was added", body = ApiResponse<Vec<String>>),
+        (status = 422, description = "Invalid or duplicate exercises", body = ValidationErrorResponse),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn create_exercises_bulk(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+    ValidatedJson(request): ValidatedJson<BulkExercisesRequest>,
+) -> Result<Response, StatusCode> {
+    if !caller.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    match state.advisor.seed_exercises(request.exercises).await {
+        Ok(ExerciseSeeding::Inserted(count)) => Ok((
+            StatusCode::CREATED,
+            Json(ApiResponse::success(count)),
+        ).into_response()),
+        Ok(ExerciseSeeding::ExistingIds(ids)) => Ok((
+            StatusCode::CONFLICT,
+            Json(ApiResponse {
+                success: false,
+                message: format!("Exercises already exist: {}", ids.join(", ")),
+                data: Some(ids),
+            }),
+        ).into_response()),
+        Err(e) => {
+            warn!("Bulk exercise insert failed: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/recipes/search",
+    tag = "menu",
+    summary = "Search recipes",
+    params(
+        SearchQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the client's copy"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified of the client's copy"),
+    ),
+    responses(
+        (status = 200, description = "Success, with ETag and Last-Modified", body = ApiResponse<Vec<crate::models::food::Recipe>>),
+        (status = 304, description = "The client's copy is current"),
+    ),
+)]
+pub async fn search_recipes(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let searched = async {
+        Ok::<_, anyhow::Error>((state.advisor.search_recipes(&query.q).await?, state.advisor.recipes_modified_at().await?))
+    };
+    match searched.await {
+        Ok((recipes, modified_at)) => {
+            info!("Recipe search '{}' matched {} recipes", query.q, recipes.len());
+            crate::caching::conditional_json(&headers, &ApiResponse::success(recipes), modified_at)
+        }
+        Err(e) => {
+            warn!("Failed to search recipes: {}", e);
+            Json(ApiResponse::<Vec<crate::models::food::Recipe>>::error(format!("Failed to search recipes: {}", e))).into_response()
+        }
+    }
+}
+
+/// Resolve a scanned barcode to a food's nutrition through Open Food Facts
+#[utoipa::path(
+    post,
+    path = "/api/nutrition/barcode",
+    tag = "menu",
+    summary = "Resolve a product barcode to nutrition",
+    request_body = BarcodeLookupRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::models::food::Food>),
+        (status = 404, description = "Open Food Facts doesn't know the barcode, or scanning is disabled", body = ApiResponse<crate::models::food::Food>),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 502, description = "Open Food Facts could not be reached", body = ApiResponse<crate::models::food::Food>),
+    ),
+)]
+pub async fn lookup_barcode(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(request): ValidatedJson<BarcodeLookupRequest>,
+) -> (StatusCode, Json<ApiResponse<crate::models::food::Food>>) {
+    // Anyone may scan, so only a full rollout reaches callers here
+    if !crate::features::is_enabled(&state.config.features, crate::features::BARCODE_SCAN, None) {
+        return (StatusCode::NOT_FOUND, Json(ApiResponse::error("Barcode scanning is not enabled".to_string())));
+    }
+
+    match state.open_food_facts.product(&request.barcode).await {
+        Ok(Some(food)) => (StatusCode::OK, Json(ApiResponse::success(food))),
+        Ok(None) => (StatusCode::NOT_FOUND, Json(ApiResponse::error(format!(
+            "Barcode {} isn't in Open Food Facts; add the food by name instead",
+            request.barcode
+        )))),
+        Err(e) => {
+            warn!("Barcode lookup {} failed: {}", request.barcode, e);
+            (StatusCode::BAD_GATEWAY, Json(ApiResponse::error(format!("Barcode lookup failed: {}", e))))
+        }
+    }
+}
+
+/// Local foods win; FoodData Central is only asked about the rest
+#[utoipa::path(
+    get,
+    path = "/api/foods/lookup",
+    tag = "menu",
+    summary = "Look up a food's nutrients",
+    params(
+        SearchQuery,
+        ("If-None-Match" = Option<String>, Header, description = "ETag of the client's copy"),
+    ),
+    responses(
+        (status = 200, description = "Success, with an ETag", body = ApiResponse<crate::models::food::Food>),
+        (status = 304, description = "The client's copy is current"),
+    ),
+)]
+pub async fn lookup_food(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+    headers: HeaderMap,
+) -> Response {
+    // Foods have no modification time, so only the ETag is offered
+    if let Some(food) = state.menu_optimizer.find_food(&query.q).await {
+        return crate::caching::conditional_json(&headers, &ApiResponse::success(food), None);
+    }
+
+    let Some(fdc) = &state.fdc_client else {
+        return Json(ApiResponse::<crate::models::food::Food>::error(format!("No food matches '{}'", query.q))).into_response();
+    };
+    match fdc.lookup(&query.q).await {
+        Ok(Some(food)) => crate::caching::conditional_json(&headers, &ApiResponse::success(food), None),
+        Ok(None) => Json(ApiResponse::<crate::models::food::Food>::error(format!("No food matches '{}'", query.q))).into_response(),
+        Err(e) => {
+            warn!("Food lookup '{}' failed: {}", query.q, e);
+            Json(ApiResponse::<crate::models::food::Food>::error(format!("Food lookup failed: {}", e))).into_response()
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/ai/analyze-form",
+    tag = "ai",
+    summary = "Analyze exercise form from a video",
+    request_body = AnalyzeFormRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::FormAnalysis>),
+    ),
+)]
+pub async fn analyze_form(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AnalyzeFormRequest>,
+) -> Result<Json<ApiResponse<crate::FormAnalysis>>, StatusCode> {
+    info!("🎥 Starting form analysis with RTX 5070...");
+    
+    let video_data = match base64::prelude::Engine::decode(&base64::prelude::BASE64_STANDARD, &request.video_base64) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to decode video data: {}", e);
+            return Ok(Json(ApiResponse::error("Invalid video data".to_string())));
+        }
+    };
+
+    match state.ai_analyzer.analyze_form(&video_data).await {
+        Ok(analysis) => {
+            info!("✅ Form analysis completed using RTX 5070");
+            Ok(Json(ApiResponse::success(analysis)))
+        }
+        Err(e) => {
+            warn!("❌ Form analysis failed: {}", e);
+            Ok(Json(ApiResponse::error(format!("Analysis failed: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/ai/compare-form",
+    tag = "ai",
+    summary = "Compare a pose against a reference",
+    request_body = CompareFormRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::FormComparison>),
+    ),
+)]
+pub async fn compare_form(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CompareFormRequest>,
+) -> Result<Json<ApiResponse<crate::FormComparison>>, StatusCode> {
+    let reference = match request.reference_pose
+        .or_else(|| crate::ai_analytics::form_comparison::reference_pose(&request.user_pose.exercise))
+    {
+        Some(reference) => reference,
+        None => {
+            return Ok(Json(ApiResponse::error(format!(
+                "No reference pose for exercise '{}'", request.user_pose.exercise
+            ))));
+        }
+    };
+
+    let comparison = state.ai_analyzer.compare_to_reference(&request.user_pose, &reference);
+    info!("Compared {} form to reference: similarity {:.2}, {} cues",
+          comparison.exercise, comparison.similarity, comparison.cues.len());
+    Ok(Json(ApiResponse::success(comparison)))
+}
+
+/// Whether the body really is the JPEG or PNG its content type claims
+fn is_supported_image(content_type: &str, body: &[u8]) -> bool {
+    match content_type {
+        "image/jpeg" => body.starts_with(&[0xFF, 0xD8, 0xFF]),
+        "image/png" => body.starts_with(b"\x89PNG\r\n\x1a\n"),
+        _ => false,
+    }
+}
+
+/// Form feedback from a single photo posted as a raw JPEG/PNG body.
+/// Anything that isn't a JPEG or PNG is rejected with 415; the route's body
+/// limit rejects oversized uploads with 413 before this runs.
+#[utoipa::path(
+    post,
+    path = "/api/ai/analyze-form/image",
+    tag = "ai",
+    summary = "Analyze exercise form from a photo",
+    request_body(content = Vec<u8>, content_type = "image/jpeg", description = "JPEG or PNG image"),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::ml_client::ImageFormFeedback>),
+        (status = 413, description = "Image too large"),
+        (status = 415, description = "Not a JPEG or PNG image"),
+    ),
+)]
+pub async fn analyze_form_image(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<crate::ml_client::ImageFormFeedback>>, StatusCode> {
+    let content_type = headers.get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(';').next().unwrap_or("").trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    if !is_supported_image(&content_type, &body) {
+        warn!("Rejected form image upload with content type '{}'", content_type);
+        return Err(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    match state.ml_client.analyze_form_image(&body).await {
+        Ok(feedback) => {
+            info!("Form image analysis completed in {:.2}ms", feedback.processing_time_ms);
+            Ok(Json(ApiResponse::success(feedback)))
+        }
+        Err(e) => {
+            warn!("Form image analysis failed: {}", e);
+            Ok(Json(ApiResponse::error(format!("Analysis failed: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "system",
+    summary = "Liveness check",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<String>),
+    ),
+)]
+pub async fn health_check() -> Json<ApiResponse<String>> {
+    Json(ApiResponse::success("Fitness Advisor AI is healthy! 💪".to_string()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/database/health",
+    tag = "system",
+    summary = "Database status",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::database::DatabaseHealth>),
+        (status = 401, description = "Metrics token configured and missing or wrong"),
+    ),
+)]
+pub async fn database_health(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<crate::database::DatabaseHealth>> {
+    match state.advisor.database_health().await {
+        Ok(health) => Json(ApiResponse::success(health)),
+        Err(e) => {
+            warn!("Database health check failed: {}", e);
+            Json(ApiResponse::error(format!("Database error: {}", e)))
+        }
+    }
+}
+
+/// Run one readiness check, failing it when it takes longer than `timeout`
+async fn check_subsystem(
+    name: &str,
+    required: bool,
+    timeout: Duration,
+    check: impl std::future::Future<Output = Result<(), String>>,
+) -> SubsystemHealth {
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await
+        .unwrap_or_else(|_| Err(format!("timed out after {} ms", timeout.as_millis())));
+
+    SubsystemHealth {
+        name: name.to_string(),
+        status: if result.is_ok() { HealthStatus::Healthy } else { HealthStatus::Unhealthy },
+        required,
+        latency_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/health/ready",
+    tag = "system",
+    summary = "Readiness of the database and ML service",
+    responses(
+        (status = 200, description = "Healthy, or degraded with only optional subsystems down", body = ApiResponse<ReadinessReport>),
+        (status = 503, description = "A required subsystem is down", body = ApiResponse<ReadinessReport>),
+    ),
+)]
+pub async fn readiness_check(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<ApiResponse<ReadinessReport>>) {
+    let timeout = Duration::from_millis(state.config.server.readiness_check_timeout_ms);
+
+    // Checked concurrently so the probe takes as long as the slowest check
+    let (database, ml_service) = tokio::join!(
+        check_subsystem("database", true, timeout, async {
+            state.advisor.database_health().await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        check_subsystem("ml_service", false, timeout, async {
+            if state.ml_client.is_available().await {
+                Ok(())
+            } else {
+                Err("ML service unreachable or models not loaded".to_string())
+            }
+        }),
+    );
+
+    let report = ReadinessReport::new(vec![database, ml_service]);
+    let (status, message) = match report.status {
+        HealthStatus::Healthy => (StatusCode::OK, "Ready"),
+        HealthStatus::Degraded => (StatusCode::OK, "Ready with degraded subsystems"),
+        HealthStatus::Unhealthy => (StatusCode::SERVICE_UNAVAILABLE, "Not ready"),
+    };
+    if report.status != HealthStatus::Healthy {
+        warn!("Readiness check: {}", message);
+    }
+
+    (status, Json(ApiResponse {
+        success: report.status != HealthStatus::Unhealthy,
+        data: Some(report),
+        message: message.to_string(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/gpu-status",
+    tag = "system",
+    summary = "Compute device reported by the ML service",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::GpuStatus>),
+    ),
+)]
+pub async fn gpu_status(
+    State(state): State<Arc<AppState>>,
+) -> Json<ApiResponse<crate::GpuStatus>> {
+    let mut features = vec![
+        "AI workout recommendations".to_string(),
+        "Database-backed analytics".to_string(),
+    ];
+
+    let status = match state.ml_client.device_info().await {
+        Ok(device) => {
+            features.splice(0..0, [
+                "Real-time pose estimation".to_string(),
+                "Form analysis".to_string(),
+                "Motion tracking".to_string(),
+            ]);
+            crate::GpuStatus {
+                ml_service_available: true,
+                gpu_available: device.cuda_available,
+                device: device.device,
+                gpu_name: device.gpu_name,
+                compute_capability: device.compute_capability,
+                vram_total_mb: device.vram_total_mb,
+                vram_used_mb: device.vram_used_mb,
+                cuda_version: device.cuda_version,
+                ready_for_ai: true,
+                features,
+            }
+        }
+        Err(e) => {
+            warn!("Could not get device info from ML service: {}", e);
+            crate::GpuStatus {
+                ml_service_available: false,
+                device: "unavailable".to_string(),
+                gpu_available: false,
+                gpu_name: None,
+                compute_capability: None,
+                vram_total_mb: None,
+                vram_used_mb: None,
+                cuda_version: None,
+                ready_for_ai: false,
+                features,
+            }
+        }
+    };
+
+    Json(ApiResponse::success(status))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/ml/analyze-frame",
+    tag = "ml",
+    summary = "Analyze a frame with the ML service",
+    request_body = AnalyzeFrameRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+    ),
+)]
+pub async fn ml_analyze_frame(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AnalyzeFrameRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    match state.ml_client.analyze_frame_realtime(request.frame_base64).await {
+        Ok(response) => {
+            if response.success {
+                info!("ML frame analysis completed in {:.2}ms", response.processing_time_ms);
+                Ok(Json(ApiResponse::success(response.result)))
+            } else {
+                warn!("ML frame analysis failed: {:?}", response.error);
+                Ok(Json(ApiResponse::error(
+                    response.error.unwrap_or("Analysis failed".to_string())
+                )))
+            }
+        }
+        Err(e) => {
+            warn!("ML service request failed: {}", e);
+            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/ml/analyze-video",
+    tag = "ml",
+    summary = "Analyze a video with the ML service",
+    request_body = AnalyzeVideoRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+    ),
+)]
+pub async fn ml_analyze_video(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<AnalyzeVideoRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    match state.ml_client.analyze_video(request.video_base64, "detailed").await {
+        Ok(response) => {
+            if response.success {
+                info!("ML video analysis completed in {:.2}ms", response.processing_time_ms);
+                Ok(Json(ApiResponse::success(response.result)))
+            } else {
+                warn!("ML video analysis failed: {:?}", response.error);
+                Ok(Json(ApiResponse::error(
+                    response.error.unwrap_or("Analysis failed".to_string())
+                )))
+            }
+        }
+        Err(e) => {
+            warn!("ML service request failed: {}", e);
+            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/ml/analyze-batch",
+    tag = "ml",
+    summary = "Batch-analyze a video file",
+    request_body = MLBatchRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+    ),
+)]
+pub async fn ml_analyze_batch(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<MLBatchRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    match state.ml_client.analyze_batch(request.video_path).await {
+        Ok(response) => {
+            if response.success {
+                info!("ML batch analysis completed in {:.2}ms", response.processing_time_ms);
+                Ok(Json(ApiResponse::success(response.result)))
+            } else {
+                warn!("ML batch analysis failed: {:?}", response.error);
+                Ok(Json(ApiResponse::error(
+                    response.error.unwrap_or("Analysis failed".to_string())
+                )))
+            }
+        }
+        Err(e) => {
+            warn!("ML service request failed: {}", e);
+            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/ml/status",
+    tag = "ml",
+    summary = "ML service status",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+    ),
+)]
+pub async fn ml_service_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    match state.ml_client.models_status().await {
+        Ok(status) => {
+            info!("ML service status retrieved successfully");
+            Ok(Json(ApiResponse::success(serde_json::to_value(status).unwrap())))
+        }
+        Err(e) => {
+            warn!("Failed to get ML service status: {}", e);
+            Ok(Json(ApiResponse::error(format!("ML service unavailable: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/menu/optimize",
+    tag = "menu",
+    summary = "Optimize a meal plan",
+    request_body = OptimizeMealPlanRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+        (status = 422, description = "Invalid fields", body = ValidationErrorResponse),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn optimize_meal_plan(
+    State(state): State<Arc<AppState>>,
+    Extension(caller): Extension<AuthUser>,
+    ValidatedJson(request): ValidatedJson<OptimizeMealPlanRequest>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    caller.authorize(&state.advisor, &request.user_id).await?;
+
+    let user_result = state.advisor.get_user(&request.user_id).await;
+    let user = match user_result {
+        Ok(Some(user)) => user,
+        Ok(None) => {
+            warn!("User not found for meal plan optimization: {}", request.user_id);
+            return Ok(Json(ApiResponse::error("User not found".to_string())));
+        }
+        Err(e) => {
+            warn!("Failed to get user {}: {}", request.user_id, e);
+            return Ok(Json(ApiResponse::error(format!("Database error: {}", e))));
+        }
+    };
+
+    let constraints = match state.menu_optimizer.generate_nutrition_constraints(&user, &request.goals).await {
+        Ok(constraints) => optimization::NutritionConstraints {
+            budget_per_day: request.budget_per_day.or(constraints.budget_per_day),
+            calorie_distribution: request.calorie_distribution.or(constraints.calorie_distribution),
+            eating_window: request.eating_window.or(constraints.eating_window),
+            ..constraints
+        },
+        Err(e) => {
+            warn!("Failed to generate nutrition constraints for user {}: {}", request.user_id, e);
+            return Ok(Json(ApiResponse::error(format!("Constraint generation failed: {}", e))));
+        }
+    };
+
+    let preferences = request.preferences.unwrap_or_else(|| optimization::UserPreferences {
+        dietary_restrictions: vec![],
+        allergens_to_avoid: vec![],
+        cuisine_preferences: vec!["American".to_string(), "Italian".to_string()],
+        disliked_foods: vec![],
+        preferred_foods: vec![],
+        taste_preferences: optimization::TastePreferences {
+            sweetness_preference: 0.0,
+            saltiness_preference: 0.0,
+            sourness_preference: 0.0,
+            bitterness_preference: 0.0,
+            umami_preference: 0.0,
+            spiciness_preference: 0.0,
+            spice_tolerance: 0.5,
+        },
+        cooking_skill_level: optimization::CookingSkillLevel::Intermediate,
+        equipment_available: vec![
+            optimization::CookingEquipment::Stovetop,
+            optimization::CookingEquipment::Oven,
+            optimization::CookingEquipment::Microwave,
+        ],
+        meal_variety_importance: 0.7,
+        cost_importance: 0.5,
+        health_importance: 0.8,
+        convenience_importance: 0.6,
+    });
+
+    let objectives = request.objectives.unwrap_or_else(|| vec![
+        optimization::OptimizationObjective::MaximizeNutrition,
+        optimization::OptimizationObjective::MaximizeTasteScore,
+        optimization::OptimizationObjective::BalanceMacros,
+        optimization::OptimizationObjective::MaximizeVariety,
+    ]);
+
+    let opt_request = optimization::OptimizationRequest {
+        user_id: request.user_id.clone(),
+        constraints,
+        preferences,
+        objectives,
+        time_horizon_days: request.time_horizon_days,
+        algorithm_config: optimization::AlgorithmConfig::default(),
+        max_recipe_repetitions_per_week: request.max_recipe_repetitions_per_week
+            .unwrap_or(optimization::DEFAULT_MAX_RECIPE_REPETITIONS_PER_WEEK),
+        month: request.month,
+    };
+
+    // Axum drops this future when the client disconnects, and the guard then
+    // stops the search instead of leaving it running for nobody
+    let cancel = CancellationToken::new();
+    let _cancel_on_disconnect = cancel.clone().drop_guard();
+    let variant = crate::experiments::assign(
+        crate::experiments::MEAL_PLAN_ALGORITHM,
+        &request.user_id,
+        &state.config.experiments.meal_plan_algorithm,
+    ).cloned();
+    let hooks = RunHooks { cancel: Some(cancel), algorithm: variant.clone(), ..RunHooks::default() };
+
+    let result = match request.progress_session_id {
+        Some(session_id) => {
+            let (progress, updates) = tokio::sync::mpsc::unbounded_channel();
+            let forwarder = tokio::spawn(
+                state.realtime_sessions.clone().forward_optimization_progress(session_id, updates)
+            );
+            let hooks = RunHooks { progress: Some(progress), ..hooks };
+            let result = state.menu_optimizer.optimize_meal_plan_with_hooks(opt_request, hooks).await;
+            // The optimizer has dropped its sender, so this only drains what's left
+            let _ = forwarder.await;
+            result
+        }
+        None => state.menu_optimizer.optimize_meal_plan_with_hooks(opt_request, hooks).await,
+    };
+
+    if let Some(variant) = variant {
+        let solution = result.as_ref().ok();
+        let exposure = crate::ExperimentExposure {
+            experiment: crate::experiments::MEAL_PLAN_ALGORITHM.to_string(),
+            variant: format!("{:?}", variant),
+            user_id: request.user_id.clone(),
+            served_by: solution.map(|s| format!("{:?}", s.algorithm_metadata.algorithm_used)),
+            succeeded: solution.is_some(),
+            score: solution.map(|s| s.fitness_score),
+            runtime_ms: solution.map(|s| s.algorithm_metadata.execution_time_ms),
+        };
+        if let Err(e) = state.advisor.record_exposure(&exposure).await {
+            warn!("Failed to record experiment exposure for user {}: {}", request.user_id, e);
+        }
+    }
+
+    match result {
+        Ok(solution) => {
+            info!("Menu optimization completed for user {}", request.user_id);
+            let solution = serde_json::to_value(solution).unwrap();
+            if let Err(e) = state.advisor.record_meal_plan_generated(&request.user_id, solution.clone()).await {
+                warn!("Failed to count meal plan for user {}: {}", request.user_id, e);
+            }
+            Ok(Json(ApiResponse::success(solution)))
+        }
+        Err(e) => {
+            warn!("Menu optimization failed for user {}: {}", request.user_id, e);
+            Ok(Json(ApiResponse::error(format!("Optimization failed: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/menu/status",
+    tag = "menu",
+    summary = "Menu optimizer status",
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<serde_json::Value>),
+    ),
+)]
+pub async fn menu_optimizer_status(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, StatusCode> {
+    let metrics = state.menu_optimizer.get_metrics().await;
+    let (cache_size, hit_rate) = state.menu_optimizer.get_cache_stats().await;
+    
+    let recipe_count = state.menu_optimizer.get_recipe_count().await;
+    let food_count = state.menu_optimizer.get_food_count().await;
+
+    let status = serde_json::json!({
+        "service": "Menu Optimizer",
+        "status": "healthy",
+        "metrics": metrics,
+        "cache": {
+            "size": cache_size,
+            "hit_rate": hit_rate
+        },
+        "data": {
+            "recipes": recipe_count,
+            "foods": food_count
+        }
+    });
+
+    Ok(Json(ApiResponse::success(status)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "system",
+    summary = "Prometheus metrics",
+    responses(
+        (status = 200, description = "Success", body = String, content_type = "text/plain"),
+        (status = 401, description = "Metrics token configured and missing or wrong"),
+    ),
+)]
+pub async fn prometheus_metrics(
+    State(state): State<Arc<AppState>>,
+) -> impl IntoResponse {
+    let metrics = state.menu_optimizer.get_metrics().await;
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.to_prometheus(),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/menu/recommendations/{user_id}",
+    tag = "menu",
+    summary = "Menu recommendations",
+    params(("user_id" = String, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<String>>),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 403, description = "Caller may not access this user"),
+    ),
+    security(("bearer" = [])),
+)]
+pub async fn get_menu_recommendations(
+    Path(user_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<ApiResponse<Vec<String>>>, StatusCode> {
+    match state.menu_optimizer.get_optimization_recommendations(&user_id).await {
+        Ok(recommendations) => {
+            info!("Retrieved menu recommendations for user {}", user_id);
+            Ok(Json(ApiResponse::success(recommendations)))
+        }
+        Err(e) => {
+            warn!("Failed to get menu recommendations for user {}: {}", user_id, e);
+            Ok(Json(ApiResponse::error(format!("Failed to get recommendations: {}", e))))
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/menu/suggest-recipes",
+    tag = "menu",
+    summary = "Suggest recipes from a pantry",
+    request_body = SuggestRecipesRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<Vec<crate::models::food::RecipeSuggestion>>),
+    ),
+)]
+pub async fn suggest_recipes(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SuggestRecipesRequest>,
+) -> Result<Json<ApiResponse<Vec<crate::models::food::RecipeSuggestion>>>, StatusCode> {
+    // Ranking still works without feedback, just less well
+    let feedback = state.advisor.feedback_scores().await.unwrap_or_else(|e| {
+        warn!("Failed to load recommendation feedback: {}", e);
+        Default::default()
+    });
+    let suggestions = state.menu_optimizer.suggest_recipes(&request.available_food_ids, &feedback).await;
+    info!("Suggested {} recipes from a pantry of {} foods",
+          suggestions.len(), request.available_food_ids.len());
+    Ok(Json(ApiResponse::success(suggestions)))
+}
+
+/// Preview a recipe with one ingredient swapped, e.g. to avoid an allergen
+#[utoipa::path(
+    post,
+    path = "/api/menu/recipes/{recipe_id}/substitute",
+    tag = "menu",
+    summary = "Swap an ingredient in a recipe",
+    params(("recipe_id" = String, Path, description = "Recipe id")),
+    request_body = SubstituteIngredientRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::models::food::Recipe>),
+    ),
+)]
+pub async fn substitute_ingredient(
+    Path(recipe_id): Path<String>,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SubstituteIngredientRequest>,
+) -> Result<Json<ApiResponse<crate::models::food::Recipe>>, StatusCode> {
+    match state.menu_optimizer
+        .substitute_ingredient(&recipe_id, &request.food_id, &request.replacement_food_id)
+        .await
+    {
+        Ok(recipe) => Ok(Json(ApiResponse::success(recipe))),
+        Err(e) => {
+            warn!("Failed to substitute {} in recipe {}: {}", request.food_id, recipe_id, e);
+            Ok(Json(ApiResponse::error(format!("Substitution failed: {}", e))))
+        }
+    }
+}
+
+/// Shopping list for a solution previously returned by /api/menu/optimize
+#[utoipa::path(
+    post,
+    path = "/api/menu/grocery-list",
+    tag = "menu",
+    summary = "Grocery list for an optimized plan",
+    request_body = optimization::OptimizationSolution,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<crate::models::food::GroceryList>),
+    ),
+)]
+pub async fn build_grocery_list(
+    State(state): State<Arc<AppState>>,
+    Json(solution): Json<optimization::OptimizationSolution>,
+) -> Result<Json<ApiResponse<crate::models::food::GroceryList>>, StatusCode> {
+    let list = state.menu_optimizer.build_grocery_list(&solution).await;
+    info!("Built grocery list with {} sections for meal plan {}",
+          list.sections.len(), solution.meal_plan_id);
+    Ok(Json(ApiResponse::success(list)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/menu/diff",
+    tag = "menu",
+    summary = "What changed between two optimized plans",
+    request_body = MealPlanDiffRequest,
+    responses(
+        (status = 200, description = "Success", body = ApiResponse<optimization::MealPlanDiff>),
+    ),
+)]
+pub async fn diff_meal_plans(
+    Json(request): Json<MealPlanDiffRequest>,
+) -> Json<ApiResponse<optimization::MealPlanDiff>> {
+    let diff = crate::MenuOptimizer::diff_solutions(&request.old, &request.new);
+    info!("Diffed meal plans {} and {}", diff.old_meal_plan_id, diff.new_meal_plan_id);
+    Json(ApiResponse::success(diff))
+}
+
+/// Build the CORS layer from server config. Invalid origins, methods and
+/// headers are skipped with a warning; preflight requests are answered for
+/// every route the layer wraps.
+pub fn cors_layer(config: &ServerConfig) -> CorsLayer {
+    let allow_origin = if config.cors_origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(config.cors_origins.iter().filter_map(|origin| {
+            HeaderValue::from_str(origin)
+                .map_err(|_| warn!("Ignoring invalid CORS origin: {}", origin))
+                .ok()
+        }))
+    };
+
+    let methods: Vec<Method> = config.cors_methods.iter()
+        .filter_map(|method| {
+            Method::from_bytes(method.to_uppercase().as_bytes())
+                .map_err(|_| warn!("Ignoring invalid CORS method: {}", method))
+                .ok()
+        })
+        .collect();
+
+    let headers: Vec<HeaderName> = config.cors_headers.iter()
+        .filter_map(|name| {
+            HeaderName::from_bytes(name.to_lowercase().as_bytes())
+                .map_err(|_| warn!("Ignoring invalid CORS header: {}", name))
+                .ok()
+        })
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        // Lets browser clients read the version to send back in If-Match
+        .expose_headers([header::ETAG])
+        .allow_credentials(config.cors_allow_credentials)
+}
+
+/// Gzip or brotli, whichever the client's Accept-Encoding prefers, for
+/// responses of at least `compression_min_bytes`. Event streams and images
+/// are sent as they are; WebSocket upgrades have no body to compress.
+pub fn compression_layer(config: &ServerConfig) -> CompressionLayer<impl Predicate> {
+    let predicate = SizeAbove::new(config.compression_min_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    CompressionLayer::new().compress_when(predicate)
+}
+
+pub fn create_router(state: Arc<AppState>) -> Router {
+    let cors = cors_layer(&state.config.server);
+    let compression = compression_layer(&state.config.server);
+    let image_body_limit = DefaultBodyLimit::max(state.config.ml_service.max_image_upload_bytes);
+    let body_limit = DefaultBodyLimit::max(state.config.server.max_body_bytes);
+    let body_timeout = axum::middleware::from_fn_with_state(state.clone(), crate::middleware::body_read_timeout);
+
+    // Routes touching a particular user's data need a bearer token; those
+    // with a :user_id are limited to that user, their coaches and admins
+    let user_routes = Router::new()
+        .route("/api/users", get(get_all_users))
+        .route("/api/users/batch", post(create_users_batch))
+        .route("/api/users/:user_id", get(get_user).put(update_user).delete(delete_user))
+        .route("/api/users/:user_id/recommendations", get(get_workout_recommendation))
+        .route("/api/users/:user_id/schedule", get(get_weekly_schedule))
+        .route("/api/users/:user_id/carb-cycle", get(get_carb_cycle_plan))
+        .route("/api/users/:user_id/progress", get(get_progress_analysis))
+        .route("/api/users/:user_id/streak", get(get_streak))
+        .route("/api/users/:user_id/report.pdf", get(get_report_pdf))
+        .route("/api/users/:user_id/export", get(export_user_data))
+        .route("/api/users/:user_id/workouts", get(get_user_workouts))
+        .route("/api/users/:user_id/measurements", post(record_measurement).get(get_measurements))
+        .route("/api/users/:user_id/health-import", post(import_health_data))
+        .route("/api/users/:user_id/food-log", get(get_food_log))
+        .route("/api/users/:user_id/food-log/import", post(import_food_diary))
+        .route("/api/users/:user_id/integrations/fitbit/sync", post(sync_fitbit))
+        .route("/api/users/:user_id/coaches/:coach_id", put(link_coach).delete(unlink_coach))
+        .route("/api/users/:user_id/clients", get(get_clients))
+        .route("/api/users/:user_id/favorites", get(list_favorites))
+        .route("/api/users/:user_id/goals", post(create_goal).get(list_goals))
+        .route("/api/users/:user_id/goals/:goal_id", get(get_goal))
+        .route("/api/users/:user_id/achievements", get(list_achievements))
+        .route("/api/users/:user_id/feedback/:recommendation_id", put(record_feedback))
+        .route("/api/recommendations/:recommendation_id/feedback", get(get_feedback_summary))
+        .route("/api/users/:user_id/favorites/:kind/:item_id", put(add_favorite).delete(remove_favorite))
+        .route("/api/workouts", post(log_workout))
+        .route("/api/exercises/bulk", post(create_exercises_bulk))
+        .route("/api/experiments/:experiment/results", get(get_experiment_results))
+        .route("/api/webhooks", post(create_webhook).get(list_webhooks))
+        .route("/api/webhooks/:webhook_id", delete(delete_webhook))
+        .route("/api/webhooks/:webhook_id/deliveries", get(list_webhook_deliveries))
+        .route("/api/menu/optimize", post(optimize_meal_plan))
+        .route("/api/menu/recommendations/:user_id", get(get_menu_recommendations))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), crate::auth::require_auth));
+
+    // Operational data for scrapers and operators, behind the metrics token
+    // rather than user auth
+    let ops_routes = Router::new()
+        .route("/api/database/health", get(database_health))
+        .route("/metrics", get(prometheus_metrics))
+        .route_layer(axum::middleware::from_fn_with_state(state.clone(), crate::middleware::require_metrics_token));
+
+    Router::new()
+        .merge(user_routes)
+        .route("/api/auth/login", post(login))
+        .route("/api/users", post(create_user))
+        
+        .route("/api/exercises", get(get_exercises))
+        .route("/api/exercises/search", get(search_exercises))
+        .route("/api/exercises/:exercise_id/alternatives", get(get_exercise_alternatives))
+        .route("/api/recipes/search", get(search_recipes))
+        .route("/api/foods/lookup", get(lookup_food))
+        .route("/api/nutrition/barcode", post(lookup_barcode))
+ 
+        
+        .route("/api/ai/analyze-form", post(analyze_form))
+        .route("/api/ai/analyze-form/image", post(analyze_form_image).layer(image_body_limit))
+        .route("/api/ai/compare-form", post(compare_form))
+        .route("/api/ai/realtime", get(crate::websocket::websocket_handler))
         
         .route("/api/ml/analyze-frame", post(ml_analyze_frame))
         .route("/api/ml/analyze-video", post(ml_analyze_video))
         .route("/api/ml/analyze-batch", post(ml_analyze_batch))
         .route("/api/ml/status", get(ml_service_status))
         
-        .route("/api/menu/optimize", post(optimize_meal_plan))
         .route("/api/menu/status", get(menu_optimizer_status))
-        .route("/api/menu/recommendations/:user_id", get(get_menu_recommendations))
+        .route("/api/menu/suggest-recipes", post(suggest_recipes))
+        .route("/api/menu/recipes/:recipe_id/substitute", post(substitute_ingredient))
+        .route("/api/menu/grocery-list", post(build_grocery_list))
+        .route("/api/menu/diff", post(diff_meal_plans))
         
+        .route("/api/openapi.json", get(crate::openapi::openapi_json))
+        .route("/api/docs", get(crate::openapi::swagger_ui))
+
         .route("/api/health", get(health_check))
-        .route("/api/database/health", get(database_health))
+        .route("/api/health/ready", get(readiness_check))
         .route("/api/gpu-status", get(gpu_status))
-        
+        .layer(cors)
+
+        // Added after the CORS layer, so browsers on other origins can't read them
+        .merge(ops_routes)
         .with_state(state)
         
         .layer(
             ServiceBuilder::new()
-                .layer(CorsLayer::permissive())
+                .layer(axum::middleware::from_fn(crate::middleware::request_tracing))
+                .layer(compression)
+                .layer(body_limit)
+                .layer(body_timeout)
                 .into_inner()
         )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum_test::TestServer;
+    use utoipa::OpenApi;
+    use crate::config::Config;
+
+    fn cors_test_server() -> TestServer {
+        let mut config = Config::default();
+        config.server.cors_origins = vec!["https://app.example.com".to_string()];
+
+        let app = Router::new()
+            .route("/api/health", get(|| async { "ok" }))
+            .layer(cors_layer(&config.server));
+        TestServer::new(app).unwrap()
+    }
+
+    async fn test_state(dir: &std::path::Path) -> Arc<AppState> {
+        test_state_with_config(dir, Config::default()).await
+    }
+
+    async fn test_state_with_config(dir: &std::path::Path, mut config: Config) -> Arc<AppState> {
+        config.database.url = format!("sqlite://{}", dir.join("api_test.db").display());
+
+        Arc::new(AppState {
+            advisor: Arc::new(crate::FitnessAdvisor::new(&config.database).await.unwrap()),
+            ai_analyzer: Arc::new(crate::AIMotionAnalyzer::new()),
+            ml_client: Arc::new(crate::MLServiceClient::from_config(&config.ml_service).unwrap()),
+            fdc_client: crate::fdc_client::FdcClient::from_config(&config.fdc).map(Arc::new),
+            open_food_facts: Arc::new(crate::open_food_facts::OpenFoodFactsClient::from_config(&config.open_food_facts)),
+            fitbit_client: crate::integrations::fitbit::FitbitClient::from_config(&config.fitbit).map(Arc::new),
+            menu_optimizer: Arc::new(crate::MenuOptimizer::new()),
+            realtime_sessions: Arc::new(crate::websocket::RealtimeSessions::new()),
+            tokens: Arc::new(crate::auth::TokenSigner::new(&config.auth)),
+            config: Arc::new(config),
+        })
+    }
+
+    const TEST_PASSWORD: &str = "correct-horse-battery";
+
+    fn test_user() -> crate::User {
+        crate::User {
+            id: "api_user".to_string(),
+            name: "API User".to_string(),
+            age: 30,
+            height: 170.0,
+            weight: 65.0,
+            fitness_level: crate::FitnessLevel::Beginner,
+            goals: vec![FitnessGoal::GeneralHealth],
+            preferences: crate::models::user::UserPreferences {
+                preferred_exercise_types: vec![],
+                available_equipment: vec![],
+                workout_duration_minutes: 30,
+                workouts_per_week: 3,
+                preferred_time_of_day: None,
+                unit_system: crate::models::UnitSystem::Metric,
+                limitations: vec![],
+                warmup_cooldown: true,
+            },
+            role: crate::models::user::Role::Client,
+            version: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_workout_retry_with_same_idempotency_key_logs_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let submission = |id: &str| serde_json::json!({
+            "workout": {
+                "id": id,
+                "user_id": "api_user",
+                "date": "2025-08-14",
+                "exercises": [],
+                "total_duration_minutes": 30,
+                "calories_burned": 150.0,
+                "user_rating": 4,
+                "notes": null
+            }
+        });
+
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+
+        let first = server.post("/api/workouts")
+            .authorization_bearer(&token)
+            .add_header(HeaderName::from_static(IDEMPOTENCY_KEY_HEADER), HeaderValue::from_static("retry-1"))
+            .json(&submission("workout_a"))
+            .await;
+        // The client regenerated the workout id on retry; the key still wins
+        let retry = server.post("/api/workouts")
+            .authorization_bearer(&token)
+            .add_header(HeaderName::from_static(IDEMPOTENCY_KEY_HEADER), HeaderValue::from_static("retry-1"))
+            .json(&submission("workout_b"))
+            .await;
+
+        let first: serde_json::Value = first.json();
+        let retry: serde_json::Value = retry.json();
+        assert_eq!(first["data"]["id"], "workout_a");
+        assert_eq!(retry["data"]["id"], "workout_a");
+
+        let workouts = state.advisor.get_user_workouts("api_user").await.unwrap();
+        assert_eq!(workouts.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_workout_id_of_another_user_is_not_overwritten() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        state.advisor.register_user(crate::User { id: "other_user".to_string(), ..test_user() }, None).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let submission = |user_id: &str, notes: &str| serde_json::json!({
+            "workout": {
+                "id": "shared_id",
+                "user_id": user_id,
+                "date": "2025-08-14",
+                "exercises": [],
+                "total_duration_minutes": 30,
+                "calories_burned": 150.0,
+                "user_rating": 4,
+                "notes": notes
+            }
+        });
+
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let other_token = state.tokens.issue("other_user", vec![]).unwrap();
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&submission("api_user", "mine")).await
+            .assert_status_ok();
+        server.post("/api/workouts").authorization_bearer(&other_token)
+            .json(&submission("other_user", "taken over")).await
+            .assert_status(StatusCode::CONFLICT);
+        // The owner can still update their own session
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&submission("api_user", "edited")).await
+            .assert_status_ok();
+
+        let workout = state.advisor.db.get_workout("shared_id").await.unwrap().unwrap();
+        assert_eq!(workout.user_id, "api_user");
+        assert_eq!(workout.notes.as_deref(), Some("edited"));
+        assert!(state.advisor.get_user_workouts("other_user").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_configured_origin_only() {
+        let server = cors_test_server();
+
+        let allowed = server.get("/api/health")
+            .add_header(header::ORIGIN, HeaderValue::from_static("https://app.example.com"))
+            .await;
+        assert_eq!(
+            allowed.maybe_header(header::ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some(HeaderValue::from_static("https://app.example.com"))
+        );
+
+        let denied = server.get("/api/health")
+            .add_header(header::ORIGIN, HeaderValue::from_static("https://evil.example.com"))
+            .await;
+        assert!(denied.maybe_header(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_openapi_document_describes_users_and_optimization() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(create_router(test_state(dir.path()).await)).unwrap();
+
+        let response = server.get("/api/openapi.json").await;
+        response.assert_status_ok();
+        let spec: serde_json::Value = response.json();
+
+        let paths = &spec["paths"];
+        assert!(paths["/api/users"]["post"].is_object());
+        assert!(paths["/api/users/{user_id}"]["get"].is_object());
+        assert_eq!(
+            paths["/api/users"]["post"]["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/CreateUserRequest",
+        );
+        assert!(paths["/api/menu/optimize"]["post"]["responses"]["422"].is_object());
+        assert!(paths["/api/users/{user_id}"]["get"]["security"].is_array());
+
+        let schemas = &spec["components"]["schemas"];
+        for name in ["User", "WorkoutSession", "OptimizationRequest", "OptimizeMealPlanRequest", "MealPreferences"] {
+            assert!(schemas[name].is_object(), "{} schema missing", name);
+        }
+        assert!(schemas["User"]["properties"]["age"].is_object());
+        assert!(schemas["OptimizationRequest"]["properties"]["constraints"].is_object());
+
+        server.get("/api/docs").await.assert_status_ok();
+    }
+
+    /// Every documented path and method must be routed, so the document
+    /// can't drift from the router
+    #[tokio::test]
+    async fn test_openapi_paths_are_all_routed() {
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(create_router(test_state(dir.path()).await)).unwrap();
+        let spec = serde_json::to_value(crate::openapi::ApiDoc::openapi()).unwrap();
+
+        for (path, operations) in spec["paths"].as_object().unwrap() {
+            let url = path.replace(['{', '}'], "");
+            for method in operations.as_object().unwrap().keys() {
+                let method = Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+                let response = server.method(method.clone(), &url).await;
+                let status = response.status_code();
+                // The router's own 404 has no body; a handler may send one
+                // explaining that the placeholder id doesn't exist
+                assert!(
+                    status != StatusCode::NOT_FOUND || !response.text().is_empty(),
+                    "{} {} is not routed", method, path
+                );
+                assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{} {} is not routed", method, path);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cors_preflight() {
+        let server = cors_test_server();
+
+        let preflight = server.method(Method::OPTIONS, "/api/health")
+            .add_header(header::ORIGIN, HeaderValue::from_static("https://app.example.com"))
+            .add_header(header::ACCESS_CONTROL_REQUEST_METHOD, HeaderValue::from_static("POST"))
+            .await;
+        preflight.assert_status_ok();
+        assert!(preflight.maybe_header(header::ACCESS_CONTROL_ALLOW_METHODS).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_user_routes_require_matching_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), Some(TEST_PASSWORD)).await.unwrap();
+        state.advisor.register_user(crate::User { id: "other_user".to_string(), ..test_user() }, None).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let login = server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "api_user", "password": TEST_PASSWORD }))
+            .await;
+        let login: serde_json::Value = login.json();
+        let token = login["data"]["token"].as_str().unwrap().to_string();
+
+        server.get("/api/users/api_user").authorization_bearer(&token).await.assert_status_ok();
+        server.get("/api/users/other_user").authorization_bearer(&token).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.get("/api/users/api_user").await.assert_status(StatusCode::UNAUTHORIZED);
+        server.get("/api/users/api_user").authorization_bearer("not.a.token").await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        // Unknown users can't log in
+        server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "nobody", "password": TEST_PASSWORD }))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_sign_up_never_overwrites_an_existing_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": test_user(), "password": TEST_PASSWORD }))
+            .await
+            .assert_status_ok();
+        let takeover = crate::User { name: "Someone Else".to_string(), ..test_user() };
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": takeover, "password": "attacker-password" }))
+            .await
+            .assert_status(StatusCode::CONFLICT);
+
+        server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "api_user", "password": "attacker-password" }))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "api_user", "password": TEST_PASSWORD }))
+            .await
+            .assert_status_ok();
+        let stored = state.advisor.db.get_user("api_user").await.unwrap().unwrap();
+        assert_eq!(stored.name, test_user().name);
+    }
+
+    #[tokio::test]
+    async fn test_configured_admin_ids_cannot_be_claimed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.auth.admin_user_ids = vec!["gym_admin".to_string()];
+        let state = test_state_with_config(dir.path(), config).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+        let login = |user_id: &'static str| server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": user_id, "password": TEST_PASSWORD }));
+
+        // Signing up under an admin's id, even as a client, is refused
+        let claimed = crate::User { id: "gym_admin".to_string(), ..test_user() };
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": claimed, "password": TEST_PASSWORD }))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+        login("gym_admin").await.assert_status(StatusCode::UNAUTHORIZED);
+
+        let admin_token = state.tokens.issue("root", vec![crate::auth::ADMIN_SCOPE.to_string()]).unwrap();
+        let batch: serde_json::Value = server.post("/api/users/batch").authorization_bearer(&admin_token)
+            .json(&serde_json::json!({ "users": [{ "user": claimed, "password": TEST_PASSWORD }] }))
+            .await
+            .json();
+        assert_eq!(batch["data"]["created"], 0);
+        assert_eq!(batch["data"]["results"][0]["errors"][0]["field"], "user.id");
+
+        // Provisioned out-of-band, the account gets admin until it's deleted
+        assert!(state.advisor.register_user(claimed.clone(), Some(TEST_PASSWORD)).await.unwrap());
+        let body: serde_json::Value = login("gym_admin").await.json();
+        let claims = state.tokens.verify(body["data"]["token"].as_str().unwrap()).unwrap();
+        assert_eq!(claims.scope, vec![crate::auth::ADMIN_SCOPE.to_string()]);
+
+        state.advisor.delete_user("gym_admin", false).await.unwrap();
+        login("gym_admin").await.assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_batch_registration_reports_duplicates_per_user() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let user = |id: &str| serde_json::json!({
+            "user": crate::User { id: id.to_string(), ..test_user() },
+            "password": TEST_PASSWORD,
+        });
+        // "api_user" already exists and "member_b" appears twice
+        let batch = serde_json::json!({
+            "users": [user("member_a"), user("api_user"), user("member_b"), user("member_b"), user("member_c")]
+        });
+
+        let client_token = state.tokens.issue("api_user", vec![]).unwrap();
+        server.post("/api/users/batch").authorization_bearer(&client_token).json(&batch).await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        let admin_token = state.tokens.issue("gym_admin", vec![crate::auth::ADMIN_SCOPE.to_string()]).unwrap();
+        let response = server.post("/api/users/batch").authorization_bearer(&admin_token).json(&batch).await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["data"]["created"], 3);
+        assert_eq!(body["data"]["rejected"], 2);
+        let statuses: Vec<&str> = body["data"]["results"].as_array().unwrap().iter()
+            .map(|r| r["status"].as_str().unwrap())
+            .collect();
+        assert_eq!(statuses, ["created", "duplicate", "created", "duplicate", "created"]);
+        assert_eq!(body["data"]["results"][1]["errors"][0]["field"], "user.id");
+
+        // Created users can log in; the existing user wasn't overwritten
+        for id in ["member_a", "member_b", "member_c"] {
+            server.post("/api/auth/login")
+                .json(&serde_json::json!({ "user_id": id, "password": TEST_PASSWORD }))
+                .await
+                .assert_status_ok();
+        }
+        server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "api_user", "password": TEST_PASSWORD }))
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        server.post("/api/users/batch").authorization_bearer(&admin_token)
+            .json(&serde_json::json!({ "users": [] }))
+            .await
+            .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_stale_user_update_is_rejected_with_412() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+
+        let response = server.get("/api/users/api_user").authorization_bearer(&token).await;
+        let etag = response.header(header::ETAG);
+        assert_eq!(etag, "\"1\"");
+        let mut user: serde_json::Value = response.json::<serde_json::Value>()["data"].clone();
+
+        let update = |user: serde_json::Value, if_match: Option<HeaderValue>| {
+            let mut request = server.put("/api/users/api_user")
+                .authorization_bearer(&token)
+                .json(&serde_json::json!({ "user": user }));
+            if let Some(if_match) = if_match {
+                request = request.add_header(header::IF_MATCH, if_match);
+            }
+            request
+        };
+
+        // The first editor saves against the version they read
+        user["weight"] = serde_json::json!(63.5);
+        let response = update(user.clone(), Some(etag.clone())).await;
+        response.assert_status_ok();
+        let fresh_etag = response.header(header::ETAG);
+        assert_eq!(fresh_etag, "\"2\"");
+
+        // A second editor still holding version 1 is refused
+        user["weight"] = serde_json::json!(70.0);
+        update(user.clone(), Some(etag)).await.assert_status(StatusCode::PRECONDITION_FAILED);
+        update(user.clone(), None).await.assert_status(StatusCode::PRECONDITION_REQUIRED);
+        assert_eq!(state.advisor.get_user("api_user").await.unwrap().unwrap().weight, 63.5);
+
+        // Re-reading gives the version to save against
+        let response = update(user.clone(), Some(fresh_etag)).await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::ETAG), "\"3\"");
+        assert_eq!(state.advisor.get_user("api_user").await.unwrap().unwrap().weight, 70.0);
+
+        // Clients can't promote themselves
+        user["role"] = serde_json::json!("Coach");
+        update(user, Some(HeaderValue::from_static("\"3\""))).await.assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_user_is_rejected_with_field_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let mut user = serde_json::to_value(test_user()).unwrap();
+        user["age"] = serde_json::json!(0);
+        user["height"] = serde_json::json!(-170.0);
+        let response = server.post("/api/users")
+            .json(&serde_json::json!({ "user": user, "password": "short" }))
+            .await;
+
+        response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["success"], false);
+        let fields: Vec<&str> = body["errors"].as_array().unwrap().iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["user.age", "user.height", "password"]);
+        assert!(body["errors"][0]["reason"].as_str().unwrap().contains("between 1 and 120"));
+
+        // Nothing was saved
+        assert!(state.advisor.get_user("api_user").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_imperial_registration_is_stored_metric() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let mut user = serde_json::to_value(test_user()).unwrap();
+        user["height"] = serde_json::json!(67.0);
+        user["weight"] = serde_json::json!(154.3);
+        user["preferences"]["unit_system"] = serde_json::json!("Imperial");
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": user, "units": "Imperial" }))
+            .await
+            .assert_status_ok();
+
+        let stored = state.advisor.get_user("api_user").await.unwrap().unwrap();
+        assert_eq!(stored.preferences.unit_system, crate::UnitSystem::Imperial);
+        assert!((stored.height - 170.18).abs() < 0.01, "height stored as {} cm", stored.height);
+        assert!((stored.weight - 69.99).abs() < 0.01, "weight stored as {} kg", stored.weight);
+
+        let pounds = crate::UnitSystem::Imperial.weight_from_kg(stored.weight as f64);
+        assert!((pounds - 154.3).abs() < 0.01, "read back as {} lb", pounds);
+    }
+
+    #[tokio::test]
+    async fn test_coach_access_is_limited_to_linked_clients() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let coach = crate::User { id: "coach".to_string(), role: crate::Role::Coach, ..test_user() };
+        for user in [coach, test_user(), crate::User { id: "other_user".to_string(), ..test_user() }] {
+            server.post("/api/users")
+                .json(&serde_json::json!({ "user": user, "password": TEST_PASSWORD }))
+                .await
+                .assert_status_ok();
+        }
+
+        let token_for = |user_id: &'static str| {
+            let server = &server;
+            async move {
+                let login: serde_json::Value = server.post("/api/auth/login")
+                    .json(&serde_json::json!({ "user_id": user_id, "password": TEST_PASSWORD }))
+                    .await
+                    .json();
+                login["data"]["token"].as_str().unwrap().to_string()
+            }
+        };
+        let coach_token = token_for("coach").await;
+        let client_token = token_for("api_user").await;
+        let other_token = token_for("other_user").await;
+
+        // The client chooses their coach; the coach can't link themselves
+        server.put("/api/users/api_user/coaches/coach").authorization_bearer(&coach_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.put("/api/users/api_user/coaches/coach").authorization_bearer(&client_token).await
+            .assert_status_ok();
+
+        // Linked client: allowed, including writes checked in the handler
+        server.get("/api/users/api_user").authorization_bearer(&coach_token).await.assert_status_ok();
+        let logged: serde_json::Value = server.post("/api/workouts")
+            .authorization_bearer(&coach_token)
+            .json(&serde_json::json!({ "workout": {
+                "id": "coached_workout",
+                "user_id": "api_user",
+                "date": "2025-08-13",
+                "exercises": [],
+                "total_duration_minutes": 30,
+                "calories_burned": 200.0,
+                "user_rating": null,
+                "notes": null
+            }}))
+            .await
+            .json();
+        assert_eq!(logged["success"], true);
+        let clients: serde_json::Value = server.get("/api/users/coach/clients")
+            .authorization_bearer(&coach_token).await.json();
+        assert_eq!(clients["data"][0]["id"], "api_user");
+
+        // Managing a client's data doesn't extend to deleting their account
+        server.delete("/api/users/api_user").authorization_bearer(&coach_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.delete("/api/users/api_user?hard=true").authorization_bearer(&coach_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.get("/api/users/api_user").authorization_bearer(&client_token).await.assert_status_ok();
+
+        // Unlinked client
+        server.get("/api/users/other_user").authorization_bearer(&coach_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Clients only see themselves
+        server.get("/api/users/api_user").authorization_bearer(&other_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.get("/api/users/coach/clients").authorization_bearer(&client_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Unlinking revokes access straight away
+        server.delete("/api/users/api_user/coaches/coach").authorization_bearer(&client_token).await
+            .assert_status_ok();
+        server.get("/api/users/api_user").authorization_bearer(&coach_token).await
+            .assert_status(StatusCode::FORBIDDEN);
+
+        // Admin can't be self-assigned at sign-up
+        let admin = crate::User { id: "sneaky".to_string(), role: crate::Role::Admin, ..test_user() };
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": admin, "password": TEST_PASSWORD }))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_favorite_recipe_is_idempotent_and_removable() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        let favorite = "/api/users/api_user/favorites/recipe/greek_yogurt_berry_bowl";
+        for _ in 0..2 {
+            let added: serde_json::Value = server.put(favorite).authorization_bearer(&token).await.json();
+            assert_eq!(added["success"], true);
+        }
+        server.put("/api/users/api_user/favorites/exercise/squat").authorization_bearer(&token).await
+            .assert_status_ok();
+        let missing: serde_json::Value = server.put("/api/users/api_user/favorites/recipe/no_such_recipe")
+            .authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(missing["success"], false);
+        server.put("/api/users/api_user/favorites/workout/squat").authorization_bearer(&token).await
+            .assert_status(StatusCode::BAD_REQUEST);
+
+        let recipes: serde_json::Value = server.get("/api/users/api_user/favorites")
+            .add_query_param("kind", "recipe")
+            .authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(recipes["data"], serde_json::json!([{
+            "kind": "recipe",
+            "item_id": "greek_yogurt_berry_bowl",
+            "created_at": recipes["data"][0]["created_at"],
+        }]));
+        let all: serde_json::Value = server.get("/api/users/api_user/favorites")
+            .authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(all["data"].as_array().unwrap().len(), 2);
+
+        let removed: serde_json::Value = server.delete(favorite).authorization_bearer(&token).await.json();
+        assert_eq!(removed["success"], true);
+        let removed_again: serde_json::Value = server.delete(favorite).authorization_bearer(&token).await.json();
+        assert_eq!(removed_again["success"], false);
+        let recipes: serde_json::Value = server.get("/api/users/api_user/favorites?kind=recipe")
+            .authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(recipes["data"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_strength_goal_tracks_logged_lifts_to_completion() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        let log_squat = |id: &str, date: &str, weight_kg: f32| serde_json::json!({
+            "workout": {
+                "id": id,
+                "user_id": "api_user",
+                "date": date,
+                "exercises": [{
+                    "exercise_id": "squat",
+                    "sets": 3,
+                    "reps": 5,
+                    "weight_kg": weight_kg,
+                    "duration_seconds": null,
+                    "rest_seconds": 120,
+                    "completed": true
+                }],
+                "total_duration_minutes": 45,
+                "calories_burned": 250.0,
+                "user_rating": 4,
+                "notes": null
+            }
+        });
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_squat("squat_1", "2025-07-01", 60.0)).await
+            .assert_status_ok();
+
+        let created: serde_json::Value = server.post("/api/users/api_user/goals")
+            .authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "title": "Squat 100kg",
+                "metric": "exercise_weight",
+                "exercise_id": "squat",
+                "target_value": 100.0,
+                "deadline": "2099-12-31"
+            }))
+            .await
+            .json();
+        assert_eq!(created["success"], true, "{}", created);
+        assert_eq!(created["data"]["goal"]["start_value"], 60.0);
+        assert_eq!(created["data"]["completion_percent"], 0.0);
+        let goal = format!("/api/users/api_user/goals/{}", created["data"]["goal"]["id"].as_str().unwrap());
+
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_squat("squat_2", "2025-07-08", 80.0)).await
+            .assert_status_ok();
+        let halfway: serde_json::Value = server.get(&goal).authorization_bearer(&token).await.json();
+        assert_eq!(halfway["data"]["current_value"], 80.0);
+        assert_eq!(halfway["data"]["completion_percent"], 50.0);
+        assert_eq!(halfway["data"]["completed"], false);
+
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_squat("squat_3", "2025-07-15", 102.5)).await
+            .assert_status_ok();
+        let goals: serde_json::Value = server.get("/api/users/api_user/goals").authorization_bearer(&token).await.json();
+        assert_eq!(goals["data"].as_array().unwrap().len(), 1);
+        assert_eq!(goals["data"][0]["completion_percent"], 100.0);
+        assert_eq!(goals["data"][0]["completed"], true);
+        let completed_at = goals["data"][0]["goal"]["completed_at"].clone();
+        assert!(completed_at.is_string());
+
+        // A lighter session afterwards doesn't undo or re-stamp the goal
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_squat("squat_4", "2025-07-22", 70.0)).await
+            .assert_status_ok();
+        let after: serde_json::Value = server.get(&goal).authorization_bearer(&token).await.json();
+        assert_eq!(after["data"]["completed"], true);
+        assert_eq!(after["data"]["goal"]["completed_at"], completed_at);
+
+        server.get("/api/users/api_user/goals/no_such_goal").authorization_bearer(&token).await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_seventh_consecutive_workout_awards_streak_badge_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        let today = chrono::Utc::now().date_naive();
+        let log_day = |days_ago: i64, id: String| serde_json::json!({
+            "workout": {
+                "id": id,
+                "user_id": "api_user",
+                "date": (today - chrono::Duration::days(days_ago)).format("%Y-%m-%d").to_string(),
+                "exercises": [],
+                "total_duration_minutes": 30,
+                "calories_burned": 150.0,
+                "user_rating": 4,
+                "notes": null
+            }
+        });
+        let badges = || async {
+            let body: serde_json::Value = server.get("/api/users/api_user/achievements")
+                .authorization_bearer(&token)
+                .await
+                .json();
+            body["data"].as_array().unwrap().iter()
+                .map(|a| a["id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        for days_ago in (1..=6).rev() {
+            server.post("/api/workouts").authorization_bearer(&token)
+                .json(&log_day(days_ago, format!("day_{}", days_ago))).await
+                .assert_status_ok();
+        }
+        assert_eq!(badges().await, ["first_workout"]);
+
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_day(0, "day_0".to_string())).await
+            .assert_status_ok();
+        assert_eq!(badges().await, ["first_workout", "streak_7"]);
+
+        // A second session the same day keeps the streak but earns nothing new
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&log_day(0, "day_0_evening".to_string())).await
+            .assert_status_ok();
+        assert_eq!(badges().await, ["first_workout", "streak_7"]);
+    }
+
+    #[tokio::test]
+    async fn test_recipe_with_better_feedback_is_suggested_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.menu_optimizer.add_recipes(crate::sample_data::SampleDataSet::new().recipes).await.unwrap();
+        let mut tokens = Vec::new();
+        for id in ["rater_1", "rater_2"] {
+            state.advisor.register_user(crate::User { id: id.to_string(), ..test_user() }, None).await.unwrap();
+            tokens.push(state.tokens.issue(id, vec![]).unwrap());
+        }
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        // Both the chicken salad and the stir fry can be made from this pantry
+        let pantry = serde_json::json!({
+            "available_food_ids": ["chicken_breast", "spinach", "olive_oil", "broccoli", "brown_rice"]
+        });
+        let suggested = || async {
+            let body: serde_json::Value = server.post("/api/menu/suggest-recipes").json(&pantry).await.json();
+            body["data"].as_array().unwrap().iter()
+                .map(|s| s["recipe_id"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        let before = suggested().await;
+        let (first, second) = (before[0].clone(), before[1].clone());
+        let mut top_two = [first.clone(), second.clone()];
+        top_two.sort();
+        assert_eq!(top_two, ["chicken_vegetable_stir_fry", "grilled_chicken_salad"]);
+
+        let rate = |user: &'static str, token: String, recipe: String, rating: &'static str| {
+            let server = &server;
+            async move {
+                server.put(&format!("/api/users/{}/feedback/{}", user, recipe))
+                    .authorization_bearer(token)
+                    .json(&serde_json::json!({ "rating": rating, "comment": null }))
+                    .await
+                    .json::<serde_json::Value>()
+            }
+        };
+        // Mixed on the first, liked by both on the second
+        rate("rater_1", tokens[0].clone(), first.clone(), "down").await;
+        rate("rater_2", tokens[1].clone(), first.clone(), "up").await;
+        rate("rater_1", tokens[0].clone(), second.clone(), "down").await;
+        rate("rater_1", tokens[0].clone(), second.clone(), "up").await;
+        let summary = rate("rater_2", tokens[1].clone(), second.clone(), "up").await;
+        // rater_1's second vote replaced their first
+        assert_eq!(summary["data"]["thumbs_up"], 2);
+        assert_eq!(summary["data"]["thumbs_down"], 0);
+
+        let mixed: serde_json::Value = server.get(&format!("/api/recommendations/{}/feedback", first))
+            .authorization_bearer(&tokens[0])
+            .await
+            .json();
+        assert_eq!(mixed["data"]["thumbs_up"], 1);
+        assert_eq!(mixed["data"]["thumbs_down"], 1);
+
+        let after = suggested().await;
+        assert_eq!(after[0], second);
+        assert_eq!(after[1], first);
+
+        // Voting on someone else's behalf is refused
+        server.put(&format!("/api/users/rater_2/feedback/{}", first))
+            .authorization_bearer(&tokens[0])
+            .json(&serde_json::json!({ "rating": "down" }))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_large_responses_are_gzipped_when_accepted() {
+        use std::io::Read;
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        let plain = server.get("/api/exercises").await;
+        assert!(plain.maybe_header(header::CONTENT_ENCODING).is_none());
+        let expected: serde_json::Value = plain.json();
+        assert!(plain.as_bytes().len() >= 1024, "exercise list should be over the threshold");
+
+        let gzipped = server.get("/api/exercises")
+            .add_header(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip"))
+            .await;
+        gzipped.assert_status_ok();
+        assert_eq!(gzipped.header(header::CONTENT_ENCODING), "gzip");
+        let mut json = String::new();
+        flate2::read::GzDecoder::new(gzipped.as_bytes().as_ref()).read_to_string(&mut json).unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&json).unwrap(), expected);
+
+        // Tiny responses aren't worth it
+        let health = server.get("/api/health")
+            .add_header(header::ACCEPT_ENCODING, HeaderValue::from_static("gzip, br"))
+            .await;
+        assert!(health.maybe_header(header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_bodyweight_alternatives_to_barbell_squat() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let mut barbell_squat = state.advisor.get_exercise("squat").await.unwrap().unwrap();
+        barbell_squat.id = "barbell_squat".to_string();
+        barbell_squat.name = "Barbell Back Squat".to_string();
+        barbell_squat.equipment_needed = vec![crate::Equipment::Barbells];
+        barbell_squat.difficulty_level = 6;
+        state.advisor.seed_exercises(vec![barbell_squat]).await.unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let response = server.get("/api/exercises/barbell_squat/alternatives")
+            .add_query_param("equipment", "None")
+            .await;
+        response.assert_status_ok();
+        let alternatives: Vec<crate::Exercise> = serde_json::from_value(response.json::<serde_json::Value>()["data"].clone()).unwrap();
+
+        assert!(!alternatives.is_empty());
+        assert_eq!(alternatives[0].id, "squat");
+        for alternative in &alternatives {
+            assert!(
+                alternative.primary_muscles.iter().any(|m| matches!(m, crate::MuscleGroup::Legs | crate::MuscleGroup::Glutes)),
+                "{} doesn't train legs or glutes", alternative.id
+            );
+            assert!(
+                alternative.equipment_needed.iter().all(|e| *e == crate::Equipment::None),
+                "{} needs equipment", alternative.id
+            );
+        }
+
+        let easy: serde_json::Value = server.get("/api/exercises/barbell_squat/alternatives")
+            .add_query_param("equipment", "None")
+            .add_query_param("max_difficulty", "2")
+            .await
+            .json();
+        assert!(easy["data"].as_array().unwrap().iter().all(|e| e["difficulty_level"].as_u64().unwrap() <= 2));
+
+        server.get("/api/exercises/barbell_squat/alternatives")
+            .add_query_param("equipment", "Kettlebell")
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+        server.get("/api/exercises/no_such_exercise/alternatives")
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_exercises_are_stored_with_their_metadata() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+        let admin = state.tokens.issue("gym_admin", vec![crate::auth::ADMIN_SCOPE.to_string()]).unwrap();
+
+        let exercise = |i: u32| serde_json::json!({
+            "id": format!("library_{}", i),
+            "name": format!("Library Exercise {}", i),
+            "description": "Imported from the exercise library",
+            "exercise_type": if i.is_multiple_of(2) { "Strength" } else { "Flexibility" },
+            "equipment_needed": if i.is_multiple_of(3) { vec!["Dumbbells", "Bench"] } else { vec!["None"] },
+            "difficulty_level": i % 10 + 1,
+            "primary_muscles": ["Legs"],
+            "secondary_muscles": if i.is_multiple_of(2) { vec!["Glutes", "Core"] } else { vec![] },
+            "instructions": ["Brace", "Move"],
+            "safety_tips": ["Stay controlled"],
+        });
+        let library: Vec<_> = (0..20).map(exercise).collect();
+
+        let created = server.post("/api/exercises/bulk")
+            .authorization_bearer(&admin)
+            .json(&serde_json::json!({ "exercises": library }))
+            .await;
+        created.assert_status(StatusCode::CREATED);
+        assert_eq!(created.json::<serde_json::Value>()["data"], 20);
+
+        let listed: serde_json::Value = server.get("/api/exercises").await.json();
+        for expected in &library {
+            let stored = listed["data"].as_array().unwrap().iter()
+                .find(|e| e["id"] == expected["id"])
+                .unwrap_or_else(|| panic!("{} missing", expected["id"]));
+            for field in ["name", "exercise_type", "equipment_needed", "difficulty_level", "primary_muscles", "secondary_muscles"] {
+                assert_eq!(stored[field], expected[field], "{} of {}", field, expected["id"]);
+            }
+        }
+
+        // Repeated ids in the request, or ids already stored, add nothing
+        let duplicate = server.post("/api/exercises/bulk")
+            .authorization_bearer(&admin)
+            .json(&serde_json::json!({ "exercises": [exercise(20), exercise(20)] }))
+            .await;
+        duplicate.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(duplicate.json::<serde_json::Value>()["errors"][0]["field"], "exercises[1].id");
+
+        let conflict = server.post("/api/exercises/bulk")
+            .authorization_bearer(&admin)
+            .json(&serde_json::json!({ "exercises": [exercise(21), exercise(3)] }))
+            .await;
+        conflict.assert_status(StatusCode::CONFLICT);
+        assert_eq!(conflict.json::<serde_json::Value>()["data"], serde_json::json!(["library_3"]));
+        assert!(state.advisor.get_exercise("library_21").await.unwrap().is_none());
+
+        let user = state.tokens.issue("api_user", vec![]).unwrap();
+        server.post("/api/exercises/bulk")
+            .authorization_bearer(&user)
+            .json(&serde_json::json!({ "exercises": [exercise(22)] }))
+            .await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_exercise_list_revalidates_with_304() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        let server = TestServer::new(create_router(state)).unwrap();
+
+        let first = server.get("/api/exercises").await;
+        first.assert_status_ok();
+        let etag = first.header(header::ETAG);
+        let last_modified = first.header(header::LAST_MODIFIED);
+        assert!(etag.to_str().unwrap().starts_with('"'));
+        assert_eq!(first.header(header::CACHE_CONTROL), "no-cache");
+
+        let revalidated = server.get("/api/exercises").add_header(header::IF_NONE_MATCH, etag.clone()).await;
+        revalidated.assert_status(StatusCode::NOT_MODIFIED);
+        assert!(revalidated.as_bytes().is_empty());
+        assert_eq!(revalidated.header(header::ETAG), etag);
+
+        let by_date = server.get("/api/exercises").add_header(header::IF_MODIFIED_SINCE, last_modified).await;
+        by_date.assert_status(StatusCode::NOT_MODIFIED);
+
+        // A different copy, or a search with other results, gets the full body
+        server.get("/api/exercises").add_header(header::IF_NONE_MATCH, HeaderValue::from_static("\"outdated\"")).await
+            .assert_status_ok();
+        let search = server.get("/api/exercises/search?q=squat").add_header(header::IF_NONE_MATCH, etag).await;
+        search.assert_status_ok();
+        assert!(!search.as_bytes().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_workout_publishes_workout_logged_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let mut events = state.advisor.subscribe();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        server.post("/api/workouts").authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "workout": {
+                    "id": "published_workout",
+                    "user_id": "api_user",
+                    "date": "2024-03-04",
+                    "exercises": [],
+                    "total_duration_minutes": 45,
+                    "calories_burned": 210.0,
+                    "user_rating": 5,
+                    "notes": "felt strong"
+                }
+            })).await
+            .assert_status_ok();
+
+        // Published before the response, so it's already waiting
+        match events.try_recv().unwrap() {
+            crate::events::DomainEvent::WorkoutLogged { workout, .. } => {
+                assert_eq!(workout.id, "published_workout");
+                assert_eq!(workout.user_id, "api_user");
+                assert_eq!(workout.date, "2024-03-04");
+                assert_eq!(workout.total_duration_minutes, 45);
+                assert_eq!(workout.notes.as_deref(), Some("felt strong"));
+            }
+            other => panic!("expected WorkoutLogged, got {:?}", other),
+        }
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_logged_workout_is_posted_to_webhook_with_valid_signature() {
+        const SECRET: &str = "whsec_0123456789abcdef";
+
+        // Receiver that hands each request's signature and body to the test
+        let (received_tx, mut received_rx) = tokio::sync::mpsc::unbounded_channel::<(String, axum::body::Bytes)>();
+        let receiver = Router::new().route("/hook", post(move |headers: axum::http::HeaderMap, body: axum::body::Bytes| {
+            let received_tx = received_tx.clone();
+            async move {
+                let signature = headers[crate::webhooks::SIGNATURE_HEADER].to_str().unwrap().to_string();
+                received_tx.send((signature, body)).unwrap();
+                StatusCode::OK
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let receiver_url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, receiver).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let dispatcher = tokio::spawn(state.advisor.webhook_dispatcher(&state.config.webhooks).run());
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let admin_token = state.tokens.issue("gym_admin", vec![crate::auth::ADMIN_SCOPE.to_string()]).unwrap();
+        let user_token = state.tokens.issue("api_user", vec![]).unwrap();
+        let subscribe = serde_json::json!({ "url": receiver_url, "events": ["workout_logged"], "secret": SECRET });
+        server.post("/api/webhooks").authorization_bearer(&user_token).json(&subscribe).await
+            .assert_status(StatusCode::FORBIDDEN);
+        server.post("/api/webhooks").authorization_bearer(&admin_token)
+            .json(&serde_json::json!({ "url": "ftp://example.com", "events": [], "secret": "short" })).await
+            .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+        let created: serde_json::Value = server.post("/api/webhooks").authorization_bearer(&admin_token)
+            .json(&subscribe).await
+            .json();
+        let webhook_id = created["data"]["id"].as_str().unwrap().to_string();
+
+        server.post("/api/workouts").authorization_bearer(&user_token)
+            .json(&serde_json::json!({
+                "workout": {
+                    "id": "hooked_workout",
+                    "user_id": "api_user",
+                    "date": "2024-03-04",
+                    "exercises": [],
+                    "total_duration_minutes": 30,
+                    "calories_burned": 150.0,
+                    "user_rating": 4,
+                    "notes": null
+                }
+            })).await
+            .assert_status_ok();
+
+        let (signature, body) = tokio::time::timeout(Duration::from_secs(5), received_rx.recv()).await
+            .expect("webhook was not delivered")
+            .unwrap();
+        assert!(crate::webhooks::verify(SECRET, &body, &signature));
+        assert!(!crate::webhooks::verify("some_other_secret", &body, &signature));
+        let payload: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(payload["event"], "workout_logged");
+        assert_eq!(payload["user_id"], "api_user");
+        assert_eq!(payload["data"]["id"], "hooked_workout");
+
+        // The delivery is marked done once the receiver has answered
+        let mut deliveries = serde_json::Value::Null;
+        for _ in 0..50 {
+            deliveries = server.get(&format!("/api/webhooks/{}/deliveries", webhook_id))
+                .authorization_bearer(&admin_token)
+                .await
+                .json();
+            if deliveries["data"][0]["status"] == "delivered" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(deliveries["data"][0]["status"], "delivered");
+        assert_eq!(deliveries["data"][0]["attempts"], 1);
+        dispatcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_webhook_deliveries_survive_a_flooded_event_bus() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let subscription = state.advisor
+            .create_webhook("http://127.0.0.1:9/hook".to_string(), vec![crate::WebhookEvent::PlanGenerated], "whsec_0123456789abcdef")
+            .await
+            .unwrap();
+        // A subscriber that never reads, so the bus overflows
+        let mut lagging = state.advisor.subscribe();
+
+        let published = crate::events::EVENT_BUS_CAPACITY + 16;
+        for i in 0..published {
+            state.advisor.record_meal_plan_generated("api_user", serde_json::json!({ "plan": i })).await.unwrap();
+        }
+
+        assert!(matches!(lagging.try_recv(), Err(tokio::sync::broadcast::error::TryRecvError::Lagged(_))));
+        let deliveries = state.advisor.webhook_deliveries(&subscription.id).await.unwrap();
+        assert_eq!(deliveries.len(), published);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_feature_flag_hides_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.features.insert(crate::features::PDF_REPORT.to_string(), crate::config::FeatureFlag {
+            enabled: true,
+            rollout_percent: 0,
+        });
+        config.features.insert(crate::features::BARCODE_SCAN.to_string(), crate::config::FeatureFlag {
+            enabled: false,
+            rollout_percent: 100,
+        });
+        let state = test_state_with_config(dir.path(), config).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        server.get("/api/users/api_user/report.pdf")
+            .authorization_bearer(&token)
+            .await
+            .assert_status(StatusCode::NOT_FOUND);
+        let scan = server.post("/api/nutrition/barcode")
+            .json(&serde_json::json!({ "barcode": "3017620422003" }))
+            .await;
+        scan.assert_status(StatusCode::NOT_FOUND);
+        let body: serde_json::Value = scan.json();
+        assert_eq!(body["message"], "Barcode scanning is not enabled");
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_is_rejected_before_the_handler() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.server.max_body_bytes = 1024;
+        let state = test_state_with_config(dir.path(), config).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let oversized = crate::User { name: "x".repeat(2048), ..test_user() };
+        let response = server.post("/api/users")
+            .json(&serde_json::json!({ "user": oversized, "password": TEST_PASSWORD }))
+            .await;
+        response.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(state.advisor.db.get_user("api_user").await.unwrap().is_none());
+
+        // The same request under the limit goes through
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": test_user(), "password": TEST_PASSWORD }))
+            .await
+            .assert_status_ok();
+    }
+
+    #[tokio::test]
+    async fn test_metrics_require_the_metrics_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.metrics.token = Some("scrape-token".to_string());
+        let state = test_state_with_config(dir.path(), config).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let user_token = state.tokens.issue("api_user", vec![crate::auth::ADMIN_SCOPE.to_string()]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        server.get("/metrics").await.assert_status(StatusCode::UNAUTHORIZED);
+        server.get("/metrics").authorization_bearer("wrong-token").await.assert_status(StatusCode::UNAUTHORIZED);
+        // User tokens, even an admin's, don't open operational endpoints
+        server.get("/metrics").authorization_bearer(&user_token).await.assert_status(StatusCode::UNAUTHORIZED);
+        server.get("/api/database/health").await.assert_status(StatusCode::UNAUTHORIZED);
+
+        let metrics = server.get("/metrics")
+            .authorization_bearer("scrape-token")
+            .add_header(header::ORIGIN, HeaderValue::from_static("http://localhost:3000"))
+            .await;
+        metrics.assert_status_ok();
+        assert!(metrics.header(header::CONTENT_TYPE).to_str().unwrap().starts_with("text/plain; version=0.0.4"));
+        assert!(metrics.text().contains("# TYPE"));
+        // Not readable from a browser even on an allowed origin
+        assert!(metrics.maybe_header(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+        server.get("/api/database/health").authorization_bearer("scrape-token").await.assert_status_ok();
+
+        // Public routes are unaffected and keep their CORS headers
+        let exercises = server.get("/api/exercises")
+            .add_header(header::ORIGIN, HeaderValue::from_static("http://localhost:3000"))
+            .await;
+        exercises.assert_status_ok();
+        assert!(exercises.maybe_header(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_report_pdf_for_populated_and_empty_users() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let empty = server.get("/api/users/api_user/report.pdf").authorization_bearer(&token).await;
+        empty.assert_status_ok();
+        assert!(empty.as_bytes().starts_with(b"%PDF-"));
+        let empty_len = empty.as_bytes().len();
+
+        for (i, date) in ["2025-07-01", "2025-07-08", "2025-07-15"].into_iter().enumerate() {
+            server.post("/api/workouts").authorization_bearer(&token)
+                .json(&serde_json::json!({
+                    "workout": {
+                        "id": format!("report_{}", i),
+                        "user_id": "api_user",
+                        "date": date,
+                        "exercises": [{
+                            "exercise_id": "squat",
+                            "sets": 3,
+                            "reps": 5,
+                            "weight_kg": 60.0 + 5.0 * i as f32,
+                            "duration_seconds": null,
+                            "rest_seconds": 120,
+                            "completed": true
+                        }],
+                        "total_duration_minutes": 45,
+                        "calories_burned": 250.0,
+                        "user_rating": 4,
+                        "notes": null
+                    }
+                }))
+                .await
+                .assert_status_ok();
+            server.post("/api/users/api_user/measurements").authorization_bearer(&token)
+                .json(&serde_json::json!({ "date": date, "weight_kg": 65.0 - i as f32 * 0.5 }))
+                .await
+                .assert_status_ok();
+        }
+
+        let populated = server.get("/api/users/api_user/report.pdf").authorization_bearer(&token).await;
+        populated.assert_status_ok();
+        assert_eq!(populated.header(header::CONTENT_TYPE), "application/pdf");
+        let pdf = populated.as_bytes();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(pdf.len() > 2_000, "report is only {} bytes", pdf.len());
+        assert!(pdf.len() > empty_len);
+        assert!(pdf.windows(b"%%EOF".len()).any(|w| w == b"%%EOF"));
+
+        let other = state.tokens.issue("someone_else", vec![]).unwrap();
+        server.get("/api/users/api_user/report.pdf").authorization_bearer(&other).await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_password_login_and_lockout() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.auth.max_failed_logins = 3;
+        let state = test_state_with_config(dir.path(), config).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        server.post("/api/users")
+            .json(&serde_json::json!({ "user": test_user(), "password": TEST_PASSWORD }))
+            .await
+            .assert_status_ok();
+
+        let stored = state.advisor.db.get_credentials("api_user").await.unwrap().unwrap();
+        assert!(!stored.password_hash.contains(TEST_PASSWORD));
+        assert!(stored.password_hash.starts_with("pbkdf2-sha256$"));
+
+        let login = |password: &str| server.post("/api/auth/login")
+            .json(&serde_json::json!({ "user_id": "api_user", "password": password }));
+
+        login(TEST_PASSWORD).await.assert_status_ok();
+        login("wrong-password").await.assert_status(StatusCode::UNAUTHORIZED);
+        login("wrong-password").await.assert_status(StatusCode::UNAUTHORIZED);
+        // The third failure locks the account, after which even the right
+        // password is refused
+        login("wrong-password").await.assert_status(StatusCode::TOO_MANY_REQUESTS);
+        login(TEST_PASSWORD).await.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[tokio::test]
+    async fn test_readiness_degrades_without_ml_and_fails_without_database() {
+        // An ML service that never answers its health check
+        let app = Router::new().route("/health", get(|| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            "late"
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ml_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.ml_service.base_url = ml_url;
+        config.server.readiness_check_timeout_ms = 200;
+        let state = test_state_with_config(dir.path(), config).await;
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let started = Instant::now();
+        let response = server.get("/api/health/ready").await;
+        assert!(started.elapsed() < Duration::from_secs(5), "probe waited on the hung ML service");
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["status"], "degraded");
+        assert_eq!(body["data"]["subsystems"][0]["name"], "database");
+        assert_eq!(body["data"]["subsystems"][0]["status"], "healthy");
+        assert_eq!(body["data"]["subsystems"][1]["status"], "unhealthy");
+        assert!(body["data"]["subsystems"][1]["error"].as_str().unwrap().contains("timed out"));
+
+        state.advisor.close().await;
+        let response = server.get("/api/health/ready").await;
+        response.assert_status(StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["status"], "unhealthy");
+        assert_eq!(body["data"]["subsystems"][0]["status"], "unhealthy");
+    }
+
+    /// Stands in for the Python ML service's detailed frame analysis
+    async fn mock_ml_service() -> String {
+        let app = Router::new().route("/analyze/frame", post(|| async {
+            Json(serde_json::json!({
+                "success": true,
+                "processing_time_ms": 12.5,
+                "result": {
+                    "score": 0.72,
+                    "exercise": "squat",
+                    "joint_angles": { "left_knee": 88.0, "right_knee": 91.5, "hip": 75.0 },
+                    "feedback": ["Sit back into your hips"],
+                    "warnings": ["Knees are caving inward"]
+                },
+                "error": null
+            }))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    // 1x1 transparent PNG
+    const PNG_FIXTURE: &[u8] = &[
+        0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F, 0x15, 0xC4,
+        0x89, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00, 0x01, 0x00, 0x00,
+        0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE,
+        0x42, 0x60, 0x82,
+    ];
+
+    #[tokio::test]
+    async fn test_form_image_upload_returns_structured_feedback() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.ml_service.base_url = mock_ml_service().await;
+        config.ml_service.max_image_upload_bytes = 1024;
+        let server = TestServer::new(create_router(test_state_with_config(dir.path(), config).await)).unwrap();
+
+        let response = server.post("/api/ai/analyze-form/image")
+            .content_type("image/png")
+            .bytes(Bytes::from_static(PNG_FIXTURE))
+            .await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["exercise"], "squat");
+        assert_eq!(body["data"]["joint_angles"]["left_knee"], 88.0);
+        assert_eq!(body["data"]["cue"], "Knees are caving inward");
+
+        let wrong_type = server.post("/api/ai/analyze-form/image")
+            .content_type("text/plain")
+            .bytes(Bytes::from_static(b"not an image"))
+            .await;
+        wrong_type.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut oversized = PNG_FIXTURE.to_vec();
+        oversized.resize(2048, 0);
+        let too_large = server.post("/api/ai/analyze-form/image")
+            .content_type("image/png")
+            .bytes(Bytes::from(oversized))
+            .await;
+        too_large.assert_status(StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_barcode_resolves_through_open_food_facts() {
+        let app = Router::new().route("/api/v2/product/:file", get(|Path(file): Path<String>| async move {
+            if file != "3017620422003.json" {
+                return (StatusCode::NOT_FOUND, Json(serde_json::json!({
+                    "status": 0,
+                    "status_verbose": "product not found"
+                })));
+            }
+            (StatusCode::OK, Json(serde_json::json!({
+                "status": 1,
+                "code": "3017620422003",
+                "product": {
+                    "product_name": "Nutella",
+                    "brands": "Ferrero",
+                    "categories_tags": ["en:spreads", "en:sweet-spreads", "en:hazelnut-spreads"],
+                    "allergens_tags": ["en:milk", "en:nuts", "en:soybeans"],
+                    "nutriments": {
+                        "energy-kcal_100g": 539,
+                        "proteins_100g": 6.3,
+                        "carbohydrates_100g": 57.5,
+                        "fat_100g": 30.9,
+                        "sugars_100g": 56.3,
+                        "sodium_100g": 0.0428
+                    }
+                }
+            })))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = Config::default();
+        config.open_food_facts.base_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(create_router(test_state_with_config(dir.path(), config).await)).unwrap();
+
+        let response = server.post("/api/nutrition/barcode")
+            .json(&serde_json::json!({ "barcode": "3017620422003" }))
+            .await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["id"], "off_3017620422003");
+        assert_eq!(body["data"]["name"], "Nutella (Ferrero)");
+        assert_eq!(body["data"]["nutrition_per_100g"]["calories"], 539.0);
+        assert_eq!(body["data"]["nutrition_per_100g"]["protein_g"], 6.3);
+        assert_eq!(body["data"]["nutrition_per_100g"]["carbs_g"], 57.5);
+        assert_eq!(body["data"]["nutrition_per_100g"]["fat_g"], 30.9);
+        assert_eq!(body["data"]["nutrition_per_100g"]["sodium_mg"], 42.8);
+        assert_eq!(body["data"]["allergens"], serde_json::json!(["Dairy", "TreeNuts", "Soy"]));
+
+        let unknown = server.post("/api/nutrition/barcode")
+            .json(&serde_json::json!({ "barcode": "0000000000000" }))
+            .await;
+        unknown.assert_status(StatusCode::NOT_FOUND);
+        let body: serde_json::Value = unknown.json();
+        assert!(body["message"].as_str().unwrap().contains("isn't in Open Food Facts"));
+
+        server.post("/api/nutrition/barcode")
+            .json(&serde_json::json!({ "barcode": "12ab" }))
+            .await
+            .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_gpu_status_reports_ml_service_device() {
+        let app = Router::new().route("/device", get(|| async {
+            Json(serde_json::json!({
+                "device": "cuda",
+                "cuda_available": true,
+                "gpu_name": "NVIDIA GeForce RTX 4060",
+                "compute_capability": "8.9",
+                "vram_total_mb": 8188,
+                "vram_used_mb": 512,
+                "cuda_version": "12.1"
+            }))
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let ml_url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.ml_service.base_url = ml_url;
+        let server = TestServer::new(create_router(test_state_with_config(dir.path(), config).await)).unwrap();
+
+        let response = server.get("/api/gpu-status").await;
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["data"]["device"], "cuda");
+        assert_eq!(body["data"]["gpu_available"], true);
+        assert_eq!(body["data"]["gpu_name"], "NVIDIA GeForce RTX 4060");
+        assert_eq!(body["data"]["vram_total_mb"], 8188);
+        assert_eq!(body["data"]["vram_used_mb"], 512);
+
+        // Nothing listens on the default ML service URL in tests
+        let dir = tempfile::tempdir().unwrap();
+        let server = TestServer::new(create_router(test_state(dir.path()).await)).unwrap();
+        let body: serde_json::Value = server.get("/api/gpu-status").await.json();
+        assert_eq!(body["data"]["device"], "unavailable");
+        assert_eq!(body["data"]["ml_service_available"], false);
+        assert_eq!(body["data"]["gpu_available"], false);
+        assert!(body["data"]["gpu_name"].is_null());
+    }
+
+    /// Stands in for the Fitbit Web API: the same day of activity for any
+    /// date, and only `access-2` accepted, which refreshing `refresh-1` issues.
+    /// After `activity_days` days of activity it rate limits.
+    async fn mock_fitbit(activity_days: usize) -> String {
+        use axum::{extract::Form, http::HeaderMap};
+        use std::collections::HashMap;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        fn authorized(headers: &HeaderMap) -> bool {
+            headers.get(header::AUTHORIZATION).and_then(|v| v.to_str().ok()) == Some("Bearer access-2")
+        }
+
+        let served = Arc::new(AtomicUsize::new(0));
+        let app = Router::new()
+            .route("/1/user/-/activities/date/:date", get(move |headers: HeaderMap| async move {
+                if !authorized(&headers) {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                if served.fetch_add(1, Ordering::SeqCst) >= activity_days {
+                    return StatusCode::TOO_MANY_REQUESTS.into_response();
+                }
+                Json(serde_json::json!({
+                    "summary": { "steps": 8000, "fairlyActiveMinutes": 20, "veryActiveMinutes": 15, "sedentaryMinutes": 700 }
+                })).into_response()
+            }))
+            .route("/1/user/-/activities/heart/date/:date/1d.json", get(|headers: HeaderMap| async move {
+                if !authorized(&headers) {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                Json(serde_json::json!({
+                    "activities-heart": [{
+                        "dateTime": "2026-10-16",
+                        "value": {
+                            "restingHeartRate": 58,
+                            "heartRateZones": [
+                                { "name": "Out of Range", "min": 30, "max": 97, "minutes": 1200 },
+                                { "name": "Fat Burn", "min": 97, "max": 136, "minutes": 60 },
+                                { "name": "Cardio", "min": 136, "max": 167, "minutes": 0 }
+                            ]
+                        }
+                    }]
+                })).into_response()
+            }))
+            .route("/oauth2/token", post(|Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(form.get("grant_type").map(String::as_str), Some("refresh_token"));
+                if form.get("refresh_token").map(String::as_str) != Some("refresh-1") {
+                    return StatusCode::BAD_REQUEST.into_response();
+                }
+                Json(serde_json::json!({ "access_token": "access-2", "refresh_token": "refresh-2" })).into_response()
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_fitbit_failure_after_refresh_keeps_tokens_and_fetched_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let fitbit_url = mock_fitbit(1).await;
+        let mut config = Config::default();
+        config.fitbit.client_id = Some("client".to_string());
+        config.fitbit.client_secret = Some("secret".to_string());
+        config.fitbit.token_url = format!("{}/oauth2/token", fitbit_url);
+        config.fitbit.base_url = fitbit_url;
+        let state = test_state_with_config(dir.path(), config).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        // Today is fetched after refreshing, then yesterday is rate limited
+        let response = server.post("/api/users/api_user/integrations/fitbit/sync").authorization_bearer(&token)
+            .json(&serde_json::json!({ "access_token": "access-1", "refresh_token": "refresh-1", "days": 3 }))
+            .await;
+        response.assert_status(StatusCode::BAD_GATEWAY);
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["data"]["imported"], 4);
+        assert_eq!(body["data"]["tokens"]["access_token"], "access-2");
+        assert_eq!(body["data"]["tokens"]["refresh_token"], "refresh-2");
+
+        let metrics = state.advisor.db.get_activity_metrics("api_user").await.unwrap();
+        let today = chrono::Utc::now().date_naive().to_string();
+        assert_eq!(metrics.len(), 4);
+        assert!(metrics.iter().all(|m| m.date == today));
+    }
+
+    #[tokio::test]
+    async fn test_fitbit_sync_maps_metrics_once_per_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let fitbit_url = mock_fitbit(usize::MAX).await;
+        let mut config = Config::default();
+        config.fitbit.client_id = Some("client".to_string());
+        config.fitbit.client_secret = Some("secret".to_string());
+        config.fitbit.token_url = format!("{}/oauth2/token", fitbit_url);
+        config.fitbit.base_url = fitbit_url;
+        let state = test_state_with_config(dir.path(), config).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        // The expired access token is refreshed and the new pair handed back
+        let first = server.post("/api/users/api_user/integrations/fitbit/sync").authorization_bearer(&token)
+            .json(&serde_json::json!({ "access_token": "access-1", "refresh_token": "refresh-1", "days": 2 }))
+            .await;
+        first.assert_status_ok();
+        let body: serde_json::Value = first.json();
+        assert_eq!(body["data"]["imported"], 8);
+        assert_eq!(body["data"]["tokens"]["access_token"], "access-2");
+        assert_eq!(body["data"]["tokens"]["refresh_token"], "refresh-2");
+
+        let metrics = state.advisor.db.get_activity_metrics("api_user").await.unwrap();
+        let today = chrono::Utc::now().date_naive().to_string();
+        let value = |kind: crate::ActivityMetricKind| metrics.iter()
+            .find(|m| m.date == today && m.metric == kind)
+            .map(|m| m.value);
+        assert!(metrics.iter().all(|m| m.source == crate::integrations::fitbit::FITBIT_SOURCE));
+        assert_eq!(value(crate::ActivityMetricKind::Steps), Some(8000.0));
+        assert_eq!(value(crate::ActivityMetricKind::ActiveMinutes), Some(35.0));
+        assert_eq!(value(crate::ActivityMetricKind::RestingHeartRate), Some(58.0));
+        assert_eq!(value(crate::ActivityMetricKind::AverageHeartRate), Some(66.0));
+
+        // Syncing the same days again stores nothing new
+        let again = server.post("/api/users/api_user/integrations/fitbit/sync").authorization_bearer(&token)
+            .json(&serde_json::json!({ "access_token": "access-2", "refresh_token": "refresh-2", "days": 2 }))
+            .await;
+        again.assert_status_ok();
+        assert_eq!(again.json::<serde_json::Value>()["data"]["imported"], 0);
+        assert_eq!(state.advisor.db.get_activity_metrics("api_user").await.unwrap().len(), 8);
+
+        let progress: serde_json::Value = server.get("/api/users/api_user/progress").authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(progress["data"]["activity"]["average_daily_steps"], 8000.0);
+        assert_eq!(progress["data"]["activity"]["latest_resting_heart_rate"], 58.0);
+
+        let other_dir = tempfile::tempdir().unwrap();
+        let unconfigured = test_state(other_dir.path()).await;
+        TestServer::new(create_router(unconfigured)).unwrap()
+            .post("/api/users/api_user/integrations/fitbit/sync").authorization_bearer(&token)
+            .json(&serde_json::json!({ "access_token": "a", "refresh_token": "r" }))
+            .await
+            .assert_status(StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_health_import_stores_each_record_type_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        let payload = serde_json::json!({
+            "source": "apple_health",
+            "steps": [{ "date": "2026-10-14", "count": 9000 }, { "date": "2026-10-15", "count": 7000 }],
+            "heart_rate": [{ "date": "2026-10-15", "resting_bpm": 55, "average_bpm": 72 }],
+            "sleep": [
+                { "external_id": "sleep-1", "start": "2026-10-14T22:30:00Z", "end": "2026-10-15T06:30:00Z" },
+                // The same night again under a new id
+                { "external_id": "sleep-1b", "start": "2026-10-14T22:30:00Z", "end": "2026-10-15T06:30:00Z" }
+            ],
+            "workouts": [
+                { "external_id": "run-1", "start": "2026-10-15T07:00:00Z", "duration_minutes": 30,
+                  "exercise_type": "Cardio", "activity": "Running" },
+                { "external_id": "yoga-1", "start": "2026-10-15T18:00:00Z", "duration_minutes": 45, "calories": 150.0 }
+            ]
+        });
+        let first = server.post("/api/users/api_user/health-import").authorization_bearer(&token)
+            .json(&payload)
+            .await;
+        first.assert_status_ok();
+        let summary = first.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(summary["steps"], serde_json::json!({ "imported": 2, "duplicates": 0 }));
+        assert_eq!(summary["heart_rate"], serde_json::json!({ "imported": 2, "duplicates": 0 }));
+        assert_eq!(summary["sleep"], serde_json::json!({ "imported": 1, "duplicates": 1 }));
+        assert_eq!(summary["workouts"], serde_json::json!({ "imported": 2, "duplicates": 0 }));
+
+        let metrics = state.advisor.db.get_activity_metrics("api_user").await.unwrap();
+        assert_eq!(metrics.len(), 4);
+        assert!(metrics.iter().all(|m| m.source == "apple_health"));
+        let sleep = state.advisor.db.get_sleep_sessions("api_user").await.unwrap();
+        assert_eq!(sleep.len(), 1);
+        assert_eq!(sleep[0].hours(), 8.0);
+
+        // 8 MET for half an hour at the profile weight
+        let workouts = state.advisor.db.get_user_workouts("api_user").await.unwrap();
+        assert_eq!(workouts.len(), 2);
+        let run = workouts.iter().find(|w| w.id.ends_with("run-1")).unwrap();
+        assert_eq!(run.date, "2026-10-15");
+        assert_eq!(run.calories_burned, Some(8.0 * test_user().weight * 0.5));
+        assert!(run.calories_estimated);
+        let yoga = workouts.iter().find(|w| w.id.ends_with("yoga-1")).unwrap();
+        assert_eq!(yoga.calories_burned, Some(150.0));
+        assert!(!yoga.calories_estimated);
+
+        let again = server.post("/api/users/api_user/health-import").authorization_bearer(&token)
+            .json(&payload)
+            .await;
+        again.assert_status_ok();
+        let summary = again.json::<serde_json::Value>()["data"].clone();
+        for kind in ["steps", "heart_rate", "sleep", "workouts"] {
+            assert_eq!(summary[kind]["imported"], 0, "{} imported twice", kind);
+        }
+        assert_eq!(state.advisor.db.get_activity_metrics("api_user").await.unwrap().len(), 4);
+        assert_eq!(state.advisor.db.get_user_workouts("api_user").await.unwrap().len(), 2);
+
+        // A client without step or workout permissions sends only sleep
+        server.post("/api/users/api_user/health-import").authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "source": "google_fit",
+                "sleep": [{ "external_id": "g-1", "start": "2026-10-15T23:00:00Z", "end": "2026-10-16T06:00:00Z" }]
+            }))
+            .await
+            .assert_status_ok();
+        let progress: serde_json::Value = server.get("/api/users/api_user/progress").authorization_bearer(&token)
+            .await
+            .json();
+        assert_eq!(progress["data"]["activity"]["average_sleep_hours"], 7.5);
+        assert_eq!(progress["data"]["activity"]["latest_resting_heart_rate"], 55.0);
+        assert_eq!(progress["data"]["total_workouts"], 2);
+
+        server.post("/api/users/api_user/health-import").authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "source": "apple_health",
+                "sleep": [{ "external_id": "bad", "start": "2026-10-15T06:00:00Z", "end": "2026-10-15T05:00:00Z" }]
+            }))
+            .await
+            .assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_food_diary_import_matches_source_totals() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.menu_optimizer.add_foods(crate::sample_data::create_sample_foods()).await.unwrap();
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        // The chicken has no macros of its own and is filled in from the
+        // nutrition database; the bar has none and isn't in it
+        let diary = "\
+Date,Time,Meal,Food,Quantity (g),Calories,Protein (g),Carbohydrates (g),Fat (g)
+2026-10-14,07:30,Breakfast,\"Oatmeal, rolled\",40,150,5,27,3
+2026-10-14,12:15,Lunch,Chicken Breast (skinless),200,,,,
+2026-10-14,19:00,Dinner,Homemade Lasagna,350,\"1,050\",55,90,48
+10/15/2026,8:00 AM,Breakfast,Large Eggs,100,143,12.6,0.7,9.5
+10/15/2026,13:00,Lunch,Mystery Bar,,,,,
+2026-10-15,,Snack,Apple,,95,0.5,25,0.3
+";
+        let import = || server.post("/api/users/api_user/food-log/import").authorization_bearer(&token)
+            .content_type("text/csv")
+            .bytes(diary.as_bytes().to_vec().into());
+
+        let first = import().await;
+        first.assert_status_ok();
+        let summary = first.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(summary["imported"], 5);
+        assert_eq!(summary["resolved"], 2);
+        assert_eq!(summary["skipped"].as_array().unwrap().len(), 1);
+        assert_eq!(summary["skipped"][0]["row"], 5);
+
+        let days: serde_json::Value = server.get("/api/users/api_user/food-log").authorization_bearer(&token)
+            .await
+            .json();
+        let days = days["data"].as_array().unwrap();
+        let totals = |day: &serde_json::Value| ["calories", "protein_g", "carbs_g", "fat_g"]
+            .map(|field| day[field].as_f64().unwrap());
+        let close = |actual: [f64; 4], expected: [f64; 4]| actual.iter().zip(expected).all(|(a, e)| (a - e).abs() < 1e-6);
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0]["date"], "2026-10-14");
+        assert!(close(totals(&days[0]), [150.0 + 330.0 + 1050.0, 5.0 + 62.0 + 55.0, 27.0 + 90.0, 3.0 + 7.2 + 48.0]));
+        assert_eq!(days[1]["date"], "2026-10-15");
+        assert!(close(totals(&days[1]), [143.0 + 95.0, 12.6 + 0.5, 0.7 + 25.0, 9.5 + 0.3]));
+        let oatmeal = &days[0]["entries"][0];
+        assert_eq!(oatmeal["food_name"], "Oatmeal, rolled");
+        assert_eq!(oatmeal["time"], "07:30");
+        assert!(oatmeal["food_id"].is_null());
+        assert_eq!(days[0]["entries"][1]["food_id"], "chicken_breast");
+
+        let again = import().await;
+        again.assert_status_ok();
+        let summary = again.json::<serde_json::Value>()["data"].clone();
+        assert_eq!(summary["imported"], 0);
+        assert_eq!(summary["skipped"].as_array().unwrap().len(), 6);
+        assert_eq!(summary["skipped"][0]["reason"], "Already imported");
+
+        // The same breakfast as JSON is recognised too
+        let json = server.post("/api/users/api_user/food-log/import").authorization_bearer(&token)
+            .json(&serde_json::json!([
+                { "date": "2026-10-15", "time": "08:00", "food": "Large Eggs", "calories": 143.0 },
+                { "date": "2026-10-16", "food": "Banana", "calories": 105.0, "carbs_g": 27.0 }
+            ]))
+            .await;
+        json.assert_status_ok();
+        assert_eq!(json.json::<serde_json::Value>()["data"]["imported"], 1);
+
+        server.post("/api/users/api_user/food-log/import").authorization_bearer(&token)
+            .text("Date,Food")
+            .await
+            .assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_export_contains_every_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path()).await;
+        state.advisor.register_user(test_user(), None).await.unwrap();
+        let token = state.tokens.issue("api_user", vec![]).unwrap();
+        let server = TestServer::new(create_router(state.clone())).unwrap();
+
+        for (i, date) in ["2025-07-01", "2025-07-03", "2025-07-05"].into_iter().enumerate() {
+            server.post("/api/workouts").authorization_bearer(&token)
+                .json(&serde_json::json!({
+                    "workout": {
+                        "id": format!("export_{}", i),
+                        "user_id": "api_user",
+                        "date": date,
+                        "exercises": [{
+                            "exercise_id": "squat",
+                            "sets": 3,
+                            "reps": 5,
+                            "weight_kg": 60.0,
+                            "duration_seconds": null,
+                            "rest_seconds": 120,
+                            "completed": true
+                        }],
+                        "total_duration_minutes": 45,
+                        "calories_burned": 250.0,
+                        "user_rating": 4,
+                        "notes": null
+                    }
+                }))
+                .await
+                .assert_status_ok();
+        }
+        for date in ["2025-07-01", "2025-07-08"] {
+            server.post("/api/users/api_user/measurements").authorization_bearer(&token)
+                .json(&serde_json::json!({ "date": date, "weight_kg": 65.0 }))
+                .await
+                .assert_status_ok();
+        }
+        server.post("/api/users/api_user/food-log/import").authorization_bearer(&token)
+            .json(&serde_json::json!([
+                { "date": "2025-07-01", "time": "08:00", "food": "Porridge", "calories": 300.0 },
+                { "date": "2025-07-01", "time": "13:00", "food": "Soup", "calories": 250.0 },
+                { "date": "2025-07-02", "food": "Toast", "calories": 180.0 }
+            ]))
+            .await
+            .assert_status_ok();
+        server.post("/api/users/api_user/goals").authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "title": "Squat 100kg",
+                "metric": "exercise_weight",
+                "exercise_id": "squat",
+                "target_value": 100.0,
+                "deadline": "2099-12-31"
+            }))
+            .await
+            .assert_status_ok();
+        server.put("/api/users/api_user/favorites/exercise/squat").authorization_bearer(&token).await
+            .assert_status_ok();
+        server.post("/api/users/api_user/health-import").authorization_bearer(&token)
+            .json(&serde_json::json!({
+                "source": "google_fit",
+                "steps": [{ "date": "2025-07-01", "count": 8000 }],
+                "sleep": [{ "external_id": "night", "start": "2025-07-01T22:00:00Z", "end": "2025-07-02T06:00:00Z" }]
+            }))
+            .await
+            .assert_status_ok();
+
+        let response = server.get("/api/users/api_user/export").authorization_bearer(&token).await;
+        response.assert_status_ok();
+        assert_eq!(response.header(header::CONTENT_DISPOSITION), "attachment; filename=\"export-api_user.json\"");
+        let export: serde_json::Value = response.json();
+        assert_eq!(export["schema_version"], crate::export::EXPORT_SCHEMA_VERSION);
+        assert_eq!(export["user_id"], "api_user");
+        let sections: Vec<&str> = export["sections"].as_array().unwrap().iter().map(|s| s.as_str().unwrap()).collect();
+        assert_eq!(sections, crate::export::EXPORT_SECTIONS);
+        for section in &sections {
+            assert!(!export[section].is_null(), "missing section {}", section);
+        }
+
+        let count = |section: &str| export[section].as_array().map(Vec::len);
+        assert_eq!(export["profile"]["id"], "api_user");
+        assert_eq!(count("workouts"), Some(3));
+        assert_eq!(count("measurements"), Some(2));
+        let food_days = export["food_log"].as_array().unwrap();
+        assert_eq!(food_days.iter().map(|day| day["entries"].as_array().unwrap().len()).sum::<usize>(), 3);
+        assert_eq!(count("goals"), Some(1));
+        assert_eq!(count("favorites"), Some(1));
+        assert_eq!(count("activity_metrics"), Some(1));
+        assert_eq!(count("sleep"), Some(1));
+        assert!(count("achievements").is_some());
+        assert!(export["plans"]["weekly_schedule"].is_object());
+        assert!(export["plans"]["carb_cycle"].is_object());
+        // No credentials leave the server
+        assert!(!response.text().contains("password"));
+
+        server.get("/api/users/someone_else/export").authorization_bearer(&token).await
+            .assert_status(StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,272 @@
+// src/auth.rs - Token authentication for the API
+
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use ring::{hmac, pbkdf2, rand::{SecureRandom, SystemRandom}};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{config::AuthConfig, models::user::Role, AppState, FitnessAdvisor};
+
+/// Scope granting access to every user's data
+pub const ADMIN_SCOPE: &str = "admin";
+/// Scope granting access to linked clients' data
+pub const COACH_SCOPE: &str = "coach";
+/// Scope of a user limited to their own data
+pub const CLIENT_SCOPE: &str = "client";
+
+/// The token scope carrying a role
+pub fn role_scope(role: Role) -> &'static str {
+    match role {
+        Role::Client => CLIENT_SCOPE,
+        Role::Coach => COACH_SCOPE,
+        Role::Admin => ADMIN_SCOPE,
+    }
+}
+
+/// Path parameter naming the user a route operates on
+const USER_ID_PARAM: &str = "user_id";
+
+/// Shortest password accepted at registration
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+/// PBKDF2-HMAC-SHA256 work factor, per OWASP's current recommendation
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const PBKDF2_SCHEME: &str = "pbkdf2-sha256";
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// Verified against when the user has no credentials, so an unknown user
+/// takes as long to reject as a wrong password
+const DUMMY_PASSWORD_HASH: &str =
+    "pbkdf2-sha256$600000$AAAAAAAAAAAAAAAAAAAAAA$AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA";
+
+/// Salted PBKDF2-HMAC-SHA256 hash in the form
+/// `pbkdf2-sha256$<iterations>$<salt>$<hash>`
+pub fn hash_password(password: &str) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new().fill(&mut salt).map_err(|_| anyhow!("Failed to generate salt"))?;
+
+    let mut hash = [0u8; HASH_LEN];
+    let iterations = NonZeroU32::new(PBKDF2_ITERATIONS).expect("iterations are non-zero");
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, iterations, &salt, password.as_bytes(), &mut hash);
+
+    Ok(format!(
+        "{}${}${}${}",
+        PBKDF2_SCHEME,
+        PBKDF2_ITERATIONS,
+        BASE64_URL_SAFE_NO_PAD.encode(salt),
+        BASE64_URL_SAFE_NO_PAD.encode(hash),
+    ))
+}
+
+/// Check a password against a stored hash in constant time. Malformed
+/// hashes never match.
+pub fn verify_password(password: &str, stored_hash: &str) -> bool {
+    let mut parts = stored_hash.split('$');
+    let (Some(PBKDF2_SCHEME), Some(iterations), Some(salt), Some(hash), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+
+    let (Some(iterations), Ok(salt), Ok(hash)) = (
+        iterations.parse().ok().and_then(NonZeroU32::new),
+        BASE64_URL_SAFE_NO_PAD.decode(salt),
+        BASE64_URL_SAFE_NO_PAD.decode(hash),
+    ) else {
+        return false;
+    };
+
+    pbkdf2::verify(pbkdf2::PBKDF2_HMAC_SHA256, iterations, &salt, password.as_bytes(), &hash).is_ok()
+}
+
+/// Burn the same time as a real password check without a real hash
+pub fn verify_dummy_password(password: &str) {
+    let _ = verify_password(password, DUMMY_PASSWORD_HASH);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginOutcome {
+    Success,
+    /// Unknown user or wrong password; deliberately indistinguishable
+    InvalidCredentials,
+    /// Too many recent failures; the password was not checked
+    LockedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// The authenticated user's id
+    pub sub: String,
+    pub iat: i64,
+    pub exp: i64,
+    #[serde(default)]
+    pub scope: Vec<String>,
+}
+
+/// Issues and verifies HS256 JSON Web Tokens
+pub struct TokenSigner {
+    key: hmac::Key,
+    ttl: chrono::Duration,
+}
+
+impl TokenSigner {
+    pub fn new(config: &AuthConfig) -> Self {
+        Self {
+            key: hmac::Key::new(hmac::HMAC_SHA256, config.jwt_secret.as_bytes()),
+            ttl: chrono::Duration::minutes(config.token_ttl_minutes as i64),
+        }
+    }
+
+    pub fn issue(&self, user_id: &str, scope: Vec<String>) -> Result<String> {
+        let now = chrono::Utc::now();
+        let claims = Claims {
+            sub: user_id.to_string(),
+            iat: now.timestamp(),
+            exp: (now + self.ttl).timestamp(),
+            scope,
+        };
+
+        let header = BASE64_URL_SAFE_NO_PAD.encode(br#"{"alg":"HS256","typ":"JWT"}"#);
+        let payload = BASE64_URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims)?);
+        let signing_input = format!("{}.{}", header, payload);
+        let signature = hmac::sign(&self.key, signing_input.as_bytes());
+
+        Ok(format!("{}.{}", signing_input, BASE64_URL_SAFE_NO_PAD.encode(signature.as_ref())))
+    }
+
+    /// Check the signature and expiry, returning the token's claims
+    pub fn verify(&self, token: &str) -> Result<Claims> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(anyhow!("Malformed token"));
+        };
+
+        let signature = BASE64_URL_SAFE_NO_PAD.decode(signature)
+            .map_err(|_| anyhow!("Malformed token signature"))?;
+        hmac::verify(&self.key, format!("{}.{}", header, payload).as_bytes(), &signature)
+            .map_err(|_| anyhow!("Invalid token signature"))?;
+
+        // Only accept the algorithm we sign with
+        let header: serde_json::Value = serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(header)?)?;
+        if header["alg"] != "HS256" {
+            return Err(anyhow!("Unsupported token algorithm"));
+        }
+
+        let claims: Claims = serde_json::from_slice(&BASE64_URL_SAFE_NO_PAD.decode(payload)?)?;
+        if claims.exp <= chrono::Utc::now().timestamp() {
+            return Err(anyhow!("Token expired"));
+        }
+
+        Ok(claims)
+    }
+}
+
+/// The caller identified by a valid bearer token, available to handlers as
+/// an `Extension<AuthUser>` on protected routes
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: String,
+    pub scope: Vec<String>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.scope.iter().any(|s| s == ADMIN_SCOPE)
+    }
+
+    pub fn is_coach(&self) -> bool {
+        self.scope.iter().any(|s| s == COACH_SCOPE)
+    }
+
+    /// Check the caller may read or change `user_id`'s data: their own, a
+    /// linked client's if they're a coach, anyone's if they're an admin.
+    /// 403 when not allowed.
+    pub async fn authorize(&self, advisor: &FitnessAdvisor, user_id: &str) -> std::result::Result<(), StatusCode> {
+        if self.user_id == user_id || self.is_admin() {
+            return Ok(());
+        }
+
+        // Links are looked up on every request so unlinking takes effect
+        // without waiting for the coach's token to expire
+        if self.is_coach() {
+            match advisor.is_coach_of(&self.user_id, user_id).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Failed to check coach {} for client {}: {}", self.user_id, user_id, e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            }
+        }
+
+        Err(StatusCode::FORBIDDEN)
+    }
+}
+
+/// The token from an `Authorization: Bearer` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Reject requests without a valid bearer token (401) and `:user_id` routes
+/// the caller isn't authorized for (403), then hand the caller to the
+/// handler as an `AuthUser` extension
+pub async fn require_auth(
+    State(state): State<Arc<AppState>>,
+    path: Option<Path<HashMap<String, String>>>,
+    mut request: Request,
+    next: Next,
+) -> std::result::Result<Response, StatusCode> {
+    let token = bearer_token(request.headers()).ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let claims = state.tokens.verify(token).map_err(|_| StatusCode::UNAUTHORIZED)?;
+    let user = AuthUser { user_id: claims.sub, scope: claims.scope };
+
+    if let Some(user_id) = path.as_ref().and_then(|Path(params)| params.get(USER_ID_PARAM)) {
+        user.authorize(&state.advisor, user_id).await?;
+    }
+
+    request.extensions_mut().insert(user);
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer(secret: &str) -> TokenSigner {
+        TokenSigner::new(&AuthConfig { jwt_secret: secret.to_string(), ..AuthConfig::default() })
+    }
+
+    #[test]
+    fn test_token_round_trip_and_tampering() {
+        let tokens = signer("a-test-secret-that-is-long-enough!!");
+        let token = tokens.issue("alice", vec![]).unwrap();
+        assert_eq!(tokens.verify(&token).unwrap().sub, "alice");
+
+        // Signed with a different key
+        assert!(signer("another-secret-that-is-long-enough!").verify(&token).is_err());
+
+        // Payload swapped for another user's
+        let forged_payload = BASE64_URL_SAFE_NO_PAD.encode(br#"{"sub":"bob","iat":0,"exp":99999999999}"#);
+        let parts: Vec<&str> = token.split('.').collect();
+        let forged = format!("{}.{}.{}", parts[0], forged_payload, parts[2]);
+        assert!(tokens.verify(&forged).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+// src/caching.rs - Conditional GETs for catalog responses, so clients can
+// revalidate a cached copy instead of downloading it again
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use ring::digest;
+use serde::Serialize;
+
+/// Clients may keep a copy but must check it is current before using it
+const CACHE_CONTROL: &str = "no-cache";
+
+/// HTTP-date as used by `Last-Modified` and `If-Modified-Since`
+const HTTP_DATE_FORMAT: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// Strong ETag over the exact response body
+pub fn etag(body: &[u8]) -> String {
+    let hash = digest::digest(&digest::SHA256, body);
+    let hex: String = hash.as_ref()[..16].iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("\"{}\"", hex)
+}
+
+pub fn http_date(at: DateTime<Utc>) -> String {
+    at.format(HTTP_DATE_FORMAT).to_string()
+}
+
+/// Whether the client's copy is still current. `If-None-Match` wins over
+/// `If-Modified-Since` when both are sent.
+fn not_modified(request: &HeaderMap, etag: &str, last_modified: Option<DateTime<Utc>>) -> bool {
+    if let Some(if_none_match) = request.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok()) {
+        return if_none_match.split(',')
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+    }
+
+    let since = request.get(header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| DateTime::parse_from_rfc2822(v).ok());
+    match (since, last_modified) {
+        // HTTP dates have whole seconds, so compare at that precision
+        (Some(since), Some(modified)) => modified.timestamp() <= since.timestamp(),
+        _ => false,
+    }
+}
+
+/// `body` as JSON with `ETag`, `Last-Modified` (when known) and
+/// `Cache-Control` headers, or an empty 304 when the request's validators
+/// show the client already has it
+pub fn conditional_json<T: Serialize>(request: &HeaderMap, body: &T, last_modified: Option<DateTime<Utc>>) -> Response {
+    let body = match serde_json::to_vec(body) {
+        Ok(body) => body,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    let etag = etag(&body);
+
+    let mut response = if not_modified(request, &etag, last_modified) {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+    };
+
+    let headers = response.headers_mut();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(CACHE_CONTROL));
+    if let Ok(value) = HeaderValue::from_str(&etag) {
+        headers.insert(header::ETAG, value);
+    }
+    if let Some(value) = last_modified.and_then(|at| HeaderValue::from_str(&http_date(at)).ok()) {
+        headers.insert(header::LAST_MODIFIED, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validators_decide_when_a_copy_is_current() {
+        let modified = DateTime::parse_from_rfc3339("2024-03-04T10:00:00.750Z").unwrap().with_timezone(&Utc);
+        let etag = etag(b"[]");
+        let request = |name, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, HeaderValue::from_str(value).unwrap());
+            headers
+        };
+
+        assert!(not_modified(&request(header::IF_MODIFIED_SINCE, &http_date(modified)), &etag, Some(modified)));
+        assert!(!not_modified(&request(header::IF_MODIFIED_SINCE, "Mon, 04 Mar 2024 09:59:59 GMT"), &etag, Some(modified)));
+        assert!(!not_modified(&request(header::IF_MODIFIED_SINCE, "not a date"), &etag, Some(modified)));
+        assert!(!not_modified(&request(header::IF_MODIFIED_SINCE, &http_date(modified)), &etag, None));
+
+        // A stale ETag means the content changed, whatever the date says
+        let mut stale = request(header::IF_MODIFIED_SINCE, &http_date(modified));
+        stale.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"0123\""));
+        assert!(!not_modified(&stale, &etag, Some(modified)));
+        assert!(not_modified(&request(header::IF_NONE_MATCH, &format!("\"0123\", W/{}", etag)), &etag, None));
+    }
+}
@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::ml_client::MLTask;
+use crate::models::UnitSystem;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     pub server: ServerConfig,
@@ -13,13 +16,379 @@ pub struct Config {
     pub logging: LoggingConfig,
     pub ai_analysis: AIAnalysisConfig,
     pub fitness: FitnessConfig,
+    #[serde(default)]
+    pub auth: AuthConfig,
+    #[serde(default)]
+    pub fdc: FdcConfig,
+    #[serde(default)]
+    pub open_food_facts: OpenFoodFactsConfig,
+    #[serde(default)]
+    pub fitbit: FitbitConfig,
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    #[serde(default)]
+    pub experiments: ExperimentConfig,
+    /// Feature flags by name; see `features`
+    #[serde(default)]
+    pub features: HashMap<String, FeatureFlag>,
+    #[serde(default)]
+    pub plates: PlateConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub demo: DemoConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Allowed CORS origins; a single "*" allows any origin
     pub cors_origins: Vec<String>,
+    #[serde(default = "default_cors_methods")]
+    pub cors_methods: Vec<String>,
+    #[serde(default = "default_cors_headers")]
+    pub cors_headers: Vec<String>,
+    /// Whether browsers may send cookies/auth headers cross-origin.
+    /// Not allowed together with a wildcard origin.
+    #[serde(default)]
+    pub cors_allow_credentials: bool,
+    /// How long to wait for in-flight requests when shutting down
+    #[serde(default = "default_shutdown_grace_period_seconds")]
+    pub shutdown_grace_period_seconds: u64,
+    /// Longest each subsystem check in the readiness probe may take
+    #[serde(default = "default_readiness_check_timeout_ms")]
+    pub readiness_check_timeout_ms: u64,
+    /// Smallest response body that is gzip/brotli compressed
+    #[serde(default = "default_compression_min_bytes")]
+    pub compression_min_bytes: u16,
+    /// Largest request body accepted; bigger ones get 413. Image uploads have
+    /// their own limit, `ml_service.max_image_upload_bytes`.
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Longest a client may pause while sending a request body before the
+    /// request is dropped
+    #[serde(default = "default_body_read_timeout_seconds")]
+    pub body_read_timeout_seconds: u64,
+}
+
+fn default_shutdown_grace_period_seconds() -> u64 {
+    30
+}
+
+fn default_readiness_check_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_compression_min_bytes() -> u16 {
+    1024
+}
+
+fn default_max_body_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_body_read_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_cors_methods() -> Vec<String> {
+    ["GET", "POST", "PUT", "DELETE", "OPTIONS"]
+        .iter()
+        .map(|m| m.to_string())
+        .collect()
+}
+
+fn default_cors_headers() -> Vec<String> {
+    ["content-type", "authorization", "x-request-id", "idempotency-key", "if-match"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect()
+}
+
+/// Secret used to sign API tokens when none is configured. It's public, so
+/// the server refuses to start with it unless `auth.allow_dev_secret` is set.
+pub const DEV_JWT_SECRET: &str = "dev-only-jwt-secret-change-me-in-production";
+
+/// Shortest accepted token signing secret, in bytes
+const MIN_JWT_SECRET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuthConfig {
+    /// HMAC-SHA256 key for signing API tokens
+    pub jwt_secret: String,
+    #[serde(default = "default_token_ttl_minutes")]
+    pub token_ttl_minutes: u64,
+    /// Users whose tokens carry the admin scope
+    #[serde(default)]
+    pub admin_user_ids: Vec<String>,
+    /// Consecutive wrong passwords before an account is locked
+    #[serde(default = "default_max_failed_logins")]
+    pub max_failed_logins: u32,
+    #[serde(default = "default_lockout_minutes")]
+    pub lockout_minutes: u64,
+    /// Accept `DEV_JWT_SECRET` for local development. Anyone can sign
+    /// tokens, admin ones included, with it.
+    #[serde(default)]
+    pub allow_dev_secret: bool,
+}
+
+fn default_token_ttl_minutes() -> u64 {
+    60
+}
+
+fn default_max_failed_logins() -> u32 {
+    5
+}
+
+fn default_lockout_minutes() -> u64 {
+    15
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            jwt_secret: DEV_JWT_SECRET.to_string(),
+            token_ttl_minutes: default_token_ttl_minutes(),
+            admin_user_ids: Vec::new(),
+            max_failed_logins: default_max_failed_logins(),
+            lockout_minutes: default_lockout_minutes(),
+            allow_dev_secret: false,
+        }
+    }
+}
+
+/// USDA FoodData Central, used to look up foods missing from the local table
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FdcConfig {
+    /// Lookups are disabled without a key
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default = "default_fdc_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_fdc_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_fdc_base_url() -> String {
+    "https://api.nal.usda.gov/fdc/v1".to_string()
+}
+
+fn default_fdc_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for FdcConfig {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            base_url: default_fdc_base_url(),
+            timeout_seconds: default_fdc_timeout_seconds(),
+        }
+    }
+}
+
+/// Fitbit Web API, used to import daily activity and heart rate. Users
+/// authorize the app with Fitbit themselves; these are the app's OAuth
+/// client credentials, used to refresh their tokens.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FitbitConfig {
+    /// Imports are disabled without client credentials
+    #[serde(default)]
+    pub client_id: Option<String>,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    #[serde(default = "default_fitbit_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_fitbit_token_url")]
+    pub token_url: String,
+    #[serde(default = "default_fitbit_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_fitbit_base_url() -> String {
+    "https://api.fitbit.com".to_string()
+}
+
+fn default_fitbit_token_url() -> String {
+    "https://api.fitbit.com/oauth2/token".to_string()
+}
+
+fn default_fitbit_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for FitbitConfig {
+    fn default() -> Self {
+        Self {
+            client_id: None,
+            client_secret: None,
+            base_url: default_fitbit_base_url(),
+            token_url: default_fitbit_token_url(),
+            timeout_seconds: default_fitbit_timeout_seconds(),
+        }
+    }
+}
+
+/// Open Food Facts, used to resolve scanned barcodes; no key is needed
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OpenFoodFactsConfig {
+    #[serde(default = "default_open_food_facts_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_open_food_facts_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+fn default_open_food_facts_base_url() -> String {
+    "https://world.openfoodfacts.org".to_string()
+}
+
+fn default_open_food_facts_timeout_seconds() -> u64 {
+    10
+}
+
+impl Default for OpenFoodFactsConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_open_food_facts_base_url(),
+            timeout_seconds: default_open_food_facts_timeout_seconds(),
+        }
+    }
+}
+
+/// Delivery of webhook events to subscribed integrators
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Attempts before a delivery is dead-lettered
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait before the first retry; doubles with every further failure
+    #[serde(default = "default_webhook_initial_backoff_seconds")]
+    pub initial_backoff_seconds: u64,
+    /// How often retries that have come due are picked up
+    #[serde(default = "default_webhook_poll_interval_seconds")]
+    pub poll_interval_seconds: u64,
+}
+
+fn default_webhook_timeout_seconds() -> u64 {
+    10
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    6
+}
+
+fn default_webhook_initial_backoff_seconds() -> u64 {
+    30
+}
+
+fn default_webhook_poll_interval_seconds() -> u64 {
+    15
+}
+
+/// Running A/B experiments. Users are split evenly and deterministically
+/// across each experiment's variants; an empty list turns it off.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExperimentConfig {
+    /// Algorithms meal plan optimizations are split across. Variants that
+    /// aren't implemented yet are served by the recommended algorithm and
+    /// counted as fallbacks.
+    #[serde(default)]
+    pub meal_plan_algorithm: Vec<crate::models::optimization::AlgorithmType>,
+}
+
+/// Gates a feature, either for everyone or for a stable share of users
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FeatureFlag {
+    #[serde(default = "default_feature_enabled")]
+    pub enabled: bool,
+    /// Share of users (0-100) who get the feature while it's enabled
+    #[serde(default = "default_feature_rollout_percent")]
+    pub rollout_percent: u8,
+}
+
+fn default_feature_enabled() -> bool {
+    true
+}
+
+fn default_feature_rollout_percent() -> u8 {
+    100
+}
+
+/// Access to operational endpoints (`/metrics`, `/api/database/health`),
+/// separate from user authentication so scrapers need no user account
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Bearer token scrapers must send; the endpoints are open without one
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Sample data for trying the API locally
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DemoConfig {
+    /// Register the demo users at startup. They all share a published
+    /// password, so never turn this on for a deployed server.
+    #[serde(default)]
+    pub seed: bool,
+}
+
+/// Barbell and plates assumed when showing how to load a suggested weight.
+/// Metric users get the kg set and imperial users the lb set.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlateConfig {
+    #[serde(default = "default_kg_plates")]
+    pub kg: PlateSet,
+    #[serde(default = "default_lb_plates")]
+    pub lb: PlateSet,
+}
+
+/// A bar and the plate sizes available for it, in one unit. Each size is
+/// assumed to come in as many pairs as needed.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlateSet {
+    pub bar: f32,
+    pub plates: Vec<f32>,
+}
+
+fn default_kg_plates() -> PlateSet {
+    PlateSet { bar: 20.0, plates: vec![25.0, 20.0, 15.0, 10.0, 5.0, 2.5, 1.25] }
+}
+
+fn default_lb_plates() -> PlateSet {
+    PlateSet { bar: 45.0, plates: vec![45.0, 35.0, 25.0, 10.0, 5.0, 2.5] }
+}
+
+impl Default for PlateConfig {
+    fn default() -> Self {
+        Self {
+            kg: default_kg_plates(),
+            lb: default_lb_plates(),
+        }
+    }
+}
+
+impl PlateConfig {
+    /// The plate set for a user's unit system
+    pub fn for_units(&self, units: UnitSystem) -> &PlateSet {
+        match units {
+            UnitSystem::Metric => &self.kg,
+            UnitSystem::Imperial => &self.lb,
+        }
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: default_webhook_timeout_seconds(),
+            max_attempts: default_webhook_max_attempts(),
+            initial_backoff_seconds: default_webhook_initial_backoff_seconds(),
+            poll_interval_seconds: default_webhook_poll_interval_seconds(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -27,6 +396,45 @@ pub struct DatabaseConfig {
     pub url: String,
     pub max_connections: u32,
     pub connection_timeout_seconds: u64,
+    /// How long an Idempotency-Key is remembered after the write it guarded
+    #[serde(default = "default_idempotency_key_ttl_hours")]
+    pub idempotency_key_ttl_hours: u64,
+    /// Longest a single database operation may take, including waiting for
+    /// a connection, before it fails with a timeout
+    #[serde(default = "default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+    /// How long SQLite keeps retrying a statement that finds the database
+    /// locked (SQLITE_BUSY) before giving up
+    #[serde(default = "default_busy_timeout_ms")]
+    pub busy_timeout_ms: u64,
+    /// Users and exercises each kept in memory for `get_user` and
+    /// `get_exercise`; 0 turns the cache off
+    #[serde(default = "default_cache_capacity")]
+    pub cache_capacity: usize,
+    /// How long a cached record is served before it is read again, which
+    /// bounds staleness from writes made outside this process
+    #[serde(default = "default_cache_ttl_seconds")]
+    pub cache_ttl_seconds: u64,
+}
+
+fn default_idempotency_key_ttl_hours() -> u64 {
+    24
+}
+
+fn default_query_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_busy_timeout_ms() -> u64 {
+    5_000
+}
+
+fn default_cache_capacity() -> usize {
+    1_000
+}
+
+fn default_cache_ttl_seconds() -> u64 {
+    60
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -35,7 +443,37 @@ pub struct MLServiceConfig {
     pub timeout_seconds: u64,
     pub retry_attempts: u32,
     pub health_check_interval_seconds: u64,
+    /// Largest image accepted by the form-analysis photo upload
+    #[serde(default = "default_max_image_upload_bytes")]
+    pub max_image_upload_bytes: usize,
     pub endpoints: MLEndpoints,
+    /// Base URLs of model backends by name, for running models as separate
+    /// services
+    #[serde(default)]
+    pub backends: HashMap<String, String>,
+    /// Backend that serves each task; tasks not listed go to `base_url`
+    #[serde(default)]
+    pub tasks: HashMap<MLTask, String>,
+}
+
+impl MLServiceConfig {
+    /// Base URL of the service handling `task`
+    pub fn task_url(&self, task: MLTask) -> Result<&str> {
+        let Some(backend) = self.tasks.get(&task) else {
+            return Ok(&self.base_url);
+        };
+        self.backends.get(backend)
+            .filter(|url| !url.is_empty())
+            .map(String::as_str)
+            .ok_or_else(|| anyhow!(
+                "ml_service.tasks.{} routes to backend '{}', which has no URL in [ml_service.backends]",
+                task.as_str(), backend
+            ))
+    }
+}
+
+fn default_max_image_upload_bytes() -> usize {
+    5 * 1024 * 1024
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -49,10 +487,15 @@ pub struct MLEndpoints {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LoggingConfig {
+    /// Default level: error, warn, info, debug, trace or off
     pub level: String,
+    /// "pretty" for people, "json" for log aggregators
     pub format: String,
     pub file_enabled: bool,
     pub file_path: String,
+    /// Per-module levels layered over `level`, e.g. `sqlx = "warn"`
+    #[serde(default)]
+    pub modules: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -159,6 +602,13 @@ impl Config {
                 self.server.port = port;
             }
         }
+        if let Ok(origins) = std::env::var("FITNESS_CORS_ORIGINS") {
+            self.server.cors_origins = origins
+                .split(',')
+                .map(|o| o.trim().to_string())
+                .filter(|o| !o.is_empty())
+                .collect();
+        }
 
         // Database overrides
         if let Ok(db_url) = std::env::var("FITNESS_DATABASE_URL") {
@@ -175,6 +625,39 @@ impl Config {
             }
         }
 
+        // Auth overrides
+        if let Ok(secret) = std::env::var("FITNESS_JWT_SECRET") {
+            self.auth.jwt_secret = secret;
+        }
+        if let Ok(allow) = std::env::var("FITNESS_ALLOW_DEV_JWT_SECRET") {
+            if let Ok(allow) = allow.parse() {
+                self.auth.allow_dev_secret = allow;
+            }
+        }
+
+        if let Ok(token) = std::env::var("FITNESS_METRICS_TOKEN") {
+            self.metrics.token = Some(token);
+        }
+
+        if let Ok(seed) = std::env::var("FITNESS_DEMO_SEED") {
+            if let Ok(seed) = seed.parse() {
+                self.demo.seed = seed;
+            }
+        }
+
+        // FoodData Central overrides
+        if let Ok(key) = std::env::var("FITNESS_FDC_API_KEY") {
+            self.fdc.api_key = Some(key);
+        }
+
+        // Fitbit overrides
+        if let Ok(client_id) = std::env::var("FITNESS_FITBIT_CLIENT_ID") {
+            self.fitbit.client_id = Some(client_id);
+        }
+        if let Ok(client_secret) = std::env::var("FITNESS_FITBIT_CLIENT_SECRET") {
+            self.fitbit.client_secret = Some(client_secret);
+        }
+
         // Logging overrides
         if let Ok(log_level) = std::env::var("FITNESS_LOG_LEVEL") {
             self.logging.level = log_level;
@@ -203,10 +686,43 @@ impl Config {
             return Err(anyhow!("Invalid server port: {}", self.server.port));
         }
 
+        // Validate CORS settings
+        let wildcard_origin = self.server.cors_origins.iter().any(|o| o == "*");
+        if wildcard_origin && self.server.cors_allow_credentials {
+            return Err(anyhow!("CORS credentials cannot be allowed with a wildcard origin"));
+        }
+        for origin in self.server.cors_origins.iter().filter(|o| *o != "*") {
+            if !origin.starts_with("http://") && !origin.starts_with("https://") {
+                return Err(anyhow!("Invalid CORS origin: {}", origin));
+            }
+        }
+
         // Validate ML service URL
         if self.ml_service.base_url.is_empty() {
             return Err(anyhow!("ML service base URL is empty"));
         }
+        for task in MLTask::ALL {
+            self.ml_service.task_url(task)?;
+        }
+
+        if self.auth.jwt_secret.len() < MIN_JWT_SECRET_LEN {
+            return Err(anyhow!("JWT secret must be at least {} bytes", MIN_JWT_SECRET_LEN));
+        }
+        if self.auth.jwt_secret == DEV_JWT_SECRET && !self.auth.allow_dev_secret {
+            return Err(anyhow!(
+                "auth.jwt_secret is the public development secret; set FITNESS_JWT_SECRET, \
+                 or FITNESS_ALLOW_DEV_JWT_SECRET=true for local development"
+            ));
+        }
+
+        for set in [&self.plates.kg, &self.plates.lb] {
+            if set.bar < 0.0 || set.plates.is_empty() || set.plates.iter().any(|plate| *plate <= 0.0) {
+                return Err(anyhow!("Plate sets need a bar of at least 0 and positive plate sizes"));
+            }
+        }
+
+        // Validate logging settings
+        crate::logging::LogSettings::from_config(&self.logging, None)?;
 
         // Validate macro ratios sum to 1.0
         let muscle_gain_sum = self.fitness.macro_ratios.muscle_gain.protein +
@@ -227,18 +743,35 @@ impl Default for Config {
             server: ServerConfig {
                 host: "0.0.0.0".to_string(),
                 port: 3000,
-                cors_origins: vec!["http://localhost:3000".to_string()],
+                cors_origins: vec![
+                    "http://localhost:3000".to_string(),
+                    "http://127.0.0.1:3000".to_string(),
+                ],
+                cors_methods: default_cors_methods(),
+                cors_headers: default_cors_headers(),
+                cors_allow_credentials: false,
+                shutdown_grace_period_seconds: default_shutdown_grace_period_seconds(),
+                readiness_check_timeout_ms: default_readiness_check_timeout_ms(),
+                compression_min_bytes: default_compression_min_bytes(),
+                max_body_bytes: default_max_body_bytes(),
+                body_read_timeout_seconds: default_body_read_timeout_seconds(),
             },
             database: DatabaseConfig {
                 url: "sqlite:./fitness_advisor.db".to_string(),
                 max_connections: 10,
                 connection_timeout_seconds: 30,
+                idempotency_key_ttl_hours: 24,
+                query_timeout_ms: default_query_timeout_ms(),
+                busy_timeout_ms: default_busy_timeout_ms(),
+                cache_capacity: default_cache_capacity(),
+                cache_ttl_seconds: default_cache_ttl_seconds(),
             },
             ml_service: MLServiceConfig {
                 base_url: "http://127.0.0.1:8001".to_string(),
                 timeout_seconds: 30,
                 retry_attempts: 3,
                 health_check_interval_seconds: 60,
+                max_image_upload_bytes: default_max_image_upload_bytes(),
                 endpoints: MLEndpoints {
                     health: "/health".to_string(),
                     analyze_frame: "/analyze/frame".to_string(),
@@ -246,12 +779,15 @@ impl Default for Config {
                     analyze_batch: "/analyze/batch".to_string(),
                     models_status: "/models/status".to_string(),
                 },
+                backends: HashMap::new(),
+                tasks: HashMap::new(),
             },
             logging: LoggingConfig {
                 level: "info".to_string(),
-                format: "json".to_string(),
+                format: "pretty".to_string(),
                 file_enabled: true,
                 file_path: "./logs/fitness_advisor.log".to_string(),
+                modules: HashMap::from([("sqlx".to_string(), "warn".to_string())]),
             },
             ai_analysis: AIAnalysisConfig {
                 realtime_max_latency_ms: 50,
@@ -308,6 +844,16 @@ impl Default for Config {
                     },
                 },
             },
+            auth: AuthConfig::default(),
+            fdc: FdcConfig::default(),
+            open_food_facts: OpenFoodFactsConfig::default(),
+            fitbit: FitbitConfig::default(),
+            webhooks: WebhookConfig::default(),
+            experiments: ExperimentConfig::default(),
+            features: HashMap::new(),
+            plates: PlateConfig::default(),
+            metrics: MetricsConfig::default(),
+            demo: DemoConfig::default(),
         }
     }
 }
@@ -320,9 +866,15 @@ mod tests {
 
     #[test]
     fn test_default_config() {
-        let config = Config::default();
+        let mut config = Config::default();
         assert_eq!(config.server.port, 3000);
         assert_eq!(config.database.url, "sqlite:./fitness_advisor.db");
+        // The built-in signing secret is public, so it needs opting in to
+        assert!(config.validate().is_err());
+        config.auth.allow_dev_secret = true;
+        assert!(config.validate().is_ok());
+        config.auth.allow_dev_secret = false;
+        config.auth.jwt_secret = "a-deployment-secret-of-at-least-32-bytes".to_string();
         assert!(config.validate().is_ok());
     }
 
@@ -432,5 +984,23 @@ carbs = 0.40
         assert_eq!(config.logging.level, "debug");
         assert_eq!(config.ai_analysis.realtime_max_latency_ms, 30);
         assert_eq!(config.fitness.default_workout_duration_minutes, 60);
+        // CORS settings not present in the file fall back to defaults
+        assert_eq!(config.server.cors_origins, vec!["http://test.com"]);
+        assert_eq!(config.server.cors_methods, default_cors_methods());
+        assert!(!config.server.cors_allow_credentials);
+    }
+
+    #[test]
+    fn test_cors_validation() {
+        let mut config = Config::default();
+        config.auth.allow_dev_secret = true;
+        config.server.cors_origins = vec!["*".to_string()];
+        assert!(config.validate().is_ok());
+
+        config.server.cors_allow_credentials = true;
+        assert!(config.validate().is_err());
+
+        config.server.cors_origins = vec!["app.example.com".to_string()];
+        assert!(config.validate().is_err());
     }
 }
\ No newline at end of file
@@ -3,6 +3,12 @@
 use serde::{Deserialize, Serialize};
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Upper bounds (ms) of the fixed execution-time histogram buckets
+pub const EXECUTION_TIME_BUCKETS_MS: [f64; 12] = [
+    10.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0, 60000.0, 300000.0,
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemMetrics {
@@ -10,11 +16,26 @@ pub struct SystemMetrics {
     pub avg_optimization_time_ms: f64,
     pub successful_optimizations: u64,
     pub failed_optimizations: u64,
+    /// Runs stopped early, e.g. because the client went away; not failures
+    #[serde(default)]
+    pub cancelled_optimizations: u64,
     pub cache_hits: u64,
     pub cache_misses: u64,
     pub active_users: u64,
     pub total_meals_generated: u64,
     pub uptime_seconds: u64,
+    #[serde(default)]
+    pub execution_time_histograms: Vec<LatencyHistogram>,
+}
+
+/// Fixed-bucket histogram of optimization execution times for one algorithm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyHistogram {
+    pub algorithm_type: String,
+    /// Per-bucket counts matching `EXECUTION_TIME_BUCKETS_MS`, plus a final overflow bucket
+    pub bucket_counts: Vec<u64>,
+    pub count: u64,
+    pub sum_ms: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,6 +65,7 @@ pub struct MetricsCollector {
     start_time: Instant,
     metrics: SystemMetrics,
     optimization_history: Vec<OptimizationMetrics>,
+    latency_histogram: LatencyHistogram,
 }
 
 impl Default for MetricsCollector {
@@ -61,13 +83,16 @@ impl MetricsCollector {
                 avg_optimization_time_ms: 0.0,
                 successful_optimizations: 0,
                 failed_optimizations: 0,
+                cancelled_optimizations: 0,
                 cache_hits: 0,
                 cache_misses: 0,
                 active_users: 0,
                 total_meals_generated: 0,
                 uptime_seconds: 0,
+                execution_time_histograms: Vec::new(),
             },
             optimization_history: Vec::new(),
+            latency_histogram: LatencyHistogram::new("all"),
         }
     }
     
@@ -86,6 +111,9 @@ impl MetricsCollector {
         
         self.metrics.avg_optimization_time_ms = 
             (current_avg * (total_successful - 1.0) + new_time) / total_successful;
+
+        self.observe_execution_time(&opt_metrics.algorithm_type, opt_metrics.execution_time_ms);
+        self.latency_histogram.observe(new_time);
         
         self.optimization_history.push(opt_metrics);
         
@@ -95,9 +123,26 @@ impl MetricsCollector {
         }
     }
     
+    fn observe_execution_time(&mut self, algorithm_type: &str, execution_time_ms: f64) {
+        let histograms = &mut self.metrics.execution_time_histograms;
+        let index = match histograms.iter().position(|h| h.algorithm_type == algorithm_type) {
+            Some(index) => index,
+            None => {
+                histograms.push(LatencyHistogram::new(algorithm_type));
+                histograms.len() - 1
+            }
+        };
+
+        histograms[index].observe(execution_time_ms);
+    }
+
     pub fn record_optimization_failure(&mut self) {
         self.metrics.failed_optimizations += 1;
     }
+
+    pub fn record_optimization_cancelled(&mut self) {
+        self.metrics.cancelled_optimizations += 1;
+    }
     
     pub fn record_cache_hit(&mut self) {
         self.metrics.cache_hits += 1;
@@ -137,6 +182,13 @@ impl MetricsCollector {
         stats.insert("avg_convergence_score".to_string(), avg_convergence);
         stats.insert("avg_solution_quality".to_string(), avg_quality);
         stats.insert("total_optimizations".to_string(), self.optimization_history.len() as f64);
+        stats.insert("success_rate".to_string(), self.get_success_rate());
+
+        for (key, percentile) in [("p50_execution_time_ms", 50.0), ("p95_execution_time_ms", 95.0), ("p99_execution_time_ms", 99.0)] {
+            if let Some(value) = self.get_latency_percentile(percentile) {
+                stats.insert(key.to_string(), value);
+            }
+        }
         
         stats
     }
@@ -150,6 +202,12 @@ impl MetricsCollector {
         }
     }
     
+    /// Estimated optimization latency (ms) at percentile `p` (0-100), or `None`
+    /// before any optimization has completed
+    pub fn get_latency_percentile(&self, p: f64) -> Option<f64> {
+        self.latency_histogram.percentile(p)
+    }
+
     pub fn get_success_rate(&self) -> f64 {
         let total = self.metrics.successful_optimizations + self.metrics.failed_optimizations;
         if total == 0 {
@@ -158,4 +216,216 @@ impl MetricsCollector {
             self.metrics.successful_optimizations as f64 / total as f64
         }
     }
-}
\ No newline at end of file
+}
+
+impl LatencyHistogram {
+    pub fn new(algorithm_type: impl Into<String>) -> Self {
+        Self {
+            algorithm_type: algorithm_type.into(),
+            bucket_counts: vec![0; EXECUTION_TIME_BUCKETS_MS.len() + 1],
+            count: 0,
+            sum_ms: 0.0,
+        }
+    }
+
+    pub fn observe(&mut self, value_ms: f64) {
+        let bucket = EXECUTION_TIME_BUCKETS_MS.iter()
+            .position(|upper| value_ms <= *upper)
+            .unwrap_or(EXECUTION_TIME_BUCKETS_MS.len());
+
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum_ms += value_ms;
+    }
+
+    /// Estimate the value at percentile `p` (0-100) by interpolating linearly
+    /// within the bucket that contains it. Values in the overflow bucket are
+    /// reported as the largest finite bucket bound.
+    pub fn percentile(&self, p: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (p.clamp(0.0, 100.0) / 100.0) * self.count as f64;
+        let mut cumulative = 0;
+
+        for (bucket, count) in self.bucket_counts.iter().enumerate() {
+            let previous = cumulative;
+            cumulative += count;
+
+            if (cumulative as f64) < rank || *count == 0 {
+                continue;
+            }
+
+            let Some(upper) = EXECUTION_TIME_BUCKETS_MS.get(bucket) else {
+                break;
+            };
+            let lower = if bucket == 0 { 0.0 } else { EXECUTION_TIME_BUCKETS_MS[bucket - 1] };
+            let fraction = (rank - previous as f64) / *count as f64;
+
+            return Some(lower + (upper - lower) * fraction);
+        }
+
+        EXECUTION_TIME_BUCKETS_MS.last().copied()
+    }
+}
+
+impl SystemMetrics {
+    /// Render the metrics in the Prometheus text exposition format (version 0.0.4)
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        let counters = [
+            ("optimizations_total", "Total optimization requests received", self.optimization_requests),
+            ("optimizations_successful_total", "Optimizations that completed successfully", self.successful_optimizations),
+            ("optimizations_failed_total", "Optimizations that failed", self.failed_optimizations),
+            ("optimizations_cancelled_total", "Optimizations cancelled before finishing", self.cancelled_optimizations),
+            ("cache_hits_total", "Optimization cache hits", self.cache_hits),
+            ("cache_misses_total", "Optimization cache misses", self.cache_misses),
+            ("meals_generated_total", "Meal plans generated", self.total_meals_generated),
+        ];
+
+        for (name, help, value) in counters {
+            write_metric_header(&mut out, name, help, "counter");
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        let cache_total = self.cache_hits + self.cache_misses;
+        let cache_hit_rate = if cache_total == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / cache_total as f64
+        };
+
+        let gauges = [
+            ("cache_hit_rate", "Ratio of optimization cache hits to lookups", cache_hit_rate),
+            ("optimization_avg_time_ms", "Average optimization execution time in milliseconds", self.avg_optimization_time_ms),
+            ("active_users", "Currently active users", self.active_users as f64),
+            ("uptime_seconds", "Seconds since the metrics collector started", self.uptime_seconds as f64),
+        ];
+
+        for (name, help, value) in gauges {
+            write_metric_header(&mut out, name, help, "gauge");
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        let name = "optimization_execution_time_ms";
+        write_metric_header(&mut out, name, "Optimization execution time in milliseconds", "histogram");
+        for histogram in &self.execution_time_histograms {
+            let label = escape_label_value(&histogram.algorithm_type);
+            let mut cumulative = 0;
+
+            for (upper, count) in EXECUTION_TIME_BUCKETS_MS.iter().zip(&histogram.bucket_counts) {
+                cumulative += count;
+                let _ = writeln!(out, "{}_bucket{{algorithm_type=\"{}\",le=\"{}\"}} {}", name, label, upper, cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{{algorithm_type=\"{}\",le=\"+Inf\"}} {}", name, label, histogram.count);
+            let _ = writeln!(out, "{}_sum{{algorithm_type=\"{}\"}} {}", name, label, histogram.sum_ms);
+            let _ = writeln!(out, "{}_count{{algorithm_type=\"{}\"}} {}", name, label, histogram.count);
+        }
+
+        out
+    }
+}
+
+fn write_metric_header(out: &mut String, name: &str, help: &str, metric_type: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, metric_type);
+}
+
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_metrics(name: &str, time_ms: f64) -> OptimizationMetrics {
+        OptimizationMetrics {
+            algorithm_type: name.to_string(),
+            execution_time_ms: time_ms,
+            iterations: 10,
+            convergence_score: 0.8,
+            constraint_violations: 0,
+            solution_quality: 0.8,
+        }
+    }
+
+    /// Minimal check of the text exposition format: comments are HELP/TYPE lines
+    /// and every sample is `name{labels} value` with a numeric value
+    fn assert_valid_exposition(text: &str) {
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                let keyword = parts.next().unwrap();
+                assert!(keyword == "HELP" || keyword == "TYPE", "bad comment: {}", line);
+                assert!(parts.next().is_some() && parts.next().is_some(), "incomplete comment: {}", line);
+                continue;
+            }
+
+            let (series, value) = line.rsplit_once(' ').expect("sample without value");
+            assert!(value.parse::<f64>().is_ok(), "non-numeric value: {}", line);
+
+            let name = match series.find('{') {
+                Some(brace) => {
+                    assert!(series.ends_with('}'), "unterminated labels: {}", line);
+                    &series[..brace]
+                }
+                None => series,
+            };
+            assert!(!name.is_empty());
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':'), "bad name: {}", line);
+        }
+    }
+
+    #[test]
+    fn test_prometheus_export() {
+        let mut collector = MetricsCollector::new();
+        collector.record_optimization_start();
+        collector.record_cache_miss();
+        collector.record_optimization_success(Duration::from_millis(120), sample_metrics("Genetic Algorithm", 120.0));
+        collector.record_optimization_start();
+        collector.record_cache_hit();
+        collector.record_optimization_start();
+        collector.record_optimization_cancelled();
+
+        let text = collector.get_current_metrics().to_prometheus();
+        assert_valid_exposition(&text);
+
+        assert!(text.contains("# TYPE cache_hit_rate gauge"));
+        assert!(text.contains("\ncache_hit_rate 0.5\n"));
+        assert!(text.contains("optimizations_total 3"));
+        assert!(text.contains("\noptimizations_cancelled_total 1\n"));
+        assert!(text.contains("\noptimizations_failed_total 0\n"));
+        assert!(text.contains("optimization_execution_time_ms_bucket{algorithm_type=\"Genetic Algorithm\",le=\"100\"} 0"));
+        assert!(text.contains("optimization_execution_time_ms_bucket{algorithm_type=\"Genetic Algorithm\",le=\"250\"} 1"));
+        assert!(text.contains("optimization_execution_time_ms_count{algorithm_type=\"Genetic Algorithm\"} 1"));
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let mut collector = MetricsCollector::new();
+        assert_eq!(collector.get_latency_percentile(95.0), None);
+
+        // 90 fast runs in the 50-100ms bucket, 10 slow runs in the 1000-2500ms bucket
+        for i in 0..100 {
+            let time_ms = if i < 90 { 80 } else { 2000 };
+            collector.record_optimization_start();
+            collector.record_optimization_success(
+                Duration::from_millis(time_ms),
+                sample_metrics("Genetic Algorithm", time_ms as f64),
+            );
+        }
+
+        let p50 = collector.get_latency_percentile(50.0).unwrap();
+        assert!(p50 > 50.0 && p50 <= 100.0, "p50 = {}", p50);
+
+        let p95 = collector.get_latency_percentile(95.0).unwrap();
+        assert!(p95 > 1000.0 && p95 <= 2500.0, "p95 = {}", p95);
+
+        let stats = collector.get_optimization_stats();
+        assert_eq!(stats.get("p95_execution_time_ms"), Some(&p95));
+        assert_eq!(stats.get("success_rate"), Some(&1.0));
+    }
+}
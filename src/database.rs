@@ -2,41 +2,63 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use sqlx::{sqlite::SqlitePool, Row, SqlitePool as Pool};
+use utoipa::ToSchema;
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions, SqliteRow},
+    Row, Sqlite, SqlitePool as Pool, Transaction,
+};
+use futures_util::future::BoxFuture;
+use std::future::Future;
 use std::str::FromStr;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::{info, warn};
 
 use crate::{
-    User, Exercise, WorkoutSession, ExerciseSet, ProgressAnalysis,
-    FitnessLevel, FitnessGoal, ExerciseType, Equipment, MuscleGroup, UserPreferences
+    User, Exercise, WorkoutSession, ExerciseSet, BodyMeasurement, Page, PageRequest, Favorite, FavoriteKind,
+    GoalMetric, UserGoal,
+    DeliveryStatus, WebhookDelivery, WebhookEvent, WebhookSubscription, FeedbackRating, FeedbackSummary,
+    ExperimentExposure, VariantResults, ActivityMetric, ActivityMetricKind, SleepSession,
+    FoodLogEntry,
 };
+use crate::config::DatabaseConfig;
+use crate::models::exercise::{estimate_1rm, MAX_REPS_FOR_1RM};
+use crate::models::food::Recipe;
+use crate::models::validation::{describe_field_errors, Validate};
+use crate::sample_data::{create_sample_exercises, create_sample_recipes};
 
-// Database connection and management
-pub struct DatabaseManager {
-    pool: SqlitePool,
-}
+/// Maximum number of matches returned by a full-text search
+const SEARCH_RESULT_LIMIT: i64 = 20;
 
-impl DatabaseManager {
-    // Initialize database connection and create tables
-    pub async fn new(database_url: &str) -> Result<Self> {
-        info!("🗄️  Connecting to database: {}", database_url);
-        
-        let pool = SqlitePool::connect(database_url).await?;
-        
-        let manager = Self { pool };
-        manager.create_tables().await?;
-        manager.seed_exercises().await?;
-        
-        info!("✅ Database initialized successfully");
-        Ok(manager)
-    }
+/// A single schema change within a migration
+enum MigrationStep {
+    Sql(&'static str),
+    /// ALTER TABLE ADD COLUMN, skipped when the column already exists so the
+    /// migration is safe on databases that gained the column another way
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+}
 
-    // Create all necessary tables
-    async fn create_tables(&self) -> Result<()> {
-        info!("📋 Creating database tables...");
+struct Migration {
+    version: i64,
+    description: &'static str,
+    steps: &'static [MigrationStep],
+}
 
-        // Users table
-        sqlx::query(r#"
+/// Schema migrations in the order they are applied. Never edit a released
+/// migration; append a new one instead. Every step must be idempotent so
+/// databases created before versioning existed can be migrated.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial schema",
+        steps: &[
+            MigrationStep::Sql(r#"
             CREATE TABLE IF NOT EXISTS users (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -49,10 +71,8 @@ impl DatabaseManager {
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )
-        "#).execute(&self.pool).await?;
-
-        // Exercises table
-        sqlx::query(r#"
+            "#),
+            MigrationStep::Sql(r#"
             CREATE TABLE IF NOT EXISTS exercises (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -66,10 +86,8 @@ impl DatabaseManager {
                 safety_tips TEXT NOT NULL, -- JSON array
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP
             )
-        "#).execute(&self.pool).await?;
-
-        // Workout sessions table
-        sqlx::query(r#"
+            "#),
+            MigrationStep::Sql(r#"
             CREATE TABLE IF NOT EXISTS workout_sessions (
                 id TEXT PRIMARY KEY,
                 user_id TEXT NOT NULL,
@@ -81,10 +99,8 @@ impl DatabaseManager {
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (user_id) REFERENCES users (id)
             )
-        "#).execute(&self.pool).await?;
-
-        // Exercise sets table (many-to-many: sessions <-> exercises)
-        sqlx::query(r#"
+            "#),
+            MigrationStep::Sql(r#"
             CREATE TABLE IF NOT EXISTS exercise_sets (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 workout_session_id TEXT NOT NULL,
@@ -98,10 +114,8 @@ impl DatabaseManager {
                 FOREIGN KEY (workout_session_id) REFERENCES workout_sessions (id),
                 FOREIGN KEY (exercise_id) REFERENCES exercises (id)
             )
-        "#).execute(&self.pool).await?;
-
-        // User progress tracking table
-        sqlx::query(r#"
+            "#),
+            MigrationStep::Sql(r#"
             CREATE TABLE IF NOT EXISTS user_progress (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
                 user_id TEXT NOT NULL,
@@ -113,220 +127,1708 @@ impl DatabaseManager {
                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (user_id) REFERENCES users (id)
             )
+            "#),
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "mark estimated workout calories",
+        steps: &[MigrationStep::AddColumn {
+            table: "workout_sessions",
+            column: "calories_estimated",
+            definition: "BOOLEAN NOT NULL DEFAULT FALSE",
+        }],
+    },
+    Migration {
+        version: 3,
+        description: "body measurements",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS body_measurements (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                weight_kg REAL NOT NULL,
+                waist_cm REAL,
+                hip_cm REAL,
+                arm_cm REAL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (user_id, date),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 4,
+        description: "full-text search over exercises and recipes",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS exercises_fts USING fts5(
+                exercise_id UNINDEXED,
+                name,
+                description,
+                muscles
+            )
+            "#),
+            // Backfill the index from exercises saved before it existed
+            MigrationStep::Sql("DELETE FROM exercises_fts"),
+            MigrationStep::Sql(r#"
+            INSERT INTO exercises_fts (exercise_id, name, description, muscles)
+            SELECT id, name, description, primary_muscles || ' ' || secondary_muscles
+            FROM exercises
+            "#),
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS recipes (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                data TEXT NOT NULL, -- JSON object
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#),
+            MigrationStep::Sql(r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS recipes_fts USING fts5(
+                recipe_id UNINDEXED,
+                name,
+                description,
+                tags
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 5,
+        description: "soft-deleted users",
+        steps: &[MigrationStep::AddColumn {
+            table: "users",
+            column: "deleted_at",
+            definition: "DATETIME",
+        }],
+    },
+    Migration {
+        version: 6,
+        description: "workout idempotency keys",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS idempotency_keys (
+                user_id TEXT NOT NULL,
+                idempotency_key TEXT NOT NULL,
+                workout_session_id TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, idempotency_key),
+                FOREIGN KEY (workout_session_id) REFERENCES workout_sessions (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 7,
+        description: "user credentials",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS user_credentials (
+                user_id TEXT PRIMARY KEY,
+                password_hash TEXT NOT NULL,
+                failed_attempts INTEGER NOT NULL DEFAULT 0,
+                locked_until DATETIME,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 8,
+        description: "user roles and coach-client links",
+        steps: &[
+            MigrationStep::AddColumn {
+                table: "users",
+                column: "role",
+                definition: "TEXT NOT NULL DEFAULT '\"Client\"'",
+            },
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS coach_clients (
+                coach_id TEXT NOT NULL,
+                client_id TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (coach_id, client_id),
+                FOREIGN KEY (coach_id) REFERENCES users (id),
+                FOREIGN KEY (client_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 9,
+        description: "user versions for optimistic concurrency",
+        steps: &[
+            MigrationStep::AddColumn {
+                table: "users",
+                column: "version",
+                definition: "INTEGER NOT NULL DEFAULT 1",
+            },
+        ],
+    },
+    Migration {
+        version: 10,
+        description: "exercise set tempo and target RPE",
+        steps: &[
+            MigrationStep::AddColumn {
+                table: "exercise_sets",
+                column: "tempo",
+                definition: "TEXT",
+            },
+            MigrationStep::AddColumn {
+                table: "exercise_sets",
+                column: "target_rpe",
+                definition: "REAL",
+            },
+        ],
+    },
+    Migration {
+        version: 11,
+        description: "exercise set superset and circuit groups",
+        steps: &[
+            MigrationStep::AddColumn {
+                table: "exercise_sets",
+                column: "group_id",
+                definition: "TEXT",
+            },
+        ],
+    },
+    Migration {
+        version: 12,
+        description: "exercise demo links and form cues",
+        steps: &[
+            MigrationStep::AddColumn {
+                table: "exercises",
+                column: "demo_url",
+                definition: "TEXT",
+            },
+            MigrationStep::AddColumn {
+                table: "exercises",
+                column: "cues",
+                definition: "TEXT NOT NULL DEFAULT '[]'",
+            },
+        ],
+    },
+    Migration {
+        version: 13,
+        description: "favorite recipes and exercises",
+        steps: &[MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS favorites (
+                user_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                item_id TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, kind, item_id),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#)],
+    },
+    Migration {
+        version: 14,
+        description: "user goals",
+        steps: &[MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS goals (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                title TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                exercise_id TEXT,
+                start_value REAL NOT NULL,
+                target_value REAL NOT NULL,
+                deadline TEXT,
+                created_at DATETIME NOT NULL,
+                completed_at DATETIME,
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#)],
+    },
+    Migration {
+        version: 15,
+        description: "achievements and activity counts",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS achievements (
+                user_id TEXT NOT NULL,
+                achievement_id TEXT NOT NULL,
+                awarded_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, achievement_id),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS activity_counts (
+                user_id TEXT NOT NULL,
+                activity TEXT NOT NULL,
+                count INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (user_id, activity),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 16,
+        description: "webhook subscriptions and deliveries",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS webhook_subscriptions (
+                id TEXT PRIMARY KEY,
+                url TEXT NOT NULL,
+                secret TEXT NOT NULL,
+                events TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#),
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id TEXT PRIMARY KEY,
+                subscription_id TEXT NOT NULL,
+                event TEXT NOT NULL,
+                payload TEXT NOT NULL,
+                status TEXT NOT NULL,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at DATETIME,
+                last_error TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (subscription_id) REFERENCES webhook_subscriptions (id)
+            )
+            "#),
+            MigrationStep::Sql(
+                "CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries (status, next_attempt_at)"
+            ),
+        ],
+    },
+    Migration {
+        version: 17,
+        description: "thumbs up/down feedback on recommendations",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS recommendation_feedback (
+                user_id TEXT NOT NULL,
+                recommendation_id TEXT NOT NULL,
+                rating TEXT NOT NULL,
+                comment TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, recommendation_id),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+            MigrationStep::Sql(
+                "CREATE INDEX IF NOT EXISTS idx_recommendation_feedback ON recommendation_feedback (recommendation_id)"
+            ),
+        ],
+    },
+    Migration {
+        version: 18,
+        description: "experiment exposures and outcomes",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS experiment_exposures (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                experiment TEXT NOT NULL,
+                variant TEXT NOT NULL,
+                user_id TEXT NOT NULL,
+                served_by TEXT,
+                succeeded INTEGER NOT NULL,
+                score REAL,
+                runtime_ms REAL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#),
+            MigrationStep::Sql(
+                "CREATE INDEX IF NOT EXISTS idx_experiment_exposures ON experiment_exposures (experiment, variant)"
+            ),
+        ],
+    },
+    Migration {
+        version: 19,
+        description: "estimated one-rep maxes",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS one_rep_maxes (
+                user_id TEXT NOT NULL,
+                exercise_id TEXT NOT NULL,
+                one_rm_kg REAL NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, exercise_id),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 20,
+        description: "imported activity metrics",
+        steps: &[
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS activity_metrics (
+                user_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                source TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                value REAL NOT NULL,
+                imported_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, date, source, metric),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 21,
+        description: "imported sleep sessions",
+        steps: &[
+            // A source's record is the same night whether matched by its id
+            // or by when it started
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS sleep_sessions (
+                user_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                external_id TEXT NOT NULL,
+                start_time DATETIME NOT NULL,
+                end_time DATETIME NOT NULL,
+                imported_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                PRIMARY KEY (user_id, source, external_id),
+                UNIQUE (user_id, source, start_time),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 22,
+        description: "food log",
+        steps: &[
+            // An unknown time is stored as '' so it still takes part in the
+            // uniqueness check (NULLs never conflict)
+            MigrationStep::Sql(r#"
+            CREATE TABLE IF NOT EXISTS food_log (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                date TEXT NOT NULL,
+                time TEXT NOT NULL DEFAULT '',
+                meal TEXT,
+                food_name TEXT NOT NULL,
+                food_id TEXT,
+                quantity_g REAL,
+                calories REAL NOT NULL,
+                protein_g REAL NOT NULL,
+                carbs_g REAL NOT NULL,
+                fat_g REAL NOT NULL,
+                source TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE (user_id, date, food_name, time),
+                FOREIGN KEY (user_id) REFERENCES users (id)
+            )
+            "#),
+        ],
+    },
+    Migration {
+        version: 23,
+        description: "webhook delivery owners",
+        steps: &[
+            // Payloads hold the user's data, so a hard delete must find
+            // their deliveries; older rows take the owner from the payload
+            MigrationStep::AddColumn {
+                table: "webhook_deliveries",
+                column: "user_id",
+                definition: "TEXT",
+            },
+            MigrationStep::Sql(
+                "UPDATE webhook_deliveries SET user_id = json_extract(payload, '$.user_id') WHERE user_id IS NULL"
+            ),
+            MigrationStep::Sql(
+                "CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_user ON webhook_deliveries (user_id)"
+            ),
+        ],
+    },
+];
+
+/// Result of a compare-and-swap user update
+#[derive(Debug)]
+pub enum UserUpdate {
+    /// Saved; holds the user as stored, with their new version
+    Updated(User),
+    /// The user has changed since the expected version; holds the current one
+    VersionMismatch(u64),
+    NotFound,
+}
+
+/// Result of adding a batch of exercises to the library
+#[derive(Debug)]
+pub enum ExerciseSeeding {
+    /// All were added; holds how many
+    Inserted(usize),
+    /// Nothing was added because these ids already exist
+    ExistingIds(Vec<String>),
+}
+
+/// A user's stored password hash and lockout state
+#[derive(Debug, Clone)]
+pub struct StoredCredentials {
+    pub password_hash: String,
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Turn free text into an FTS5 query that matches any of its words as a
+/// prefix, so "glute" finds "glutes". Returns None when nothing searchable
+/// is left after stripping FTS syntax.
+fn fts_match_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("\"{}\"*", term.to_lowercase()))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" OR "))
+    }
+}
+
+fn exercise_from_row(row: &SqliteRow) -> Result<Exercise> {
+    Ok(Exercise {
+        id: row.get("id"),
+        name: row.get("name"),
+        description: row.get("description"),
+        exercise_type: serde_json::from_str(&row.get::<String, _>("exercise_type"))?,
+        equipment_needed: serde_json::from_str(&row.get::<String, _>("equipment_needed"))?,
+        difficulty_level: row.get::<i64, _>("difficulty_level") as u32,
+        primary_muscles: serde_json::from_str(&row.get::<String, _>("primary_muscles"))?,
+        secondary_muscles: serde_json::from_str(&row.get::<String, _>("secondary_muscles"))?,
+        instructions: serde_json::from_str(&row.get::<String, _>("instructions"))?,
+        safety_tips: serde_json::from_str(&row.get::<String, _>("safety_tips"))?,
+        demo_url: row.get("demo_url"),
+        cues: serde_json::from_str(&row.get::<String, _>("cues"))?,
+    })
+}
+
+fn user_from_row(row: &SqliteRow) -> Result<User> {
+    Ok(User {
+        id: row.get("id"),
+        name: row.get("name"),
+        age: row.get::<i64, _>("age") as u32,
+        height: row.get("height"),
+        weight: row.get("weight"),
+        fitness_level: serde_json::from_str(&row.get::<String, _>("fitness_level"))?,
+        goals: serde_json::from_str(&row.get::<String, _>("goals"))?,
+        preferences: serde_json::from_str(&row.get::<String, _>("preferences"))?,
+        role: serde_json::from_str(&row.get::<String, _>("role"))?,
+        version: row.get::<i64, _>("version") as u64,
+    })
+}
+
+fn webhook_delivery_from_row(row: &SqliteRow) -> Result<WebhookDelivery> {
+    let event: String = row.get("event");
+    let status: String = row.get("status");
+    Ok(WebhookDelivery {
+        id: row.get("id"),
+        subscription_id: row.get("subscription_id"),
+        event: WebhookEvent::parse(&event)
+            .ok_or_else(|| anyhow::anyhow!("Unknown webhook event: {}", event))?,
+        payload: row.get("payload"),
+        status: DeliveryStatus::parse(&status)
+            .ok_or_else(|| anyhow::anyhow!("Unknown delivery status: {}", status))?,
+        attempts: row.get::<i64, _>("attempts") as u32,
+        next_attempt_at: row.get("next_attempt_at"),
+        last_error: row.get("last_error"),
+        created_at: row.get("created_at"),
+    })
+}
+
+/// Searchable tags for a recipe: cuisine, meal type, dietary flags and ingredients
+fn recipe_search_tags(recipe: &Recipe) -> String {
+    let mut tags: Vec<String> = Vec::new();
+    if let Some(cuisine) = &recipe.cuisine_type {
+        tags.push(cuisine.clone());
+    }
+    tags.push(format!("{:?}", recipe.meal_type));
+    tags.extend(recipe.dietary_flags.iter().map(|flag| format!("{:?}", flag)));
+    tags.extend(recipe.ingredients.iter().map(|ingredient| ingredient.food_id.replace('_', " ")));
+    tags.join(" ")
+}
+
+/// A database operation ran past `database.query_timeout_ms`. Returned
+/// through `anyhow`, so callers that care can `downcast_ref` it.
+#[derive(Debug, thiserror::Error)]
+#[error("database operation {operation} timed out after {timeout_ms}ms")]
+pub struct QueryTimeout {
+    pub operation: &'static str,
+    pub timeout_ms: u64,
+}
+
+/// A workout was saved under an id that belongs to another user's session.
+/// Returned through `anyhow` like `QueryTimeout`.
+#[derive(Debug, thiserror::Error)]
+#[error("workout id {workout_id} belongs to another user")]
+pub struct WorkoutIdTaken {
+    pub workout_id: String,
+}
+
+/// In-memory copies of frequently read records, keyed by id. Entries expire
+/// after `ttl`; writes through `DatabaseManager` invalidate them, and when
+/// full the oldest entry makes room.
+struct RecordCache<V> {
+    state: Mutex<CacheState<V>>,
+    capacity: usize,
+    ttl: Duration,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+struct CacheState<V> {
+    entries: HashMap<String, (Instant, V)>,
+    /// Bumped by every invalidation, so a read that started before a write
+    /// can't put the old value back afterwards
+    generation: u64,
+}
+
+impl<V: Clone> RecordCache<V> {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(CacheState { entries: HashMap::new(), generation: 0 }),
+            capacity,
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    fn get(&self, id: &str) -> Option<V> {
+        let mut state = self.state.lock().unwrap();
+        let cached = match state.entries.get(id) {
+            Some((cached_at, value)) if cached_at.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                state.entries.remove(id);
+                None
+            }
+            None => None,
+        };
+        let counter = if cached.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Take before reading from the database and pass to `insert`
+    fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    fn insert(&self, id: &str, value: V, read_at_generation: u64) {
+        let mut state = self.state.lock().unwrap();
+        if self.capacity == 0 || state.generation != read_at_generation {
+            return;
+        }
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(id) {
+            let oldest = state.entries.iter()
+                .min_by_key(|(_, (cached_at, _))| *cached_at)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.entries.insert(id.to_string(), (Instant::now(), value));
+    }
+
+    fn invalidate(&self, id: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.entries.remove(id);
+        state.generation += 1;
+    }
+}
+
+// Database connection and management
+pub struct DatabaseManager {
+    pool: SqlitePool,
+    idempotency_key_ttl: Duration,
+    query_timeout: Duration,
+    /// Operations abandoned by `timed` since startup
+    query_timeouts: AtomicU64,
+    users: RecordCache<User>,
+    exercises: RecordCache<Exercise>,
+}
+
+impl DatabaseManager {
+    // Initialize a connection pool sized from config and create tables
+    pub async fn new(config: &DatabaseConfig) -> Result<Self> {
+        info!("🗄️  Connecting to database: {} (pool size {})", config.url, config.max_connections);
+
+        // WAL lets readers proceed while a write is in progress
+        let options = SqliteConnectOptions::from_str(&config.url)?
+            .create_if_missing(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .busy_timeout(Duration::from_millis(config.busy_timeout_ms));
+
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections.max(1))
+            .acquire_timeout(Duration::from_secs(config.connection_timeout_seconds))
+            .connect_with(options)
+            .await?;
+        
+        let manager = Self {
+            pool,
+            idempotency_key_ttl: Duration::from_secs(config.idempotency_key_ttl_hours * 3600),
+            query_timeout: Duration::from_millis(config.query_timeout_ms),
+            query_timeouts: AtomicU64::new(0),
+            users: RecordCache::new(config.cache_capacity, Duration::from_secs(config.cache_ttl_seconds)),
+            exercises: RecordCache::new(config.cache_capacity, Duration::from_secs(config.cache_ttl_seconds)),
+        };
+        manager.run_migrations().await?;
+        manager.seed_sample_exercises().await?;
+        manager.seed_recipes().await?;
+        
+        info!("✅ Database initialized successfully");
+        Ok(manager)
+    }
+
+    // Run a database operation under the query timeout. Dropping the
+    // operation rolls back any transaction it had open, and SQLite's busy
+    // timeout has already been retrying it if it was waiting on a lock.
+    async fn timed<T>(&self, operation: &'static str, query: impl Future<Output = Result<T>>) -> Result<T> {
+        match tokio::time::timeout(self.query_timeout, query).await {
+            Ok(result) => result,
+            Err(_) => {
+                self.query_timeouts.fetch_add(1, Ordering::Relaxed);
+                warn!("Database operation {} timed out after {:?}", operation, self.query_timeout);
+                Err(QueryTimeout { operation, timeout_ms: self.query_timeout.as_millis() as u64 }.into())
+            }
+        }
+    }
+
+    /// Run `work` in a transaction that commits if it succeeds and rolls
+    /// back if it fails, so related rows are written all together or not at
+    /// all. `work` gets the transaction to run its statements on; tying the
+    /// transaction to `'a` lets it borrow anything that lives that long.
+    pub async fn with_transaction<'a, T, F>(&self, work: F) -> Result<T>
+    where
+        F: for<'t> FnOnce(&'t mut Transaction<'a, Sqlite>) -> BoxFuture<'t, Result<T>>,
+    {
+        let mut tx: Transaction<'a, Sqlite> = self.pool.begin().await?;
+        match work(&mut tx).await {
+            Ok(value) => {
+                tx.commit().await?;
+                Ok(value)
+            }
+            Err(e) => {
+                tx.rollback().await?;
+                Err(e)
+            }
+        }
+    }
+
+    // Bring the schema up to date by applying pending migrations in order
+    async fn run_migrations(&self) -> Result<()> {
+        sqlx::query(r#"
+            CREATE TABLE IF NOT EXISTS schema_migrations (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
         "#).execute(&self.pool).await?;
 
-        info!("✅ All tables created successfully");
+        let current = self.schema_version().await?;
+        let pending: Vec<&Migration> = MIGRATIONS.iter()
+            .filter(|m| m.version > current)
+            .collect();
+
+        if pending.is_empty() {
+            info!("📋 Database schema is up to date (version {})", current);
+            return Ok(());
+        }
+
+        for migration in pending {
+            info!("📋 Applying migration {}: {}", migration.version, migration.description);
+
+            let mut tx = self.pool.begin().await?;
+            for step in migration.steps {
+                match step {
+                    MigrationStep::Sql(sql) => {
+                        sqlx::query(sql).execute(&mut *tx).await?;
+                    }
+                    MigrationStep::AddColumn { table, column, definition } => {
+                        let columns: Vec<String> = sqlx::query(&format!("PRAGMA table_info({})", table))
+                            .fetch_all(&mut *tx).await?
+                            .iter()
+                            .map(|row| row.get("name"))
+                            .collect();
+
+                        if !columns.iter().any(|c| c == column) {
+                            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+                                .execute(&mut *tx).await?;
+                        }
+                    }
+                }
+            }
+
+            sqlx::query("INSERT INTO schema_migrations (version, description) VALUES (?, ?)")
+                .bind(migration.version)
+                .bind(migration.description)
+                .execute(&mut *tx).await?;
+            tx.commit().await?;
+        }
+
+        info!("✅ Database schema migrated to version {}", self.schema_version().await?);
         Ok(())
     }
 
+    // Highest applied migration version, 0 for a fresh database
+    pub async fn schema_version(&self) -> Result<i64> {
+        self.timed("schema_version", async {
+            let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations")
+                .fetch_one(&self.pool).await?;
+            Ok(version.unwrap_or(0))
+        }).await
+    }
+
     // Seed initial exercise data
-    async fn seed_exercises(&self) -> Result<()> {
+    async fn seed_sample_exercises(&self) -> Result<()> {
         // Check if exercises already exist
         let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM exercises")
             .fetch_one(&self.pool).await?;
 
-        if count > 0 {
+        let exercises = create_sample_exercises();
+        let exercise_count = exercises.len();
+
+        // Re-seed when the catalog has grown so older databases pick up new exercises
+        if count as usize >= exercise_count {
             info!("📚 Exercises already seeded, skipping...");
             return Ok(());
         }
 
         info!("🌱 Seeding initial exercises...");
 
-        let exercises = vec![
-            Exercise {
-                id: "pushup".to_string(),
-                name: "Push-up".to_string(),
-                description: "Classic bodyweight chest exercise".to_string(),
-                exercise_type: ExerciseType::Strength,
-                equipment_needed: vec![Equipment::None],
-                difficulty_level: 3,
-                primary_muscles: vec![MuscleGroup::Chest],
-                secondary_muscles: vec![MuscleGroup::Arms, MuscleGroup::Core],
-                instructions: vec![
-                    "Start in plank position".to_string(),
-                    "Lower body until chest nearly touches floor".to_string(),
-                    "Push back up to starting position".to_string(),
-                ],
-                safety_tips: vec![
-                    "Keep body straight".to_string(),
-                    "Don't let hips sag".to_string(),
-                ],
-            },
-            Exercise {
-                id: "squat".to_string(),
-                name: "Squat".to_string(),
-                description: "Fundamental lower body exercise".to_string(),
-                exercise_type: ExerciseType::Strength,
-                equipment_needed: vec![Equipment::None],
-                difficulty_level: 2,
-                primary_muscles: vec![MuscleGroup::Legs, MuscleGroup::Glutes],
-                secondary_muscles: vec![MuscleGroup::Core],
-                instructions: vec![
-                    "Stand with feet shoulder-width apart".to_string(),
-                    "Lower hips back and down".to_string(),
-                    "Return to standing position".to_string(),
-                ],
-                safety_tips: vec![
-                    "Keep knees behind toes".to_string(),
-                    "Maintain neutral spine".to_string(),
-                ],
-            },
-            Exercise {
-                id: "plank".to_string(),
-                name: "Plank".to_string(),
-                description: "Core strengthening exercise".to_string(),
-                exercise_type: ExerciseType::Strength,
-                equipment_needed: vec![Equipment::None],
-                difficulty_level: 3,
-                primary_muscles: vec![MuscleGroup::Core],
-                secondary_muscles: vec![MuscleGroup::Shoulders, MuscleGroup::Back],
-                instructions: vec![
-                    "Start in push-up position".to_string(),
-                    "Keep body straight from head to heels".to_string(),
-                    "Hold position".to_string(),
-                ],
-                safety_tips: vec![
-                    "Don't let hips sag or rise".to_string(),
-                    "Breathe normally".to_string(),
-                ],
-            },
-            // Add more exercises
-            Exercise {
-                id: "burpee".to_string(),
-                name: "Burpee".to_string(),
-                description: "Full body explosive exercise".to_string(),
-                exercise_type: ExerciseType::Cardio,
-                equipment_needed: vec![Equipment::None],
-                difficulty_level: 8,
-                primary_muscles: vec![MuscleGroup::Legs, MuscleGroup::Core],
-                secondary_muscles: vec![MuscleGroup::Chest, MuscleGroup::Arms],
-                instructions: vec![
-                    "Start standing".to_string(),
-                    "Drop to squat, hands on floor".to_string(),
-                    "Jump feet back to plank".to_string(),
-                    "Do a push-up".to_string(),
-                    "Jump feet forward".to_string(),
-                    "Explosive jump up".to_string(),
-                ],
-                safety_tips: vec![
-                    "Land softly".to_string(),
-                    "Keep core tight throughout".to_string(),
-                    "Start slowly and build intensity".to_string(),
-                ],
-            },
-            Exercise {
-                id: "deadlift".to_string(),
-                name: "Deadlift".to_string(),
-                description: "Compound pulling exercise".to_string(),
-                exercise_type: ExerciseType::Strength,
-                equipment_needed: vec![Equipment::Barbells],
-                difficulty_level: 7,
-                primary_muscles: vec![MuscleGroup::Back, MuscleGroup::Legs],
-                secondary_muscles: vec![MuscleGroup::Core, MuscleGroup::Arms],
-                instructions: vec![
-                    "Stand with feet hip-width apart".to_string(),
-                    "Grip barbell with hands outside legs".to_string(),
-                    "Keep back straight, chest up".to_string(),
-                    "Drive through heels to lift".to_string(),
-                    "Lower with control".to_string(),
-                ],
-                safety_tips: vec![
-                    "Never round your back".to_string(),
-                    "Start with light weight".to_string(),
-                    "Keep bar close to body".to_string(),
-                ],
-            },
-        ];
-
         for exercise in exercises {
             self.save_exercise(&exercise).await?;
         }
 
-        info!("✅ {} exercises seeded successfully", 5);
-        Ok(())
+        info!("✅ {} exercises seeded successfully", exercise_count);
+        Ok(())
+    }
+
+    // Seed the sample recipe catalog so it can be searched
+    async fn seed_recipes(&self) -> Result<()> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM recipes")
+            .fetch_one(&self.pool).await?;
+
+        let recipes = create_sample_recipes();
+        let recipe_count = recipes.len();
+
+        if count as usize >= recipe_count {
+            info!("📚 Recipes already seeded, skipping...");
+            return Ok(());
+        }
+
+        info!("🌱 Seeding sample recipes...");
+
+        for recipe in &recipes {
+            self.save_recipe(recipe).await?;
+        }
+
+        info!("✅ {} recipes seeded successfully", recipe_count);
+        Ok(())
+    }
+
+    // === USER OPERATIONS ===
+
+    pub async fn save_user(&self, user: &User) -> Result<()> {
+        self.timed("save_user", async {
+            self.with_transaction(|tx| Box::pin(Self::write_user(tx, user))).await?;
+            self.users.invalidate(&user.id);
+
+            info!("💾 User {} saved to database", user.id);
+            Ok(())
+        }).await
+    }
+
+    /// Insert a new user, with their password hash when given. Unlike
+    /// `save_user` this never overwrites: it returns false and writes nothing
+    /// when the id is taken, including by a deleted user.
+    pub async fn insert_user(&self, user: &User, password_hash: Option<&str>) -> Result<bool> {
+        self.timed("insert_user", async {
+            let inserted = self.with_transaction(|tx| Box::pin(async move {
+                let inserted = sqlx::query(r#"
+                    INSERT OR IGNORE INTO users
+                    (id, name, age, height, weight, fitness_level, goals, preferences, role)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#)
+                .bind(&user.id)
+                .bind(&user.name)
+                .bind(user.age as i64)
+                .bind(user.height)
+                .bind(user.weight)
+                .bind(serde_json::to_string(&user.fitness_level)?)
+                .bind(serde_json::to_string(&user.goals)?)
+                .bind(serde_json::to_string(&user.preferences)?)
+                .bind(serde_json::to_string(&user.role)?)
+                .execute(&mut **tx).await?
+                .rows_affected() > 0;
+
+                if !inserted {
+                    return Ok(false);
+                }
+
+                if let Some(password_hash) = password_hash {
+                    sqlx::query("INSERT INTO user_credentials (user_id, password_hash) VALUES (?, ?)")
+                        .bind(&user.id)
+                        .bind(password_hash)
+                        .execute(&mut **tx).await?;
+                }
+
+                Ok(true)
+            })).await?;
+            // Only a deleted user could have been cached under this id
+            self.users.invalidate(&user.id);
+
+            if !inserted {
+                return Ok(false);
+            }
+
+            info!("💾 User {} inserted into database", user.id);
+            Ok(true)
+        }).await
+    }
+
+    async fn write_user(tx: &mut Transaction<'_, Sqlite>, user: &User) -> Result<()> {
+        // Only the profile changes on an existing user: replacing the row
+        // would reset created_at and bring back a soft-deleted user
+        sqlx::query(r#"
+            INSERT INTO users
+            (id, name, age, height, weight, fitness_level, goals, preferences, role, version, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, CURRENT_TIMESTAMP)
+            ON CONFLICT (id) DO UPDATE SET
+                name = excluded.name,
+                age = excluded.age,
+                height = excluded.height,
+                weight = excluded.weight,
+                fitness_level = excluded.fitness_level,
+                goals = excluded.goals,
+                preferences = excluded.preferences,
+                role = excluded.role,
+                version = users.version + 1,
+                updated_at = CURRENT_TIMESTAMP
+        "#)
+        .bind(&user.id)
+        .bind(&user.name)
+        .bind(user.age as i64)
+        .bind(user.height)
+        .bind(user.weight)
+        .bind(serde_json::to_string(&user.fitness_level)?)
+        .bind(serde_json::to_string(&user.goals)?)
+        .bind(serde_json::to_string(&user.preferences)?)
+        .bind(serde_json::to_string(&user.role)?)
+        .execute(&mut **tx).await?;
+
+        Ok(())
+    }
+
+    /// Update a user only if they are still at `expected_version`, bumping
+    /// the version, so concurrent edits can't silently overwrite each other
+    pub async fn update_user(&self, user: &User, expected_version: u64) -> Result<UserUpdate> {
+        self.timed("update_user", async {
+            let mut tx = self.pool.begin().await?;
+
+            let row = sqlx::query(r#"
+                UPDATE users SET
+                    name = ?, age = ?, height = ?, weight = ?, fitness_level = ?,
+                    goals = ?, preferences = ?, role = ?,
+                    version = version + 1, updated_at = CURRENT_TIMESTAMP
+                WHERE id = ? AND version = ? AND deleted_at IS NULL
+                RETURNING id, name, age, height, weight, fitness_level, goals, preferences, role, version
+            "#)
+            .bind(&user.name)
+            .bind(user.age as i64)
+            .bind(user.height)
+            .bind(user.weight)
+            .bind(serde_json::to_string(&user.fitness_level)?)
+            .bind(serde_json::to_string(&user.goals)?)
+            .bind(serde_json::to_string(&user.preferences)?)
+            .bind(serde_json::to_string(&user.role)?)
+            .bind(&user.id)
+            .bind(expected_version as i64)
+            .fetch_optional(&mut *tx).await?;
+
+            let outcome = match row {
+                Some(row) => UserUpdate::Updated(user_from_row(&row)?),
+                None => {
+                    let current: Option<i64> = sqlx::query_scalar(
+                        "SELECT version FROM users WHERE id = ? AND deleted_at IS NULL"
+                    )
+                    .bind(&user.id)
+                    .fetch_optional(&mut *tx).await?;
+
+                    match current {
+                        Some(version) => UserUpdate::VersionMismatch(version as u64),
+                        None => UserUpdate::NotFound,
+                    }
+                }
+            };
+            tx.commit().await?;
+            self.users.invalidate(&user.id);
+
+            Ok(outcome)
+        }).await
+    }
+
+    /// Credentials of an active (not deleted) user
+    pub async fn get_credentials(&self, user_id: &str) -> Result<Option<StoredCredentials>> {
+        self.timed("get_credentials", async {
+            let row = sqlx::query(r#"
+                SELECT c.password_hash, c.locked_until
+                FROM user_credentials c JOIN users u ON u.id = c.user_id
+                WHERE c.user_id = ? AND u.deleted_at IS NULL
+            "#)
+            .bind(user_id)
+            .fetch_optional(&self.pool).await?;
+
+            Ok(row.map(|row| StoredCredentials {
+                password_hash: row.get("password_hash"),
+                locked_until: row.get("locked_until"),
+            }))
+        }).await
+    }
+
+    /// Count a failed login, locking the account once `max_attempts` is
+    /// reached. The counter restarts after a lockout. Returns whether the
+    /// account is now locked.
+    pub async fn record_failed_login(&self, user_id: &str, max_attempts: u32, lockout: Duration) -> Result<bool> {
+        self.timed("record_failed_login", async {
+            let locked_until = chrono::Utc::now() + chrono::Duration::from_std(lockout)?;
+            // An explicit transaction so the update is committed before we
+            // return, rather than whenever the RETURNING statement is reset
+            let mut tx = self.pool.begin().await?;
+            let locked: Option<bool> = sqlx::query_scalar(r#"
+                UPDATE user_credentials SET
+                    locked_until = CASE WHEN failed_attempts + 1 >= ? THEN ? ELSE locked_until END,
+                    failed_attempts = CASE WHEN failed_attempts + 1 >= ? THEN 0 ELSE failed_attempts + 1 END,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE user_id = ?
+                RETURNING failed_attempts = 0
+            "#)
+            .bind(max_attempts as i64)
+            .bind(locked_until)
+            .bind(max_attempts as i64)
+            .bind(user_id)
+            .fetch_optional(&mut *tx).await?;
+            tx.commit().await?;
+
+            let locked = locked.unwrap_or(false);
+            if locked {
+                warn!("🔒 User {} locked out after {} failed logins", user_id, max_attempts);
+            }
+            Ok(locked)
+        }).await
+    }
+
+    pub async fn clear_failed_logins(&self, user_id: &str) -> Result<()> {
+        self.timed("clear_failed_logins", async {
+            sqlx::query(r#"
+                UPDATE user_credentials SET failed_attempts = 0, locked_until = NULL, updated_at = CURRENT_TIMESTAMP
+                WHERE user_id = ? AND (failed_attempts > 0 OR locked_until IS NOT NULL)
+            "#)
+            .bind(user_id)
+            .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    pub async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
+        if let Some(user) = self.users.get(user_id) {
+            return Ok(Some(user));
+        }
+        let generation = self.users.generation();
+
+        self.timed("get_user", async {
+            let row = sqlx::query(r#"
+                SELECT id, name, age, height, weight, fitness_level, goals, preferences, role, version
+                FROM users WHERE id = ? AND deleted_at IS NULL
+            "#)
+            .bind(user_id)
+            .fetch_optional(&self.pool).await?;
+
+            let user = row.as_ref().map(user_from_row).transpose()?;
+            if let Some(user) = &user {
+                self.users.insert(user_id, user.clone(), generation);
+            }
+            Ok(user)
+        }).await
+    }
+
+    pub async fn get_all_users(&self) -> Result<Vec<User>> {
+        self.timed("get_all_users", async {
+            let rows = sqlx::query(r#"
+                SELECT id, name, age, height, weight, fitness_level, goals, preferences, role, version
+                FROM users WHERE deleted_at IS NULL ORDER BY created_at DESC
+            "#)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter().map(user_from_row).collect()
+        }).await
+    }
+
+    // One page of active users, oldest first so pages stay stable as users sign up
+    pub async fn get_users_page(&self, page: PageRequest) -> Result<Page<User>> {
+        self.timed("get_users_page", async {
+            let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL")
+                .fetch_one(&self.pool).await?;
+
+            let limit = page.effective_limit();
+            let rows = sqlx::query(r#"
+                SELECT id, name, age, height, weight, fitness_level, goals, preferences, role, version
+                FROM users WHERE deleted_at IS NULL
+                ORDER BY created_at ASC, id ASC
+                LIMIT ? OFFSET ?
+            "#)
+            .bind(limit as i64)
+            .bind(page.offset as i64)
+            .fetch_all(&self.pool).await?;
+
+            Ok(Page {
+                items: rows.iter().map(user_from_row).collect::<Result<_>>()?,
+                total: total as u64,
+                limit,
+                offset: page.offset,
+            })
+        }).await
+    }
+
+    // Soft delete hides the user from every lookup but keeps their data;
+    // hard delete erases the user and everything recorded for them.
+    // Returns false when there was no matching user to delete.
+    pub async fn delete_user(&self, user_id: &str, hard: bool) -> Result<bool> {
+        self.timed("delete_user", async {
+            if !hard {
+                let result = sqlx::query(
+                    "UPDATE users SET deleted_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP \
+                     WHERE id = ? AND deleted_at IS NULL"
+                )
+                .bind(user_id)
+                .execute(&self.pool).await?;
+                self.users.invalidate(user_id);
+
+                if result.rows_affected() > 0 {
+                    info!("🗑️  User {} soft-deleted", user_id);
+                }
+                return Ok(result.rows_affected() > 0);
+            }
+
+            let result = self.with_transaction(|tx| Box::pin(async move {
+                // Children first so foreign keys are never left dangling
+                sqlx::query(r#"
+                    DELETE FROM exercise_sets WHERE workout_session_id IN
+                        (SELECT id FROM workout_sessions WHERE user_id = ?)
+                "#)
+                .bind(user_id)
+                .execute(&mut **tx).await?;
+
+                for table in [
+                    "idempotency_keys", "workout_sessions", "body_measurements", "user_progress", "user_credentials", "favorites",
+                    "goals", "achievements", "activity_counts", "recommendation_feedback",
+                    "experiment_exposures", "one_rep_maxes", "activity_metrics", "sleep_sessions", "food_log",
+                    "webhook_deliveries",
+                ] {
+                    sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", table))
+                        .bind(user_id)
+                        .execute(&mut **tx).await?;
+                }
+
+                sqlx::query("DELETE FROM coach_clients WHERE coach_id = ? OR client_id = ?")
+                    .bind(user_id)
+                    .bind(user_id)
+                    .execute(&mut **tx).await?;
+
+                let result = sqlx::query("DELETE FROM users WHERE id = ?")
+                    .bind(user_id)
+                    .execute(&mut **tx).await?;
+
+                Ok(result)
+            })).await?;
+            self.users.invalidate(user_id);
+
+            if result.rows_affected() > 0 {
+                info!("🗑️  User {} and all their data permanently deleted", user_id);
+            }
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    // === COACH-CLIENT LINKS ===
+
+    /// Let `coach_id` see and manage `client_id`'s data. Linking twice is a no-op.
+    pub async fn link_coach(&self, coach_id: &str, client_id: &str) -> Result<()> {
+        self.timed("link_coach", async {
+            sqlx::query("INSERT OR IGNORE INTO coach_clients (coach_id, client_id) VALUES (?, ?)")
+                .bind(coach_id)
+                .bind(client_id)
+                .execute(&self.pool).await?;
+
+            info!("🤝 Coach {} linked to client {}", coach_id, client_id);
+            Ok(())
+        }).await
+    }
+
+    /// Returns false when the two weren't linked
+    pub async fn unlink_coach(&self, coach_id: &str, client_id: &str) -> Result<bool> {
+        self.timed("unlink_coach", async {
+            let result = sqlx::query("DELETE FROM coach_clients WHERE coach_id = ? AND client_id = ?")
+                .bind(coach_id)
+                .bind(client_id)
+                .execute(&self.pool).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    pub async fn is_coach_of(&self, coach_id: &str, client_id: &str) -> Result<bool> {
+        self.timed("is_coach_of", async {
+            let linked: Option<i64> = sqlx::query_scalar(
+                "SELECT 1 FROM coach_clients WHERE coach_id = ? AND client_id = ?"
+            )
+            .bind(coach_id)
+            .bind(client_id)
+            .fetch_optional(&self.pool).await?;
+
+            Ok(linked.is_some())
+        }).await
+    }
+
+    /// Active clients linked to a coach, by id
+    pub async fn get_clients(&self, coach_id: &str) -> Result<Vec<User>> {
+        self.timed("get_clients", async {
+            let rows = sqlx::query(r#"
+                SELECT u.id, u.name, u.age, u.height, u.weight, u.fitness_level, u.goals, u.preferences, u.role, u.version
+                FROM coach_clients l JOIN users u ON u.id = l.client_id
+                WHERE l.coach_id = ? AND u.deleted_at IS NULL
+                ORDER BY u.id
+            "#)
+            .bind(coach_id)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter().map(user_from_row).collect()
+        }).await
+    }
+
+    // === FAVORITES ===
+
+    /// Returns false when the item was already a favorite
+    pub async fn add_favorite(&self, user_id: &str, kind: FavoriteKind, item_id: &str) -> Result<bool> {
+        self.timed("add_favorite", async {
+            let result = sqlx::query("INSERT OR IGNORE INTO favorites (user_id, kind, item_id) VALUES (?, ?, ?)")
+                .bind(user_id)
+                .bind(kind.as_str())
+                .bind(item_id)
+                .execute(&self.pool).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    /// Returns false when the item wasn't a favorite
+    pub async fn remove_favorite(&self, user_id: &str, kind: FavoriteKind, item_id: &str) -> Result<bool> {
+        self.timed("remove_favorite", async {
+            let result = sqlx::query("DELETE FROM favorites WHERE user_id = ? AND kind = ? AND item_id = ?")
+                .bind(user_id)
+                .bind(kind.as_str())
+                .bind(item_id)
+                .execute(&self.pool).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    /// A user's favorites, optionally of one kind, most recent first
+    pub async fn list_favorites(&self, user_id: &str, kind: Option<FavoriteKind>) -> Result<Vec<Favorite>> {
+        self.timed("list_favorites", async {
+            let rows = sqlx::query(r#"
+                SELECT kind, item_id, created_at FROM favorites
+                WHERE user_id = ? AND (? IS NULL OR kind = ?)
+                ORDER BY created_at DESC, rowid DESC
+            "#)
+            .bind(user_id)
+            .bind(kind.map(|k| k.as_str()))
+            .bind(kind.map(|k| k.as_str()))
+            .fetch_all(&self.pool).await?;
+
+            rows.iter()
+                .map(|row| {
+                    let stored: String = row.get("kind");
+                    Ok(Favorite {
+                        kind: FavoriteKind::parse(&stored)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown favorite kind: {}", stored))?,
+                        item_id: row.get("item_id"),
+                        created_at: row.get("created_at"),
+                    })
+                })
+                .collect()
+        }).await
+    }
+
+    /// Whether the recipe or exercise a favorite would point at exists
+    pub async fn favorite_target_exists(&self, kind: FavoriteKind, item_id: &str) -> Result<bool> {
+        self.timed("favorite_target_exists", async {
+            let table = match kind {
+                FavoriteKind::Recipe => "recipes",
+                FavoriteKind::Exercise => "exercises",
+            };
+            let found: Option<i64> = sqlx::query_scalar(&format!("SELECT 1 FROM {} WHERE id = ?", table))
+                .bind(item_id)
+                .fetch_optional(&self.pool).await?;
+
+            Ok(found.is_some())
+        }).await
+    }
+
+    // === RECOMMENDATION FEEDBACK ===
+
+    /// Record a user's vote, replacing any earlier one on the same recommendation
+    pub async fn record_feedback(
+        &self,
+        user_id: &str,
+        recommendation_id: &str,
+        rating: FeedbackRating,
+        comment: Option<&str>,
+    ) -> Result<()> {
+        self.timed("record_feedback", async {
+            sqlx::query(r#"
+                INSERT INTO recommendation_feedback (user_id, recommendation_id, rating, comment)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT (user_id, recommendation_id) DO UPDATE SET
+                    rating = excluded.rating,
+                    comment = excluded.comment,
+                    updated_at = CURRENT_TIMESTAMP
+            "#)
+            .bind(user_id)
+            .bind(recommendation_id)
+            .bind(rating.as_str())
+            .bind(comment)
+            .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    pub async fn feedback_summary(&self, recommendation_id: &str) -> Result<FeedbackSummary> {
+        self.timed("feedback_summary", async {
+            let row = sqlx::query(r#"
+                SELECT COALESCE(SUM(rating = 'up'), 0) AS thumbs_up, COALESCE(SUM(rating = 'down'), 0) AS thumbs_down
+                FROM recommendation_feedback WHERE recommendation_id = ?
+            "#)
+            .bind(recommendation_id)
+            .fetch_one(&self.pool).await?;
+
+            Ok(FeedbackSummary {
+                recommendation_id: recommendation_id.to_string(),
+                thumbs_up: row.get::<i64, _>("thumbs_up") as u32,
+                thumbs_down: row.get::<i64, _>("thumbs_down") as u32,
+            })
+        }).await
+    }
+
+    /// Net votes (up minus down) of every recommendation that has any
+    pub async fn feedback_scores(&self) -> Result<std::collections::HashMap<String, i64>> {
+        self.timed("feedback_scores", async {
+            let rows = sqlx::query(r#"
+                SELECT recommendation_id, SUM(CASE rating WHEN 'up' THEN 1 ELSE -1 END) AS net
+                FROM recommendation_feedback GROUP BY recommendation_id
+            "#)
+            .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter().map(|row| (row.get("recommendation_id"), row.get("net"))).collect())
+        }).await
+    }
+
+    // === EXPERIMENTS ===
+
+    pub async fn record_exposure(&self, exposure: &ExperimentExposure) -> Result<()> {
+        self.timed("record_exposure", async {
+            sqlx::query(r#"
+                INSERT INTO experiment_exposures (experiment, variant, user_id, served_by, succeeded, score, runtime_ms)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#)
+            .bind(&exposure.experiment)
+            .bind(&exposure.variant)
+            .bind(&exposure.user_id)
+            .bind(&exposure.served_by)
+            .bind(exposure.succeeded)
+            .bind(exposure.score)
+            .bind(exposure.runtime_ms)
+            .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    /// Outcome metrics per variant of an experiment, in variant order
+    pub async fn experiment_results(&self, experiment: &str) -> Result<Vec<VariantResults>> {
+        self.timed("experiment_results", async {
+            let rows = sqlx::query(r#"
+                SELECT variant,
+                       COUNT(*) AS requests,
+                       COUNT(DISTINCT user_id) AS users,
+                       SUM(succeeded) AS successes,
+                       AVG(CASE WHEN succeeded THEN score END) AS average_score,
+                       AVG(CASE WHEN succeeded THEN runtime_ms END) AS average_runtime_ms,
+                       SUM(served_by IS NOT NULL AND served_by != variant) AS fallbacks
+                FROM experiment_exposures
+                WHERE experiment = ?
+                GROUP BY variant
+                ORDER BY variant
+            "#)
+            .bind(experiment)
+            .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter().map(|row| VariantResults {
+                variant: row.get("variant"),
+                requests: row.get::<i64, _>("requests") as u32,
+                users: row.get::<i64, _>("users") as u32,
+                successes: row.get::<i64, _>("successes") as u32,
+                average_score: row.get("average_score"),
+                average_runtime_ms: row.get("average_runtime_ms"),
+                fallbacks: row.get::<i64, _>("fallbacks") as u32,
+            }).collect())
+        }).await
+    }
+
+    // === GOALS ===
+
+    pub async fn insert_goal(&self, goal: &UserGoal) -> Result<()> {
+        self.timed("insert_goal", async {
+            sqlx::query(r#"
+                INSERT INTO goals (id, user_id, title, metric, exercise_id, start_value, target_value,
+                                   deadline, created_at, completed_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#)
+            .bind(&goal.id)
+            .bind(&goal.user_id)
+            .bind(&goal.title)
+            .bind(goal.metric.as_str())
+            .bind(&goal.exercise_id)
+            .bind(goal.start_value)
+            .bind(goal.target_value)
+            .bind(&goal.deadline)
+            .bind(&goal.created_at)
+            .bind(&goal.completed_at)
+            .execute(&self.pool).await?;
+
+            info!("🎯 Goal {} set for user {}", goal.id, goal.user_id);
+            Ok(())
+        }).await
+    }
+
+    /// A user's goals, oldest first
+    pub async fn get_goals(&self, user_id: &str) -> Result<Vec<UserGoal>> {
+        self.timed("get_goals", async {
+            let rows = sqlx::query(r#"
+                SELECT id, user_id, title, metric, exercise_id, start_value, target_value,
+                       deadline, created_at, completed_at
+                FROM goals
+                WHERE user_id = ?
+                ORDER BY created_at ASC, rowid ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter()
+                .map(|row| {
+                    let stored: String = row.get("metric");
+                    Ok(UserGoal {
+                        id: row.get("id"),
+                        user_id: row.get("user_id"),
+                        title: row.get("title"),
+                        metric: GoalMetric::parse(&stored)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown goal metric: {}", stored))?,
+                        exercise_id: row.get("exercise_id"),
+                        start_value: row.get("start_value"),
+                        target_value: row.get("target_value"),
+                        deadline: row.get("deadline"),
+                        created_at: row.get("created_at"),
+                        completed_at: row.get("completed_at"),
+                    })
+                })
+                .collect()
+        }).await
+    }
+
+    /// Record when a goal was reached. A goal already completed keeps its
+    /// original timestamp; returns false in that case.
+    pub async fn complete_goal(&self, goal_id: &str, completed_at: &str) -> Result<bool> {
+        self.timed("complete_goal", async {
+            let result = sqlx::query("UPDATE goals SET completed_at = ? WHERE id = ? AND completed_at IS NULL")
+                .bind(completed_at)
+                .bind(goal_id)
+                .execute(&self.pool).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    // === ACHIEVEMENTS ===
+
+    /// Returns false when the user already had the achievement
+    pub async fn award_achievement(&self, user_id: &str, achievement_id: &str) -> Result<bool> {
+        self.timed("award_achievement", async {
+            let result = sqlx::query("INSERT OR IGNORE INTO achievements (user_id, achievement_id) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(achievement_id)
+                .execute(&self.pool).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    /// Ids and award times of a user's achievements, earliest first
+    pub async fn get_achievements(&self, user_id: &str) -> Result<Vec<(String, String)>> {
+        self.timed("get_achievements", async {
+            let rows = sqlx::query(r#"
+                SELECT achievement_id, awarded_at FROM achievements
+                WHERE user_id = ?
+                ORDER BY awarded_at ASC, rowid ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter().map(|row| (row.get("achievement_id"), row.get("awarded_at"))).collect())
+        }).await
+    }
+
+    /// Count one more of an activity that isn't otherwise stored, such as
+    /// generating a meal plan. Returns the new count.
+    pub async fn increment_activity(&self, user_id: &str, activity: &str) -> Result<u32> {
+        self.timed("increment_activity", async {
+            let count: i64 = sqlx::query_scalar(r#"
+                INSERT INTO activity_counts (user_id, activity, count) VALUES (?, ?, 1)
+                ON CONFLICT (user_id, activity) DO UPDATE SET count = count + 1
+                RETURNING count
+            "#)
+            .bind(user_id)
+            .bind(activity)
+            .fetch_one(&self.pool).await?;
+
+            Ok(count as u32)
+        }).await
+    }
+
+    pub async fn activity_count(&self, user_id: &str, activity: &str) -> Result<u32> {
+        self.timed("activity_count", async {
+            let count: Option<i64> = sqlx::query_scalar(
+                "SELECT count FROM activity_counts WHERE user_id = ? AND activity = ?"
+            )
+            .bind(user_id)
+            .bind(activity)
+            .fetch_optional(&self.pool).await?;
+
+            Ok(count.unwrap_or(0) as u32)
+        }).await
+    }
+
+    // === WEBHOOKS ===
+
+    pub async fn insert_webhook(&self, subscription: &WebhookSubscription, secret: &str) -> Result<()> {
+        self.timed("insert_webhook", async {
+            sqlx::query("INSERT INTO webhook_subscriptions (id, url, secret, events, created_at) VALUES (?, ?, ?, ?, ?)")
+                .bind(&subscription.id)
+                .bind(&subscription.url)
+                .bind(secret)
+                .bind(serde_json::to_string(&subscription.events)?)
+                .bind(&subscription.created_at)
+                .execute(&self.pool).await?;
+
+            info!("🪝 Webhook {} registered for {}", subscription.id, subscription.url);
+            Ok(())
+        }).await
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<WebhookSubscription>> {
+        self.timed("list_webhooks", async {
+            let rows = sqlx::query("SELECT id, url, events, created_at FROM webhook_subscriptions ORDER BY created_at, rowid")
+                .fetch_all(&self.pool).await?;
+
+            rows.iter()
+                .map(|row| Ok(WebhookSubscription {
+                    id: row.get("id"),
+                    url: row.get("url"),
+                    events: serde_json::from_str(&row.get::<String, _>("events"))?,
+                    created_at: row.get("created_at"),
+                }))
+                .collect()
+        }).await
+    }
+
+    /// Remove a subscription and its delivery history. Returns false when
+    /// there was no such subscription.
+    pub async fn delete_webhook(&self, subscription_id: &str) -> Result<bool> {
+        self.timed("delete_webhook", async {
+            let result = self.with_transaction(|tx| Box::pin(async move {
+                sqlx::query("DELETE FROM webhook_deliveries WHERE subscription_id = ?")
+                    .bind(subscription_id)
+                    .execute(&mut **tx).await?;
+                let result = sqlx::query("DELETE FROM webhook_subscriptions WHERE id = ?")
+                    .bind(subscription_id)
+                    .execute(&mut **tx).await?;
+                Ok(result)
+            })).await?;
+
+            Ok(result.rows_affected() > 0)
+        }).await
+    }
+
+    /// Queue `payload` for every subscription to `event`, due immediately.
+    /// Returns the number of deliveries queued.
+    pub async fn enqueue_webhook_deliveries(&self, event: WebhookEvent, user_id: &str, payload: &str, now: &str) -> Result<usize> {
+        self.timed("enqueue_webhook_deliveries", async {
+            let subscribers: Vec<String> = self.list_webhooks().await?.into_iter()
+                .filter(|subscription| subscription.events.contains(&event))
+                .map(|subscription| subscription.id)
+                .collect();
+
+            self.with_transaction(|tx| Box::pin(async move {
+                for subscription_id in &subscribers {
+                    sqlx::query(r#"
+                        INSERT INTO webhook_deliveries
+                        (id, subscription_id, event, user_id, payload, status, attempts, next_attempt_at, created_at)
+                        VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?)
+                    "#)
+                    .bind(uuid::Uuid::new_v4().to_string())
+                    .bind(subscription_id)
+                    .bind(event.as_str())
+                    .bind(user_id)
+                    .bind(payload)
+                    .bind(DeliveryStatus::Pending.as_str())
+                    .bind(now)
+                    .bind(now)
+                    .execute(&mut **tx).await?;
+                }
+                Ok(subscribers.len())
+            })).await
+        }).await
+    }
+
+    /// Pending deliveries due by `now`, oldest first, with the URL and
+    /// secret of their subscription
+    pub async fn due_webhook_deliveries(&self, now: &str, limit: i64) -> Result<Vec<(WebhookDelivery, String, String)>> {
+        self.timed("due_webhook_deliveries", async {
+            let rows = sqlx::query(r#"
+                SELECT d.id, d.subscription_id, d.event, d.payload, d.status, d.attempts, d.next_attempt_at,
+                       d.last_error, d.created_at, s.url, s.secret
+                FROM webhook_deliveries d JOIN webhook_subscriptions s ON s.id = d.subscription_id
+                WHERE d.status = ? AND d.next_attempt_at <= ?
+                ORDER BY d.next_attempt_at, d.rowid
+                LIMIT ?
+            "#)
+            .bind(DeliveryStatus::Pending.as_str())
+            .bind(now)
+            .bind(limit)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter()
+                .map(|row| Ok((webhook_delivery_from_row(row)?, row.get("url"), row.get("secret"))))
+                .collect()
+        }).await
+    }
+
+    /// Save the outcome of a delivery attempt
+    pub async fn record_webhook_attempt(&self, delivery: &WebhookDelivery) -> Result<()> {
+        self.timed("record_webhook_attempt", async {
+            sqlx::query(r#"
+                UPDATE webhook_deliveries
+                SET status = ?, attempts = ?, next_attempt_at = ?, last_error = ?
+                WHERE id = ?
+            "#)
+            .bind(delivery.status.as_str())
+            .bind(delivery.attempts as i64)
+            .bind(&delivery.next_attempt_at)
+            .bind(&delivery.last_error)
+            .bind(&delivery.id)
+            .execute(&self.pool).await?;
+
+            Ok(())
+        }).await
+    }
+
+    /// A subscription's deliveries, most recent first
+    pub async fn webhook_deliveries(&self, subscription_id: &str) -> Result<Vec<WebhookDelivery>> {
+        self.timed("webhook_deliveries", async {
+            let rows = sqlx::query(r#"
+                SELECT id, subscription_id, event, payload, status, attempts, next_attempt_at, last_error, created_at
+                FROM webhook_deliveries
+                WHERE subscription_id = ?
+                ORDER BY created_at DESC, rowid DESC
+            "#)
+            .bind(subscription_id)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter().map(webhook_delivery_from_row).collect()
+        }).await
     }
 
-    // === USER OPERATIONS ===
+    // === EXERCISE OPERATIONS ===
 
-    pub async fn save_user(&self, user: &User) -> Result<()> {
-        sqlx::query(r#"
-            INSERT OR REPLACE INTO users 
-            (id, name, age, height, weight, fitness_level, goals, preferences, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
-        "#)
-        .bind(&user.id)
-        .bind(&user.name)
-        .bind(user.age as i64)
-        .bind(user.height)
-        .bind(user.weight)
-        .bind(serde_json::to_string(&user.fitness_level)?)
-        .bind(serde_json::to_string(&user.goals)?)
-        .bind(serde_json::to_string(&user.preferences)?)
-        .execute(&self.pool).await?;
+    pub async fn save_exercise(&self, exercise: &Exercise) -> Result<()> {
+        self.timed("save_exercise", async {
+            exercise.validate_fields()
+                .map_err(|errors| anyhow::anyhow!("Invalid exercise: {}", describe_field_errors(&errors)))?;
 
-        info!("💾 User {} saved to database", user.id);
-        Ok(())
+            self.with_transaction(|tx| Box::pin(Self::write_exercise(tx, exercise))).await?;
+            self.exercises.invalidate(&exercise.id);
+            Ok(())
+        }).await
     }
 
-    pub async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
-        let row = sqlx::query(r#"
-            SELECT id, name, age, height, weight, fitness_level, goals, preferences
-            FROM users WHERE id = ?
-        "#)
-        .bind(user_id)
-        .fetch_optional(&self.pool).await?;
-
-        match row {
-            Some(row) => {
-                let user = User {
-                    id: row.get("id"),
-                    name: row.get("name"),
-                    age: row.get::<i64, _>("age") as u32,
-                    height: row.get("height"),
-                    weight: row.get("weight"),
-                    fitness_level: serde_json::from_str(&row.get::<String, _>("fitness_level"))?,
-                    goals: serde_json::from_str(&row.get::<String, _>("goals"))?,
-                    preferences: serde_json::from_str(&row.get::<String, _>("preferences"))?,
-                };
-                Ok(Some(user))
+    /// Add a library of new exercises in one transaction. Nothing is written
+    /// when any id is already taken; the taken ids are returned instead.
+    pub async fn seed_exercises(&self, exercises: Vec<Exercise>) -> Result<ExerciseSeeding> {
+        self.timed("seed_exercises", async {
+            for exercise in &exercises {
+                exercise.validate_fields().map_err(|errors| {
+                    anyhow::anyhow!("Invalid exercise {}: {}", exercise.id, describe_field_errors(&errors))
+                })?;
             }
-            None => Ok(None),
-        }
-    }
 
-    pub async fn get_all_users(&self) -> Result<Vec<User>> {
-        let rows = sqlx::query(r#"
-            SELECT id, name, age, height, weight, fitness_level, goals, preferences
-            FROM users ORDER BY created_at DESC
-        "#)
-        .fetch_all(&self.pool).await?;
+            let outcome = self.with_transaction(|tx| Box::pin(async move {
+                let mut taken = Vec::new();
+                for exercise in &exercises {
+                    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM exercises WHERE id = ?)")
+                        .bind(&exercise.id)
+                        .fetch_one(&mut **tx).await?;
+                    if exists {
+                        taken.push(exercise.id.clone());
+                    }
+                }
+                if !taken.is_empty() {
+                    return Ok(ExerciseSeeding::ExistingIds(taken));
+                }
 
-        let mut users = Vec::new();
-        for row in rows {
-            let user = User {
-                id: row.get("id"),
-                name: row.get("name"),
-                age: row.get::<i64, _>("age") as u32,
-                height: row.get("height"),
-                weight: row.get("weight"),
-                fitness_level: serde_json::from_str(&row.get::<String, _>("fitness_level"))?,
-                goals: serde_json::from_str(&row.get::<String, _>("goals"))?,
-                preferences: serde_json::from_str(&row.get::<String, _>("preferences"))?,
-            };
-            users.push(user);
-        }
+                for exercise in &exercises {
+                    Self::write_exercise(tx, exercise).await?;
+                }
+                Ok(ExerciseSeeding::Inserted(exercises.len()))
+            })).await?;
 
-        Ok(users)
+            if let ExerciseSeeding::Inserted(count) = outcome {
+                info!("🌱 {} exercises added to the library", count);
+            }
+            Ok(outcome)
+        }).await
     }
 
-    // === EXERCISE OPERATIONS ===
+    // The row and its search index entry are written together so they never drift
+    async fn write_exercise(tx: &mut Transaction<'_, Sqlite>, exercise: &Exercise) -> Result<()> {
+        let primary_muscles = serde_json::to_string(&exercise.primary_muscles)?;
+        let secondary_muscles = serde_json::to_string(&exercise.secondary_muscles)?;
 
-    pub async fn save_exercise(&self, exercise: &Exercise) -> Result<()> {
         sqlx::query(r#"
             INSERT OR REPLACE INTO exercises 
             (id, name, description, exercise_type, equipment_needed, difficulty_level, 
-             primary_muscles, secondary_muscles, instructions, safety_tips)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             primary_muscles, secondary_muscles, instructions, safety_tips, demo_url, cues)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#)
         .bind(&exercise.id)
         .bind(&exercise.name)
@@ -334,104 +1836,266 @@ impl DatabaseManager {
         .bind(serde_json::to_string(&exercise.exercise_type)?)
         .bind(serde_json::to_string(&exercise.equipment_needed)?)
         .bind(exercise.difficulty_level as i64)
-        .bind(serde_json::to_string(&exercise.primary_muscles)?)
-        .bind(serde_json::to_string(&exercise.secondary_muscles)?)
+        .bind(&primary_muscles)
+        .bind(&secondary_muscles)
         .bind(serde_json::to_string(&exercise.instructions)?)
         .bind(serde_json::to_string(&exercise.safety_tips)?)
-        .execute(&self.pool).await?;
+        .bind(&exercise.demo_url)
+        .bind(serde_json::to_string(&exercise.cues)?)
+        .execute(&mut **tx).await?;
+
+        sqlx::query("DELETE FROM exercises_fts WHERE exercise_id = ?")
+            .bind(&exercise.id)
+            .execute(&mut **tx).await?;
+
+        sqlx::query("INSERT INTO exercises_fts (exercise_id, name, description, muscles) VALUES (?, ?, ?, ?)")
+            .bind(&exercise.id)
+            .bind(&exercise.name)
+            .bind(&exercise.description)
+            .bind(format!("{} {}", primary_muscles, secondary_muscles))
+            .execute(&mut **tx).await?;
 
         Ok(())
     }
 
     pub async fn get_exercise(&self, exercise_id: &str) -> Result<Option<Exercise>> {
-        let row = sqlx::query(r#"
-            SELECT id, name, description, exercise_type, equipment_needed, difficulty_level,
-                   primary_muscles, secondary_muscles, instructions, safety_tips
-            FROM exercises WHERE id = ?
-        "#)
-        .bind(exercise_id)
-        .fetch_optional(&self.pool).await?;
+        if let Some(exercise) = self.exercises.get(exercise_id) {
+            return Ok(Some(exercise));
+        }
+        let generation = self.exercises.generation();
 
-        match row {
-            Some(row) => {
-                let exercise = Exercise {
-                    id: row.get("id"),
-                    name: row.get("name"),
-                    description: row.get("description"),
-                    exercise_type: serde_json::from_str(&row.get::<String, _>("exercise_type"))?,
-                    equipment_needed: serde_json::from_str(&row.get::<String, _>("equipment_needed"))?,
-                    difficulty_level: row.get::<i64, _>("difficulty_level") as u32,
-                    primary_muscles: serde_json::from_str(&row.get::<String, _>("primary_muscles"))?,
-                    secondary_muscles: serde_json::from_str(&row.get::<String, _>("secondary_muscles"))?,
-                    instructions: serde_json::from_str(&row.get::<String, _>("instructions"))?,
-                    safety_tips: serde_json::from_str(&row.get::<String, _>("safety_tips"))?,
-                };
-                Ok(Some(exercise))
+        self.timed("get_exercise", async {
+            let row = sqlx::query(r#"
+                SELECT id, name, description, exercise_type, equipment_needed, difficulty_level,
+                       primary_muscles, secondary_muscles, instructions, safety_tips, demo_url, cues
+                FROM exercises WHERE id = ?
+            "#)
+            .bind(exercise_id)
+            .fetch_optional(&self.pool).await?;
+
+            let exercise = row.as_ref().map(exercise_from_row).transpose()?;
+            if let Some(exercise) = &exercise {
+                self.exercises.insert(exercise_id, exercise.clone(), generation);
             }
-            None => Ok(None),
-        }
+            Ok(exercise)
+        }).await
     }
 
     pub async fn get_all_exercises(&self) -> Result<Vec<Exercise>> {
-        let rows = sqlx::query(r#"
-            SELECT id, name, description, exercise_type, equipment_needed, difficulty_level,
-                   primary_muscles, secondary_muscles, instructions, safety_tips
-            FROM exercises ORDER BY name
-        "#)
-        .fetch_all(&self.pool).await?;
+        self.timed("get_all_exercises", async {
+            let rows = sqlx::query(r#"
+                SELECT id, name, description, exercise_type, equipment_needed, difficulty_level,
+                       primary_muscles, secondary_muscles, instructions, safety_tips, demo_url, cues
+                FROM exercises ORDER BY name
+            "#)
+            .fetch_all(&self.pool).await?;
 
-        let mut exercises = Vec::new();
-        for row in rows {
-            let exercise = Exercise {
-                id: row.get("id"),
-                name: row.get("name"),
-                description: row.get("description"),
-                exercise_type: serde_json::from_str(&row.get::<String, _>("exercise_type"))?,
-                equipment_needed: serde_json::from_str(&row.get::<String, _>("equipment_needed"))?,
-                difficulty_level: row.get::<i64, _>("difficulty_level") as u32,
-                primary_muscles: serde_json::from_str(&row.get::<String, _>("primary_muscles"))?,
-                secondary_muscles: serde_json::from_str(&row.get::<String, _>("secondary_muscles"))?,
-                instructions: serde_json::from_str(&row.get::<String, _>("instructions"))?,
-                safety_tips: serde_json::from_str(&row.get::<String, _>("safety_tips"))?,
+            rows.iter().map(exercise_from_row).collect()
+        }).await
+    }
+
+    /// When an exercise was last added or replaced; replacing a row resets
+    /// its created_at
+    pub async fn exercises_modified_at(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        self.timed("exercises_modified_at", async {
+            let modified = sqlx::query_scalar("SELECT MAX(created_at) FROM exercises")
+                .fetch_one(&self.pool).await?;
+            Ok(modified)
+        }).await
+    }
+
+    // Keyword search ranked by relevance; name matches outweigh muscle
+    // groups, which outweigh the description
+    pub async fn search_exercises(&self, query: &str) -> Result<Vec<Exercise>> {
+        self.timed("search_exercises", async {
+            let Some(match_query) = fts_match_query(query) else {
+                return Ok(Vec::new());
             };
-            exercises.push(exercise);
-        }
 
-        Ok(exercises)
+            let ids: Vec<String> = sqlx::query_scalar(r#"
+                SELECT exercise_id FROM exercises_fts
+                WHERE exercises_fts MATCH ?
+                ORDER BY bm25(exercises_fts, 0.0, 10.0, 1.0, 5.0)
+                LIMIT ?
+            "#)
+            .bind(match_query)
+            .bind(SEARCH_RESULT_LIMIT)
+            .fetch_all(&self.pool).await?;
+
+            let mut exercises = Vec::with_capacity(ids.len());
+            for id in ids {
+                if let Some(exercise) = self.get_exercise(&id).await? {
+                    exercises.push(exercise);
+                }
+            }
+
+            Ok(exercises)
+        }).await
+    }
+
+    // === RECIPE OPERATIONS ===
+
+    pub async fn recipes_modified_at(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        self.timed("recipes_modified_at", async {
+            let modified = sqlx::query_scalar("SELECT MAX(updated_at) FROM recipes")
+                .fetch_one(&self.pool).await?;
+            Ok(modified)
+        }).await
+    }
+
+    pub async fn save_recipe(&self, recipe: &Recipe) -> Result<()> {
+        self.timed("save_recipe", async {
+            self.with_transaction(|tx| Box::pin(async move {
+                sqlx::query(r#"
+                    INSERT OR REPLACE INTO recipes (id, name, data, updated_at)
+                    VALUES (?, ?, ?, CURRENT_TIMESTAMP)
+                "#)
+                .bind(&recipe.id)
+                .bind(&recipe.name)
+                .bind(serde_json::to_string(recipe)?)
+                .execute(&mut **tx).await?;
+
+                sqlx::query("DELETE FROM recipes_fts WHERE recipe_id = ?")
+                    .bind(&recipe.id)
+                    .execute(&mut **tx).await?;
+
+                sqlx::query("INSERT INTO recipes_fts (recipe_id, name, description, tags) VALUES (?, ?, ?, ?)")
+                    .bind(&recipe.id)
+                    .bind(&recipe.name)
+                    .bind(&recipe.description)
+                    .bind(recipe_search_tags(recipe))
+                    .execute(&mut **tx).await?;
+
+                Ok(())
+            })).await
+        }).await
+    }
+
+    // Keyword search over recipe names, descriptions and tags
+    pub async fn search_recipes(&self, query: &str) -> Result<Vec<Recipe>> {
+        self.timed("search_recipes", async {
+            let Some(match_query) = fts_match_query(query) else {
+                return Ok(Vec::new());
+            };
+
+            let rows = sqlx::query(r#"
+                SELECT r.data
+                FROM recipes_fts
+                JOIN recipes r ON r.id = recipes_fts.recipe_id
+                WHERE recipes_fts MATCH ?
+                ORDER BY bm25(recipes_fts, 0.0, 10.0, 1.0, 5.0)
+                LIMIT ?
+            "#)
+            .bind(match_query)
+            .bind(SEARCH_RESULT_LIMIT)
+            .fetch_all(&self.pool).await?;
+
+            let mut recipes = Vec::with_capacity(rows.len());
+            for row in rows {
+                recipes.push(serde_json::from_str(&row.get::<String, _>("data"))?);
+            }
+
+            Ok(recipes)
+        }).await
     }
 
     // === WORKOUT OPERATIONS ===
 
     pub async fn save_workout(&self, workout: &WorkoutSession) -> Result<()> {
-        // Start transaction
-        let mut tx = self.pool.begin().await?;
+        self.timed("save_workout", async {
+            self.with_transaction(|tx| Box::pin(Self::write_workout(tx, workout))).await?;
 
-        // Insert workout session
-        sqlx::query(r#"
-            INSERT OR REPLACE INTO workout_sessions 
-            (id, user_id, date, total_duration_minutes, calories_burned, user_rating, notes)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            info!("💾 Workout {} saved to database", workout.id);
+            Ok(())
+        }).await
+    }
+
+    // Save a workout guarded by a client-supplied idempotency key. A key the
+    // user already sent within the expiry window stores nothing and returns
+    // the id of the workout originally saved with it.
+    pub async fn save_workout_idempotent(&self, workout: &WorkoutSession, key: &str) -> Result<String> {
+        self.timed("save_workout_idempotent", async {
+            let expiry = format!("-{} seconds", self.idempotency_key_ttl.as_secs());
+            let (workout_id, replayed) = self.with_transaction(|tx| Box::pin(async move {
+                // Purging expired keys first also takes the write lock, so concurrent
+                // retries of the same key are serialized
+                sqlx::query("DELETE FROM idempotency_keys WHERE created_at < datetime('now', ?)")
+                    .bind(expiry)
+                    .execute(&mut **tx).await?;
+
+                let existing: Option<String> = sqlx::query_scalar(
+                    "SELECT workout_session_id FROM idempotency_keys WHERE user_id = ? AND idempotency_key = ?"
+                )
+                .bind(&workout.user_id)
+                .bind(key)
+                .fetch_optional(&mut **tx).await?;
+
+                if let Some(workout_id) = existing {
+                    return Ok((workout_id, true));
+                }
+
+                Self::write_workout(tx, workout).await?;
+
+                sqlx::query("INSERT INTO idempotency_keys (user_id, idempotency_key, workout_session_id) VALUES (?, ?, ?)")
+                    .bind(&workout.user_id)
+                    .bind(key)
+                    .bind(&workout.id)
+                    .execute(&mut **tx).await?;
+
+                Ok((workout.id.clone(), false))
+            })).await?;
+
+            if replayed {
+                info!("♻️  Idempotency key replayed, workout {} already saved", workout_id);
+            } else {
+                info!("💾 Workout {} saved to database", workout_id);
+            }
+            Ok(workout_id)
+        }).await
+    }
+
+    async fn write_workout(tx: &mut Transaction<'_, Sqlite>, workout: &WorkoutSession) -> Result<()> {
+        // Insert the session, or replace it when the id is already the same
+        // user's; a session never moves between users
+        let written = sqlx::query(r#"
+            INSERT INTO workout_sessions
+            (id, user_id, date, total_duration_minutes, calories_burned, calories_estimated, user_rating, notes)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (id) DO UPDATE SET
+                date = excluded.date,
+                total_duration_minutes = excluded.total_duration_minutes,
+                calories_burned = excluded.calories_burned,
+                calories_estimated = excluded.calories_estimated,
+                user_rating = excluded.user_rating,
+                notes = excluded.notes
+            WHERE workout_sessions.user_id = excluded.user_id
         "#)
         .bind(&workout.id)
         .bind(&workout.user_id)
         .bind(&workout.date)
         .bind(workout.total_duration_minutes as i64)
         .bind(workout.calories_burned)
+        .bind(workout.calories_estimated)
         .bind(workout.user_rating.map(|r| r as i64))
         .bind(&workout.notes)
-        .execute(&mut *tx).await?;
+        .execute(&mut **tx).await?
+        .rows_affected();
+        if written == 0 {
+            return Err(WorkoutIdTaken { workout_id: workout.id.clone() }.into());
+        }
 
         // Delete existing exercise sets for this workout
         sqlx::query("DELETE FROM exercise_sets WHERE workout_session_id = ?")
             .bind(&workout.id)
-            .execute(&mut *tx).await?;
+            .execute(&mut **tx).await?;
 
         // Insert exercise sets
         for exercise_set in &workout.exercises {
             sqlx::query(r#"
                 INSERT INTO exercise_sets 
-                (workout_session_id, exercise_id, sets, reps, weight_kg, duration_seconds, rest_seconds, completed)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                (workout_session_id, exercise_id, sets, reps, weight_kg, duration_seconds, rest_seconds, completed,
+                 tempo, target_rpe, group_id)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#)
             .bind(&workout.id)
             .bind(&exercise_set.exercise_id)
@@ -441,33 +2105,302 @@ impl DatabaseManager {
             .bind(exercise_set.duration_seconds.map(|d| d as i64))
             .bind(exercise_set.rest_seconds as i64)
             .bind(exercise_set.completed)
-            .execute(&mut *tx).await?;
+            .bind(&exercise_set.tempo)
+            .bind(exercise_set.target_rpe)
+            .bind(&exercise_set.group_id)
+            .execute(&mut **tx).await?;
         }
 
-        // Commit transaction
-        tx.commit().await?;
+        // Heavy completed sets update the lifter's estimated maxes; only a
+        // better estimate replaces a stored one
+        for exercise_set in &workout.exercises {
+            let Some(weight_kg) = exercise_set.weight_kg.filter(|w| *w > 0.0) else {
+                continue;
+            };
+            if !exercise_set.completed || !(1..=MAX_REPS_FOR_1RM).contains(&exercise_set.reps) {
+                continue;
+            }
+            sqlx::query(r#"
+                INSERT INTO one_rep_maxes (user_id, exercise_id, one_rm_kg) VALUES (?, ?, ?)
+                ON CONFLICT (user_id, exercise_id) DO UPDATE SET
+                    one_rm_kg = excluded.one_rm_kg, updated_at = CURRENT_TIMESTAMP
+                WHERE excluded.one_rm_kg > one_rep_maxes.one_rm_kg
+            "#)
+            .bind(&workout.user_id)
+            .bind(&exercise_set.exercise_id)
+            .bind(estimate_1rm(weight_kg, exercise_set.reps))
+            .execute(&mut **tx).await?;
+        }
 
-        info!("💾 Workout {} saved to database", workout.id);
         Ok(())
     }
 
+    /// The user's estimated one-rep max per exercise, from their logged
+    /// heavy sets
+    pub async fn get_one_rep_maxes(&self, user_id: &str) -> Result<HashMap<String, f32>> {
+        self.timed("get_one_rep_maxes", async {
+            let rows = sqlx::query("SELECT exercise_id, one_rm_kg FROM one_rep_maxes WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter()
+                .map(|row| (row.get::<String, _>("exercise_id"), row.get::<f64, _>("one_rm_kg") as f32))
+                .collect())
+        }).await
+    }
+
+    // === ACTIVITY METRICS ===
+
+    /// Store metrics imported from a wearable. A metric already stored for
+    /// the same user, date and source is overwritten rather than duplicated,
+    /// so a day can be re-imported once it's complete. Returns how many
+    /// were new.
+    pub async fn save_activity_metrics(&self, metrics: &[ActivityMetric]) -> Result<usize> {
+        self.timed("save_activity_metrics", async {
+            self.with_transaction(|tx| Box::pin(async move {
+                let mut added = 0;
+                for metric in metrics {
+                    let existing: Option<i64> = sqlx::query_scalar(
+                        "SELECT 1 FROM activity_metrics WHERE user_id = ? AND date = ? AND source = ? AND metric = ?"
+                    )
+                    .bind(&metric.user_id)
+                    .bind(&metric.date)
+                    .bind(&metric.source)
+                    .bind(metric.metric.as_str())
+                    .fetch_optional(&mut **tx).await?;
+                    if existing.is_none() {
+                        added += 1;
+                    }
+
+                    sqlx::query(r#"
+                        INSERT INTO activity_metrics (user_id, date, source, metric, value) VALUES (?, ?, ?, ?, ?)
+                        ON CONFLICT (user_id, date, source, metric) DO UPDATE SET
+                            value = excluded.value, imported_at = CURRENT_TIMESTAMP
+                    "#)
+                    .bind(&metric.user_id)
+                    .bind(&metric.date)
+                    .bind(&metric.source)
+                    .bind(metric.metric.as_str())
+                    .bind(metric.value)
+                    .execute(&mut **tx).await?;
+                }
+                Ok(added)
+            })).await
+        }).await
+    }
+
+    /// A user's imported metrics from every source, oldest first
+    pub async fn get_activity_metrics(&self, user_id: &str) -> Result<Vec<ActivityMetric>> {
+        self.timed("get_activity_metrics", async {
+            let rows = sqlx::query(r#"
+                SELECT user_id, date, source, metric, value FROM activity_metrics
+                WHERE user_id = ?
+                ORDER BY date ASC, source ASC, metric ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            rows.iter()
+                .map(|row| {
+                    let stored: String = row.get("metric");
+                    Ok(ActivityMetric {
+                        user_id: row.get("user_id"),
+                        date: row.get("date"),
+                        source: row.get("source"),
+                        metric: ActivityMetricKind::parse(&stored)
+                            .ok_or_else(|| anyhow::anyhow!("Unknown activity metric: {}", stored))?,
+                        value: row.get("value"),
+                    })
+                })
+                .collect()
+        }).await
+    }
+
+    /// Stores sleep sessions not seen before and returns how many that was.
+    /// A session matching a stored one's external id or start is skipped.
+    pub async fn save_sleep_sessions(&self, sessions: &[SleepSession]) -> Result<usize> {
+        self.timed("save_sleep_sessions", async {
+            self.with_transaction(|tx| Box::pin(async move {
+                let mut added = 0;
+                for session in sessions {
+                    let result = sqlx::query(r#"
+                        INSERT OR IGNORE INTO sleep_sessions (user_id, source, external_id, start_time, end_time)
+                        VALUES (?, ?, ?, ?, ?)
+                    "#)
+                    .bind(&session.user_id)
+                    .bind(&session.source)
+                    .bind(&session.external_id)
+                    .bind(session.start)
+                    .bind(session.end)
+                    .execute(&mut **tx).await?;
+                    added += result.rows_affected() as usize;
+                }
+                Ok(added)
+            })).await
+        }).await
+    }
+
+    /// A user's sleep from every source, earliest first
+    pub async fn get_sleep_sessions(&self, user_id: &str) -> Result<Vec<SleepSession>> {
+        self.timed("get_sleep_sessions", async {
+            let rows = sqlx::query(r#"
+                SELECT user_id, source, external_id, start_time, end_time FROM sleep_sessions
+                WHERE user_id = ?
+                ORDER BY start_time ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter()
+                .map(|row| SleepSession {
+                    user_id: row.get("user_id"),
+                    source: row.get("source"),
+                    external_id: row.get("external_id"),
+                    start: row.get("start_time"),
+                    end: row.get("end_time"),
+                })
+                .collect())
+        }).await
+    }
+
+    /// Stores food log entries, skipping any matching a stored entry's
+    /// date, food and time. Returns whether each one was stored.
+    pub async fn save_food_log_entries(&self, entries: &[FoodLogEntry]) -> Result<Vec<bool>> {
+        self.timed("save_food_log_entries", async {
+            self.with_transaction(|tx| Box::pin(async move {
+                let mut stored = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    let result = sqlx::query(r#"
+                        INSERT OR IGNORE INTO food_log
+                        (id, user_id, date, time, meal, food_name, food_id, quantity_g,
+                         calories, protein_g, carbs_g, fat_g, source)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#)
+                    .bind(&entry.id)
+                    .bind(&entry.user_id)
+                    .bind(&entry.date)
+                    .bind(entry.time.as_deref().unwrap_or(""))
+                    .bind(&entry.meal)
+                    .bind(&entry.food_name)
+                    .bind(&entry.food_id)
+                    .bind(entry.quantity_g)
+                    .bind(entry.calories)
+                    .bind(entry.protein_g)
+                    .bind(entry.carbs_g)
+                    .bind(entry.fat_g)
+                    .bind(&entry.source)
+                    .execute(&mut **tx).await?;
+                    stored.push(result.rows_affected() > 0);
+                }
+                Ok(stored)
+            })).await
+        }).await
+    }
+
+    /// A user's food log, oldest first
+    pub async fn get_food_log(&self, user_id: &str) -> Result<Vec<FoodLogEntry>> {
+        self.timed("get_food_log", async {
+            let rows = sqlx::query(r#"
+                SELECT id, user_id, date, time, meal, food_name, food_id, quantity_g,
+                       calories, protein_g, carbs_g, fat_g, source
+                FROM food_log
+                WHERE user_id = ?
+                ORDER BY date ASC, time ASC, created_at ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            Ok(rows.iter()
+                .map(|row| {
+                    let time: String = row.get("time");
+                    FoodLogEntry {
+                        id: row.get("id"),
+                        user_id: row.get("user_id"),
+                        date: row.get("date"),
+                        time: Some(time).filter(|time| !time.is_empty()),
+                        meal: row.get("meal"),
+                        food_name: row.get("food_name"),
+                        food_id: row.get("food_id"),
+                        quantity_g: row.get("quantity_g"),
+                        calories: row.get("calories"),
+                        protein_g: row.get("protein_g"),
+                        carbs_g: row.get("carbs_g"),
+                        fat_g: row.get("fat_g"),
+                        source: row.get("source"),
+                    }
+                })
+                .collect())
+        }).await
+    }
+
     pub async fn get_user_workouts(&self, user_id: &str) -> Result<Vec<WorkoutSession>> {
-        let rows = sqlx::query(r#"
-            SELECT id, user_id, date, total_duration_minutes, calories_burned, user_rating, notes
-            FROM workout_sessions 
-            WHERE user_id = ? 
-            ORDER BY date DESC
-        "#)
-        .bind(user_id)
-        .fetch_all(&self.pool).await?;
+        self.timed("get_user_workouts", async {
+            let rows = sqlx::query(r#"
+                SELECT id, user_id, date, total_duration_minutes, calories_burned, calories_estimated, user_rating, notes
+                FROM workout_sessions 
+                WHERE user_id = ? 
+                ORDER BY date DESC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
+
+            self.workouts_from_rows(rows).await
+        }).await
+    }
+
+    pub async fn get_workout(&self, workout_id: &str) -> Result<Option<WorkoutSession>> {
+        self.timed("get_workout", async {
+            let rows = sqlx::query(r#"
+                SELECT id, user_id, date, total_duration_minutes, calories_burned, calories_estimated, user_rating, notes
+                FROM workout_sessions
+                WHERE id = ?
+            "#)
+            .bind(workout_id)
+            .fetch_all(&self.pool).await?;
+
+            Ok(self.workouts_from_rows(rows).await?.pop())
+        }).await
+    }
+
+    // One page of a user's workout history, newest first
+    pub async fn get_user_workouts_page(&self, user_id: &str, page: PageRequest) -> Result<Page<WorkoutSession>> {
+        self.timed("get_user_workouts_page", async {
+            let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workout_sessions WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&self.pool).await?;
+
+            let limit = page.effective_limit();
+            let rows = sqlx::query(r#"
+                SELECT id, user_id, date, total_duration_minutes, calories_burned, calories_estimated, user_rating, notes
+                FROM workout_sessions
+                WHERE user_id = ?
+                ORDER BY date DESC, id ASC
+                LIMIT ? OFFSET ?
+            "#)
+            .bind(user_id)
+            .bind(limit as i64)
+            .bind(page.offset as i64)
+            .fetch_all(&self.pool).await?;
+
+            Ok(Page {
+                items: self.workouts_from_rows(rows).await?,
+                total: total as u64,
+                limit,
+                offset: page.offset,
+            })
+        }).await
+    }
 
+    // Attach each session's exercise sets to its row
+    async fn workouts_from_rows(&self, rows: Vec<SqliteRow>) -> Result<Vec<WorkoutSession>> {
         let mut workouts = Vec::new();
         for row in rows {
             let workout_id: String = row.get("id");
             
             // Get exercise sets for this workout
             let exercise_rows = sqlx::query(r#"
-                SELECT exercise_id, sets, reps, weight_kg, duration_seconds, rest_seconds, completed
+                SELECT exercise_id, sets, reps, weight_kg, duration_seconds, rest_seconds, completed,
+                       tempo, target_rpe, group_id
                 FROM exercise_sets 
                 WHERE workout_session_id = ?
             "#)
@@ -484,6 +2417,9 @@ impl DatabaseManager {
                     duration_seconds: ex_row.get::<Option<i64>, _>("duration_seconds").map(|d| d as u32),
                     rest_seconds: ex_row.get::<i64, _>("rest_seconds") as u32,
                     completed: ex_row.get("completed"),
+                    tempo: ex_row.get("tempo"),
+                    target_rpe: ex_row.get("target_rpe"),
+                    group_id: ex_row.get("group_id"),
                 };
                 exercises.push(exercise_set);
             }
@@ -495,6 +2431,7 @@ impl DatabaseManager {
                 exercises,
                 total_duration_minutes: row.get::<i64, _>("total_duration_minutes") as u32,
                 calories_burned: row.get("calories_burned"),
+                calories_estimated: row.get("calories_estimated"),
                 user_rating: row.get::<Option<i64>, _>("user_rating").map(|r| r as u32),
                 notes: row.get("notes"),
             };
@@ -504,62 +2441,697 @@ impl DatabaseManager {
         Ok(workouts)
     }
 
-    // === ANALYTICS ===
+    // === MEASUREMENT OPERATIONS ===
 
-    pub async fn get_user_progress_analysis(&self, user_id: &str) -> Result<ProgressAnalysis> {
-        let workouts = self.get_user_workouts(user_id).await?;
-        
-        let total_workouts = workouts.len() as u32;
-        let avg_duration = if total_workouts > 0 {
-            workouts.iter()
-                .map(|w| w.total_duration_minutes as f32)
-                .sum::<f32>() / total_workouts as f32
-        } else {
-            0.0
-        };
+    // Record a measurement, replacing any existing entry for the same date
+    pub async fn save_measurement(&self, measurement: &BodyMeasurement) -> Result<()> {
+        self.timed("save_measurement", async {
+            sqlx::query(r#"
+                INSERT INTO body_measurements (user_id, date, weight_kg, waist_cm, hip_cm, arm_cm)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT (user_id, date) DO UPDATE SET
+                    weight_kg = excluded.weight_kg,
+                    waist_cm = excluded.waist_cm,
+                    hip_cm = excluded.hip_cm,
+                    arm_cm = excluded.arm_cm,
+                    updated_at = CURRENT_TIMESTAMP
+            "#)
+            .bind(&measurement.user_id)
+            .bind(&measurement.date)
+            .bind(measurement.weight_kg)
+            .bind(measurement.waist_cm)
+            .bind(measurement.hip_cm)
+            .bind(measurement.arm_cm)
+            .execute(&self.pool).await?;
+
+            info!("📏 Measurement for user {} on {} saved", measurement.user_id, measurement.date);
+            Ok(())
+        }).await
+    }
 
-        let total_calories = workouts.iter()
-            .filter_map(|w| w.calories_burned)
-            .sum::<f32>();
+    pub async fn get_user_measurements(&self, user_id: &str) -> Result<Vec<BodyMeasurement>> {
+        self.timed("get_user_measurements", async {
+            let rows = sqlx::query(r#"
+                SELECT user_id, date, weight_kg, waist_cm, hip_cm, arm_cm
+                FROM body_measurements
+                WHERE user_id = ?
+                ORDER BY date ASC
+            "#)
+            .bind(user_id)
+            .fetch_all(&self.pool).await?;
 
-        // Calculate consistency score based on recent workout frequency
-        let consistency_score = if total_workouts >= 5 { 0.9 }
-        else if total_workouts >= 3 { 0.7 }
-        else if total_workouts >= 1 { 0.5 }
-        else { 0.0 };
+            let measurements = rows.iter()
+                .map(|row| BodyMeasurement {
+                    user_id: row.get("user_id"),
+                    date: row.get("date"),
+                    weight_kg: row.get("weight_kg"),
+                    waist_cm: row.get("waist_cm"),
+                    hip_cm: row.get("hip_cm"),
+                    arm_cm: row.get("arm_cm"),
+                })
+                .collect();
 
-        Ok(ProgressAnalysis {
-            total_workouts,
-            average_duration_minutes: avg_duration,
-            total_calories_burned: total_calories,
-            consistency_score,
-        })
+            Ok(measurements)
+        }).await
     }
 
     // Database health check
+    pub async fn close(&self) {
+        self.pool.close().await;
+    }
+
     pub async fn health_check(&self) -> Result<DatabaseHealth> {
-        let users_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
-            .fetch_one(&self.pool).await?;
-        
-        let exercises_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM exercises")
-            .fetch_one(&self.pool).await?;
-            
-        let workouts_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workout_sessions")
-            .fetch_one(&self.pool).await?;
+        self.timed("health_check", async {
+            let users_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL")
+                .fetch_one(&self.pool).await?;
 
-        Ok(DatabaseHealth {
-            connected: true,
-            users_count: users_count as u32,
-            exercises_count: exercises_count as u32,
-            workouts_count: workouts_count as u32,
-        })
+            let exercises_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM exercises")
+                .fetch_one(&self.pool).await?;
+
+            let workouts_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workout_sessions")
+                .fetch_one(&self.pool).await?;
+
+            let schema_version = self.schema_version().await?;
+            let pool_size = self.pool.size();
+            let pool_idle = self.pool.num_idle() as u32;
+            let pool_max_connections = self.pool.options().get_max_connections();
+
+            Ok(DatabaseHealth {
+                connected: true,
+                users_count: users_count as u32,
+                exercises_count: exercises_count as u32,
+                workouts_count: workouts_count as u32,
+                schema_version,
+                pool_size,
+                pool_idle,
+                pool_max_connections,
+                pool_utilization: pool_size.saturating_sub(pool_idle) as f32 / pool_max_connections.max(1) as f32,
+                query_timeouts: self.query_timeouts.load(Ordering::Relaxed),
+                cache_hits: self.users.hits.load(Ordering::Relaxed) + self.exercises.hits.load(Ordering::Relaxed),
+                cache_misses: self.users.misses.load(Ordering::Relaxed) + self.exercises.misses.load(Ordering::Relaxed),
+            })
+        }).await
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct DatabaseHealth {
     pub connected: bool,
     pub users_count: u32,
     pub exercises_count: u32,
     pub workouts_count: u32,
+    /// Latest applied schema migration
+    pub schema_version: i64,
+    /// Open connections in the pool
+    pub pool_size: u32,
+    pub pool_idle: u32,
+    pub pool_max_connections: u32,
+    /// Fraction of the maximum pool size currently in use
+    pub pool_utilization: f32,
+    /// Operations that ran past the query timeout since startup
+    pub query_timeouts: u64,
+    /// User and exercise lookups answered from memory since startup
+    pub cache_hits: u64,
+    /// User and exercise lookups that went to the database
+    pub cache_misses: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FitnessGoal, FitnessLevel};
+    use tempfile::tempdir;
+
+    fn test_config(dir: &std::path::Path, max_connections: u32) -> DatabaseConfig {
+        DatabaseConfig {
+            url: format!("sqlite://{}", dir.join("test.db").display()),
+            max_connections,
+            connection_timeout_seconds: 10,
+            idempotency_key_ttl_hours: 24,
+            query_timeout_ms: 10_000,
+            busy_timeout_ms: 5_000,
+            cache_capacity: 100,
+            cache_ttl_seconds: 60,
+        }
+    }
+
+    fn test_user() -> User {
+        User {
+            id: "test_user".to_string(),
+            name: "Test User".to_string(),
+            age: 30,
+            height: 175.0,
+            weight: 73.5,
+            fitness_level: FitnessLevel::Intermediate,
+            goals: vec![FitnessGoal::WeightLoss],
+            preferences: crate::models::user::UserPreferences {
+                preferred_exercise_types: vec![],
+                available_equipment: vec![],
+                workout_duration_minutes: 45,
+                workouts_per_week: 3,
+                preferred_time_of_day: None,
+                unit_system: crate::models::UnitSystem::Metric,
+                limitations: vec![],
+                warmup_cooldown: true,
+            },
+            role: crate::models::user::Role::Client,
+            version: 0,
+        }
+    }
+
+    fn measurement(date: &str, weight_kg: f32) -> BodyMeasurement {
+        BodyMeasurement {
+            user_id: "test_user".to_string(),
+            date: date.to_string(),
+            weight_kg,
+            waist_cm: None,
+            hip_cm: None,
+            arm_cm: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_measurement_series_is_ordered_and_upserted() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 5)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        db.save_measurement(&measurement("2025-08-10", 72.0)).await.unwrap();
+        db.save_measurement(&measurement("2025-08-01", 73.5)).await.unwrap();
+        db.save_measurement(&measurement("2025-08-05", 72.8)).await.unwrap();
+        // Re-recording a date replaces the earlier entry
+        db.save_measurement(&measurement("2025-08-10", 71.6)).await.unwrap();
+
+        let series = db.get_user_measurements("test_user").await.unwrap();
+        let dates: Vec<&str> = series.iter().map(|m| m.date.as_str()).collect();
+        assert_eq!(dates, vec!["2025-08-01", "2025-08-05", "2025-08-10"]);
+        assert_eq!(series.last().unwrap().weight_kg, 71.6);
+    }
+
+    fn exercise(id: &str, name: &str, description: &str, primary_muscles: Vec<crate::MuscleGroup>) -> Exercise {
+        Exercise {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.to_string(),
+            exercise_type: crate::ExerciseType::Strength,
+            equipment_needed: vec![],
+            difficulty_level: 3,
+            primary_muscles,
+            secondary_muscles: vec![],
+            instructions: vec![],
+            safety_tips: vec![],
+            demo_url: None,
+            cues: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_exercise_demo_url_is_validated_and_stored() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+
+        let mut curl = exercise("bicep_curl", "Bicep Curl", "Elbow flexion", vec![crate::MuscleGroup::Arms]);
+        curl.cues = vec!["Elbows pinned to your sides".to_string()];
+        for bad in ["not a url", "ftp://example.com/curl.mp4", "https://"] {
+            curl.demo_url = Some(bad.to_string());
+            assert!(db.save_exercise(&curl).await.is_err(), "{} was accepted", bad);
+        }
+        assert!(db.get_exercise("bicep_curl").await.unwrap().is_none());
+
+        curl.demo_url = Some("https://example.com/demos/curl.mp4".to_string());
+        db.save_exercise(&curl).await.unwrap();
+        let stored = db.get_exercise("bicep_curl").await.unwrap().unwrap();
+        assert_eq!(stored.demo_url, curl.demo_url);
+        assert_eq!(stored.cues, curl.cues);
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_keyword_matches() {
+        use crate::MuscleGroup;
+
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+
+        db.save_exercise(&exercise("bicep_curl", "Bicep Curl", "Elbow flexion for the arms", vec![MuscleGroup::Arms])).await.unwrap();
+        db.save_exercise(&exercise("calf_raise", "Calf Raise", "Rise onto the toes", vec![MuscleGroup::Calves])).await.unwrap();
+        db.save_exercise(&exercise("hip_thrust", "Hip Thrust", "Loaded hip extension", vec![MuscleGroup::Glutes])).await.unwrap();
+
+        let results = db.search_exercises("glute").await.unwrap();
+        let ids: Vec<&str> = results.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids.first(), Some(&"glute_bridge"));
+        assert!(ids.contains(&"hip_thrust"));
+        assert!(!ids.contains(&"bicep_curl"));
+        assert!(!ids.contains(&"calf_raise"));
+
+        // Re-saving an exercise replaces its index entry rather than duplicating it
+        db.save_exercise(&exercise("hip_thrust", "Barbell Hip Thrust", "Loaded hip extension", vec![MuscleGroup::Glutes])).await.unwrap();
+        let results = db.search_exercises("hip thrust").await.unwrap();
+        assert_eq!(results.iter().filter(|e| e.id == "hip_thrust").count(), 1);
+        assert_eq!(results[0].name, "Barbell Hip Thrust");
+
+        // FTS syntax alone is not a query
+        assert!(db.search_exercises("  \"*  ").await.unwrap().is_empty());
+
+        let recipes = db.search_recipes("salmon").await.unwrap();
+        assert_eq!(recipes.first().map(|r| r.id.as_str()), Some("salmon_rice_bowl"));
+    }
+
+    #[tokio::test]
+    async fn test_soft_then_hard_delete_user() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        assert!(db.insert_user(&test_user(), Some("not-a-real-hash")).await.unwrap());
+        db.save_measurement(&measurement("2025-08-01", 73.5)).await.unwrap();
+        db.save_workout_idempotent(&WorkoutSession {
+            id: "test_workout".to_string(),
+            user_id: "test_user".to_string(),
+            date: "2025-08-01".to_string(),
+            exercises: vec![ExerciseSet {
+                exercise_id: "squat".to_string(),
+                sets: 3,
+                reps: 12,
+                weight_kg: None,
+                duration_seconds: None,
+                rest_seconds: 60,
+                completed: true,
+                tempo: None,
+                target_rpe: None,
+                group_id: None,
+            }],
+            total_duration_minutes: 30,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: Some(4),
+            notes: None,
+        }, "retry-1").await.unwrap();
+
+        db.insert_webhook(&WebhookSubscription {
+            id: "hook".to_string(),
+            url: "https://example.com/hook".to_string(),
+            events: vec![WebhookEvent::WorkoutLogged],
+            created_at: "2025-08-01 00:00:00".to_string(),
+        }, "whsec_0123456789abcdef").await.unwrap();
+        for user_id in ["test_user", "other_user"] {
+            let payload = serde_json::json!({ "event": "workout_logged", "user_id": user_id }).to_string();
+            db.enqueue_webhook_deliveries(WebhookEvent::WorkoutLogged, user_id, &payload, "2025-08-01 00:00:00")
+                .await.unwrap();
+        }
+
+        assert!(db.delete_user("test_user", false).await.unwrap());
+        assert!(db.get_user("test_user").await.unwrap().is_none());
+        assert!(db.get_all_users().await.unwrap().is_empty());
+        assert_eq!(db.health_check().await.unwrap().users_count, 0);
+        // Soft delete keeps the data
+        assert_eq!(db.get_user_workouts("test_user").await.unwrap().len(), 1);
+        assert_eq!(db.get_user_measurements("test_user").await.unwrap().len(), 1);
+        assert!(!db.delete_user("test_user", false).await.unwrap());
+
+        // Saving over a deleted user updates the profile but doesn't revive
+        // them or reset when they were created
+        sqlx::query("UPDATE users SET created_at = '2020-01-01 00:00:00' WHERE id = 'test_user'")
+            .execute(&db.pool).await.unwrap();
+        db.save_user(&User { name: "Resaved".to_string(), ..test_user() }).await.unwrap();
+        assert!(db.get_user("test_user").await.unwrap().is_none());
+        let (name, created_at): (String, String) = sqlx::query_as("SELECT name, created_at FROM users WHERE id = 'test_user'")
+            .fetch_one(&db.pool).await.unwrap();
+        assert_eq!((name.as_str(), created_at.as_str()), ("Resaved", "2020-01-01 00:00:00"));
+
+        assert!(db.delete_user("test_user", true).await.unwrap());
+        assert!(db.get_user_workouts("test_user").await.unwrap().is_empty());
+        assert!(db.get_user_measurements("test_user").await.unwrap().is_empty());
+        for table in ["exercise_sets", "idempotency_keys", "user_credentials"] {
+            let rows: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&db.pool).await.unwrap();
+            assert_eq!(rows, 0, "{} rows left behind", table);
+        }
+        // Queued webhook payloads carry the user's data too; other users'
+        // deliveries stay
+        let deliveries = db.webhook_deliveries("hook").await.unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert!(deliveries[0].payload.contains("other_user"));
+        let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+            .fetch_one(&db.pool).await.unwrap();
+        assert_eq!(users, 0);
+        assert!(!db.delete_user("test_user", true).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_expired_idempotency_key_is_reusable() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        let workout = |id: &str| WorkoutSession {
+            id: id.to_string(),
+            user_id: "test_user".to_string(),
+            date: "2025-08-01".to_string(),
+            exercises: vec![],
+            total_duration_minutes: 20,
+            calories_burned: Some(100.0),
+            calories_estimated: false,
+            user_rating: None,
+            notes: None,
+        };
+
+        assert_eq!(db.save_workout_idempotent(&workout("first"), "key").await.unwrap(), "first");
+        assert_eq!(db.save_workout_idempotent(&workout("second"), "key").await.unwrap(), "first");
+
+        sqlx::query("UPDATE idempotency_keys SET created_at = '2000-01-01 00:00:00'")
+            .execute(&db.pool).await.unwrap();
+        assert_eq!(db.save_workout_idempotent(&workout("third"), "key").await.unwrap(), "third");
+        assert_eq!(db.get_user_workouts("test_user").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_set_tempo_rpe_and_group_round_trip() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        let set = |exercise_id: &str, tempo: Option<&str>, target_rpe: Option<f32>| ExerciseSet {
+            exercise_id: exercise_id.to_string(),
+            sets: 3,
+            reps: 5,
+            weight_kg: Some(100.0),
+            duration_seconds: None,
+            rest_seconds: 150,
+            completed: true,
+            tempo: tempo.map(str::to_string),
+            target_rpe,
+            group_id: None,
+        };
+        db.save_workout(&WorkoutSession {
+            id: "tempo_workout".to_string(),
+            user_id: "test_user".to_string(),
+            date: "2025-08-01".to_string(),
+            exercises: vec![
+                ExerciseSet { group_id: Some("superset_1".to_string()), ..set("squat", Some("3010"), Some(8.5)) },
+                ExerciseSet { group_id: Some("superset_1".to_string()), ..set("deadlift", None, None) },
+                set("pushup", None, None),
+            ],
+            total_duration_minutes: 40,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: None,
+            notes: None,
+        }).await.unwrap();
+
+        let workout = db.get_workout("tempo_workout").await.unwrap().unwrap();
+        let squat = workout.exercises.iter().find(|s| s.exercise_id == "squat").unwrap();
+        assert_eq!(squat.tempo.as_deref(), Some("3010"));
+        assert_eq!(squat.target_rpe, Some(8.5));
+        let deadlift = workout.exercises.iter().find(|s| s.exercise_id == "deadlift").unwrap();
+        assert_eq!(deadlift.tempo, None);
+        assert_eq!(deadlift.target_rpe, None);
+        assert_eq!(squat.group_id.as_deref(), Some("superset_1"));
+        assert_eq!(deadlift.group_id.as_deref(), Some("superset_1"));
+        let pushup = workout.exercises.iter().find(|s| s.exercise_id == "pushup").unwrap();
+        assert_eq!(pushup.group_id, None);
+
+        // Sets stored before the fields existed still deserialize
+        let legacy: ExerciseSet = serde_json::from_str(
+            r#"{"exercise_id":"squat","sets":3,"reps":5,"weight_kg":null,"duration_seconds":null,"rest_seconds":90,"completed":false}"#
+        ).unwrap();
+        assert_eq!(legacy.tempo, None);
+        assert_eq!(legacy.target_rpe, None);
+        assert_eq!(legacy.group_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_heavy_sets_keep_the_best_one_rep_max_estimate() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        let set = |exercise_id: &str, reps: u32, weight_kg: f32, completed: bool| ExerciseSet {
+            exercise_id: exercise_id.to_string(),
+            sets: 3,
+            reps,
+            weight_kg: Some(weight_kg),
+            duration_seconds: None,
+            rest_seconds: 150,
+            completed,
+            tempo: None,
+            target_rpe: None,
+            group_id: None,
+        };
+        let workout = |id: &str, exercises: Vec<ExerciseSet>| WorkoutSession {
+            id: id.to_string(),
+            user_id: "test_user".to_string(),
+            date: "2025-08-01".to_string(),
+            exercises,
+            total_duration_minutes: 40,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: None,
+            notes: None,
+        };
+
+        db.save_workout(&workout("heavy", vec![
+            set("deadlift", 5, 100.0, true),
+            // Too many reps to say much about a max, and a set never finished
+            set("squat", 15, 60.0, true),
+            set("pushup", 5, 20.0, false),
+        ])).await.unwrap();
+        db.save_workout(&workout("lighter", vec![set("deadlift", 5, 80.0, true)])).await.unwrap();
+
+        let maxes = db.get_one_rep_maxes("test_user").await.unwrap();
+        assert_eq!(maxes.len(), 1);
+        assert_eq!(maxes["deadlift"], estimate_1rm(100.0, 5));
+    }
+
+    #[tokio::test]
+    async fn test_users_page_window_and_total() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        for i in 1..=25 {
+            let mut user = test_user();
+            user.id = format!("user_{:02}", i);
+            db.save_user(&user).await.unwrap();
+        }
+
+        let page = db.get_users_page(PageRequest::new(10, 10)).await.unwrap();
+        let ids: Vec<String> = page.items.iter().map(|u| u.id.clone()).collect();
+        let expected: Vec<String> = (11..=20).map(|i| format!("user_{:02}", i)).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(page.total, 25);
+        assert_eq!(page.limit, 10);
+        assert_eq!(page.offset, 10);
+
+        let last = db.get_users_page(PageRequest::new(10, 20)).await.unwrap();
+        assert_eq!(last.items.len(), 5);
+        assert!(db.get_users_page(PageRequest::new(10, 30)).await.unwrap().items.is_empty());
+        assert_eq!(db.get_users_page(PageRequest::new(0, 0)).await.unwrap().limit, 1);
+    }
+
+    #[tokio::test]
+    async fn test_experiment_results_aggregate_per_variant() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        let exposure = |variant: &str, user: &str, served_by: &str, score: Option<f64>| ExperimentExposure {
+            experiment: "meal_plan_algorithm".to_string(),
+            variant: variant.to_string(),
+            user_id: user.to_string(),
+            served_by: score.map(|_| served_by.to_string()),
+            succeeded: score.is_some(),
+            score,
+            runtime_ms: score.map(|_| 100.0),
+        };
+        for recorded in [
+            exposure("GeneticAlgorithm", "a", "GeneticAlgorithm", Some(0.8)),
+            exposure("GeneticAlgorithm", "a", "GeneticAlgorithm", Some(0.6)),
+            exposure("GeneticAlgorithm", "b", "GeneticAlgorithm", None),
+            exposure("SimulatedAnnealing", "c", "GeneticAlgorithm", Some(0.5)),
+        ] {
+            db.record_exposure(&recorded).await.unwrap();
+        }
+
+        let results = db.experiment_results("meal_plan_algorithm").await.unwrap();
+        assert_eq!(results.len(), 2);
+        let genetic = &results[0];
+        assert_eq!((genetic.requests, genetic.users, genetic.successes, genetic.fallbacks), (3, 2, 2, 0));
+        assert!((genetic.average_score.unwrap() - 0.7).abs() < 1e-9);
+        let annealing = &results[1];
+        assert_eq!((annealing.variant.as_str(), annealing.fallbacks), ("SimulatedAnnealing", 1));
+        assert!(db.experiment_results("other").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads_share_the_pool() {
+        let dir = tempdir().unwrap();
+        let db = std::sync::Arc::new(DatabaseManager::new(&test_config(dir.path(), 4)).await.unwrap());
+
+        let mut tasks = Vec::new();
+        for i in 0..50 {
+            let db = db.clone();
+            tasks.push(tokio::spawn(async move {
+                // Mix in a few writes so readers contend with a writer
+                if i % 10 == 0 {
+                    let mut user = test_user();
+                    user.id = format!("user_{}", i);
+                    db.save_user(&user).await?;
+                }
+                db.get_all_users().await
+            }));
+        }
+
+        for task in tasks {
+            task.await.unwrap().expect("concurrent query failed");
+        }
+
+        let health = db.health_check().await.unwrap();
+        assert_eq!(health.users_count, 5);
+        assert_eq!(health.pool_max_connections, 4);
+        assert!(health.pool_size <= 4);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_lookups_are_cached_until_written() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        assert_eq!(db.get_user("test_user").await.unwrap().unwrap().name, "Test User");
+        // Changed behind the cache's back, so only a database read would see it
+        sqlx::query("UPDATE users SET name = 'Changed' WHERE id = 'test_user'")
+            .execute(&db.pool).await.unwrap();
+        assert_eq!(db.get_user("test_user").await.unwrap().unwrap().name, "Test User");
+        let health = db.health_check().await.unwrap();
+        assert_eq!((health.cache_hits, health.cache_misses), (1, 1));
+
+        let mut renamed = test_user();
+        renamed.name = "Renamed".to_string();
+        db.save_user(&renamed).await.unwrap();
+        assert_eq!(db.get_user("test_user").await.unwrap().unwrap().name, "Renamed");
+        assert_eq!(db.health_check().await.unwrap().cache_misses, 2);
+
+        db.delete_user("test_user", false).await.unwrap();
+        assert!(db.get_user("test_user").await.unwrap().is_none());
+
+        let mut squat = db.get_exercise("squat").await.unwrap().unwrap();
+        squat.name = "Back Squat".to_string();
+        db.save_exercise(&squat).await.unwrap();
+        assert_eq!(db.get_exercise("squat").await.unwrap().unwrap().name, "Back Squat");
+    }
+
+    #[tokio::test]
+    async fn test_failed_transaction_rolls_back_every_write() {
+        let dir = tempdir().unwrap();
+        let db = DatabaseManager::new(&test_config(dir.path(), 2)).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+        let before = db.health_check().await.unwrap();
+
+        let mut user = test_user();
+        user.id = "partial_user".to_string();
+        let result: Result<()> = db.with_transaction(|tx| Box::pin(async move {
+            DatabaseManager::write_user(tx, &user).await?;
+            sqlx::query("INSERT INTO favorites (user_id, kind, item_id) VALUES ('partial_user', 'exercise', 'squat')")
+                .execute(&mut **tx).await?;
+            // The same favorite again violates the primary key
+            sqlx::query("INSERT INTO favorites (user_id, kind, item_id) VALUES ('partial_user', 'exercise', 'squat')")
+                .execute(&mut **tx).await?;
+            Ok(())
+        })).await;
+
+        assert!(result.is_err());
+        assert!(db.get_user("partial_user").await.unwrap().is_none());
+        assert!(db.list_favorites("partial_user", None).await.unwrap().is_empty());
+        assert_eq!(db.health_check().await.unwrap().users_count, before.users_count);
+
+        // A failure the closure reports itself rolls back the same way
+        let result: Result<()> = db.with_transaction(|tx| Box::pin(async move {
+            sqlx::query("UPDATE users SET name = 'Renamed' WHERE id = 'test_user'")
+                .execute(&mut **tx).await?;
+            Err(anyhow::anyhow!("grocery list could not be built"))
+        })).await;
+        assert_eq!(result.unwrap_err().to_string(), "grocery list could not be built");
+        assert_eq!(db.get_user("test_user").await.unwrap().unwrap().name, "Test User");
+    }
+
+    #[tokio::test]
+    async fn test_operations_behind_a_held_write_lock_time_out() {
+        use sqlx::{ConnectOptions, Connection};
+
+        let dir = tempdir().unwrap();
+        let mut config = test_config(dir.path(), 1);
+        config.query_timeout_ms = 200;
+        config.busy_timeout_ms = 1_000;
+        let db = DatabaseManager::new(&config).await.unwrap();
+        db.save_user(&test_user()).await.unwrap();
+
+        // A write transaction holding the only connection: a competing read
+        // waits for it, then gives up
+        let mut tx = db.pool.begin().await.unwrap();
+        sqlx::query("UPDATE users SET name = 'Locked' WHERE id = 'test_user'")
+            .execute(&mut *tx).await.unwrap();
+        let started = std::time::Instant::now();
+        let error = db.get_user("test_user").await.unwrap_err();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        let timeout = error.downcast_ref::<QueryTimeout>().expect("expected a query timeout");
+        assert_eq!(timeout.operation, "get_user");
+        assert_eq!(timeout.timeout_ms, 200);
+        tx.rollback().await.unwrap();
+        assert_eq!(db.get_user("test_user").await.unwrap().unwrap().name, "Test User");
+
+        // Another process holding the write lock: SQLite retries the write
+        // until the query timeout cuts it off
+        let mut other = SqliteConnectOptions::from_str(&config.url).unwrap().connect().await.unwrap();
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut other).await.unwrap();
+        let mut user = test_user();
+        user.id = "blocked_user".to_string();
+        let error = db.save_user(&user).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<QueryTimeout>().unwrap().operation, "save_user");
+        sqlx::query("ROLLBACK").execute(&mut other).await.unwrap();
+        other.close().await.unwrap();
+
+        let health = db.health_check().await.unwrap();
+        assert_eq!(health.query_timeouts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_migrates_pre_versioning_database() {
+        let dir = tempdir().unwrap();
+        let config = test_config(dir.path(), 2);
+
+        // Fixture: the schema as created before migrations existed
+        {
+            let options = SqliteConnectOptions::from_str(&config.url).unwrap().create_if_missing(true);
+            let pool = SqlitePool::connect_with(options).await.unwrap();
+            for sql in [
+                "CREATE TABLE users (id TEXT PRIMARY KEY, name TEXT NOT NULL, age INTEGER NOT NULL, \
+                 height REAL NOT NULL, weight REAL NOT NULL, fitness_level TEXT NOT NULL, \
+                 goals TEXT NOT NULL, preferences TEXT NOT NULL, \
+                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP, updated_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+                "CREATE TABLE workout_sessions (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, date TEXT NOT NULL, \
+                 total_duration_minutes INTEGER NOT NULL, calories_burned REAL, user_rating INTEGER, notes TEXT, \
+                 created_at DATETIME DEFAULT CURRENT_TIMESTAMP)",
+                "INSERT INTO users (id, name, age, height, weight, fitness_level, goals, preferences) \
+                 VALUES ('legacy_user', 'Legacy User', 40, 170.0, 80.0, '\"Beginner\"', '[\"WeightLoss\"]', \
+                 '{\"preferred_exercise_types\":[],\"available_equipment\":[],\"workout_duration_minutes\":30,\
+                 \"workouts_per_week\":2,\"preferred_time_of_day\":null}')",
+                "INSERT INTO workout_sessions (id, user_id, date, total_duration_minutes, calories_burned, user_rating) \
+                 VALUES ('legacy_workout', 'legacy_user', '2025-01-01', 30, 150.0, 4)",
+            ] {
+                sqlx::query(sql).execute(&pool).await.unwrap();
+            }
+            pool.close().await;
+        }
+
+        let db = DatabaseManager::new(&config).await.unwrap();
+        let latest = MIGRATIONS.last().unwrap().version;
+        assert_eq!(db.schema_version().await.unwrap(), latest);
+        assert_eq!(db.health_check().await.unwrap().schema_version, latest);
+
+        let user = db.get_user("legacy_user").await.unwrap().expect("user survives migration");
+        assert_eq!(user.name, "Legacy User");
+        let workouts = db.get_user_workouts("legacy_user").await.unwrap();
+        assert_eq!(workouts.len(), 1);
+        assert_eq!(workouts[0].calories_burned, Some(150.0));
+        assert!(!workouts[0].calories_estimated);
+
+        // Re-opening an up-to-date database applies nothing
+        drop(db);
+        let reopened = DatabaseManager::new(&config).await.unwrap();
+        assert_eq!(reopened.schema_version().await.unwrap(), latest);
+    }
 }
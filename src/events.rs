@@ -0,0 +1,73 @@
+// src/events.rs - In-process bus for domain events, so other subsystems can
+// observe what happened without the core methods calling them. Best effort:
+// webhooks, which must not lose events, are queued durably when an event is
+// published instead of through the bus.
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast;
+
+use crate::models::goal::UserGoal;
+use crate::models::workout::WorkoutSession;
+
+/// Events buffered per subscriber before a slow one starts missing some
+pub const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// Something that happened in the advisor that other subsystems may react to.
+/// Each carries when it happened, which subscribers may see much later.
+#[derive(Debug, Clone)]
+pub enum DomainEvent {
+    UserRegistered { user_id: String, occurred_at: DateTime<Utc> },
+    WorkoutLogged { workout: WorkoutSession, occurred_at: DateTime<Utc> },
+    GoalCompleted { goal: UserGoal, occurred_at: DateTime<Utc> },
+    /// A meal plan optimization finished; `plan` is the optimizer's solution
+    PlanOptimized { user_id: String, plan: serde_json::Value, occurred_at: DateTime<Utc> },
+}
+
+impl DomainEvent {
+    pub fn user_id(&self) -> &str {
+        match self {
+            DomainEvent::UserRegistered { user_id, .. } => user_id,
+            DomainEvent::WorkoutLogged { workout, .. } => &workout.user_id,
+            DomainEvent::GoalCompleted { goal, .. } => &goal.user_id,
+            DomainEvent::PlanOptimized { user_id, .. } => user_id,
+        }
+    }
+
+    pub fn occurred_at(&self) -> DateTime<Utc> {
+        match self {
+            DomainEvent::UserRegistered { occurred_at, .. }
+            | DomainEvent::WorkoutLogged { occurred_at, .. }
+            | DomainEvent::GoalCompleted { occurred_at, .. }
+            | DomainEvent::PlanOptimized { occurred_at, .. } => *occurred_at,
+        }
+    }
+}
+
+/// Fan-out of domain events to every subscriber. Subscribers only see events
+/// published after they subscribed, so take the receiver before spawning the
+/// task that consumes it.
+pub struct EventBus {
+    sender: broadcast::Sender<DomainEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DomainEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: DomainEvent) {
+        // No subscribers just means nothing is interested in this event
+        let _ = self.sender.send(event);
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,69 @@
+// src/experiments.rs - Deterministic assignment of users to experiment
+// variants, so alternatives can be compared on real traffic
+
+use ring::digest;
+
+/// Splits meal plan optimizations across `experiments.meal_plan_algorithm`
+pub const MEAL_PLAN_ALGORITHM: &str = "meal_plan_algorithm";
+
+/// Which of `variants` buckets a user is in. The bucket depends only on the
+/// experiment name and user id, so a user sees the same variant on every
+/// request and across restarts, while different experiments split users
+/// independently. SHA-256 is used rather than `DefaultHasher`, whose output
+/// may change between Rust releases.
+pub fn bucket(experiment: &str, user_id: &str, variants: usize) -> usize {
+    let mut input = Vec::with_capacity(experiment.len() + user_id.len() + 1);
+    input.extend_from_slice(experiment.as_bytes());
+    input.push(0);
+    input.extend_from_slice(user_id.as_bytes());
+    let hash = digest::digest(&digest::SHA256, &input);
+
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&hash.as_ref()[..8]);
+    (u64::from_be_bytes(prefix) % variants.max(1) as u64) as usize
+}
+
+/// The user's variant, or `None` when the experiment has no variants (i.e.
+/// isn't running)
+pub fn assign<'a, T>(experiment: &str, user_id: &str, variants: &'a [T]) -> Option<&'a T> {
+    if variants.is_empty() {
+        return None;
+    }
+    variants.get(bucket(experiment, user_id, variants.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_stable_and_roughly_even() {
+        let variants = ["control", "treatment"];
+        let first = assign(MEAL_PLAN_ALGORITHM, "user_42", &variants);
+        for _ in 0..10 {
+            assert_eq!(assign(MEAL_PLAN_ALGORITHM, "user_42", &variants), first);
+        }
+        assert_eq!(assign::<&str>(MEAL_PLAN_ALGORITHM, "user_42", &[]), None);
+
+        let users = 10_000;
+        for variant_count in [2, 3] {
+            let mut counts = vec![0usize; variant_count];
+            for i in 0..users {
+                counts[bucket(MEAL_PLAN_ALGORITHM, &format!("synthetic_user_{}", i), variant_count)] += 1;
+            }
+            let expected = users / variant_count;
+            for count in counts {
+                assert!(count.abs_diff(expected) < expected / 10, "uneven split: {} of {}", count, expected);
+            }
+        }
+
+        // Another experiment splits the same users differently
+        let moved = (0..1000)
+            .filter(|i| {
+                let user = format!("synthetic_user_{}", i);
+                bucket(MEAL_PLAN_ALGORITHM, &user, 2) != bucket("recipe_ranking", &user, 2)
+            })
+            .count();
+        assert!((400..600).contains(&moved));
+    }
+}
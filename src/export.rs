@@ -0,0 +1,133 @@
+// src/export.rs - Downloadable archive of everything held about a user,
+// as one self-describing JSON document
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::Serialize;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use crate::models::system::PageRequest;
+use crate::models::user::User;
+use crate::models::workout::{CarbCyclePlan, WeeklySchedule};
+use crate::FitnessAdvisor;
+
+/// Bumped whenever a section changes shape incompatibly
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+
+/// Keys following the header, in the order they're written
+pub const EXPORT_SECTIONS: &[&str] = &[
+    "profile",
+    "workouts",
+    "measurements",
+    "food_log",
+    "goals",
+    "achievements",
+    "favorites",
+    "activity_metrics",
+    "sleep",
+    "plans",
+];
+
+/// Workouts are the bulk of a long-time user's data, so they're read and
+/// sent a page at a time
+const WORKOUT_PAGE_SIZE: u32 = PageRequest::MAX_LIMIT;
+
+/// Chunks produced ahead of a slow client
+const BUFFERED_CHUNKS: usize = 8;
+
+#[derive(Serialize)]
+struct Header<'a> {
+    schema_version: u32,
+    generated_at: DateTime<Utc>,
+    user_id: &'a str,
+    sections: &'a [&'a str],
+}
+
+/// Plans aren't stored, so the export has the ones generated from the
+/// user's data as it is now
+#[derive(Serialize)]
+struct Plans {
+    weekly_schedule: WeeklySchedule,
+    carb_cycle: CarbCyclePlan,
+}
+
+/// The export, produced section by section as the client reads it. A
+/// failure part way through ends the stream with an error, leaving the
+/// client a truncated document rather than a silently incomplete one.
+pub fn stream(advisor: Arc<FitnessAdvisor>, user: User) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+    let (tx, rx) = mpsc::channel(BUFFERED_CHUNKS);
+    tokio::spawn(async move {
+        let user_id = user.id.clone();
+        let mut out = Chunks { tx };
+        if let Err(e) = write(&advisor, user, &mut out).await {
+            warn!("Export for user {} stopped: {}", user_id, e);
+            let _ = out.tx.send(Err(std::io::Error::other(e.to_string()))).await;
+        }
+    });
+    futures_util::stream::unfold(rx, |mut rx| async move { rx.recv().await.map(|chunk| (chunk, rx)) })
+}
+
+struct Chunks {
+    tx: mpsc::Sender<Result<Bytes, std::io::Error>>,
+}
+
+impl Chunks {
+    async fn send(&mut self, chunk: Vec<u8>) -> anyhow::Result<()> {
+        self.tx.send(Ok(Bytes::from(chunk))).await
+            .map_err(|_| anyhow::anyhow!("client stopped reading"))
+    }
+
+    async fn section<T: Serialize>(&mut self, name: &str, value: &T) -> anyhow::Result<()> {
+        let mut chunk = format!(",\"{}\":", name).into_bytes();
+        serde_json::to_writer(&mut chunk, value)?;
+        self.send(chunk).await
+    }
+}
+
+async fn write(advisor: &FitnessAdvisor, user: User, out: &mut Chunks) -> anyhow::Result<()> {
+    let mut header = serde_json::to_vec(&Header {
+        schema_version: EXPORT_SCHEMA_VERSION,
+        generated_at: Utc::now(),
+        user_id: &user.id,
+        sections: EXPORT_SECTIONS,
+    })?;
+    // Leave the object open for the sections
+    header.pop();
+    out.send(header).await?;
+    out.section("profile", &user).await?;
+
+    out.send(b",\"workouts\":[".to_vec()).await?;
+    let mut offset = 0;
+    loop {
+        let page = advisor.get_user_workouts_page(&user.id, PageRequest::new(WORKOUT_PAGE_SIZE, offset)).await?;
+        let mut chunk = Vec::new();
+        for workout in &page.items {
+            if offset > 0 || !chunk.is_empty() {
+                chunk.push(b',');
+            }
+            serde_json::to_writer(&mut chunk, workout)?;
+        }
+        out.send(chunk).await?;
+        offset += page.items.len() as u32;
+        if page.items.is_empty() || offset as u64 >= page.total {
+            break;
+        }
+    }
+    out.send(b"]".to_vec()).await?;
+
+    out.section("measurements", &advisor.get_measurements(&user.id).await?).await?;
+    out.section("food_log", &advisor.get_food_log(&user.id).await?).await?;
+    out.section("goals", &advisor.goals(&user.id).await?).await?;
+    out.section("achievements", &advisor.achievements(&user.id).await?).await?;
+    out.section("favorites", &advisor.list_favorites(&user.id, None).await?).await?;
+    out.section("activity_metrics", &advisor.get_activity_metrics(&user.id).await?).await?;
+    out.section("sleep", &advisor.get_sleep_sessions(&user.id).await?).await?;
+    out.section("plans", &Plans {
+        weekly_schedule: advisor.recommend_week(&user.id).await?,
+        carb_cycle: advisor.carb_cycle_plan(&user.id).await?,
+    }).await?;
+    out.send(b"}".to_vec()).await
+}
@@ -0,0 +1,266 @@
+// src/fdc_client.rs - USDA FoodData Central lookups for foods missing from the local table
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::config::FdcConfig;
+use crate::models::food::{Food, FoodCategory, NutritionFacts, TasteProfile};
+
+/// Client for the FoodData Central REST API. Every food it returns is kept
+/// in memory, so repeated lookups of the same name or FDC id are answered
+/// without another request.
+#[derive(Debug)]
+pub struct FdcClient {
+    client: Client,
+    base_url: String,
+    api_key: String,
+    cache: RwLock<HashMap<String, Food>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FdcSearchResponse {
+    foods: Vec<FdcFood>,
+}
+
+/// The fields shared by search results and the food details endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FdcFood {
+    fdc_id: u64,
+    description: String,
+    #[serde(default)]
+    food_category: Option<FdcCategory>,
+    #[serde(default)]
+    food_nutrients: Vec<FdcNutrient>,
+}
+
+/// Search results name the category; food details nest it in an object
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum FdcCategory {
+    Name(String),
+    Described { description: String },
+}
+
+/// Search results carry `nutrientNumber`/`value`, food details a nested
+/// `nutrient.number` and `amount`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FdcNutrient {
+    #[serde(default)]
+    nutrient_number: Option<String>,
+    #[serde(default)]
+    nutrient: Option<FdcNutrientInfo>,
+    #[serde(default)]
+    value: Option<f64>,
+    #[serde(default)]
+    amount: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FdcNutrientInfo {
+    number: String,
+}
+
+impl FdcClient {
+    /// `None` when no API key is configured
+    pub fn from_config(config: &FdcConfig) -> Option<Self> {
+        let api_key = config.api_key.clone().filter(|key| !key.trim().is_empty())?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Some(Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            api_key,
+            cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Look a food up by FDC id (all digits) or by name. Cached foods are
+    /// returned without calling the API; `None` when FDC has no match.
+    pub async fn lookup(&self, query: &str) -> Result<Option<Food>> {
+        let key = query.trim().to_lowercase();
+        if let Some(food) = self.cache.read().await.get(&key) {
+            return Ok(Some(food.clone()));
+        }
+
+        let found = match key.parse::<u64>() {
+            Ok(fdc_id) => self.fetch_by_id(fdc_id).await?,
+            Err(_) => self.search(&key).await?,
+        };
+        let Some(food) = found.map(FdcFood::into_food) else {
+            return Ok(None);
+        };
+
+        info!("Fetched {} from FoodData Central", food.name);
+        let mut cache = self.cache.write().await;
+        cache.insert(key, food.clone());
+        cache.insert(food.id.trim_start_matches("fdc_").to_string(), food.clone());
+        Ok(Some(food))
+    }
+
+    async fn search(&self, name: &str) -> Result<Option<FdcFood>> {
+        let response = self.client
+            .get(format!("{}/foods/search", self.base_url))
+            .query(&[("query", name), ("pageSize", "1"), ("api_key", &self.api_key)])
+            .send()
+            .await
+            .map_err(|e| anyhow!("FoodData Central search failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("FoodData Central search failed with status: {}", response.status()));
+        }
+        let results: FdcSearchResponse = response.json().await
+            .map_err(|e| anyhow!("Failed to parse FoodData Central search: {}", e))?;
+        Ok(results.foods.into_iter().next())
+    }
+
+    async fn fetch_by_id(&self, fdc_id: u64) -> Result<Option<FdcFood>> {
+        let response = self.client
+            .get(format!("{}/food/{}", self.base_url, fdc_id))
+            .query(&[("api_key", &self.api_key)])
+            .send()
+            .await
+            .map_err(|e| anyhow!("FoodData Central lookup failed: {}", e))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("FoodData Central lookup failed with status: {}", response.status()));
+        }
+        let food = response.json().await
+            .map_err(|e| anyhow!("Failed to parse FoodData Central food: {}", e))?;
+        Ok(Some(food))
+    }
+}
+
+impl FdcFood {
+    /// Nutrient amount per 100g by FDC nutrient number, 0 when not reported
+    fn nutrient(&self, number: &str) -> f64 {
+        self.food_nutrients.iter()
+            .find(|n| {
+                n.nutrient_number.as_deref() == Some(number)
+                    || n.nutrient.as_ref().is_some_and(|info| info.number == number)
+            })
+            .and_then(|n| n.value.or(n.amount))
+            .unwrap_or(0.0)
+    }
+
+    fn category(&self) -> FoodCategory {
+        match &self.food_category {
+            Some(FdcCategory::Name(name)) => FoodCategory::from_description(name),
+            Some(FdcCategory::Described { description }) => FoodCategory::from_description(description),
+            None => FoodCategory::Protein,
+        }
+    }
+
+    fn into_food(self) -> Food {
+        let nutrition_per_100g = NutritionFacts {
+            calories: self.nutrient("208"),
+            protein_g: self.nutrient("203"),
+            carbs_g: self.nutrient("205"),
+            fat_g: self.nutrient("204"),
+            fiber_g: self.nutrient("291"),
+            sugar_g: self.nutrient("269"),
+            sodium_mg: self.nutrient("307"),
+            potassium_mg: self.nutrient("306"),
+            calcium_mg: self.nutrient("301"),
+            iron_mg: self.nutrient("303"),
+            vitamin_c_mg: self.nutrient("401"),
+            vitamin_d_iu: self.nutrient("324"),
+            vitamin_b12_mcg: self.nutrient("418"),
+            folate_mcg: self.nutrient("417"),
+            omega3_g: self.nutrient("851"),
+            omega6_g: self.nutrient("675"),
+        };
+
+        Food {
+            id: format!("fdc_{}", self.fdc_id),
+            category: self.category(),
+            name: self.description,
+            nutrition_per_100g,
+            allergens: vec![],
+            dietary_flags: vec![],
+            seasonality: None,
+            cost_per_100g: None,
+            availability_score: 0.5,
+            glycemic_index: None,
+            taste_profile: TasteProfile::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Query, routing::get, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Stands in for FoodData Central's search endpoint, counting requests
+    async fn mock_fdc(requests: Arc<AtomicUsize>) -> String {
+        let app = Router::new().route("/foods/search", get(move |Query(params): Query<HashMap<String, String>>| {
+            let requests = requests.clone();
+            async move {
+                requests.fetch_add(1, Ordering::SeqCst);
+                assert_eq!(params.get("api_key").map(String::as_str), Some("test-key"));
+                Json(serde_json::json!({
+                    "foods": [{
+                        "fdcId": 169251,
+                        "description": "Mushrooms, white, raw",
+                        "foodCategory": "Vegetables and Vegetable Products",
+                        "foodNutrients": [
+                            { "nutrientNumber": "208", "value": 22.0 },
+                            { "nutrientNumber": "203", "value": 3.09 },
+                            { "nutrientNumber": "205", "value": 3.26 },
+                            { "nutrientNumber": "204", "value": 0.34 },
+                            { "nutrientNumber": "291", "value": 1.0 }
+                        ]
+                    }]
+                }))
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_looked_up_food_is_cached() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let config = FdcConfig {
+            api_key: Some("test-key".to_string()),
+            base_url: mock_fdc(requests.clone()).await,
+            ..FdcConfig::default()
+        };
+        let client = FdcClient::from_config(&config).unwrap();
+
+        let food = client.lookup("White Mushrooms").await.unwrap().unwrap();
+        assert_eq!(food.id, "fdc_169251");
+        assert_eq!(food.category, FoodCategory::Vegetables);
+        assert_eq!(food.nutrition_per_100g.calories, 22.0);
+        assert_eq!(food.nutrition_per_100g.protein_g, 3.09);
+        assert_eq!(food.nutrition_per_100g.carbs_g, 3.26);
+        assert_eq!(food.nutrition_per_100g.fat_g, 0.34);
+
+        // Same name in another case, then the FDC id: both from the cache
+        let again = client.lookup("white mushrooms").await.unwrap().unwrap();
+        let by_id = client.lookup("169251").await.unwrap().unwrap();
+        assert_eq!(again.nutrition_per_100g, food.nutrition_per_100g);
+        assert_eq!(by_id.id, food.id);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        assert!(FdcClient::from_config(&FdcConfig::default()).is_none());
+    }
+}
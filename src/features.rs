@@ -0,0 +1,69 @@
+// src/features.rs - Feature flags from `[features]` in the config, so new
+// functionality can be switched off or rolled out gradually by configuration
+// rather than by redeploying
+
+use std::collections::HashMap;
+
+use crate::config::FeatureFlag;
+
+/// POST /api/nutrition/barcode
+pub const BARCODE_SCAN: &str = "barcode_scan";
+/// GET /api/users/:user_id/report.pdf
+pub const PDF_REPORT: &str = "pdf_report";
+
+/// Whether `flag` is on for `user_id`. Flags missing from the config are on,
+/// so only features being held back need listing. A partial rollout buckets
+/// users the same way experiments do, so a user keeps the feature once they
+/// have it and raising the percentage only adds users. Anonymous callers
+/// can't be bucketed and only see fully rolled out features.
+pub fn is_enabled(flags: &HashMap<String, FeatureFlag>, flag: &str, user_id: Option<&str>) -> bool {
+    let Some(config) = flags.get(flag) else {
+        return true;
+    };
+    if !config.enabled {
+        return false;
+    }
+    if config.rollout_percent >= 100 {
+        return true;
+    }
+    match user_id {
+        Some(user_id) => crate::experiments::bucket(flag, user_id, 100) < config.rollout_percent as usize,
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(enabled: bool, rollout_percent: u8) -> HashMap<String, FeatureFlag> {
+        HashMap::from([(PDF_REPORT.to_string(), FeatureFlag { enabled, rollout_percent })])
+    }
+
+    #[test]
+    fn test_rollout_percentage_hides_exposes_and_is_stable() {
+        let users: Vec<String> = (0..1000).map(|i| format!("user_{}", i)).collect();
+        let enabled_for = |flags: &HashMap<String, FeatureFlag>| {
+            users.iter().filter(|user| is_enabled(flags, PDF_REPORT, Some(user))).count()
+        };
+
+        assert_eq!(enabled_for(&flags(true, 0)), 0);
+        assert_eq!(enabled_for(&flags(true, 100)), users.len());
+        assert_eq!(enabled_for(&flags(false, 100)), 0);
+        assert!(is_enabled(&flags(true, 100), PDF_REPORT, None));
+        assert!(!is_enabled(&flags(true, 50), PDF_REPORT, None));
+        assert!(is_enabled(&HashMap::new(), PDF_REPORT, Some("user_1")));
+
+        let half = flags(true, 50);
+        let count = enabled_for(&half);
+        assert!((400..=600).contains(&count), "50% rollout enabled {} of 1000", count);
+        for user in &users {
+            let first = is_enabled(&half, PDF_REPORT, Some(user));
+            assert_eq!(is_enabled(&half, PDF_REPORT, Some(user)), first);
+            // Widening the rollout never takes the feature away
+            if first {
+                assert!(is_enabled(&flags(true, 80), PDF_REPORT, Some(user)));
+            }
+        }
+    }
+}
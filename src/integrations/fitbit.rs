@@ -0,0 +1,192 @@
+// src/integrations/fitbit.rs - Daily activity and heart rate from the Fitbit Web API
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::time::Duration;
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::config::FitbitConfig;
+use crate::models::activity::{ActivityMetric, ActivityMetricKind};
+
+/// `source` of every metric imported from Fitbit
+pub const FITBIT_SOURCE: &str = "fitbit";
+
+/// A user's OAuth tokens, issued when they authorized the app with Fitbit.
+/// Fitbit rotates both on refresh, so callers must keep the ones returned
+/// after an import.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct FitbitTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// What a sync stored. Fitbit can fail part way through, after the tokens
+/// were rotated; the days fetched until then are still stored.
+#[derive(Debug, Default)]
+pub struct FitbitImport {
+    /// Metric records that weren't already imported
+    pub imported: usize,
+    /// Why the sync stopped early, if it did
+    pub error: Option<String>,
+}
+
+/// Client for the parts of the Fitbit Web API the advisor imports
+#[derive(Debug)]
+pub struct FitbitClient {
+    client: Client,
+    base_url: String,
+    token_url: String,
+    client_id: String,
+    client_secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActivityResponse {
+    summary: ActivitySummary,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ActivitySummary {
+    #[serde(default)]
+    steps: u64,
+    #[serde(default)]
+    fairly_active_minutes: u32,
+    #[serde(default)]
+    very_active_minutes: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeartResponse {
+    #[serde(rename = "activities-heart", default)]
+    days: Vec<HeartDay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeartDay {
+    value: HeartValue,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HeartValue {
+    #[serde(default)]
+    resting_heart_rate: Option<f64>,
+    #[serde(default)]
+    heart_rate_zones: Vec<HeartRateZone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeartRateZone {
+    min: f64,
+    max: f64,
+    #[serde(default)]
+    minutes: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+}
+
+impl FitbitClient {
+    /// `None` unless both client credentials are configured
+    pub fn from_config(config: &FitbitConfig) -> Option<Self> {
+        let client_id = config.client_id.clone().filter(|id| !id.trim().is_empty())?;
+        let client_secret = config.client_secret.clone().filter(|secret| !secret.trim().is_empty())?;
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Some(Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            token_url: config.token_url.clone(),
+            client_id,
+            client_secret,
+        })
+    }
+
+    /// Steps, active minutes and resting and average heart rate for one
+    /// day. An expired access token is refreshed once and `tokens` updated.
+    pub async fn daily_metrics(&self, user_id: &str, tokens: &mut FitbitTokens, date: NaiveDate) -> Result<Vec<ActivityMetric>> {
+        let activity: ActivityResponse = self.get(tokens, &format!("/1/user/-/activities/date/{}.json", date)).await?;
+        let heart: HeartResponse = self.get(tokens, &format!("/1/user/-/activities/heart/date/{}/1d.json", date)).await?;
+        Ok(to_metrics(user_id, date, &activity.summary, heart.days.first().map(|day| &day.value)))
+    }
+
+    async fn get<T: DeserializeOwned>(&self, tokens: &mut FitbitTokens, path: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, path);
+        let mut response = self.client.get(&url).bearer_auth(&tokens.access_token).send().await
+            .map_err(|e| anyhow!("Fitbit request failed: {}", e))?;
+
+        if response.status() == StatusCode::UNAUTHORIZED {
+            *tokens = self.refresh(tokens).await?;
+            response = self.client.get(&url).bearer_auth(&tokens.access_token).send().await
+                .map_err(|e| anyhow!("Fitbit request failed: {}", e))?;
+        }
+        if !response.status().is_success() {
+            return Err(anyhow!("Fitbit request failed with status: {}", response.status()));
+        }
+        response.json().await.map_err(|e| anyhow!("Failed to parse Fitbit response: {}", e))
+    }
+
+    async fn refresh(&self, tokens: &FitbitTokens) -> Result<FitbitTokens> {
+        let response = self.client
+            .post(&self.token_url)
+            .basic_auth(&self.client_id, Some(&self.client_secret))
+            .form(&[("grant_type", "refresh_token"), ("refresh_token", tokens.refresh_token.as_str())])
+            .send()
+            .await
+            .map_err(|e| anyhow!("Fitbit token refresh failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(anyhow!("Fitbit token refresh failed with status: {}", response.status()));
+        }
+
+        let refreshed: TokenResponse = response.json().await
+            .map_err(|e| anyhow!("Failed to parse Fitbit token response: {}", e))?;
+        info!("Refreshed Fitbit access token");
+        Ok(FitbitTokens {
+            access_token: refreshed.access_token,
+            refresh_token: refreshed.refresh_token,
+        })
+    }
+}
+
+/// Fitbit reports a day without the tracker worn as zero steps, which is
+/// left out rather than counted. It has no daily average heart rate, so
+/// that is estimated from the minutes spent in each heart rate zone.
+fn to_metrics(user_id: &str, date: NaiveDate, activity: &ActivitySummary, heart: Option<&HeartValue>) -> Vec<ActivityMetric> {
+    let metric = |metric: ActivityMetricKind, value: f64| ActivityMetric {
+        user_id: user_id.to_string(),
+        date: date.to_string(),
+        source: FITBIT_SOURCE.to_string(),
+        metric,
+        value,
+    };
+
+    let mut metrics = Vec::new();
+    if activity.steps > 0 {
+        metrics.push(metric(ActivityMetricKind::Steps, activity.steps as f64));
+        let active_minutes = activity.fairly_active_minutes + activity.very_active_minutes;
+        metrics.push(metric(ActivityMetricKind::ActiveMinutes, active_minutes as f64));
+    }
+    if let Some(heart) = heart {
+        if let Some(resting) = heart.resting_heart_rate {
+            metrics.push(metric(ActivityMetricKind::RestingHeartRate, resting));
+        }
+        let minutes: f64 = heart.heart_rate_zones.iter().map(|zone| zone.minutes).sum();
+        if minutes > 0.0 {
+            let weighted: f64 = heart.heart_rate_zones.iter()
+                .map(|zone| zone.minutes * (zone.min + zone.max) / 2.0)
+                .sum();
+            metrics.push(metric(ActivityMetricKind::AverageHeartRate, (weighted / minutes).round()));
+        }
+    }
+    metrics
+}
@@ -0,0 +1,4 @@
+// src/integrations/mod.rs - Imports from third-party fitness services
+
+pub mod fitbit;
+pub mod myfitnesspal;
@@ -0,0 +1,156 @@
+// src/integrations/myfitnesspal.rs - Food diaries exported from MyFitnessPal
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::Deserialize;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::food::Food;
+use crate::models::food_log::FoodLogEntry;
+
+/// `source` of every imported diary entry
+pub const MYFITNESSPAL_SOURCE: &str = "myfitnesspal";
+
+/// One logged food, as in a JSON diary export. Macros left out are filled
+/// in from the nutrition database when the food and quantity are known.
+#[derive(Debug, Clone, Default, Deserialize, ToSchema)]
+pub struct DiaryRow {
+    /// YYYY-MM-DD or MM/DD/YYYY
+    pub date: String,
+    /// HH:MM, HH:MM:SS or "7:30 AM"
+    #[serde(default)]
+    pub time: Option<String>,
+    #[serde(default)]
+    pub meal: Option<String>,
+    pub food: String,
+    #[serde(default)]
+    pub quantity_g: Option<f64>,
+    #[serde(default)]
+    pub calories: Option<f64>,
+    #[serde(default)]
+    pub protein_g: Option<f64>,
+    #[serde(default)]
+    pub carbs_g: Option<f64>,
+    #[serde(default)]
+    pub fat_g: Option<f64>,
+}
+
+/// Column names accepted for each field, compared ignoring case
+const DATE_COLUMNS: &[&str] = &["date"];
+const TIME_COLUMNS: &[&str] = &["time"];
+const MEAL_COLUMNS: &[&str] = &["meal"];
+const FOOD_COLUMNS: &[&str] = &["food", "food name", "name", "description"];
+const QUANTITY_COLUMNS: &[&str] = &["quantity (g)", "quantity_g", "grams", "amount (g)"];
+const CALORIES_COLUMNS: &[&str] = &["calories", "energy (kcal)", "kcal"];
+const PROTEIN_COLUMNS: &[&str] = &["protein (g)", "protein_g", "protein"];
+const CARBS_COLUMNS: &[&str] = &["carbohydrates (g)", "carbohydrates", "carbs (g)", "carbs_g", "carbs"];
+const FAT_COLUMNS: &[&str] = &["fat (g)", "fat_g", "fat", "total fat"];
+
+/// Rows of a CSV diary with a header line. Only an unusable header fails
+/// the whole file; a row that can't be read is returned as its error.
+pub fn parse_csv(text: &str) -> Result<Vec<Result<DiaryRow, String>>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let headers: Vec<String> = reader.headers()
+        .map_err(|e| format!("Unreadable CSV header: {}", e))?
+        .iter()
+        .map(|header| header.to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| headers.iter().position(|header| names.contains(&header.as_str()));
+
+    let (Some(date), Some(food)) = (column(DATE_COLUMNS), column(FOOD_COLUMNS)) else {
+        return Err("The CSV needs a Date and a Food column".to_string());
+    };
+    let (time, meal, quantity) = (column(TIME_COLUMNS), column(MEAL_COLUMNS), column(QUANTITY_COLUMNS));
+    let (calories, protein, carbs, fat) =
+        (column(CALORIES_COLUMNS), column(PROTEIN_COLUMNS), column(CARBS_COLUMNS), column(FAT_COLUMNS));
+
+    Ok(reader.records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Unreadable row: {}", e))?;
+            let field = |index: Option<usize>| index
+                .and_then(|i| record.get(i))
+                .filter(|value| !value.is_empty())
+                .map(str::to_string);
+            let number = |index: Option<usize>, name: &str| -> Result<Option<f64>, String> {
+                // Exports format thousands with a separator, e.g. "1,250"
+                field(index)
+                    .map(|value| value.replace(',', "").parse::<f64>()
+                        .map_err(|_| format!("{} '{}' isn't a number", name, value)))
+                    .transpose()
+            };
+
+            Ok(DiaryRow {
+                date: field(Some(date)).unwrap_or_default(),
+                time: field(time),
+                meal: field(meal),
+                food: field(Some(food)).unwrap_or_default(),
+                quantity_g: number(quantity, "Quantity")?,
+                calories: number(calories, "Calories")?,
+                protein_g: number(protein, "Protein")?,
+                carbs_g: number(carbs, "Carbohydrates")?,
+                fat_g: number(fat, "Fat")?,
+            })
+        })
+        .collect())
+}
+
+/// Rows of a JSON diary, an array of `DiaryRow` objects
+pub fn parse_json(body: &[u8]) -> Result<Vec<Result<DiaryRow, String>>, String> {
+    let rows: Vec<serde_json::Value> = serde_json::from_slice(body)
+        .map_err(|e| format!("Expected a JSON array of diary rows: {}", e))?;
+    Ok(rows.into_iter()
+        .map(|row| serde_json::from_value(row).map_err(|e| format!("Unreadable row: {}", e)))
+        .collect())
+}
+
+/// The log entry for a row, with `food` the nutrition database's match for
+/// it. Imported macros always win over the database's.
+pub fn to_entry(user_id: &str, row: DiaryRow, food: Option<&Food>) -> Result<FoodLogEntry, String> {
+    let date = ["%Y-%m-%d", "%m/%d/%Y"].iter()
+        .find_map(|format| NaiveDate::parse_from_str(&row.date, format).ok())
+        .ok_or_else(|| format!("Date '{}' isn't YYYY-MM-DD or MM/DD/YYYY", row.date))?;
+    let time = row.time.as_deref()
+        .map(|time| {
+            ["%H:%M", "%H:%M:%S", "%I:%M %p"].iter()
+                .find_map(|format| NaiveTime::parse_from_str(time, format).ok())
+                .map(|time| time.format("%H:%M").to_string())
+                .ok_or_else(|| format!("Time '{}' isn't HH:MM", time))
+        })
+        .transpose()?;
+    let food_name = row.food.trim().to_string();
+    if food_name.is_empty() {
+        return Err("No food name".to_string());
+    }
+
+    let from_database = |per_100g: fn(&Food) -> f64| {
+        food.zip(row.quantity_g).map(|(food, grams)| per_100g(food) * grams / 100.0)
+    };
+    let calories = row.calories.or_else(|| from_database(|f| f.nutrition_per_100g.calories))
+        .ok_or_else(|| format!("No calories for '{}', and it isn't in the nutrition database", food_name))?;
+    let protein_g = row.protein_g.or_else(|| from_database(|f| f.nutrition_per_100g.protein_g)).unwrap_or(0.0);
+    let carbs_g = row.carbs_g.or_else(|| from_database(|f| f.nutrition_per_100g.carbs_g)).unwrap_or(0.0);
+    let fat_g = row.fat_g.or_else(|| from_database(|f| f.nutrition_per_100g.fat_g)).unwrap_or(0.0);
+    let amounts = [Some(calories), Some(protein_g), Some(carbs_g), Some(fat_g), row.quantity_g];
+    if amounts.iter().flatten().any(|amount| !amount.is_finite() || *amount < 0.0) {
+        return Err(format!("Negative or invalid amount for '{}'", food_name));
+    }
+
+    Ok(FoodLogEntry {
+        id: Uuid::new_v4().to_string(),
+        user_id: user_id.to_string(),
+        date: date.to_string(),
+        time,
+        meal: row.meal,
+        food_name,
+        food_id: food.map(|food| food.id.clone()),
+        quantity_g: row.quantity_g,
+        calories,
+        protein_g,
+        carbs_g,
+        fat_g,
+        source: MYFITNESSPAL_SOURCE.to_string(),
+    })
+}
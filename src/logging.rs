@@ -0,0 +1,189 @@
+// src/logging.rs - Tracing subscriber setup driven by the logging config
+
+use anyhow::{anyhow, Result};
+use tracing::Subscriber;
+use tracing_subscriber::{filter::LevelFilter, fmt::MakeWriter, EnvFilter};
+
+use crate::config::LoggingConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    fn parse(format: &str) -> Result<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            other => Err(anyhow!("Unknown log format: {} (expected \"pretty\" or \"json\")", other)),
+        }
+    }
+}
+
+/// What the subscriber is built from: a filter directive string and a format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogSettings {
+    pub filter: String,
+    pub format: LogFormat,
+}
+
+impl LogSettings {
+    /// Combine the configured level with the per-module levels. A non-empty
+    /// `rust_log` replaces both so a single run can be made noisier without
+    /// touching the config file.
+    pub fn from_config(config: &LoggingConfig, rust_log: Option<&str>) -> Result<Self> {
+        let format = LogFormat::parse(&config.format)?;
+
+        let filter = match rust_log.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(directives) => directives.to_string(),
+            None => {
+                parse_level(&config.level)?;
+                let mut modules: Vec<_> = config.modules.iter().collect();
+                modules.sort();
+                let mut directives = vec![config.level.clone()];
+                for (module, level) in modules {
+                    parse_level(level)?;
+                    directives.push(format!("{}={}", module, level));
+                }
+                directives.join(",")
+            }
+        };
+        EnvFilter::try_new(&filter)
+            .map_err(|e| anyhow!("Invalid log filter \"{}\": {}", filter, e))?;
+
+        Ok(Self { filter, format })
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter> {
+    level.parse()
+        .map_err(|_| anyhow!("Unknown log level: {}", level))
+}
+
+/// Build the subscriber without installing it, writing to `writer`. JSON
+/// lines include the enclosing spans, so anything logged while handling a
+/// request carries the `request_id` set by the tracing middleware.
+pub fn subscriber<W>(settings: &LogSettings, writer: W) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let filter = EnvFilter::new(&settings.filter);
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer);
+
+    match settings.format {
+        LogFormat::Pretty => Box::new(builder.finish()),
+        LogFormat::Json => Box::new(
+            builder
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .finish(),
+        ),
+    }
+}
+
+/// Install the global subscriber, honouring `RUST_LOG` over the config
+pub fn init(config: &LoggingConfig) -> Result<()> {
+    let rust_log = std::env::var("RUST_LOG").ok();
+    let settings = LogSettings::from_config(config, rust_log.as_deref())?;
+    tracing::subscriber::set_global_default(subscriber(&settings, std::io::stdout))
+        .map_err(|e| anyhow!("Failed to install log subscriber: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    fn logging_config(level: &str, format: &str, modules: &[(&str, &str)]) -> LoggingConfig {
+        LoggingConfig {
+            level: level.to_string(),
+            format: format.to_string(),
+            file_enabled: false,
+            file_path: String::new(),
+            modules: modules.iter()
+                .map(|(m, l)| (m.to_string(), l.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Captured {
+        type Writer = Captured;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    #[test]
+    fn test_settings_combine_level_and_module_levels() {
+        let config = logging_config("info", "json", &[("tower_http", "debug"), ("sqlx", "warn")]);
+        let settings = LogSettings::from_config(&config, None).unwrap();
+
+        assert_eq!(settings.filter, "info,sqlx=warn,tower_http=debug");
+        assert_eq!(settings.format, LogFormat::Json);
+    }
+
+    #[test]
+    fn test_rust_log_overrides_config() {
+        let config = logging_config("info", "pretty", &[("sqlx", "warn")]);
+
+        let settings = LogSettings::from_config(&config, Some("debug,hyper=info")).unwrap();
+        assert_eq!(settings.filter, "debug,hyper=info");
+        assert_eq!(settings.format, LogFormat::Pretty);
+
+        let settings = LogSettings::from_config(&config, Some("  ")).unwrap();
+        assert_eq!(settings.filter, "info,sqlx=warn");
+    }
+
+    #[test]
+    fn test_invalid_settings_are_rejected() {
+        assert!(LogSettings::from_config(&logging_config("info", "xml", &[]), None).is_err());
+        assert!(LogSettings::from_config(&logging_config("loud", "json", &[]), None).is_err());
+        assert!(LogSettings::from_config(&logging_config("info", "json", &[("sqlx", "loud")]), None).is_err());
+    }
+
+    #[test]
+    fn test_json_output_carries_request_id() {
+        let settings = LogSettings::from_config(&logging_config("info", "json", &[]), None).unwrap();
+        let captured = Captured::default();
+
+        tracing::subscriber::with_default(subscriber(&settings, captured.clone()), || {
+            let span = tracing::info_span!("request", request_id = "req-123");
+            span.in_scope(|| {
+                tracing::info!(status = 200, "handled");
+                tracing::debug!("filtered out");
+            });
+        });
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let line: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "handled");
+        assert_eq!(line["fields"]["status"], 200);
+        assert_eq!(line["span"]["request_id"], "req-123");
+    }
+}
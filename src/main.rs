@@ -2,14 +2,29 @@
 
 mod database;
 mod ml_client;
+mod fdc_client;
+mod open_food_facts;
 mod config;
 mod core;
 mod models;
 mod advisors;
 mod sample_data;
 mod api;
+mod middleware;
+mod shutdown;
 mod ai_analytics;
 mod websocket;
+mod auth;
+mod openapi;
+mod logging;
+mod report;
+mod webhooks;
+mod events;
+mod caching;
+mod experiments;
+mod features;
+mod integrations;
+mod export;
 
 use std::sync::Arc;
 use anyhow::Result;
@@ -17,169 +32,539 @@ use uuid::Uuid;
 use tracing::{info, warn};
 
 // Database imports
-use database::DatabaseManager;
+use database::{DatabaseManager, ExerciseSeeding, UserUpdate};
 use ml_client::MLServiceClient;
 use config::Config;
-use advisors::{MenuOptimizer, menu_optimizer::DataLoader};
+use advisors::{MenuOptimizer, ProgressAnalyzer, WorkoutRecommender, menu_optimizer::DataLoader, nutrition_calc};
+use advisors::workout_recommender::AlternativeConstraints;
 use models::*;
 use ai_analytics::*;
+use events::{DomainEvent, EventBus};
 
 
 pub struct FitnessAdvisor {
     db: Arc<DatabaseManager>,
+    /// What happened, for subsystems that react to it. Goals and
+    /// achievements are updated inline instead, so they can be read back as
+    /// soon as the triggering request returns.
+    events: EventBus,
+    /// Wakes the webhook dispatcher when deliveries are queued
+    webhook_wake: Arc<tokio::sync::Notify>,
 }
 
 impl FitnessAdvisor {
-    pub async fn new(database_url: &str) -> Result<Self> {
-        let db = Arc::new(DatabaseManager::new(database_url).await?);
-        Ok(Self { db })
+    pub async fn new(database_config: &config::DatabaseConfig) -> Result<Self> {
+        let db = Arc::new(DatabaseManager::new(database_config).await?);
+        Ok(Self { db, events: EventBus::new(), webhook_wake: Arc::new(tokio::sync::Notify::new()) })
     }
 
-    pub async fn register_user(&self, user: User) -> Result<()> {
-        self.db.save_user(&user).await
+    /// Receive the domain events published from now on
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<DomainEvent> {
+        self.events.subscribe()
+    }
+
+    /// The background task that sends this advisor's webhook deliveries
+    pub fn webhook_dispatcher(&self, config: &config::WebhookConfig) -> webhooks::WebhookDispatcher {
+        webhooks::WebhookDispatcher::new(self.db.clone(), config, self.webhook_wake.clone())
+    }
+
+    /// Queue the event's webhook deliveries, then publish it on the bus. The
+    /// bus drops events for subscribers that fall behind, so deliveries are
+    /// written here, before anything can be lost. Like goals and
+    /// achievements, a failure is logged rather than failing what triggered
+    /// the event.
+    async fn emit(&self, event: DomainEvent) {
+        if let Some((webhook_event, payload)) = webhooks::payload(&event) {
+            let now = webhooks::db_timestamp(chrono::Utc::now());
+            match self.db.enqueue_webhook_deliveries(webhook_event, event.user_id(), &payload, &now).await {
+                Ok(0) => {}
+                Ok(_) => self.webhook_wake.notify_one(),
+                Err(e) => warn!("Failed to queue {} webhooks for user {}: {}", webhook_event.as_str(), event.user_id(), e),
+            }
+        }
+        self.events.publish(event);
+    }
+
+    pub async fn create_webhook(&self, url: String, events: Vec<WebhookEvent>, secret: &str) -> Result<WebhookSubscription> {
+        let subscription = WebhookSubscription {
+            id: Uuid::new_v4().to_string(),
+            url,
+            events,
+            created_at: webhooks::db_timestamp(chrono::Utc::now()),
+        };
+        self.db.insert_webhook(&subscription, secret).await?;
+        Ok(subscription)
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<WebhookSubscription>> {
+        self.db.list_webhooks().await
+    }
+
+    pub async fn delete_webhook(&self, subscription_id: &str) -> Result<bool> {
+        self.db.delete_webhook(subscription_id).await
+    }
+
+    pub async fn webhook_deliveries(&self, subscription_id: &str) -> Result<Vec<WebhookDelivery>> {
+        self.db.webhook_deliveries(subscription_id).await
+    }
+
+    /// Register a new user, and when a password is given, their credentials
+    /// in the same transaction. Only a salted hash of the password is stored.
+    /// Returns false, writing nothing, when the id is already taken.
+    pub async fn register_user(&self, user: User, password: Option<&str>) -> Result<bool> {
+        user.validate_fields()
+            .map_err(|errors| anyhow::anyhow!("Invalid user: {}", describe_field_errors(&errors)))?;
+        if password.is_some_and(|password| password.len() < auth::MIN_PASSWORD_LEN) {
+            return Err(anyhow::anyhow!("Password must be at least {} characters", auth::MIN_PASSWORD_LEN));
+        }
+
+        self.insert_user(&user, password.map(str::to_string)).await
+    }
+
+    /// Register several users, each in its own transaction, so one bad entry
+    /// doesn't stop the rest. Existing ids are reported as duplicates rather
+    /// than overwritten, and, as with sign-up, admins can't be created here,
+    /// neither by role nor under one of the configured `admin_ids`.
+    pub async fn register_users_batch(&self, registrations: Vec<(User, Option<String>)>, admin_ids: &[String]) -> BatchRegistrationReport {
+        let mut results = Vec::with_capacity(registrations.len());
+
+        for (index, (user, password)) in registrations.into_iter().enumerate() {
+            let mut errors = FieldErrors::new();
+            errors.nested("user", &user);
+            errors.check(user.role != Role::Admin, "user.role", "admins cannot be registered");
+            errors.check(!admin_ids.contains(&user.id), "user.id", "is reserved for an admin");
+            if let Some(password) = &password {
+                errors.check(
+                    password.len() >= auth::MIN_PASSWORD_LEN,
+                    "password",
+                    format!("must be at least {} characters", auth::MIN_PASSWORD_LEN),
+                );
+            }
+
+            let (status, errors) = match errors.into_result() {
+                Err(errors) => (RegistrationStatus::Invalid, errors),
+                Ok(()) => match self.insert_user(&user, password).await {
+                    Ok(true) => (RegistrationStatus::Created, Vec::new()),
+                    Ok(false) => (RegistrationStatus::Duplicate, vec![FieldError {
+                        field: "user.id".to_string(),
+                        reason: "already exists".to_string(),
+                    }]),
+                    Err(e) => {
+                        warn!("Failed to register user {} in batch: {}", user.id, e);
+                        (RegistrationStatus::Failed, vec![FieldError {
+                            field: "user".to_string(),
+                            reason: e.to_string(),
+                        }])
+                    }
+                },
+            };
+
+            results.push(RegistrationResult { index, user_id: user.id, status, errors });
+        }
+
+        BatchRegistrationReport::new(results)
+    }
+
+    async fn insert_user(&self, user: &User, password: Option<String>) -> Result<bool> {
+        let password_hash = match password {
+            Some(password) => Some(tokio::task::spawn_blocking(move || auth::hash_password(&password)).await??),
+            None => None,
+        };
+        let inserted = self.db.insert_user(user, password_hash.as_deref()).await?;
+        if inserted {
+            self.emit(DomainEvent::UserRegistered { user_id: user.id.clone(), occurred_at: chrono::Utc::now() }).await;
+        }
+        Ok(inserted)
+    }
+
+    /// Check a user's password, applying the lockout policy from `auth`
+    pub async fn login(&self, user_id: &str, password: &str, auth: &config::AuthConfig) -> Result<auth::LoginOutcome> {
+        let credentials = self.db.get_credentials(user_id).await?;
+
+        if credentials.as_ref()
+            .and_then(|c| c.locked_until)
+            .is_some_and(|until| until > chrono::Utc::now())
+        {
+            return Ok(auth::LoginOutcome::LockedOut);
+        }
+
+        // Hashing is slow by design, so keep it off the async workers
+        let password = password.to_string();
+        let stored_hash = credentials.map(|c| c.password_hash);
+        let has_credentials = stored_hash.is_some();
+        let valid = tokio::task::spawn_blocking(move || match stored_hash {
+            Some(hash) => auth::verify_password(&password, &hash),
+            None => {
+                auth::verify_dummy_password(&password);
+                false
+            }
+        }).await?;
+
+        if valid {
+            self.db.clear_failed_logins(user_id).await?;
+            return Ok(auth::LoginOutcome::Success);
+        }
+
+        if has_credentials {
+            let lockout = std::time::Duration::from_secs(auth.lockout_minutes * 60);
+            if self.db.record_failed_login(user_id, auth.max_failed_logins, lockout).await? {
+                return Ok(auth::LoginOutcome::LockedOut);
+            }
+        }
+        Ok(auth::LoginOutcome::InvalidCredentials)
     }
 
     pub async fn get_user(&self, user_id: &str) -> Result<Option<User>> {
         self.db.get_user(user_id).await
     }
 
+    /// Save changes to a user made against `expected_version`; see
+    /// `DatabaseManager::update_user`
+    pub async fn update_user(&self, user: &User, expected_version: u64) -> Result<UserUpdate> {
+        user.validate_fields()
+            .map_err(|errors| anyhow::anyhow!("Invalid user: {}", describe_field_errors(&errors)))?;
+        self.db.update_user(user, expected_version).await
+    }
+
     pub async fn get_all_users(&self) -> Result<Vec<User>> {
         self.db.get_all_users().await
     }
 
-    pub async fn recommend_workout(&self, user_id: &str) -> Result<Vec<ExerciseSet>> {
+    pub async fn get_users_page(&self, page: PageRequest) -> Result<Page<User>> {
+        self.db.get_users_page(page).await
+    }
+
+    pub async fn delete_user(&self, user_id: &str, hard: bool) -> Result<bool> {
+        self.db.delete_user(user_id, hard).await
+    }
+
+    /// Give a coach access to a client's data. Both must exist and the
+    /// coach must have the coach role.
+    pub async fn link_coach(&self, coach_id: &str, client_id: &str) -> Result<()> {
+        let coach = self.db.get_user(coach_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Coach not found"))?;
+        if coach.role != Role::Coach {
+            return Err(anyhow::anyhow!("User {} is not a coach", coach_id));
+        }
+        self.db.get_user(client_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+        self.db.link_coach(coach_id, client_id).await
+    }
+
+    pub async fn unlink_coach(&self, coach_id: &str, client_id: &str) -> Result<bool> {
+        self.db.unlink_coach(coach_id, client_id).await
+    }
+
+    pub async fn is_coach_of(&self, coach_id: &str, client_id: &str) -> Result<bool> {
+        self.db.is_coach_of(coach_id, client_id).await
+    }
+
+    pub async fn get_clients(&self, coach_id: &str) -> Result<Vec<User>> {
+        self.db.get_clients(coach_id).await
+    }
+
+    /// Save a recipe or exercise for the user. Adding one twice is a no-op
+    /// that returns false.
+    pub async fn add_favorite(&self, user_id: &str, kind: FavoriteKind, item_id: &str) -> Result<bool> {
+        if !self.db.favorite_target_exists(kind, item_id).await? {
+            return Err(anyhow::anyhow!("No {} with id {}", kind.as_str(), item_id));
+        }
+        self.db.add_favorite(user_id, kind, item_id).await
+    }
+
+    pub async fn remove_favorite(&self, user_id: &str, kind: FavoriteKind, item_id: &str) -> Result<bool> {
+        self.db.remove_favorite(user_id, kind, item_id).await
+    }
+
+    pub async fn list_favorites(&self, user_id: &str, kind: Option<FavoriteKind>) -> Result<Vec<Favorite>> {
+        self.db.list_favorites(user_id, kind).await
+    }
+
+    pub async fn recommend_workout(&self, user_id: &str, plates: &config::PlateConfig) -> Result<WorkoutRecommendation> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let recent_workouts = self.db.get_user_workouts(user_id).await?;
+        let exercises = self.db.get_all_exercises().await?;
+        let one_rep_maxes = self.db.get_one_rep_maxes(user_id).await?;
+
+        Ok(WorkoutRecommender::new(exercises)
+            .with_one_rep_maxes(one_rep_maxes)
+            .with_plates(plates.clone())
+            .recommend(&user, &recent_workouts))
+    }
+
+    /// Exercises to do instead of `exercise_id`, or `None` when there's no
+    /// such exercise; see `WorkoutRecommender::alternatives`
+    pub async fn suggest_alternatives(
+        &self,
+        exercise_id: &str,
+        constraints: &AlternativeConstraints,
+    ) -> Result<Option<Vec<Exercise>>> {
+        let exercises = self.db.get_all_exercises().await?;
+        Ok(WorkoutRecommender::new(exercises).alternatives(exercise_id, constraints))
+    }
+
+    /// A week of training and rest days; see `WorkoutRecommender::recommend_week`
+    pub async fn recommend_week(&self, user_id: &str) -> Result<WeeklySchedule> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let recent_workouts = self.db.get_user_workouts(user_id).await?;
+        let exercises = self.db.get_all_exercises().await?;
+        let one_rep_maxes = self.db.get_one_rep_maxes(user_id).await?;
+
+        Ok(WorkoutRecommender::new(exercises).with_one_rep_maxes(one_rep_maxes).recommend_week(&user, &recent_workouts))
+    }
+
+    /// Daily macro targets cycled over the recommended week; see
+    /// `nutrition_calc::carb_cycle`
+    pub async fn carb_cycle_plan(&self, user_id: &str) -> Result<CarbCyclePlan> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let schedule = self.recommend_week(user_id).await?;
+
+        let tdee = nutrition_calc::tdee(nutrition_calc::user_bmr(&user), &user.fitness_level);
+        let target = nutrition_calc::target_calories(tdee, &user.goals);
+        let daily = nutrition_calc::macro_split(target, user.weight as f64, &user.goals);
+
+        let training: Vec<bool> = schedule.days.iter().map(|day| day.focus != DayFocus::Rest).collect();
+        let days = schedule.days.into_iter()
+            .zip(nutrition_calc::carb_cycle(daily, &training))
+            .map(|(scheduled, macros)| CarbCycleDay {
+                day: scheduled.day,
+                training: scheduled.focus != DayFocus::Rest,
+                calories: nutrition_calc::macro_calories(&macros),
+                protein_g: macros.protein_g,
+                carbs_g: macros.carbs_g,
+                fat_g: macros.fat_g,
+            })
+            .collect();
+
+        Ok(CarbCyclePlan { user_id: user_id.to_string(), days })
+    }
+
+    pub async fn analyze_progress(&self, user_id: &str) -> Result<ProgressAnalysis> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let workouts = self.db.get_user_workouts(user_id).await?;
+
+        let mut analysis = ProgressAnalyzer::analyze(&user, &workouts);
+        analysis.activity = self.activity_trends(user_id).await?;
+        Ok(analysis)
+    }
+
+    async fn activity_trends(&self, user_id: &str) -> Result<Option<ActivityTrends>> {
+        let metrics = self.db.get_activity_metrics(user_id).await?;
+        let sleep = self.db.get_sleep_sessions(user_id).await?;
+        Ok(ProgressAnalyzer::activity_trends(&metrics, &sleep))
+    }
+
+    /// What the printable weekly report shows; `None` for an unknown user
+    pub async fn report_data(&self, user_id: &str) -> Result<Option<report::ReportData>> {
+        let Some(user) = self.db.get_user(user_id).await? else {
+            return Ok(None);
+        };
+        let workouts = self.db.get_user_workouts(user_id).await?;
+        let mut progress = ProgressAnalyzer::analyze(&user, &workouts);
+        progress.activity = self.activity_trends(user_id).await?;
+
+        Ok(Some(report::ReportData {
+            schedule: self.recommend_week(user_id).await?,
+            nutrition: self.carb_cycle_plan(user_id).await?,
+            progress,
+            measurements: self.db.get_user_measurements(user_id).await?,
+            generated_on: chrono::Utc::now().date_naive(),
+            user,
+        }))
+    }
+
+    /// Workout streaks as of the current date at `utc_offset`, the user's
+    /// time zone; see `ProgressAnalyzer::streak`
+    pub async fn get_streak(&self, user_id: &str, utc_offset: chrono::FixedOffset) -> Result<StreakInfo> {
         let user = self.db.get_user(user_id).await?
             .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let workouts = self.db.get_user_workouts(user_id).await?;
+        let today = chrono::Utc::now().with_timezone(&utc_offset).date_naive();
 
-        let mut recommendations = Vec::new();
-
-        match user.fitness_level {
-            FitnessLevel::Beginner => {
-                recommendations.push(ExerciseSet {
-                    exercise_id: "squat".to_string(),
-                    sets: 2,
-                    reps: 10,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 60,
-                    completed: false,
-                });
-                
-                recommendations.push(ExerciseSet {
-                    exercise_id: "pushup".to_string(),
-                    sets: 2,
-                    reps: 8,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 60,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "plank".to_string(),
-                    sets: 2,
-                    reps: 1,
-                    weight_kg: None,
-                    duration_seconds: Some(30),
-                    rest_seconds: 60,
-                    completed: false,
-                });
-            },
-            
-            FitnessLevel::Intermediate => {
-                recommendations.push(ExerciseSet {
-                    exercise_id: "squat".to_string(),
-                    sets: 3,
-                    reps: 15,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 45,
-                    completed: false,
-                });
-                
-                recommendations.push(ExerciseSet {
-                    exercise_id: "pushup".to_string(),
-                    sets: 3,
-                    reps: 12,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 45,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "burpee".to_string(),
-                    sets: 2,
-                    reps: 8,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 60,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "plank".to_string(),
-                    sets: 3,
-                    reps: 1,
-                    weight_kg: None,
-                    duration_seconds: Some(45),
-                    rest_seconds: 45,
-                    completed: false,
-                });
-            },
-            
-            _ => {
-                recommendations.push(ExerciseSet {
-                    exercise_id: "squat".to_string(),
-                    sets: 4,
-                    reps: 20,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 30,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "deadlift".to_string(),
-                    sets: 4,
-                    reps: 8,
-                    weight_kg: Some(60.0),
-                    duration_seconds: None,
-                    rest_seconds: 90,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "burpee".to_string(),
-                    sets: 3,
-                    reps: 12,
-                    weight_kg: None,
-                    duration_seconds: None,
-                    rest_seconds: 45,
-                    completed: false,
-                });
-
-                recommendations.push(ExerciseSet {
-                    exercise_id: "plank".to_string(),
-                    sets: 3,
-                    reps: 1,
-                    weight_kg: None,
-                    duration_seconds: Some(60),
-                    rest_seconds: 30,
-                    completed: false,
-                });
+        Ok(ProgressAnalyzer::streak(&user, &workouts, today))
+    }
+
+    /// Imports the last `days` days (today included) from Fitbit. Days
+    /// already imported are overwritten, not duplicated. When Fitbit fails
+    /// part way, the days fetched before the failure are still stored and
+    /// the failure returned in the result; `tokens` is current either way.
+    pub async fn import_fitbit(
+        &self,
+        client: &integrations::fitbit::FitbitClient,
+        user_id: &str,
+        tokens: &mut integrations::fitbit::FitbitTokens,
+        days: u32,
+    ) -> Result<integrations::fitbit::FitbitImport> {
+        let today = chrono::Utc::now().date_naive();
+        let mut metrics = Vec::new();
+        let mut error = None;
+        for offset in 0..days {
+            let date = today - chrono::Duration::days(offset as i64);
+            match client.daily_metrics(user_id, tokens, date).await {
+                Ok(day) => metrics.extend(day),
+                Err(e) => {
+                    error = Some(e.to_string());
+                    break;
+                }
+            }
+        }
+        let imported = self.db.save_activity_metrics(&metrics).await?;
+        Ok(integrations::fitbit::FitbitImport { imported, error })
+    }
+
+    /// Stores what a phone's health store exported. Steps and heart rate
+    /// join the daily metrics, sleep its own store, and workouts the user's
+    /// log with calories estimated when the payload has none. Records
+    /// already imported from the same source are skipped.
+    pub async fn import_health(&self, user_id: &str, import: HealthImport) -> Result<HealthImportSummary> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let source = import.source.as_str();
+        let metric = |date: &str, metric: ActivityMetricKind, value: f64| ActivityMetric {
+            user_id: user_id.to_string(),
+            date: date.to_string(),
+            source: source.to_string(),
+            metric,
+            value,
+        };
+        let count = |total: usize, imported: usize| ImportCount { imported, duplicates: total - imported };
+
+        let steps: Vec<ActivityMetric> = import.steps.iter()
+            .map(|day| metric(&day.date, ActivityMetricKind::Steps, day.count as f64))
+            .collect();
+        let heart_rate: Vec<ActivityMetric> = import.heart_rate.iter()
+            .flat_map(|day| [
+                day.resting_bpm.map(|bpm| metric(&day.date, ActivityMetricKind::RestingHeartRate, bpm as f64)),
+                day.average_bpm.map(|bpm| metric(&day.date, ActivityMetricKind::AverageHeartRate, bpm as f64)),
+            ])
+            .flatten()
+            .collect();
+        let sleep: Vec<SleepSession> = import.sleep.iter()
+            .map(|night| SleepSession {
+                user_id: user_id.to_string(),
+                source: source.to_string(),
+                external_id: night.external_id.clone(),
+                start: night.start,
+                end: night.end,
+            })
+            .collect();
+
+        let mut summary = HealthImportSummary {
+            steps: count(steps.len(), self.db.save_activity_metrics(&steps).await?),
+            heart_rate: count(heart_rate.len(), self.db.save_activity_metrics(&heart_rate).await?),
+            sleep: count(sleep.len(), self.db.save_sleep_sessions(&sleep).await?),
+            workouts: ImportCount::default(),
+        };
+
+        for imported in import.workouts {
+            // Health store ids are only unique per user
+            let id = format!("{}_{}_{}", source, user_id, imported.external_id);
+            if self.db.get_workout(&id).await?.is_some() {
+                summary.workouts.duplicates += 1;
+                continue;
             }
+
+            let met = imported.exercise_type.unwrap_or(ExerciseType::Strength).met_value();
+            let workout = WorkoutSession {
+                id,
+                user_id: user_id.to_string(),
+                date: imported.start.date_naive().to_string(),
+                exercises: vec![],
+                total_duration_minutes: imported.duration_minutes,
+                calories_burned: Some(imported.calories
+                    .unwrap_or(met * user.weight * imported.duration_minutes as f32 / 60.0)),
+                calories_estimated: imported.calories.is_none(),
+                user_rating: None,
+                notes: Some(format!("Imported from {}: {}", source, imported.activity.as_deref().unwrap_or("workout"))),
+            };
+            self.log_workout(workout, None).await?;
+            summary.workouts.imported += 1;
         }
 
-        Ok(recommendations)
+        Ok(summary)
     }
 
-    pub async fn analyze_progress(&self, user_id: &str) -> Result<ProgressAnalysis> {
-        self.db.get_user_progress_analysis(user_id).await
+    /// Imports a food diary's rows into the food log, resolving each food
+    /// against `foods`. Rows that can't be read, or were imported before,
+    /// are skipped and reported.
+    pub async fn import_food_diary(
+        &self,
+        user_id: &str,
+        rows: Vec<Result<integrations::myfitnesspal::DiaryRow, String>>,
+        foods: &MenuOptimizer,
+    ) -> Result<DiaryImportSummary> {
+        if self.db.get_user(user_id).await?.is_none() {
+            return Err(anyhow::anyhow!("User not found"));
+        }
+
+        let mut summary = DiaryImportSummary::default();
+        let mut entries = Vec::new();
+        let mut entry_rows = Vec::new();
+        for (i, row) in rows.into_iter().enumerate() {
+            let entry = match row {
+                Ok(row) => {
+                    let food = foods.find_food(&row.food).await;
+                    integrations::myfitnesspal::to_entry(user_id, row, food.as_ref())
+                }
+                Err(e) => Err(e),
+            };
+            match entry {
+                Ok(entry) => {
+                    entries.push(entry);
+                    entry_rows.push(i + 1);
+                }
+                Err(reason) => summary.skipped.push(SkippedDiaryRow { row: i + 1, reason }),
+            }
+        }
+
+        let stored = self.db.save_food_log_entries(&entries).await?;
+        for ((entry, row), stored) in entries.iter().zip(entry_rows).zip(stored) {
+            if stored {
+                summary.imported += 1;
+                summary.resolved += entry.food_id.is_some() as usize;
+            } else {
+                summary.skipped.push(SkippedDiaryRow { row, reason: "Already imported".to_string() });
+            }
+        }
+        summary.skipped.sort_by_key(|skipped| skipped.row);
+        Ok(summary)
     }
 
-    pub async fn log_workout(&self, workout: WorkoutSession) -> Result<()> {
-        self.db.save_workout(&workout).await
+    /// The user's food log by day, with each day's totals
+    pub async fn get_food_log(&self, user_id: &str) -> Result<Vec<FoodLogDay>> {
+        Ok(daily_totals(self.db.get_food_log(user_id).await?))
+    }
+
+    // Returns the stored session. With an idempotency key, a retry of an
+    // already-logged submission returns the original session unchanged.
+    pub async fn log_workout(&self, mut workout: WorkoutSession, idempotency_key: Option<&str>) -> Result<WorkoutSession> {
+        // Fill in a MET-based estimate when the caller didn't supply calories
+        if workout.calories_burned.is_none() {
+            if let Some(user) = self.db.get_user(&workout.user_id).await? {
+                let exercises = self.db.get_all_exercises().await?;
+                workout.calories_burned = Some(estimate_calories_burned(&workout, &user, &exercises));
+                workout.calories_estimated = true;
+            }
+        }
+
+        let Some(key) = idempotency_key else {
+            self.db.save_workout(&workout).await?;
+            self.after_workout_logged(&workout).await;
+            return Ok(workout);
+        };
+
+        let workout_id = self.db.save_workout_idempotent(&workout, key).await?;
+        if workout_id == workout.id {
+            self.after_workout_logged(&workout).await;
+            return Ok(workout);
+        }
+        self.db.get_workout(&workout_id).await?
+            .ok_or_else(|| anyhow::anyhow!("Workout {} recorded for idempotency key no longer exists", workout_id))
     }
 
     pub async fn get_exercise(&self, exercise_id: &str) -> Result<Option<Exercise>> {
@@ -190,35 +575,279 @@ impl FitnessAdvisor {
         self.db.get_all_exercises().await
     }
 
+    pub async fn seed_exercises(&self, exercises: Vec<Exercise>) -> Result<ExerciseSeeding> {
+        self.db.seed_exercises(exercises).await
+    }
+
+    pub async fn exercises_modified_at(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        self.db.exercises_modified_at().await
+    }
+
+    pub async fn recipes_modified_at(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        self.db.recipes_modified_at().await
+    }
+
+    pub async fn search_exercises(&self, query: &str) -> Result<Vec<Exercise>> {
+        self.db.search_exercises(query).await
+    }
+
+    pub async fn search_recipes(&self, query: &str) -> Result<Vec<models::food::Recipe>> {
+        self.db.search_recipes(query).await
+    }
+
+    pub async fn record_feedback(
+        &self,
+        user_id: &str,
+        recommendation_id: &str,
+        rating: FeedbackRating,
+        comment: Option<&str>,
+    ) -> Result<FeedbackSummary> {
+        self.db.record_feedback(user_id, recommendation_id, rating, comment).await?;
+        self.db.feedback_summary(recommendation_id).await
+    }
+
+    pub async fn feedback_summary(&self, recommendation_id: &str) -> Result<FeedbackSummary> {
+        self.db.feedback_summary(recommendation_id).await
+    }
+
+    pub async fn record_exposure(&self, exposure: &ExperimentExposure) -> Result<()> {
+        self.db.record_exposure(exposure).await
+    }
+
+    pub async fn experiment_results(&self, experiment: &str) -> Result<Vec<VariantResults>> {
+        self.db.experiment_results(experiment).await
+    }
+
+    /// Net feedback per recommendation id, for ranking
+    pub async fn feedback_scores(&self) -> Result<std::collections::HashMap<String, i64>> {
+        self.db.feedback_scores().await
+    }
+
     pub async fn get_user_workouts(&self, user_id: &str) -> Result<Vec<WorkoutSession>> {
         self.db.get_user_workouts(user_id).await
     }
 
+    pub async fn get_user_workouts_page(&self, user_id: &str, page: PageRequest) -> Result<Page<WorkoutSession>> {
+        self.db.get_user_workouts_page(user_id, page).await
+    }
+
+    pub async fn record_measurement(&self, measurement: BodyMeasurement) -> Result<()> {
+        measurement.validate(chrono::Local::now().date_naive())
+            .map_err(|e| anyhow::anyhow!(e))?;
+        self.db.get_user(&measurement.user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+
+        self.db.save_measurement(&measurement).await?;
+        self.after_activity(&measurement.user_id).await;
+        Ok(())
+    }
+
+    /// Set a goal, starting from the metric's current value
+    pub async fn create_goal(
+        &self,
+        user_id: &str,
+        title: String,
+        metric: GoalMetric,
+        exercise_id: Option<String>,
+        target_value: f64,
+        deadline: Option<String>,
+    ) -> Result<GoalProgress> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        if let Some(exercise_id) = &exercise_id {
+            self.db.get_exercise(exercise_id).await?
+                .ok_or_else(|| anyhow::anyhow!("No exercise with id {}", exercise_id))?;
+        }
+
+        let mut goal = UserGoal {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            title,
+            metric,
+            exercise_id,
+            start_value: 0.0,
+            target_value,
+            deadline,
+            created_at: chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            completed_at: None,
+        };
+        let workouts = self.db.get_user_workouts(user_id).await?;
+        let measurements = self.db.get_user_measurements(user_id).await?;
+        // Counting starts from zero; other metrics from where the user is now
+        if metric != GoalMetric::WorkoutCount {
+            goal.start_value = match ProgressAnalyzer::goal_value(&goal, &user, &workouts, &measurements) {
+                Some(value) => value,
+                // Not lifted yet
+                None if metric == GoalMetric::ExerciseWeight => 0.0,
+                None => return Err(anyhow::anyhow!("Record a waist measurement before setting a waist goal")),
+            };
+        }
+
+        self.db.insert_goal(&goal).await?;
+        let mut progress = self.goals(user_id).await?;
+        progress.retain(|p| p.goal.id == goal.id);
+        progress.pop().ok_or_else(|| anyhow::anyhow!("Goal {} was not saved", goal.id))
+    }
+
+    /// The user's goals with current progress. Goals that have reached their
+    /// target are marked completed now.
+    pub async fn goals(&self, user_id: &str) -> Result<Vec<GoalProgress>> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let workouts = self.db.get_user_workouts(user_id).await?;
+        let measurements = self.db.get_user_measurements(user_id).await?;
+        let now = chrono::Utc::now();
+
+        let mut progress = Vec::new();
+        let mut completed_now = Vec::new();
+        for mut goal in self.db.get_goals(user_id).await? {
+            let current_value = ProgressAnalyzer::goal_value(&goal, &user, &workouts, &measurements);
+            let completion_percent = current_value.map_or(0.0, |value| goal.completion_percent(value));
+            if goal.completed_at.is_none() && completion_percent >= 100.0 {
+                let completed_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
+                if self.db.complete_goal(&goal.id, &completed_at).await? {
+                    info!("Goal {} completed by user {}", goal.id, user_id);
+                    goal.completed_at = Some(completed_at);
+                    completed_now.push(goal.clone());
+                } else {
+                    goal.completed_at = Some(completed_at);
+                }
+            }
+
+            progress.push(GoalProgress {
+                current_value,
+                completion_percent,
+                completed: goal.completed_at.is_some(),
+                overdue: goal.is_overdue(now.date_naive()),
+                goal,
+            });
+        }
+        if !completed_now.is_empty() {
+            self.award_achievements(user_id).await?;
+        }
+        for goal in completed_now {
+            self.emit(DomainEvent::GoalCompleted { goal, occurred_at: now }).await;
+        }
+        Ok(progress)
+    }
+
+    /// Mark goals completed and award achievements after new data was
+    /// logged. A failure here shouldn't fail the logging itself.
+    async fn after_activity(&self, user_id: &str) {
+        if let Err(e) = self.goals(user_id).await {
+            warn!("Failed to update goals for user {}: {}", user_id, e);
+        }
+        if let Err(e) = self.award_achievements(user_id).await {
+            warn!("Failed to award achievements to user {}: {}", user_id, e);
+        }
+    }
+
+    async fn after_workout_logged(&self, workout: &WorkoutSession) {
+        self.after_activity(&workout.user_id).await;
+        self.emit(DomainEvent::WorkoutLogged { workout: workout.clone(), occurred_at: chrono::Utc::now() }).await;
+    }
+
+    /// Count a generated meal plan towards the user's achievements and
+    /// publish it
+    pub async fn record_meal_plan_generated(&self, user_id: &str, plan: serde_json::Value) -> Result<()> {
+        self.emit(DomainEvent::PlanOptimized { user_id: user_id.to_string(), plan, occurred_at: chrono::Utc::now() }).await;
+        self.db.increment_activity(user_id, MEAL_PLANS_ACTIVITY).await?;
+        self.award_achievements(user_id).await?;
+        Ok(())
+    }
+
+    /// Check every rule in `ACHIEVEMENTS` and award the ones newly met.
+    /// Returns only the achievements awarded by this call.
+    pub async fn award_achievements(&self, user_id: &str) -> Result<Vec<AchievementRule>> {
+        let user = self.db.get_user(user_id).await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let workouts = self.db.get_user_workouts(user_id).await?;
+        let activity = ActivitySummary {
+            meal_plans_generated: self.db.activity_count(user_id, MEAL_PLANS_ACTIVITY).await?,
+            goals_completed: self.db.get_goals(user_id).await?.iter()
+                .filter(|goal| goal.completed_at.is_some())
+                .count() as u32,
+            ..ProgressAnalyzer::activity_summary(&user, &workouts, chrono::Utc::now().date_naive())
+        };
+
+        let held: Vec<String> = self.db.get_achievements(user_id).await?.into_iter().map(|(id, _)| id).collect();
+        let mut awarded = Vec::new();
+        for rule in ACHIEVEMENTS.iter().filter(|rule| !held.iter().any(|id| id == rule.id)) {
+            if rule.criterion.is_met(&activity) && self.db.award_achievement(user_id, rule.id).await? {
+                info!("🏅 User {} earned {}", user_id, rule.name);
+                awarded.push(*rule);
+            }
+        }
+        Ok(awarded)
+    }
+
+    /// Achievements the user has earned, earliest first
+    pub async fn achievements(&self, user_id: &str) -> Result<Vec<Achievement>> {
+        let awarded = self.db.get_achievements(user_id).await?;
+        // Awards of since-retired rules are kept in the table but not shown
+        Ok(awarded.into_iter()
+            .filter_map(|(id, awarded_at)| {
+                let rule = AchievementRule::find(&id)?;
+                Some(Achievement {
+                    id,
+                    name: rule.name.to_string(),
+                    description: rule.description.to_string(),
+                    awarded_at,
+                })
+            })
+            .collect())
+    }
+
+    pub async fn get_measurements(&self, user_id: &str) -> Result<Vec<BodyMeasurement>> {
+        self.db.get_user_measurements(user_id).await
+    }
+
+    pub async fn get_activity_metrics(&self, user_id: &str) -> Result<Vec<ActivityMetric>> {
+        self.db.get_activity_metrics(user_id).await
+    }
+
+    pub async fn get_sleep_sessions(&self, user_id: &str) -> Result<Vec<SleepSession>> {
+        self.db.get_sleep_sessions(user_id).await
+    }
+
     pub async fn database_health(&self) -> Result<database::DatabaseHealth> {
         self.db.health_check().await
     }
+
+    /// Close the database pool, waiting for in-flight queries to finish
+    pub async fn close(&self) {
+        self.db.close().await;
+    }
 }
 
 pub struct AppState {
     pub advisor: Arc<FitnessAdvisor>,
     pub ai_analyzer: Arc<AIMotionAnalyzer>,
     pub ml_client: Arc<MLServiceClient>,
+    /// Present when a FoodData Central API key is configured
+    pub fdc_client: Option<Arc<fdc_client::FdcClient>>,
+    pub open_food_facts: Arc<open_food_facts::OpenFoodFactsClient>,
+    /// Present when Fitbit client credentials are configured
+    pub fitbit_client: Option<Arc<integrations::fitbit::FitbitClient>>,
     pub menu_optimizer: Arc<MenuOptimizer>,
+    pub realtime_sessions: Arc<websocket::RealtimeSessions>,
+    /// Signs and verifies API bearer tokens with the configured secret
+    pub tokens: Arc<auth::TokenSigner>,
     pub config: Arc<Config>,
 }
 
 
 pub async fn start_server(advisor: FitnessAdvisor, config: Config) -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
-
-    let ml_client = MLServiceClient::with_config(
-        config.ml_service.base_url.clone(),
-        config.ml_service.timeout_seconds
-    );
+    let ml_client = MLServiceClient::from_config(&config.ml_service)?;
     
     info!("Checking ML service availability...");
     if ml_client.is_available().await {
         info!("ML service is available and ready");
+        match ml_client.device_info().await {
+            Ok(device) => info!("ML service running on {} ({})",
+                device.device, device.gpu_name.as_deref().unwrap_or("no GPU")),
+            Err(e) => warn!("Could not get ML service device info: {}", e),
+        }
     } else {
         warn!("ML service not available - starting without ML features");
         warn!("To enable ML features, start the Python ML service:");
@@ -241,69 +870,107 @@ pub async fn start_server(advisor: FitnessAdvisor, config: Config) -> anyhow::Re
         }
     };
     
+    if config.auth.jwt_secret == config::DEV_JWT_SECRET {
+        warn!("Using the development JWT secret (auth.allow_dev_secret) - set FITNESS_JWT_SECRET before deploying");
+    }
+    if config.metrics.token.is_none() {
+        warn!("No metrics token configured - /metrics and /api/database/health are open; set FITNESS_METRICS_TOKEN");
+    }
+
     let state = Arc::new(AppState {
         advisor: Arc::new(advisor),
         ai_analyzer: Arc::new(AIMotionAnalyzer::new()),
         ml_client: Arc::new(ml_client),
+        fdc_client: fdc_client::FdcClient::from_config(&config.fdc).map(Arc::new),
+        open_food_facts: Arc::new(open_food_facts::OpenFoodFactsClient::from_config(&config.open_food_facts)),
+        fitbit_client: integrations::fitbit::FitbitClient::from_config(&config.fitbit).map(Arc::new),
         menu_optimizer: Arc::new(menu_optimizer),
+        realtime_sessions: Arc::new(websocket::RealtimeSessions::new()),
+        tokens: Arc::new(auth::TokenSigner::new(&config.auth)),
         config: Arc::new(config.clone()),
     });
 
+    let advisor = state.advisor.clone();
+    let webhook_dispatcher = tokio::spawn(advisor.webhook_dispatcher(&config.webhooks).run());
     let app = api::create_router(state);
 
     let bind_address = config.get_server_address();
     let listener = tokio::net::TcpListener::bind(&bind_address).await?;
     info!("Fitness Advisor AI Server starting on http://{}", bind_address);
-    info!("RTX 5070 Laptop GPU Ready for AI Processing");
     info!("SQLite Database Connected");
     info!("API Documentation:");
+    info!("  POST   /api/auth/login                     - Get a bearer token");
     info!("  POST   /api/users                          - Create user");
-    info!("  GET    /api/users                          - Get all users");
+    info!("  POST   /api/users/batch                    - Create many users (admin, per-user results)");
+    info!("  GET    /api/users?limit=&offset=           - List users (admin, paginated)");
     info!("  GET    /api/users/:id                      - Get specific user");
+    info!("  PUT    /api/users/:id                      - Update user (If-Match: ETag from GET)");
+    info!("  DELETE /api/users/:id?hard=true            - Delete user (soft by default)");
     info!("  GET    /api/users/:id/recommendations      - Get workout recommendations");
+    info!("  GET    /api/users/:id/schedule             - Get a weekly training schedule");
+    info!("  GET    /api/users/:id/carb-cycle           - Daily macros cycled around training days");
+    info!("  GET    /api/users/:id/favorites?kind=      - List favorite recipes/exercises");
+    info!("  PUT    /api/users/:id/favorites/:kind/:item - Add a favorite (DELETE to remove)");
+    info!("  POST   /api/users/:id/goals                - Set a goal (GET to list with progress)");
+    info!("  GET    /api/users/:id/goals/:goal_id       - One goal's progress");
+    info!("  GET    /api/users/:id/achievements         - Earned badges");
+    info!("  GET    /api/users/:id/report.pdf           - Printable weekly plan and progress report");
     info!("  GET    /api/users/:id/progress             - Get progress analysis");
-    info!("  GET    /api/users/:id/workouts             - Get user workout history");
+    info!("  GET    /api/users/:id/streak               - Workout streak and weekly consistency");
+    info!("  GET    /api/users/:id/workouts?limit=&offset= - Get user workout history (paginated)");
+    info!("  POST   /api/users/:id/measurements         - Record body measurement");
+    info!("  GET    /api/users/:id/measurements         - Get body measurement history");
+    info!("  POST   /api/users/:id/health-import        - Import HealthKit / Google Fit data");
+    info!("  GET    /api/users/:id/export               - Download everything held about the user (JSON)");
+    info!("  GET    /api/users/:id/food-log             - Food log with daily macro totals");
+    info!("  POST   /api/users/:id/food-log/import      - Import a MyFitnessPal diary (CSV or JSON)");
+    info!("  POST   /api/users/:id/integrations/fitbit/sync - Import activity and heart rate from Fitbit");
+    info!("  PUT    /api/users/:id/coaches/:coach_id    - Link a coach (DELETE to unlink)");
+    info!("  GET    /api/users/:id/clients              - Get a coach's linked clients");
     info!("  GET    /api/exercises                      - Get all exercises");
-    info!("  POST   /api/workouts                       - Log workout");
+    info!("  GET    /api/exercises/search?q=            - Search exercises by keyword");
+    info!("  POST   /api/exercises/bulk                 - Add many exercises at once (admin)");
+    info!("  GET    /api/exercises/:id/alternatives     - Similar exercises for given equipment and difficulty");
+    info!("  GET    /api/recipes/search?q=              - Search recipes by keyword");
+    info!("  GET    /api/foods/lookup?q=                - Food nutrients, from FoodData Central if not local");
+    info!("  POST   /api/nutrition/barcode              - Resolve a product barcode via Open Food Facts");
+    info!("  POST   /api/workouts                       - Log workout (honours Idempotency-Key)");
+    info!("  PUT    /api/users/:id/feedback/:rec_id     - Thumbs up/down on a recommendation");
+    info!("  GET    /api/recommendations/:id/feedback   - Feedback counts for a recommendation");
+    info!("  GET    /api/experiments/:name/results  - A/B experiment outcomes per variant (admin)");
+    info!("  POST   /api/webhooks                       - Subscribe to events (admin; GET to list)");
+    info!("  DELETE /api/webhooks/:webhook_id           - Unsubscribe a webhook (admin)");
+    info!("  GET    /api/webhooks/:webhook_id/deliveries - Webhook delivery status (admin)");
+    info!("  POST   /api/menu/suggest-recipes           - Recipes makeable from a pantry");
+    info!("  POST   /api/menu/recipes/:recipe_id/substitute - Swap an ingredient in a recipe");
+    info!("  POST   /api/menu/grocery-list              - Shopping list for an optimized plan");
+    info!("  POST   /api/menu/diff                      - Compare two optimized plans");
     info!("  POST   /api/ai/analyze-form                - AI form analysis (RTX 5070)");
+    info!("  POST   /api/ai/analyze-form/image          - Form feedback from a photo");
+    info!("  POST   /api/ai/compare-form                - Compare form to a reference pose");
     info!("  GET    /api/health                         - Health check");
     info!("  GET    /api/database/health                - Database health check");
-    info!("  GET    /api/gpu-status                     - RTX 5070 status");
+    info!("  GET    /api/health/ready                   - Readiness of the database and ML service");
+    info!("  GET    /api/gpu-status                     - ML service compute device");
+    info!("  GET    /metrics                            - Prometheus metrics");
+    info!("  GET    /api/openapi.json                   - OpenAPI document (Swagger UI at /api/docs)");
+
+    let grace_period = std::time::Duration::from_secs(config.server.shutdown_grace_period_seconds);
+    shutdown::serve_with_graceful_shutdown(listener, app, shutdown::shutdown_signal(), grace_period).await?;
+    webhook_dispatcher.abort();
+    advisor.close().await;
 
-    axum::serve(listener, app).await?;
+    info!("👋 Fitness Advisor AI Server stopped");
     Ok(())
 }
 
+/// Password the demo users are registered with, for trying out login locally
+const DEMO_PASSWORD: &str = "demo-password";
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    println!("Fitness Advisor AI Starting...");
-    
-    // Load configuration
-    let config = Config::load_with_env().unwrap_or_else(|e| {
-        println!("Warning: Failed to load config file, using defaults: {}", e);
-        Config::default()
-    });
-    
-    // Validate configuration
-    if let Err(e) = config.validate() {
-        return Err(anyhow::anyhow!("Invalid configuration: {}", e));
-    }
-    
-    println!("Configuration loaded successfully");
-    println!("RTX 5070 Laptop GPU - 7.7GB VRAM Ready!");
-    println!("Initializing SQLite Database...");
-    
-    // データベース接続
-    let database_url = &config.database.url;
-    
-    // Ensure we can create the database file
-    if let Err(e) = std::fs::File::create("fitness_advisor.db") {
-        println!("Warning: Could not pre-create database file: {}", e);
-    }
-    
-    let advisor = FitnessAdvisor::new(database_url).await?;
-    
+/// Demo users, a coach and a couple of logged workouts for trying the API
+/// locally. Every demo account shares a published password, so this only
+/// runs when `demo.seed` is set.
+async fn seed_demo_data(advisor: &FitnessAdvisor, config: &Config) -> anyhow::Result<()> {
     let demo_user = User {
         id: "demo_user".to_string(),
         name: "Demo User".to_string(),
@@ -318,10 +985,15 @@ async fn main() -> anyhow::Result<()> {
             workout_duration_minutes: 45,
             workouts_per_week: 4,
             preferred_time_of_day: Some("evening".to_string()),
+            unit_system: UnitSystem::Metric,
+            limitations: vec![],
+            warmup_cooldown: true,
         },
+        role: Role::Client,
+        version: 0,
     };
 
-    advisor.register_user(demo_user.clone()).await?;
+    let first_run = advisor.register_user(demo_user.clone(), Some(DEMO_PASSWORD)).await?;
     
     let beginner_user = User {
         id: "beginner_user".to_string(),
@@ -337,10 +1009,15 @@ async fn main() -> anyhow::Result<()> {
             workout_duration_minutes: 30,
             workouts_per_week: 3,
             preferred_time_of_day: Some("morning".to_string()),
+            unit_system: UnitSystem::Metric,
+            limitations: vec![],
+            warmup_cooldown: true,
         },
+        role: Role::Client,
+        version: 0,
     };
 
-    advisor.register_user(beginner_user).await?;
+    advisor.register_user(beginner_user, Some(DEMO_PASSWORD)).await?;
 
     let advanced_user = User {
         id: "advanced_user".to_string(),
@@ -356,36 +1033,98 @@ async fn main() -> anyhow::Result<()> {
             workout_duration_minutes: 60,
             workouts_per_week: 5,
             preferred_time_of_day: Some("morning".to_string()),
+            unit_system: UnitSystem::Metric,
+            limitations: vec![],
+            warmup_cooldown: true,
         },
+        role: Role::Client,
+        version: 0,
+    };
+
+    advisor.register_user(advanced_user, Some(DEMO_PASSWORD)).await?;
+
+    // A coach who can see the beginner's data
+    let demo_coach = User {
+        id: "demo_coach".to_string(),
+        name: "Demo Coach".to_string(),
+        role: Role::Coach,
+        version: 0,
+        ..demo_user.clone()
     };
+    advisor.register_user(demo_coach, Some(DEMO_PASSWORD)).await?;
+    advisor.link_coach("demo_coach", "beginner_user").await?;
 
-    advisor.register_user(advanced_user).await?;
+    // Existing demo users are left as they are, so restarts don't pile up
+    // duplicate sessions
+    if !first_run {
+        return Ok(());
+    }
 
     let demo_workout = WorkoutSession {
         id: Uuid::new_v4().to_string(),
         user_id: "demo_user".to_string(),
         date: "2025-08-13".to_string(),
-        exercises: advisor.recommend_workout("demo_user").await?,
+        exercises: completed_sets(advisor.recommend_workout("demo_user", &config.plates).await?.exercises),
         total_duration_minutes: 35,
         calories_burned: Some(180.0),
+        calories_estimated: false,
         user_rating: Some(4),
         notes: Some("Great workout! Felt strong today.".to_string()),
     };
 
-    advisor.log_workout(demo_workout).await?;
+    advisor.log_workout(demo_workout, None).await?;
 
     let demo_workout2 = WorkoutSession {
         id: Uuid::new_v4().to_string(),
         user_id: "demo_user".to_string(),
         date: "2025-08-12".to_string(),
-        exercises: advisor.recommend_workout("demo_user").await?,
+        exercises: completed_sets(advisor.recommend_workout("demo_user", &config.plates).await?.exercises),
         total_duration_minutes: 40,
         calories_burned: Some(200.0),
+        calories_estimated: false,
         user_rating: Some(5),
         notes: Some("Perfect form today!".to_string()),
     };
 
-    advisor.log_workout(demo_workout2).await?;
+    advisor.log_workout(demo_workout2, None).await?;
+
+    Ok(())
+}
+
+// Demo sessions log the recommended sets as fully completed
+fn completed_sets(sets: Vec<ExerciseSet>) -> Vec<ExerciseSet> {
+    sets.into_iter()
+        .map(|set| ExerciseSet { completed: true, ..set })
+        .collect()
+}
+
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    println!("Fitness Advisor AI Starting...");
+    
+    // Load configuration
+    let config = Config::load_with_env().unwrap_or_else(|e| {
+        println!("Warning: Failed to load config file, using defaults: {}", e);
+        Config::default()
+    });
+    
+    // Validate configuration
+    if let Err(e) = config.validate() {
+        return Err(anyhow::anyhow!("Invalid configuration: {}", e));
+    }
+
+    logging::init(&config.logging)?;
+    
+    println!("Configuration loaded successfully");
+    println!("Initializing SQLite Database...");
+    
+    // データベース接続 (the file is created on first connect if missing)
+    let advisor = FitnessAdvisor::new(&config.database).await?;
+    
+    if config.demo.seed {
+        seed_demo_data(&advisor, &config).await?;
+    }
 
     let db_health = advisor.database_health().await?;
     println!("Database initialized successfully");
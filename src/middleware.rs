@@ -0,0 +1,122 @@
+// src/middleware.rs - HTTP middleware for the API router
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{HeaderValue, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use ring::digest;
+use tower_http::timeout::TimeoutBody;
+use tracing::{info, info_span, Instrument};
+use uuid::Uuid;
+
+use crate::AppState;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest incoming request id that is honored; longer ids are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Assign each request an id (reusing a well-formed incoming `X-Request-Id`),
+/// run the handler inside a span carrying that id, log one line with the
+/// status and latency, and echo the id back in the response.
+pub async fn request_tracing(request: Request, next: Next) -> Response {
+    let request_id = request.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let span = info_span!("request", request_id = %request_id, %method, %path);
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let status = response.status().as_u16();
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    span.in_scope(|| {
+        info!(status, latency_ms, "{} {} {} {:.1}ms", method, path, status, latency_ms);
+    });
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// Fail the body read of a request whose client stalls for longer than
+/// `server.body_read_timeout_seconds`, so slow-trickle uploads can't hold a
+/// worker indefinitely. Handlers that never read the body are unaffected.
+pub async fn body_read_timeout(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let timeout = Duration::from_secs(state.config.server.body_read_timeout_seconds);
+    let request = request.map(|body| Body::new(TimeoutBody::new(timeout, body)));
+    next.run(request).await
+}
+
+/// Reject requests to operational endpoints that don't carry the configured
+/// metrics token (401). Without a configured token every request passes.
+pub async fn require_metrics_token(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if let Some(expected) = &state.config.metrics.token {
+        let authorized = crate::auth::bearer_token(request.headers())
+            .is_some_and(|token| tokens_match(token, expected));
+        if !authorized {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compares digests so the time taken doesn't reveal how much of a guessed
+/// token was right
+fn tokens_match(token: &str, expected: &str) -> bool {
+    digest::digest(&digest::SHA256, token.as_bytes()).as_ref()
+        == digest::digest(&digest::SHA256, expected.as_bytes()).as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use axum_test::TestServer;
+
+    fn test_server() -> TestServer {
+        let app = Router::new()
+            .route("/api/health", get(|| async { "ok" }))
+            .layer(middleware::from_fn(request_tracing));
+        TestServer::new(app).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_response_carries_request_id() {
+        let response = test_server().get("/api/health").await;
+
+        let request_id = response.header(REQUEST_ID_HEADER);
+        assert!(Uuid::parse_str(request_id.to_str().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_preserved() {
+        let response = test_server()
+            .get("/api/health")
+            .add_header(
+                axum::http::HeaderName::from_static(REQUEST_ID_HEADER),
+                HeaderValue::from_static("client-trace-123"),
+            )
+            .await;
+
+        assert_eq!(response.header(REQUEST_ID_HEADER), "client-trace-123");
+    }
+}
@@ -3,6 +3,7 @@
 use anyhow::{anyhow, Result};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::collections::HashMap;
 use std::time::Duration;
 use tracing::{info, warn, error};
@@ -11,6 +12,29 @@ use tracing::{info, warn, error};
 pub struct MLServiceClient {
     client: Client,
     base_url: String,
+    /// Base URLs of tasks served somewhere other than `base_url`
+    task_urls: HashMap<MLTask, String>,
+}
+
+/// Work the ML service does, each of which may run on its own backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MLTask {
+    /// Fast per-frame pose for live sessions
+    EstimatePose,
+    /// Detailed form scoring of frames, photos and videos
+    AnalyzeForm,
+}
+
+impl MLTask {
+    pub const ALL: [MLTask; 2] = [MLTask::EstimatePose, MLTask::AnalyzeForm];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MLTask::EstimatePose => "estimate_pose",
+            MLTask::AnalyzeForm => "analyze_form",
+        }
+    }
 }
 
 // Request/Response structures matching Python ML service
@@ -56,6 +80,19 @@ pub struct ModelsStatusResponse {
     pub pytorch_available: bool,
 }
 
+/// Compute device reported by the ML service's `/device` endpoint
+#[derive(Debug, Deserialize)]
+pub struct DeviceInfoResponse {
+    /// "cuda" or "cpu"
+    pub device: String,
+    pub cuda_available: bool,
+    pub gpu_name: Option<String>,
+    pub compute_capability: Option<String>,
+    pub vram_total_mb: Option<u64>,
+    pub vram_used_mb: Option<u64>,
+    pub cuda_version: Option<String>,
+}
+
 impl MLServiceClient {
     /// Create new ML service client
     pub fn new(base_url: String) -> Self {
@@ -64,7 +101,7 @@ impl MLServiceClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, base_url }
+        Self { client, base_url, task_urls: HashMap::new() }
     }
 
     /// Create client with custom configuration
@@ -74,7 +111,26 @@ impl MLServiceClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, base_url }
+        Self { client, base_url, task_urls: HashMap::new() }
+    }
+
+    /// Client routing each task to its configured backend. Fails when a
+    /// task is routed to a backend without a URL.
+    pub fn from_config(config: &crate::config::MLServiceConfig) -> Result<Self> {
+        let mut client = Self::with_config(config.base_url.clone(), config.timeout_seconds);
+        for task in MLTask::ALL {
+            let url = config.task_url(task)?;
+            if url != config.base_url {
+                info!("ML task {} served by {}", task.as_str(), url);
+                client.task_urls.insert(task, url.trim_end_matches('/').to_string());
+            }
+        }
+        Ok(client)
+    }
+
+    /// Base URL of the backend serving `task`
+    pub fn task_url(&self, task: MLTask) -> &str {
+        self.task_urls.get(&task).map(String::as_str).unwrap_or(&self.base_url)
     }
 
     /// Check if ML service is healthy
@@ -115,6 +171,25 @@ impl MLServiceClient {
         }
     }
 
+    /// Get the compute device the ML models run on
+    pub async fn device_info(&self) -> Result<DeviceInfoResponse> {
+        let url = format!("{}/device", self.base_url);
+
+        let response = self.client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Device info request failed: {}", e))?;
+
+        if response.status().is_success() {
+            let device: DeviceInfoResponse = response.json().await
+                .map_err(|e| anyhow!("Failed to parse device info: {}", e))?;
+            Ok(device)
+        } else {
+            Err(anyhow!("Device info failed with status: {}", response.status()))
+        }
+    }
+
     /// Analyze single frame for real-time feedback
     pub async fn analyze_frame_realtime(&self, frame_base64: String) -> Result<MLAnalysisResponse> {
         let request = FrameAnalysisRequest {
@@ -122,7 +197,7 @@ impl MLServiceClient {
             analysis_type: "realtime".to_string(),
         };
 
-        self.analyze_frame_internal(request).await
+        self.analyze_frame_internal(MLTask::EstimatePose, request).await
     }
 
     /// Analyze single frame with detailed analysis
@@ -132,12 +207,12 @@ impl MLServiceClient {
             analysis_type: "detailed".to_string(),
         };
 
-        self.analyze_frame_internal(request).await
+        self.analyze_frame_internal(MLTask::AnalyzeForm, request).await
     }
 
     /// Internal frame analysis method
-    async fn analyze_frame_internal(&self, request: FrameAnalysisRequest) -> Result<MLAnalysisResponse> {
-        let url = format!("{}/analyze/frame", self.base_url);
+    async fn analyze_frame_internal(&self, task: MLTask, request: FrameAnalysisRequest) -> Result<MLAnalysisResponse> {
+        let url = format!("{}/analyze/frame", self.task_url(task));
         
         let response = self.client
             .post(&url)
@@ -164,7 +239,7 @@ impl MLServiceClient {
             analysis_type: analysis_type.to_string(),
         };
 
-        let url = format!("{}/analyze/video", self.base_url);
+        let url = format!("{}/analyze/video", self.task_url(MLTask::AnalyzeForm));
         
         let response = self.client
             .post(&url)
@@ -188,7 +263,7 @@ impl MLServiceClient {
     pub async fn analyze_batch(&self, video_path: String) -> Result<MLAnalysisResponse> {
         let request = BatchAnalysisRequest { video_path };
 
-        let url = format!("{}/analyze/batch", self.base_url);
+        let url = format!("{}/analyze/batch", self.task_url(MLTask::AnalyzeForm));
         
         // Increase timeout for batch processing
         let response = self.client
@@ -232,6 +307,48 @@ impl MLServiceClient {
 
 // Helper functions for common ML operations
 impl MLServiceClient {
+    /// Detailed pose extraction and form scoring for one JPEG/PNG photo
+    pub async fn analyze_form_image(&self, image: &[u8]) -> Result<ImageFormFeedback> {
+        let frame_base64 = base64::prelude::Engine::encode(&base64::prelude::BASE64_STANDARD, image);
+        let response = self.analyze_frame_detailed(frame_base64).await?;
+
+        if !response.success {
+            return Err(anyhow!("Image analysis failed: {}",
+                response.error.unwrap_or_else(|| "Unknown error".to_string())));
+        }
+
+        let result = &response.result;
+        let strings = |key: &str| -> Vec<String> {
+            result.get(key)
+                .and_then(|v| v.as_array())
+                .map(|arr| arr.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+                .unwrap_or_default()
+        };
+        let feedback = strings("feedback");
+        let warnings = strings("warnings");
+
+        let joint_angles = result.get("joint_angles")
+            .and_then(|a| a.as_object())
+            .map(|angles| angles.iter().filter_map(|(joint, angle)| Some((joint.clone(), angle.as_f64()?))).collect())
+            .unwrap_or_default();
+
+        // Safety warnings take priority over general coaching
+        let cue = warnings.first()
+            .or(feedback.first())
+            .cloned()
+            .unwrap_or_else(|| "Form looks good - keep it up".to_string());
+
+        Ok(ImageFormFeedback {
+            score: result.get("score").and_then(|s| s.as_f64()).unwrap_or(0.0),
+            exercise: result.get("exercise").and_then(|e| e.as_str()).unwrap_or("unknown").to_string(),
+            joint_angles,
+            cue,
+            feedback,
+            warnings,
+            processing_time_ms: response.processing_time_ms,
+        })
+    }
+
     /// Quick pose analysis for WebSocket real-time feedback
     pub async fn quick_pose_analysis(&self, frame_base64: String) -> Option<QuickPoseResult> {
         match self.analyze_frame_realtime(frame_base64).await {
@@ -265,6 +382,20 @@ impl MLServiceClient {
     }
 }
 
+/// Form feedback for a single uploaded photo
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ImageFormFeedback {
+    pub score: f64,
+    pub exercise: String,
+    /// Detected joint angles in degrees, keyed by joint name
+    pub joint_angles: HashMap<String, f64>,
+    /// The single most important thing to fix
+    pub cue: String,
+    pub feedback: Vec<String>,
+    pub warnings: Vec<String>,
+    pub processing_time_ms: f64,
+}
+
 #[derive(Debug, Clone)]
 pub struct QuickPoseResult {
     pub score: f64,
@@ -304,6 +435,51 @@ mod tests {
         assert_eq!(client.get_base_url(), "http://localhost:8001");
     }
     
+    /// Backend that names itself in every analysis it returns
+    async fn mock_backend(name: &'static str) -> String {
+        use axum::{routing::post, Json, Router};
+
+        let reply = move || async move {
+            Json(serde_json::json!({
+                "success": true,
+                "processing_time_ms": 1.0,
+                "result": { "backend": name },
+                "error": null
+            }))
+        };
+        let app = Router::new()
+            .route("/analyze/frame", post(reply))
+            .route("/analyze/video", post(reply));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_tasks_reach_their_configured_backends() {
+        let mut config = crate::config::Config::default();
+        config.auth.allow_dev_secret = true;
+        config.ml_service.backends.insert("pose".to_string(), mock_backend("pose").await);
+        config.ml_service.backends.insert("form".to_string(), mock_backend("form").await);
+        config.ml_service.tasks.insert(MLTask::EstimatePose, "pose".to_string());
+        config.ml_service.tasks.insert(MLTask::AnalyzeForm, "form".to_string());
+        config.validate().unwrap();
+
+        let client = MLServiceClient::from_config(&config.ml_service).unwrap();
+        let realtime = client.analyze_frame_realtime("frame".to_string()).await.unwrap();
+        assert_eq!(realtime.result["backend"], "pose");
+        let detailed = client.analyze_frame_detailed("frame".to_string()).await.unwrap();
+        assert_eq!(detailed.result["backend"], "form");
+        let video = client.analyze_video("video".to_string(), "detailed").await.unwrap();
+        assert_eq!(video.result["backend"], "form");
+
+        config.ml_service.tasks.insert(MLTask::AnalyzeForm, "gpu".to_string());
+        let error = MLServiceClient::from_config(&config.ml_service).unwrap_err();
+        assert!(error.to_string().contains("'gpu'"), "{}", error);
+        assert!(config.validate().is_err());
+    }
+
     // Additional tests would require running ML service
     // For integration tests, see tests/integration_test.rs
 }
\ No newline at end of file
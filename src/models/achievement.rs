@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// `activity_counts` key for generated meal plans
+pub const MEAL_PLANS_ACTIVITY: &str = "meal_plans_generated";
+
+/// What a user has done so far, as the achievement rules see it
+#[derive(Debug, Clone, Default)]
+pub struct ActivitySummary {
+    pub total_workouts: u32,
+    pub current_streak_days: u32,
+    /// Heaviest completed set per exercise id (kg)
+    pub top_weights_kg: HashMap<String, f32>,
+    pub meal_plans_generated: u32,
+    pub goals_completed: u32,
+}
+
+/// The condition a badge is awarded for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    TotalWorkouts(u32),
+    StreakDays(u32),
+    /// A completed set of the exercise at or above the weight (kg)
+    ExerciseWeight { exercise_id: &'static str, kg: f32 },
+    MealPlansGenerated(u32),
+    GoalsCompleted(u32),
+}
+
+impl Criterion {
+    pub fn is_met(&self, activity: &ActivitySummary) -> bool {
+        match *self {
+            Criterion::TotalWorkouts(count) => activity.total_workouts >= count,
+            Criterion::StreakDays(days) => activity.current_streak_days >= days,
+            Criterion::ExerciseWeight { exercise_id, kg } => {
+                activity.top_weights_kg.get(exercise_id).is_some_and(|&top| top >= kg)
+            }
+            Criterion::MealPlansGenerated(count) => activity.meal_plans_generated >= count,
+            Criterion::GoalsCompleted(count) => activity.goals_completed >= count,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AchievementRule {
+    /// Stored with each award; never rename a released one
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub criterion: Criterion,
+}
+
+/// Every badge a user can earn. Adding one here is all a new badge takes;
+/// rules are re-checked whenever a workout is logged, a goal is met or a
+/// meal plan is generated.
+pub const ACHIEVEMENTS: &[AchievementRule] = &[
+    AchievementRule {
+        id: "first_workout",
+        name: "First Workout",
+        description: "Logged your first workout",
+        criterion: Criterion::TotalWorkouts(1),
+    },
+    AchievementRule {
+        id: "workouts_50",
+        name: "Half Century",
+        description: "Logged 50 workouts",
+        criterion: Criterion::TotalWorkouts(50),
+    },
+    AchievementRule {
+        id: "streak_7",
+        name: "7-Day Streak",
+        description: "Kept a workout streak going for a week",
+        criterion: Criterion::StreakDays(7),
+    },
+    AchievementRule {
+        id: "streak_30",
+        name: "30-Day Streak",
+        description: "Kept a workout streak going for a month",
+        criterion: Criterion::StreakDays(30),
+    },
+    AchievementRule {
+        id: "squat_100kg",
+        name: "Triple Digits",
+        description: "Completed a 100kg squat",
+        criterion: Criterion::ExerciseWeight { exercise_id: "squat", kg: 100.0 },
+    },
+    AchievementRule {
+        id: "meal_plans_10",
+        name: "Meal Planner",
+        description: "Generated 10 meal plans",
+        criterion: Criterion::MealPlansGenerated(10),
+    },
+    AchievementRule {
+        id: "first_goal",
+        name: "Goal Getter",
+        description: "Reached your first goal",
+        criterion: Criterion::GoalsCompleted(1),
+    },
+];
+
+impl AchievementRule {
+    pub fn find(id: &str) -> Option<&'static AchievementRule> {
+        ACHIEVEMENTS.iter().find(|rule| rule.id == id)
+    }
+}
+
+/// A badge a user has earned
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub awarded_at: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rule_ids_are_unique() {
+        let mut ids: Vec<&str> = ACHIEVEMENTS.iter().map(|rule| rule.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), ACHIEVEMENTS.len());
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use crate::models::user::ExerciseType;
+use crate::models::validation::{FieldError, FieldErrors, Validate};
+
+/// What a daily activity metric measures
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityMetricKind {
+    Steps,
+    /// Fairly plus very active minutes
+    ActiveMinutes,
+    /// Beats per minute at rest
+    RestingHeartRate,
+    /// Beats per minute averaged over the day
+    AverageHeartRate,
+}
+
+impl ActivityMetricKind {
+    /// Value stored in the activity_metrics table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityMetricKind::Steps => "steps",
+            ActivityMetricKind::ActiveMinutes => "active_minutes",
+            ActivityMetricKind::RestingHeartRate => "resting_heart_rate",
+            ActivityMetricKind::AverageHeartRate => "average_heart_rate",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "steps" => Some(ActivityMetricKind::Steps),
+            "active_minutes" => Some(ActivityMetricKind::ActiveMinutes),
+            "resting_heart_rate" => Some(ActivityMetricKind::RestingHeartRate),
+            "average_heart_rate" => Some(ActivityMetricKind::AverageHeartRate),
+            _ => None,
+        }
+    }
+}
+
+/// One day's value of a metric imported from a wearable. A user has at most
+/// one value per metric, date and source.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct ActivityMetric {
+    pub user_id: String,
+    /// Day the value covers (YYYY-MM-DD)
+    pub date: String,
+    /// Where the value came from, e.g. "fitbit"
+    pub source: String,
+    pub metric: ActivityMetricKind,
+    pub value: f64,
+}
+
+/// Daily activity and heart rate from imported metrics
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ActivityTrends {
+    /// Over the most recent week with step data
+    pub average_daily_steps: Option<f32>,
+    pub latest_resting_heart_rate: Option<f32>,
+    /// Change in resting heart rate (bpm per week); falling usually means
+    /// improving fitness
+    pub resting_heart_rate_trend: Option<f32>,
+    /// Over the most recent week with sleep data
+    #[serde(default)]
+    pub average_sleep_hours: Option<f32>,
+}
+
+/// A night's sleep imported from a phone's health store
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct SleepSession {
+    pub user_id: String,
+    pub source: String,
+    /// The health store's id for the record
+    pub external_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl SleepSession {
+    pub fn hours(&self) -> f32 {
+        (self.end - self.start).num_minutes() as f32 / 60.0
+    }
+}
+
+/// Phone health store a payload was exported from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthSource {
+    AppleHealth,
+    GoogleFit,
+}
+
+impl HealthSource {
+    /// `source` of the records imported from it
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthSource::AppleHealth => "apple_health",
+            HealthSource::GoogleFit => "google_fit",
+        }
+    }
+}
+
+/// Health data pushed by a mobile client, normalized from HealthKit or
+/// Google Fit. Every list is optional, so a client can send only what it
+/// has permission to read.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct HealthImport {
+    pub source: HealthSource,
+    #[serde(default)]
+    pub steps: Vec<DailySteps>,
+    #[serde(default)]
+    pub heart_rate: Vec<DailyHeartRate>,
+    #[serde(default)]
+    pub sleep: Vec<ImportedSleep>,
+    #[serde(default)]
+    pub workouts: Vec<ImportedWorkout>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct DailySteps {
+    /// YYYY-MM-DD
+    pub date: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct DailyHeartRate {
+    /// YYYY-MM-DD
+    pub date: String,
+    #[serde(default)]
+    pub resting_bpm: Option<f32>,
+    #[serde(default)]
+    pub average_bpm: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ImportedSleep {
+    pub external_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct ImportedWorkout {
+    pub external_id: String,
+    pub start: DateTime<Utc>,
+    pub duration_minutes: u32,
+    /// Estimated from the duration and type when missing
+    #[serde(default)]
+    pub calories: Option<f32>,
+    /// Closest match for the health store's activity; strength when missing
+    #[serde(default)]
+    pub exercise_type: Option<ExerciseType>,
+    /// The health store's name for the activity, e.g. "Running"
+    #[serde(default)]
+    pub activity: Option<String>,
+}
+
+fn is_date(date: &str) -> bool {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok()
+}
+
+fn is_bpm(bpm: Option<f32>) -> bool {
+    bpm.is_none_or(|bpm| (20.0..=250.0).contains(&bpm))
+}
+
+impl Validate for HealthImport {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        for (i, steps) in self.steps.iter().enumerate() {
+            errors.check(is_date(&steps.date), &format!("steps[{}].date", i), "must be a YYYY-MM-DD date");
+        }
+        for (i, heart) in self.heart_rate.iter().enumerate() {
+            errors.check(is_date(&heart.date), &format!("heart_rate[{}].date", i), "must be a YYYY-MM-DD date");
+            errors.check(is_bpm(heart.resting_bpm), &format!("heart_rate[{}].resting_bpm", i), "must be between 20 and 250");
+            errors.check(is_bpm(heart.average_bpm), &format!("heart_rate[{}].average_bpm", i), "must be between 20 and 250");
+        }
+        for (i, sleep) in self.sleep.iter().enumerate() {
+            errors.check(!sleep.external_id.trim().is_empty(), &format!("sleep[{}].external_id", i), "must not be empty");
+            errors.check(
+                sleep.end > sleep.start && sleep.end - sleep.start <= chrono::Duration::hours(24),
+                &format!("sleep[{}].end", i),
+                "must be after start and within a day of it",
+            );
+        }
+        for (i, workout) in self.workouts.iter().enumerate() {
+            errors.check(!workout.external_id.trim().is_empty(), &format!("workouts[{}].external_id", i), "must not be empty");
+            errors.check(
+                (1..=24 * 60).contains(&workout.duration_minutes),
+                &format!("workouts[{}].duration_minutes", i),
+                "must be between 1 minute and a day",
+            );
+            errors.check(
+                workout.calories.is_none_or(|calories| calories.is_finite() && calories >= 0.0),
+                &format!("workouts[{}].calories", i),
+                "must not be negative",
+            );
+        }
+        errors.into_result()
+    }
+}
+
+/// Records of one type a health import stored, and those skipped because
+/// they were already imported
+#[derive(Debug, Clone, Copy, Default, Serialize, ToSchema)]
+pub struct ImportCount {
+    pub imported: usize,
+    pub duplicates: usize,
+}
+
+/// What a health import stored. Steps and heart rate count stored metric
+/// records, so a day with both resting and average heart rate counts twice.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct HealthImportSummary {
+    pub steps: ImportCount,
+    pub heart_rate: ImportCount,
+    pub sleep: ImportCount,
+    pub workouts: ImportCount,
+}
@@ -1,7 +1,12 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use crate::models::user::{ExerciseType, Equipment};
+use crate::models::validation::{FieldError, FieldErrors, Validate};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Exercises are rated from 1 (beginner) to this (elite)
+pub const MAX_DIFFICULTY_LEVEL: u32 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Exercise {
     pub id: String,
     pub name: String,
@@ -13,9 +18,34 @@ pub struct Exercise {
     pub secondary_muscles: Vec<MuscleGroup>,
     pub instructions: Vec<String>,
     pub safety_tips: Vec<String>,
+    /// Link to a video or animation demonstrating the movement
+    #[serde(default)]
+    pub demo_url: Option<String>,
+    /// Short form cues to keep in mind during each rep
+    #[serde(default)]
+    pub cues: Vec<String>,
+}
+
+impl Validate for Exercise {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.id.trim().is_empty(), "id", "must not be empty");
+        errors.check(!self.name.trim().is_empty(), "name", "must not be empty");
+        errors.check(
+            (1..=MAX_DIFFICULTY_LEVEL).contains(&self.difficulty_level),
+            "difficulty_level",
+            format!("must be between 1 and {}", MAX_DIFFICULTY_LEVEL),
+        );
+        if let Some(url) = &self.demo_url {
+            let well_formed = reqwest::Url::parse(url)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host());
+            errors.check(well_formed, "demo_url", "must be an http(s) URL");
+        }
+        errors.into_result()
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum MuscleGroup {
     Chest,
     Back,
@@ -27,7 +57,7 @@ pub enum MuscleGroup {
     Calves,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ExerciseSet {
     pub exercise_id: String,
     pub sets: u32,
@@ -36,4 +66,76 @@ pub struct ExerciseSet {
     pub duration_seconds: Option<u32>,
     pub rest_seconds: u32,
     pub completed: bool,
-}
\ No newline at end of file
+    /// Seconds per rep phase: eccentric, bottom pause, concentric, top
+    /// pause, e.g. "3010"
+    #[serde(default)]
+    pub tempo: Option<String>,
+    /// Target rate of perceived exertion on the 1-10 scale
+    #[serde(default)]
+    pub target_rpe: Option<f32>,
+    /// Sets sharing a group id form a superset or circuit: they are done
+    /// back-to-back, resting only after each round. Ungrouped sets are done
+    /// on their own.
+    #[serde(default)]
+    pub group_id: Option<String>,
+}
+
+/// Above this many reps a set says more about endurance than strength, so
+/// it isn't used to estimate a one-rep max
+pub const MAX_REPS_FOR_1RM: u32 = 10;
+
+/// Estimated one-rep max from `weight_kg` lifted for `reps`. Brzycki is used
+/// up to `MAX_REPS_FOR_1RM` reps, where it tracks tested maxes closely;
+/// Epley beyond that, since Brzycki breaks down as reps approach 37.
+pub fn estimate_1rm(weight_kg: f32, reps: u32) -> f32 {
+    match reps {
+        0 | 1 => weight_kg,
+        r if r <= MAX_REPS_FOR_1RM => weight_kg * 36.0 / (37.0 - r as f32),
+        r => weight_kg * (1.0 + r as f32 / 30.0),
+    }
+}
+
+/// Weight for a set of `target_reps` given a one-rep max, the inverse of
+/// `estimate_1rm`, rounded to the nearest 0.5 kg
+pub fn suggest_weight(one_rm_kg: f32, target_reps: u32) -> f32 {
+    let weight = match target_reps {
+        0 | 1 => one_rm_kg,
+        r if r <= MAX_REPS_FOR_1RM => one_rm_kg * (37.0 - r as f32) / 36.0,
+        r => one_rm_kg / (1.0 + r as f32 / 30.0),
+    };
+    (weight * 2.0).round() / 2.0
+}
+
+impl ExerciseType {
+    /// Metabolic equivalent (MET) for the exercise type, based on the
+    /// Compendium of Physical Activities. 1 MET ≈ 1 kcal per kg per hour.
+    pub fn met_value(&self) -> f32 {
+        match self {
+            ExerciseType::Cardio => 8.0,
+            ExerciseType::Strength => 5.0,
+            ExerciseType::Flexibility => 2.5,
+            ExerciseType::Balance => 2.5,
+            ExerciseType::Sports => 7.0,
+            ExerciseType::Yoga => 3.0,
+            ExerciseType::Pilates => 3.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_rep_max_estimate_and_suggested_weight() {
+        let one_rm = estimate_1rm(100.0, 5);
+        assert!((110.0..=120.0).contains(&one_rm), "1RM {}", one_rm);
+        assert_eq!(estimate_1rm(100.0, 1), 100.0);
+
+        let for_eight = suggest_weight(one_rm, 8);
+        assert!(for_eight < 100.0 && for_eight > 80.0, "8 reps at {}", for_eight);
+        assert_eq!(suggest_weight(one_rm, 5), 100.0);
+        assert_eq!(suggest_weight(one_rm, 1), (one_rm * 2.0).round() / 2.0);
+        assert!(suggest_weight(one_rm, 15) < for_eight);
+    }
+}
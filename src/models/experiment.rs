@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// One request served under an experiment, with its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentExposure {
+    pub experiment: String,
+    pub variant: String,
+    pub user_id: String,
+    /// What actually handled the request, when it differs from the variant
+    /// (e.g. a fallback) or the variant alone doesn't say
+    pub served_by: Option<String>,
+    pub succeeded: bool,
+    /// Quality of the result, e.g. the meal plan's fitness score
+    pub score: Option<f64>,
+    pub runtime_ms: Option<f64>,
+}
+
+/// Outcome metrics of one variant
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VariantResults {
+    pub variant: String,
+    pub requests: u32,
+    pub users: u32,
+    pub successes: u32,
+    /// Of successful requests
+    pub average_score: Option<f64>,
+    pub average_runtime_ms: Option<f64>,
+    /// Requests a different implementation had to serve
+    pub fallbacks: u32,
+}
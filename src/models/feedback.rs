@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Whether a user found a recommendation helpful
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+impl FeedbackRating {
+    /// Value stored in the recommendation_feedback table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackRating::Up => "up",
+            FeedbackRating::Down => "down",
+        }
+    }
+}
+
+/// Everyone's votes on one recommendation. Each user has one vote, so
+/// rating again replaces their earlier one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct FeedbackSummary {
+    pub recommendation_id: String,
+    pub thumbs_up: u32,
+    pub thumbs_down: u32,
+}
+
+impl FeedbackSummary {
+    /// Up votes minus down votes; what ranking uses
+    pub fn net(&self) -> i64 {
+        self.thumbs_up as i64 - self.thumbs_down as i64
+    }
+}
@@ -1,9 +1,12 @@
 // src/models/food.rs - Food and nutrition data models
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use crate::core::{FitnessError, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct Food {
     pub id: String,
     pub name: String,
@@ -14,10 +17,14 @@ pub struct Food {
     pub seasonality: Option<Seasonality>,
     pub cost_per_100g: Option<f64>, // In local currency
     pub availability_score: f64,    // 0.0 to 1.0
+    /// Glycemic index (glucose = 100); `None` when unknown or the food has
+    /// no carbohydrate to rate
+    #[serde(default)]
+    pub glycemic_index: Option<f64>,
     pub taste_profile: TasteProfile,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct NutritionFacts {
     pub calories: f64,
     pub protein_g: f64,
@@ -37,7 +44,7 @@ pub struct NutritionFacts {
     pub omega6_g: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum FoodCategory {
     Protein,
     Grains,
@@ -52,7 +59,7 @@ pub enum FoodCategory {
     Supplements,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum Allergen {
     Gluten,
     Dairy,
@@ -65,7 +72,7 @@ pub enum Allergen {
     Sesame,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum DietaryFlag {
     Vegetarian,
     Vegan,
@@ -79,14 +86,14 @@ pub enum DietaryFlag {
     Organic,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct Seasonality {
     pub peak_months: Vec<u8>,     // 1-12 for months
     pub available_months: Vec<u8>, // 1-12 for months
     pub quality_by_month: HashMap<u8, f64>, // Month -> quality score (0.0-1.0)
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub struct TasteProfile {
     pub sweetness: f64,     // 0.0 to 1.0
     pub saltiness: f64,     // 0.0 to 1.0
@@ -96,7 +103,7 @@ pub struct TasteProfile {
     pub spiciness: f64,     // 0.0 to 1.0
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Recipe {
     pub id: String,
     pub name: String,
@@ -116,7 +123,7 @@ pub struct Recipe {
     pub cost_per_serving: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Ingredient {
     pub food_id: String,
     pub amount_g: f64,
@@ -124,7 +131,43 @@ pub struct Ingredient {
     pub substitutes: Vec<String>,    // Alternative food IDs
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// How well a recipe can be made from a user's pantry
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RecipeSuggestion {
+    pub recipe_id: String,
+    pub recipe_name: String,
+    /// Every ingredient (or one of its substitutes) is available
+    pub can_make: bool,
+    /// Fraction of ingredients covered by the pantry
+    pub coverage: f64,
+    /// Food ids that would still need to be bought
+    pub missing_ingredients: Vec<String>,
+}
+
+/// Shopping list for a meal plan, grouped by food category
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroceryList {
+    pub meal_plan_id: String,
+    pub sections: Vec<GrocerySection>,
+    pub estimated_cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GrocerySection {
+    pub category: FoodCategory,
+    pub items: Vec<GroceryItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GroceryItem {
+    pub food_id: String,
+    pub name: String,
+    /// Total quantity across the plan, in grams
+    pub amount_g: f64,
+    pub estimated_cost: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum DifficultyLevel {
     Easy,
     Medium,
@@ -132,7 +175,7 @@ pub enum DifficultyLevel {
     Expert,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord, ToSchema)]
 pub enum MealType {
     Breakfast,
     Lunch,
@@ -142,7 +185,7 @@ pub enum MealType {
     Beverage,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MealPlan {
     pub id: String,
     pub user_id: String,
@@ -156,7 +199,7 @@ pub struct MealPlan {
     pub generated_at: chrono::DateTime<chrono::Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PlannedMeal {
     pub date: chrono::NaiveDate,
     pub meal_type: MealType,
@@ -166,12 +209,84 @@ pub struct PlannedMeal {
     pub cost: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum GlycemicLevel {
+    Low,
+    Moderate,
+    High,
+}
+
+/// Glycemic load: glycemic index × available carbohydrate (g) / 100
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct GlycemicLoad {
+    pub value: f64,
+    pub level: GlycemicLevel,
+}
+
+impl GlycemicLoad {
+    /// Classified against the usual per-meal bands: low up to 10, high from 20
+    pub fn meal(value: f64) -> Self {
+        let level = if value <= 10.0 {
+            GlycemicLevel::Low
+        } else if value < 20.0 {
+            GlycemicLevel::Moderate
+        } else {
+            GlycemicLevel::High
+        };
+        Self { value, level }
+    }
+
+    /// Classified against the usual daily bands: low below 80, high above 120
+    pub fn daily(value: f64) -> Self {
+        let level = if value < 80.0 {
+            GlycemicLevel::Low
+        } else if value <= 120.0 {
+            GlycemicLevel::Moderate
+        } else {
+            GlycemicLevel::High
+        };
+        Self { value, level }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct DateRange {
     pub start: chrono::NaiveDate,
     pub end: chrono::NaiveDate,
 }
 
+impl FoodCategory {
+    /// Best guess from a free-text category such as "Vegetables and
+    /// Vegetable Products" or "en:dairies". Anything not recognised as
+    /// plant, dairy, fat or drink (meat, fish, poultry) is protein.
+    pub fn from_description(description: &str) -> Self {
+        let description = description.to_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| description.contains(word));
+
+        if mentions(&["vegetable"]) {
+            FoodCategory::Vegetables
+        } else if mentions(&["fruit"]) {
+            FoodCategory::Fruits
+        } else if mentions(&["dairy", "dairies", "egg", "milk", "cheese", "yogurt"]) {
+            FoodCategory::Dairy
+        } else if mentions(&["legume"]) {
+            FoodCategory::Legumes
+        } else if mentions(&["nut"]) {
+            FoodCategory::Nuts
+        } else if mentions(&["fats", "oil"]) {
+            FoodCategory::Oils
+        } else if mentions(&["spice"]) {
+            FoodCategory::Spices
+        } else if mentions(&["beverage"]) {
+            FoodCategory::Beverages
+        } else if mentions(&["cereal", "grain", "baked", "pasta", "bread"]) {
+            FoodCategory::Grains
+        } else {
+            FoodCategory::Protein
+        }
+    }
+}
+
 impl Food {
     pub fn get_nutrition_for_amount(&self, grams: f64) -> NutritionFacts {
         let multiplier = grams / 100.0;
@@ -203,6 +318,22 @@ impl Food {
         self.allergens.contains(allergen)
     }
 
+    /// Whether the food is at its peak in `month`; `None` for foods without
+    /// seasonal data, which count as available year-round
+    pub fn is_in_season(&self, month: u8) -> Option<bool> {
+        self.seasonality.as_ref().map(|s| s.peak_months.contains(&month))
+    }
+
+    /// Glycemic load of `grams` of the food, counting carbohydrate net of
+    /// fiber. Foods without a glycemic index contribute nothing.
+    pub fn glycemic_load(&self, grams: f64) -> f64 {
+        let Some(glycemic_index) = self.glycemic_index else {
+            return 0.0;
+        };
+        let available_carbs_g = (self.nutrition_per_100g.carbs_g - self.nutrition_per_100g.fiber_g).max(0.0);
+        glycemic_index * available_carbs_g * grams / 100.0 / 100.0
+    }
+
     pub fn get_seasonal_quality(&self, month: u8) -> f64 {
         self.seasonality
             .as_ref()
@@ -212,6 +343,81 @@ impl Food {
     }
 }
 
+impl Recipe {
+    /// Glycemic load of one serving, from the ingredients' foods. Unknown
+    /// foods count as carbohydrate-free.
+    pub fn glycemic_load_per_serving(&self, foods: &HashMap<String, Food>) -> GlycemicLoad {
+        let total: f64 = self.ingredients.iter()
+            .filter_map(|ingredient| {
+                foods.get(&ingredient.food_id).map(|food| food.glycemic_load(ingredient.amount_g))
+            })
+            .sum();
+        GlycemicLoad::meal(total / self.servings.max(1) as f64)
+    }
+
+    /// A copy of the recipe with `food_id` swapped for `replacement_food_id`,
+    /// e.g. to avoid an allergen. Ingredient amounts are in grams, so the
+    /// replacement keeps the same mass whatever its density. Nutrition and
+    /// cost per serving move by the difference between the two foods at that
+    /// amount, and allergens are rebuilt from the ingredients when all of
+    /// their foods are known.
+    pub fn substitute_ingredient(
+        &self,
+        food_id: &str,
+        replacement_food_id: &str,
+        foods: &HashMap<String, Food>,
+    ) -> Result<Recipe> {
+        let lookup = |id: &str| foods.get(id).ok_or_else(|| FitnessError::FoodNotFound { id: id.to_string() });
+        let original = lookup(food_id)?;
+        let replacement = lookup(replacement_food_id)?;
+        if self.ingredients.iter().any(|i| i.food_id == replacement_food_id) {
+            return Err(FitnessError::validation(format!(
+                "{} already contains {}", self.name, replacement_food_id
+            )));
+        }
+
+        let mut recipe = self.clone();
+        let ingredient = recipe.ingredients.iter_mut()
+            .find(|i| i.food_id == food_id)
+            .ok_or_else(|| FitnessError::validation(format!("{} does not contain {}", self.name, food_id)))?;
+        ingredient.food_id = replacement_food_id.to_string();
+        ingredient.substitutes.retain(|id| id != replacement_food_id);
+        let grams_per_serving = ingredient.amount_g / recipe.servings.max(1) as f64;
+
+        recipe.nutrition_per_serving.add(&original.get_nutrition_for_amount(-grams_per_serving));
+        recipe.nutrition_per_serving.add(&replacement.get_nutrition_for_amount(grams_per_serving));
+
+        if let (Some(cost), Some(old_cost), Some(new_cost)) =
+            (recipe.cost_per_serving, original.cost_per_100g, replacement.cost_per_100g)
+        {
+            recipe.cost_per_serving = Some(cost + (new_cost - old_cost) * grams_per_serving / 100.0);
+        }
+
+        let ingredient_foods: Option<Vec<&Food>> = recipe.ingredients.iter()
+            .map(|i| foods.get(&i.food_id))
+            .collect();
+        match ingredient_foods {
+            Some(ingredient_foods) => {
+                recipe.allergens.clear();
+                for allergen in ingredient_foods.iter().flat_map(|food| &food.allergens) {
+                    if !recipe.allergens.contains(allergen) {
+                        recipe.allergens.push(allergen.clone());
+                    }
+                }
+            }
+            None => {
+                for allergen in &replacement.allergens {
+                    if !recipe.allergens.contains(allergen) {
+                        recipe.allergens.push(allergen.clone());
+                    }
+                }
+            }
+        }
+
+        Ok(recipe)
+    }
+}
+
 impl NutritionFacts {
     pub fn new() -> Self {
         Self {
@@ -327,4 +533,82 @@ impl Default for TasteProfile {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample_data::{create_sample_foods, create_sample_recipes};
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn test_substituting_coconut_yogurt_updates_macros_and_allergens() {
+        let foods = create_sample_foods();
+        let bowl = create_sample_recipes().into_iter()
+            .find(|r| r.id == "greek_yogurt_berry_bowl")
+            .unwrap();
+
+        let swapped = bowl.substitute_ingredient("greek_yogurt", "coconut_yogurt", &foods).unwrap();
+
+        let yogurt = swapped.ingredients.iter().find(|i| i.food_id == "coconut_yogurt").unwrap();
+        assert_eq!(yogurt.amount_g, 200.0);
+        assert!(swapped.ingredients.iter().all(|i| i.food_id != "greek_yogurt"));
+
+        // 200g: protein 20.0 -> 1.6, fat 0.8 -> 20.0, carbs 7.2 -> 14.0, calories 118 -> 240
+        let (before, after) = (&bowl.nutrition_per_serving, &swapped.nutrition_per_serving);
+        assert_close(after.protein_g, before.protein_g - 18.4);
+        assert_close(after.fat_g, before.fat_g + 19.2);
+        assert_close(after.carbs_g, before.carbs_g + 6.8);
+        assert_close(after.calories, before.calories + 122.0);
+        assert_close(swapped.cost_per_serving.unwrap(), bowl.cost_per_serving.unwrap() + 1.4);
+
+        assert!(!swapped.allergens.contains(&Allergen::Dairy));
+        assert!(swapped.allergens.contains(&Allergen::TreeNuts));
+    }
+
+    #[test]
+    fn test_white_rice_meal_has_higher_glycemic_load_than_bean_meal() {
+        let foods = create_sample_foods();
+        let bowl = create_sample_recipes().into_iter()
+            .find(|r| r.id == "salmon_rice_bowl")
+            .unwrap();
+        let with_base = |food_id: &str| Recipe {
+            ingredients: vec![
+                Ingredient { food_id: food_id.to_string(), amount_g: 250.0, preparation: None, substitutes: vec![] },
+                Ingredient { food_id: "salmon".to_string(), amount_g: 150.0, preparation: None, substitutes: vec![] },
+            ],
+            ..bowl.clone()
+        };
+
+        // 73 × (28.2 - 0.4) × 2.5 / 100
+        let rice = with_base("white_rice").glycemic_load_per_serving(&foods);
+        assert_close(rice.value, 50.735);
+        assert_eq!(rice.level, GlycemicLevel::High);
+
+        // 30 × (23.7 - 8.7) × 2.5 / 100
+        let beans = with_base("black_beans").glycemic_load_per_serving(&foods);
+        assert_close(beans.value, 11.25);
+        assert_eq!(beans.level, GlycemicLevel::Moderate);
+
+        assert_eq!(GlycemicLoad::daily(rice.value * 3.0).level, GlycemicLevel::High);
+        assert_eq!(GlycemicLoad::daily(beans.value * 3.0).level, GlycemicLevel::Low);
+    }
+
+    #[test]
+    fn test_substitution_requires_known_foods_in_the_recipe() {
+        let foods = create_sample_foods();
+        let bowl = create_sample_recipes().into_iter()
+            .find(|r| r.id == "greek_yogurt_berry_bowl")
+            .unwrap();
+
+        assert!(matches!(
+            bowl.substitute_ingredient("greek_yogurt", "soy_yogurt", &foods),
+            Err(FitnessError::FoodNotFound { .. })
+        ));
+        assert!(bowl.substitute_ingredient("olive_oil", "coconut_yogurt", &foods).is_err());
+        assert!(bowl.substitute_ingredient("greek_yogurt", "almonds", &foods).is_err());
+    }
+}
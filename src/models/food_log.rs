@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// One food a user ate. A user has at most one entry per food, date and
+/// time, which is what makes diary imports safe to repeat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct FoodLogEntry {
+    pub id: String,
+    pub user_id: String,
+    /// Day eaten (YYYY-MM-DD)
+    pub date: String,
+    /// Time eaten (HH:MM), when known
+    pub time: Option<String>,
+    /// e.g. "Breakfast"
+    pub meal: Option<String>,
+    /// Name as logged
+    pub food_name: String,
+    /// The matching food in the nutrition database, when one was found
+    pub food_id: Option<String>,
+    pub quantity_g: Option<f64>,
+    pub calories: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    /// Where the entry came from, e.g. "myfitnesspal"
+    pub source: String,
+}
+
+/// A day's food log with its totals
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct FoodLogDay {
+    pub date: String,
+    pub calories: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    pub entries: Vec<FoodLogEntry>,
+}
+
+/// Group entries by date, oldest day first
+pub fn daily_totals(entries: Vec<FoodLogEntry>) -> Vec<FoodLogDay> {
+    let mut days: BTreeMap<String, Vec<FoodLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        days.entry(entry.date.clone()).or_default().push(entry);
+    }
+
+    days.into_iter()
+        .map(|(date, entries)| FoodLogDay {
+            date,
+            calories: entries.iter().map(|e| e.calories).sum(),
+            protein_g: entries.iter().map(|e| e.protein_g).sum(),
+            carbs_g: entries.iter().map(|e| e.carbs_g).sum(),
+            fat_g: entries.iter().map(|e| e.fat_g).sum(),
+            entries,
+        })
+        .collect()
+}
+
+/// A diary row that wasn't imported, numbered from 1 after any header
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SkippedDiaryRow {
+    pub row: usize,
+    pub reason: String,
+}
+
+/// What a food diary import stored
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct DiaryImportSummary {
+    pub imported: usize,
+    /// Imported rows matched to a food in the nutrition database
+    pub resolved: usize,
+    /// Rows already imported or that couldn't be read
+    pub skipped: Vec<SkippedDiaryRow>,
+}
@@ -0,0 +1,118 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// What a goal measures
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalMetric {
+    /// Heaviest completed set of `exercise_id` (kg)
+    ExerciseWeight,
+    /// Latest body weight (kg)
+    BodyWeight,
+    /// Latest waist measurement (cm)
+    WaistCm,
+    /// Workouts logged since the goal was set
+    WorkoutCount,
+}
+
+impl GoalMetric {
+    /// Value stored in the goals table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalMetric::ExerciseWeight => "exercise_weight",
+            GoalMetric::BodyWeight => "body_weight",
+            GoalMetric::WaistCm => "waist_cm",
+            GoalMetric::WorkoutCount => "workout_count",
+        }
+    }
+
+    pub fn parse(metric: &str) -> Option<Self> {
+        match metric {
+            "exercise_weight" => Some(GoalMetric::ExerciseWeight),
+            "body_weight" => Some(GoalMetric::BodyWeight),
+            "waist_cm" => Some(GoalMetric::WaistCm),
+            "workout_count" => Some(GoalMetric::WorkoutCount),
+            _ => None,
+        }
+    }
+}
+
+/// A measurable target such as "bench 100kg". Goals whose target is below
+/// their starting value (losing weight, trimming the waist) count down.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserGoal {
+    pub id: String,
+    pub user_id: String,
+    pub title: String,
+    pub metric: GoalMetric,
+    /// The exercise an `exercise_weight` goal tracks
+    pub exercise_id: Option<String>,
+    /// The metric's value when the goal was set
+    pub start_value: f64,
+    pub target_value: f64,
+    /// Target date (YYYY-MM-DD)
+    pub deadline: Option<String>,
+    pub created_at: String,
+    /// When the target was first reached; completed goals stay completed
+    pub completed_at: Option<String>,
+}
+
+impl UserGoal {
+    /// Share of the way from the start value to the target (0-100)
+    pub fn completion_percent(&self, current: f64) -> f64 {
+        let distance = self.target_value - self.start_value;
+        if distance == 0.0 {
+            return 100.0;
+        }
+        ((current - self.start_value) / distance * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Past the deadline without being completed
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.completed_at.is_none()
+            && self.deadline.as_deref()
+                .and_then(|deadline| NaiveDate::parse_from_str(deadline, "%Y-%m-%d").ok())
+                .is_some_and(|deadline| deadline < today)
+    }
+}
+
+/// A goal with its progress computed from logged workouts and measurements
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct GoalProgress {
+    pub goal: UserGoal,
+    /// None until there's data for the metric, e.g. no waist measurement yet
+    pub current_value: Option<f64>,
+    pub completion_percent: f64,
+    pub completed: bool,
+    pub overdue: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_loss_goal_counts_down() {
+        let goal = UserGoal {
+            id: "goal_1".to_string(),
+            user_id: "test_user".to_string(),
+            title: "Get to 75kg".to_string(),
+            metric: GoalMetric::BodyWeight,
+            exercise_id: None,
+            start_value: 80.0,
+            target_value: 75.0,
+            deadline: Some("2025-09-01".to_string()),
+            created_at: "2025-07-01 08:00:00".to_string(),
+            completed_at: None,
+        };
+
+        assert_eq!(goal.completion_percent(80.0), 0.0);
+        assert_eq!(goal.completion_percent(78.0), 40.0);
+        assert_eq!(goal.completion_percent(74.0), 100.0);
+        assert_eq!(goal.completion_percent(82.0), 0.0);
+
+        assert!(!goal.is_overdue(NaiveDate::from_ymd_opt(2025, 9, 1).unwrap()));
+        assert!(goal.is_overdue(NaiveDate::from_ymd_opt(2025, 9, 2).unwrap()));
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A dated body weight entry with optional circumference measurements.
+/// There is at most one measurement per user per date.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BodyMeasurement {
+    pub user_id: String,
+    /// Measurement date (YYYY-MM-DD)
+    pub date: String,
+    pub weight_kg: f32,
+    pub waist_cm: Option<f32>,
+    pub hip_cm: Option<f32>,
+    pub arm_cm: Option<f32>,
+}
+
+impl BodyMeasurement {
+    /// Validate the entry against `today`, rejecting malformed or future dates
+    /// and non-positive values.
+    pub fn validate(&self, today: NaiveDate) -> Result<(), String> {
+        let date = NaiveDate::parse_from_str(&self.date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid measurement date '{}', expected YYYY-MM-DD", self.date))?;
+        if date > today {
+            return Err(format!("Measurement date {} is in the future", self.date));
+        }
+
+        if self.weight_kg <= 0.0 {
+            return Err("Weight must be positive".to_string());
+        }
+
+        let circumferences = [self.waist_cm, self.hip_cm, self.arm_cm];
+        if circumferences.iter().flatten().any(|&cm| cm <= 0.0) {
+            return Err("Circumference measurements must be positive".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_future_dates() {
+        let today = NaiveDate::from_ymd_opt(2025, 8, 13).unwrap();
+        let mut measurement = BodyMeasurement {
+            user_id: "test_user".to_string(),
+            date: "2025-08-13".to_string(),
+            weight_kg: 70.0,
+            waist_cm: Some(80.0),
+            hip_cm: None,
+            arm_cm: None,
+        };
+        assert!(measurement.validate(today).is_ok());
+
+        measurement.date = "2025-08-14".to_string();
+        assert!(measurement.validate(today).is_err());
+
+        measurement.date = "13/08/2025".to_string();
+        assert!(measurement.validate(today).is_err());
+    }
+}
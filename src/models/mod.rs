@@ -5,11 +5,31 @@ pub mod optimization;
 pub mod user;
 pub mod exercise;
 pub mod workout;
+pub mod measurement;
 pub mod system;
+pub mod validation;
+pub mod units;
+pub mod goal;
+pub mod achievement;
+pub mod webhook;
+pub mod feedback;
+pub mod experiment;
+pub mod activity;
+pub mod food_log;
 
 pub use food::*;
 pub use optimization::*;
 pub use user::*;
 pub use exercise::*;
 pub use workout::*;
-pub use system::*;
\ No newline at end of file
+pub use measurement::*;
+pub use system::*;
+pub use validation::*;
+pub use units::*;
+pub use goal::*;
+pub use achievement::*;
+pub use webhook::*;
+pub use feedback::*;
+pub use experiment::*;
+pub use activity::*;
+pub use food_log::*;
\ No newline at end of file
@@ -1,10 +1,12 @@
 // src/models/optimization.rs - Optimization algorithm types and constraints
 
 use serde::{Deserialize, Serialize};
-use crate::models::food::{Allergen, DietaryFlag, NutritionFacts, MealType};
+use utoipa::ToSchema;
+use crate::models::food::{Allergen, DietaryFlag, GlycemicLoad, NutritionFacts, MealType};
+use crate::models::validation::{describe_field_errors, FieldError, FieldErrors, Validate};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OptimizationRequest {
     pub user_id: String,
     pub constraints: NutritionConstraints,
@@ -12,9 +14,22 @@ pub struct OptimizationRequest {
     pub objectives: Vec<OptimizationObjective>,
     pub time_horizon_days: u32,
     pub algorithm_config: AlgorithmConfig,
+    /// Most times a single recipe may be served per 7 days of the plan
+    #[serde(default = "default_max_recipe_repetitions_per_week")]
+    pub max_recipe_repetitions_per_week: u32,
+    /// Month the plan is for (1-12). Recipes using produce out of season
+    /// that month are penalized, not excluded.
+    #[serde(default)]
+    pub month: Option<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const DEFAULT_MAX_RECIPE_REPETITIONS_PER_WEEK: u32 = 2;
+
+fn default_max_recipe_repetitions_per_week() -> u32 {
+    DEFAULT_MAX_RECIPE_REPETITIONS_PER_WEEK
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct NutritionConstraints {
     pub daily_calories: CalorieRange,
     pub macros: MacroConstraints,
@@ -22,16 +37,104 @@ pub struct NutritionConstraints {
     pub meal_count_per_day: MealCountConstraints,
     pub budget_per_day: Option<f64>,
     pub preparation_time_max_minutes: Option<u32>,
+    /// How daily calories should be split across meal types; `None` leaves
+    /// the split to the optimizer
+    #[serde(default)]
+    pub calorie_distribution: Option<CalorieDistribution>,
+    /// Time-restricted eating, e.g. 12:00-20:00 for 16:8; every meal of the
+    /// day is placed inside it
+    #[serde(default)]
+    pub eating_window: Option<EatingWindow>,
+}
+
+/// Shortest gap the planner leaves between two meals in an eating window
+pub const MIN_MEAL_SPACING_MINUTES: i64 = 90;
+
+/// Daily eating window. An end before the start runs past midnight.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EatingWindow {
+    pub start: chrono::NaiveTime,
+    pub end: chrono::NaiveTime,
+}
+
+impl EatingWindow {
+    pub fn length_minutes(&self) -> i64 {
+        (self.end - self.start).num_minutes().rem_euclid(24 * 60)
+    }
+
+    /// Whether `meals` meals fit with at least `MIN_MEAL_SPACING_MINUTES`
+    /// between them
+    pub fn fits(&self, meals: u32) -> bool {
+        meals <= 1 || self.length_minutes() >= MIN_MEAL_SPACING_MINUTES * (meals as i64 - 1)
+    }
+
+    /// `meals` times spread evenly from the start of the window to its end
+    pub fn meal_times(&self, meals: u32) -> Vec<chrono::NaiveTime> {
+        let step = if meals > 1 { self.length_minutes() / (meals as i64 - 1) } else { 0 };
+        (0..meals as i64)
+            .map(|i| self.start + chrono::Duration::minutes(step * i))
+            .collect()
+    }
+}
+
+/// When one of the day's meals is eaten
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealTime {
+    pub meal_type: MealType,
+    pub time: chrono::NaiveTime,
+}
+
+/// Percent of daily calories for each meal type, e.g. 25/35/30/10. Shares
+/// are normalized over the meal types the plan serves, so they need not sum
+/// to 100.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CalorieDistribution {
+    pub breakfast: f64,
+    pub lunch: f64,
+    pub dinner: f64,
+    pub snacks: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl CalorieDistribution {
+    fn percent(&self, meal_type: &MealType) -> f64 {
+        match meal_type {
+            MealType::Breakfast => self.breakfast,
+            MealType::Lunch => self.lunch,
+            MealType::Dinner => self.dinner,
+            MealType::Snack => self.snacks,
+            MealType::Dessert | MealType::Beverage => 0.0,
+        }
+    }
+
+    /// Every share is non-negative and at least one is positive
+    pub fn is_valid(&self) -> bool {
+        let shares = [self.breakfast, self.lunch, self.dinner, self.snacks];
+        shares.iter().all(|share| share.is_finite() && *share >= 0.0) && shares.iter().sum::<f64>() > 0.0
+    }
+
+    /// Fraction of daily calories (0.0-1.0) meant for `meal_type` when the
+    /// day has `counts` meals
+    pub fn share(&self, meal_type: &MealType, counts: &MealCountConstraints) -> f64 {
+        if counts.count(meal_type) == 0 {
+            return 0.0;
+        }
+
+        let served: f64 = [MealType::Breakfast, MealType::Lunch, MealType::Dinner, MealType::Snack].iter()
+            .filter(|meal_type| counts.count(meal_type) > 0)
+            .map(|meal_type| self.percent(meal_type))
+            .sum();
+        if served > 0.0 { self.percent(meal_type) / served } else { 0.0 }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CalorieRange {
     pub min: f64,
     pub max: f64,
     pub target: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MacroConstraints {
     pub protein_g: Range,
     pub carbs_g: Range,
@@ -41,7 +144,7 @@ pub struct MacroConstraints {
     pub sodium_mg_max: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MicronutrientConstraints {
     pub vitamin_c_mg: Range,
     pub calcium_mg: Range,
@@ -52,13 +155,13 @@ pub struct MicronutrientConstraints {
     pub omega3_g: Range,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Range {
     pub min: f64,
     pub max: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MealCountConstraints {
     pub breakfast: u32,
     pub lunch: u32,
@@ -66,7 +169,32 @@ pub struct MealCountConstraints {
     pub snacks: u32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl MealCountConstraints {
+    pub fn total(&self) -> u32 {
+        self.breakfast + self.lunch + self.dinner + self.snacks
+    }
+
+    /// A day's meals in eating order; snacks fall between lunch and dinner
+    pub fn daily_order(&self) -> Vec<MealType> {
+        [MealType::Breakfast, MealType::Lunch, MealType::Snack, MealType::Dinner].into_iter()
+            .flat_map(|meal_type| std::iter::repeat_n(meal_type.clone(), self.count(&meal_type) as usize))
+            .collect()
+    }
+
+    pub fn count(&self, meal_type: &MealType) -> u32 {
+        match meal_type {
+            MealType::Breakfast => self.breakfast,
+            MealType::Lunch => self.lunch,
+            MealType::Dinner => self.dinner,
+            MealType::Snack => self.snacks,
+            MealType::Dessert | MealType::Beverage => 0,
+        }
+    }
+}
+
+/// Published as `MealPreferences` so it doesn't clash with the user profile's preferences
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[schema(as = MealPreferences)]
 pub struct UserPreferences {
     pub dietary_restrictions: Vec<DietaryFlag>,
     pub allergens_to_avoid: Vec<Allergen>,
@@ -82,7 +210,7 @@ pub struct UserPreferences {
     pub convenience_importance: f64,  // 0.0 to 1.0
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct TastePreferences {
     pub sweetness_preference: f64,  // -1.0 (avoid) to 1.0 (love)
     pub saltiness_preference: f64,
@@ -93,7 +221,7 @@ pub struct TastePreferences {
     pub spice_tolerance: f64,       // 0.0 to 1.0
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum CookingSkillLevel {
     Beginner,
     Intermediate,
@@ -101,7 +229,7 @@ pub enum CookingSkillLevel {
     Expert,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum CookingEquipment {
     Stovetop,
     Oven,
@@ -115,7 +243,7 @@ pub enum CookingEquipment {
     PressureCooker,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum OptimizationObjective {
     MinimizeCost,
     MaximizeNutrition,
@@ -127,7 +255,7 @@ pub enum OptimizationObjective {
     BalanceMacros,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AlgorithmConfig {
     pub algorithm_type: AlgorithmType,
     pub population_size: usize,
@@ -136,11 +264,23 @@ pub struct AlgorithmConfig {
     pub crossover_rate: f64,
     pub elitism_rate: f64,
     pub convergence_threshold: f64,
+    /// Number of consecutive generations the best fitness must stay within
+    /// `convergence_threshold` before the search stops early
+    #[serde(default = "default_convergence_generations")]
+    pub convergence_generations: usize,
     pub max_runtime_seconds: u64,
     pub parallel_evaluation: bool,
+    /// Fitness-sharing radius for niching, as a fraction of differing meal slots
+    /// (0.0-1.0). `None` disables niching.
+    #[serde(default)]
+    pub sharing_radius: Option<f64>,
+}
+
+fn default_convergence_generations() -> usize {
+    50
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum AlgorithmType {
     GeneticAlgorithm,
     ParticleSwarmOptimization,
@@ -149,7 +289,7 @@ pub enum AlgorithmType {
     Hybrid,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct OptimizationSolution {
     pub meal_plan_id: String,
     pub fitness_score: f64,
@@ -161,10 +301,62 @@ pub struct OptimizationSolution {
     pub taste_score: f64,
     pub convenience_score: f64,
     pub seasonality_score: f64,
+    /// Share of seasonal produce (by weight) that is in season for the
+    /// requested month; `None` without a month or seasonal ingredients
+    #[serde(default)]
+    pub seasonal_coverage: Option<f64>,
+    /// Glycemic load of each day of the plan
+    #[serde(default)]
+    pub daily_glycemic_load: Vec<GlycemicLoad>,
+    /// When each of a day's meals is eaten, if an eating window was requested
+    #[serde(default)]
+    pub meal_times: Vec<MealTime>,
     pub algorithm_metadata: AlgorithmMetadata,
+    /// The planned meals, one per day and meal slot
+    #[serde(default)]
+    pub meals: Vec<MealGene>,
+    /// How many times each recipe is served across the plan
+    #[serde(default)]
+    pub recipe_repetitions: HashMap<String, u32>,
+    /// Estimated hands-on plus cooking minutes for each day of the plan
+    #[serde(default)]
+    pub daily_prep_minutes: Vec<u32>,
+}
+
+/// What changed between two solutions, e.g. after re-optimizing for a new
+/// goal. Swapping the plans swaps `added` and `removed` and negates the
+/// nutrition change.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MealPlanDiff {
+    pub old_meal_plan_id: String,
+    pub new_meal_plan_id: String,
+    /// Every day planned in either solution, in order
+    pub days: Vec<DayDiff>,
+    /// New plan's nutrition summary minus the old one's
+    pub nutrition_change: NutritionChange,
+}
+
+/// Meals of one day, matched by meal slot and recipe. Each list is sorted
+/// by meal slot, then recipe id.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DayDiff {
+    pub day: u32,
+    pub added: Vec<MealGene>,
+    pub removed: Vec<MealGene>,
+    /// Served in both plans; portions are the new plan's
+    pub retained: Vec<MealGene>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct NutritionChange {
+    pub calories: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+    pub fiber_g: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ConstraintViolation {
     pub constraint_type: String,
     pub severity: ViolationSeverity,
@@ -173,7 +365,7 @@ pub struct ConstraintViolation {
     pub description: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum ViolationSeverity {
     Low,
     Medium,
@@ -181,7 +373,7 @@ pub enum ViolationSeverity {
     Critical,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AlgorithmMetadata {
     pub algorithm_used: AlgorithmType,
     pub generations_run: usize,
@@ -191,6 +383,25 @@ pub struct AlgorithmMetadata {
     pub evaluations_performed: usize,
     pub best_fitness_history: Vec<f64>,
     pub diversity_score: f64,
+    /// Whether the search was seeded from a previous solution
+    #[serde(default)]
+    pub warm_start: bool,
+    /// The run was cancelled and this is the best plan found up to then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+/// Reported after each generation of a run whose caller asked to follow it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct OptimizationProgress {
+    /// 1-based; the last report's generation equals `generations_run`
+    pub generation: usize,
+    pub max_generations: usize,
+    pub best_fitness: f64,
+    pub elapsed_ms: f64,
+    /// Time left if every remaining generation runs; converging early
+    /// finishes sooner
+    pub estimated_remaining_ms: f64,
 }
 
 #[derive(Debug)]
@@ -202,7 +413,7 @@ pub struct Individual {
     pub age: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct MealGene {
     pub day: u32,
     pub meal_type: MealType,
@@ -220,43 +431,103 @@ impl Default for AlgorithmConfig {
             crossover_rate: 0.8,
             elitism_rate: 0.1,
             convergence_threshold: 0.001,
+            convergence_generations: default_convergence_generations(),
             max_runtime_seconds: 300, // 5 minutes
             parallel_evaluation: true,
+            sharing_radius: None,
         }
     }
 }
 
 impl OptimizationRequest {
+    /// Every invalid field as a single message
     pub fn validate(&self) -> Result<(), String> {
-        // Validate calorie range
-        if self.constraints.daily_calories.min >= self.constraints.daily_calories.max {
-            return Err("Invalid calorie range: min must be less than max".to_string());
-        }
-
-        if self.constraints.daily_calories.target < self.constraints.daily_calories.min ||
-           self.constraints.daily_calories.target > self.constraints.daily_calories.max {
-            return Err("Target calories must be within min/max range".to_string());
-        }
-
-        // Validate time horizon
-        if self.time_horizon_days == 0 || self.time_horizon_days > 30 {
-            return Err("Time horizon must be between 1 and 30 days".to_string());
-        }
-
-        // Validate algorithm config
-        if self.algorithm_config.population_size < 10 {
-            return Err("Population size must be at least 10".to_string());
-        }
+        self.validate_fields().map_err(|errors| describe_field_errors(&errors))
+    }
 
-        if self.algorithm_config.max_generations == 0 {
-            return Err("Max generations must be greater than 0".to_string());
-        }
+    /// Repetition cap for the whole time horizon, scaled up per started week
+    pub fn max_recipe_repetitions(&self) -> u32 {
+        self.max_recipe_repetitions_per_week * self.time_horizon_days.div_ceil(7).max(1)
+    }
+}
 
-        if self.algorithm_config.mutation_rate < 0.0 || self.algorithm_config.mutation_rate > 1.0 {
-            return Err("Mutation rate must be between 0.0 and 1.0".to_string());
+impl Validate for OptimizationRequest {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.user_id.trim().is_empty(), "user_id", "must not be empty");
+
+        let calories = &self.constraints.daily_calories;
+        errors.check(calories.min < calories.max, "constraints.daily_calories", "min must be less than max");
+        errors.check(
+            (calories.min..=calories.max).contains(&calories.target),
+            "constraints.daily_calories.target",
+            "must be within min/max range",
+        );
+        errors.check(
+            self.constraints.budget_per_day.is_none_or(|budget| budget > 0.0),
+            "constraints.budget_per_day",
+            "must be greater than 0",
+        );
+        errors.check(
+            self.constraints.preparation_time_max_minutes != Some(0),
+            "constraints.preparation_time_max_minutes",
+            "must be greater than 0",
+        );
+        errors.check(
+            self.constraints.calorie_distribution.as_ref().is_none_or(CalorieDistribution::is_valid),
+            "constraints.calorie_distribution",
+            "shares must not be negative and must not all be zero",
+        );
+        if let Some(window) = &self.constraints.eating_window {
+            let meals = self.constraints.meal_count_per_day.total();
+            errors.check(
+                window.fits(meals),
+                "constraints.eating_window",
+                format!(
+                    "{} meals need at least {} minutes between first and last; the window is {} minutes",
+                    meals,
+                    MIN_MEAL_SPACING_MINUTES * (meals as i64 - 1),
+                    window.length_minutes(),
+                ),
+            );
         }
 
-        Ok(())
+        errors.check(
+            self.month.is_none_or(|month| (1..=12).contains(&month)),
+            "month",
+            "must be between 1 and 12",
+        );
+        errors.check(
+            self.max_recipe_repetitions_per_week > 0,
+            "max_recipe_repetitions_per_week",
+            "must be greater than 0",
+        );
+        errors.check(
+            (1..=30).contains(&self.time_horizon_days),
+            "time_horizon_days",
+            "must be between 1 and 30 days",
+        );
+
+        let algorithm = &self.algorithm_config;
+        errors.check(algorithm.population_size >= 10, "algorithm_config.population_size", "must be at least 10");
+        errors.check(algorithm.max_generations > 0, "algorithm_config.max_generations", "must be greater than 0");
+        errors.check(
+            (0.0..=1.0).contains(&algorithm.mutation_rate),
+            "algorithm_config.mutation_rate",
+            "must be between 0.0 and 1.0",
+        );
+        errors.check(
+            algorithm.sharing_radius.is_none_or(|radius| (0.0..=1.0).contains(&radius)),
+            "algorithm_config.sharing_radius",
+            "must be between 0.0 and 1.0",
+        );
+        errors.check(
+            algorithm.convergence_threshold >= 0.0,
+            "algorithm_config.convergence_threshold",
+            "must not be negative",
+        );
+
+        errors.into_result()
     }
 }
 
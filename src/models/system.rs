@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ApiResponse<T> {
     pub success: bool,
     pub data: Option<T>,
@@ -25,14 +26,107 @@ impl<T> ApiResponse<T> {
     }
 }
 
-#[derive(Serialize)]
+/// Limit/offset window requested by a list endpoint
+#[derive(Debug, Clone, Copy, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PageRequest {
+    #[serde(default = "default_page_limit")]
+    pub limit: u32,
+    #[serde(default)]
+    pub offset: u32,
+}
+
+impl PageRequest {
+    pub const DEFAULT_LIMIT: u32 = 50;
+    pub const MAX_LIMIT: u32 = 200;
+
+    pub fn new(limit: u32, offset: u32) -> Self {
+        Self { limit, offset }
+    }
+
+    /// Requested page size clamped to 1..=MAX_LIMIT
+    pub fn effective_limit(&self) -> u32 {
+        self.limit.clamp(1, Self::MAX_LIMIT)
+    }
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_LIMIT, 0)
+    }
+}
+
+fn default_page_limit() -> u32 {
+    PageRequest::DEFAULT_LIMIT
+}
+
+/// One page of a list together with the total number of matching records
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+/// Compute device of the ML service. The GPU fields are null when the
+/// service runs on the CPU or can't be reached.
+#[derive(Serialize, ToSchema)]
 pub struct GpuStatus {
+    pub ml_service_available: bool,
+    /// "cuda", "cpu", or "unavailable" when the ML service is down
+    pub device: String,
     pub gpu_available: bool,
-    pub gpu_name: String,
-    pub compute_capability: String,
-    pub vram_total_mb: u32,
-    pub vram_used_mb: u32,
-    pub cuda_version: String,
+    pub gpu_name: Option<String>,
+    pub compute_capability: Option<String>,
+    pub vram_total_mb: Option<u64>,
+    pub vram_used_mb: Option<u64>,
+    pub cuda_version: Option<String>,
     pub ready_for_ai: bool,
     pub features: Vec<String>,
-}
\ No newline at end of file
+}
+/// Overall or per-subsystem verdict of the readiness probe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Healthy,
+    /// Serving, but an optional subsystem is down
+    Degraded,
+    Unhealthy,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubsystemHealth {
+    pub name: String,
+    pub status: HealthStatus,
+    /// Whether the service can't serve requests without this subsystem
+    pub required: bool,
+    pub latency_ms: u64,
+    /// Why the check failed, when it did
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReadinessReport {
+    pub status: HealthStatus,
+    pub subsystems: Vec<SubsystemHealth>,
+}
+
+impl ReadinessReport {
+    /// Unhealthy when a required subsystem is down, degraded when only
+    /// optional ones are
+    pub fn new(subsystems: Vec<SubsystemHealth>) -> Self {
+        let failing = |required: bool| subsystems.iter()
+            .any(|s| s.required == required && s.status != HealthStatus::Healthy);
+
+        let status = if failing(true) {
+            HealthStatus::Unhealthy
+        } else if failing(false) {
+            HealthStatus::Degraded
+        } else {
+            HealthStatus::Healthy
+        };
+
+        Self { status, subsystems }
+    }
+}
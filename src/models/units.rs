@@ -0,0 +1,111 @@
+// src/models/units.rs - Metric/imperial conversions. Everything is stored
+// metric; these convert at the API boundary.
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+pub const KG_PER_LB: f64 = 0.453_592_37;
+pub const CM_PER_INCH: f64 = 2.54;
+pub const G_PER_OZ: f64 = 28.349_523_125;
+
+/// Units a user enters and reads body and food quantities in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum UnitSystem {
+    /// kg, cm and g, as stored
+    #[default]
+    Metric,
+    /// lb, inches and oz
+    Imperial,
+}
+
+impl UnitSystem {
+    /// Body weight in this system to kilograms
+    pub fn weight_to_kg(self, weight: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => weight,
+            UnitSystem::Imperial => weight * KG_PER_LB,
+        }
+    }
+
+    /// Kilograms to body weight in this system
+    pub fn weight_from_kg(self, kg: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => kg,
+            UnitSystem::Imperial => kg / KG_PER_LB,
+        }
+    }
+
+    /// Height or circumference in this system to centimetres
+    pub fn length_to_cm(self, length: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => length,
+            UnitSystem::Imperial => length * CM_PER_INCH,
+        }
+    }
+
+    /// Centimetres to height or circumference in this system
+    pub fn length_from_cm(self, cm: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => cm,
+            UnitSystem::Imperial => cm / CM_PER_INCH,
+        }
+    }
+
+    /// Food quantity in this system to grams
+    pub fn food_to_g(self, amount: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => amount,
+            UnitSystem::Imperial => amount * G_PER_OZ,
+        }
+    }
+
+    /// Grams to food quantity in this system
+    pub fn food_from_g(self, grams: f64) -> f64 {
+        match self {
+            UnitSystem::Metric => grams,
+            UnitSystem::Imperial => grams / G_PER_OZ,
+        }
+    }
+
+    pub fn weight_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "kg",
+            UnitSystem::Imperial => "lb",
+        }
+    }
+
+    pub fn length_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "cm",
+            UnitSystem::Imperial => "in",
+        }
+    }
+
+    pub fn food_unit(self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "g",
+            UnitSystem::Imperial => "oz",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_imperial_round_trips() {
+        let imperial = UnitSystem::Imperial;
+
+        let kg = imperial.weight_to_kg(154.3);
+        assert!((kg - 69.989).abs() < 0.001, "154.3 lb is {} kg", kg);
+        assert!((imperial.weight_from_kg(kg) - 154.3).abs() < 1e-9);
+
+        assert_eq!(imperial.length_to_cm(70.0), 177.8);
+        assert!((imperial.length_from_cm(177.8) - 70.0).abs() < 1e-9);
+        assert!((imperial.food_from_g(imperial.food_to_g(6.0)) - 6.0).abs() < 1e-9);
+
+        assert_eq!(UnitSystem::Metric.weight_to_kg(70.0), 70.0);
+        assert_eq!(UnitSystem::default(), UnitSystem::Metric);
+    }
+}
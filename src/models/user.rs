@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::models::units::UnitSystem;
+use crate::models::validation::{FieldError, FieldErrors, Validate};
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct User {
     pub id: String,
     pub name: String,
@@ -10,9 +14,99 @@ pub struct User {
     pub fitness_level: FitnessLevel,
     pub goals: Vec<FitnessGoal>,
     pub preferences: UserPreferences,
+    #[serde(default)]
+    pub role: Role,
+    /// Bumped on every save and sent as the ETag; updates must name the
+    /// version they were made against. Ignored in request bodies.
+    #[serde(default)]
+    pub version: u64,
+}
+
+impl User {
+    pub const MIN_AGE: u32 = 1;
+    pub const MAX_AGE: u32 = 120;
+
+    /// The user with height and weight, given in `units`, converted to the
+    /// cm and kg everything is stored in
+    pub fn into_metric(mut self, units: UnitSystem) -> Self {
+        self.height = units.length_to_cm(self.height as f64) as f32;
+        self.weight = units.weight_to_kg(self.weight as f64) as f32;
+        self
+    }
+}
+
+impl Validate for User {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.id.trim().is_empty(), "id", "must not be empty");
+        errors.check(!self.name.trim().is_empty(), "name", "must not be empty");
+        errors.check(
+            (Self::MIN_AGE..=Self::MAX_AGE).contains(&self.age),
+            "age",
+            format!("must be between {} and {}", Self::MIN_AGE, Self::MAX_AGE),
+        );
+        // BMR and calorie targets are built on height and weight
+        errors.check(self.height.is_finite() && self.height > 0.0, "height", "must be positive");
+        errors.check(self.weight.is_finite() && self.weight > 0.0, "weight", "must be positive");
+        errors.nested("preferences", &self.preferences);
+        errors.into_result()
+    }
+}
+
+impl Validate for UserPreferences {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(self.workout_duration_minutes > 0, "workout_duration_minutes", "must be positive");
+        errors.check(self.workouts_per_week <= 7, "workouts_per_week", "must be at most 7");
+        errors.into_result()
+    }
+}
+
+/// What a favorite points at
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FavoriteKind {
+    Recipe,
+    Exercise,
+}
+
+impl FavoriteKind {
+    /// Value stored in the favorites table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FavoriteKind::Recipe => "recipe",
+            FavoriteKind::Exercise => "exercise",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "recipe" => Some(FavoriteKind::Recipe),
+            "exercise" => Some(FavoriteKind::Exercise),
+            _ => None,
+        }
+    }
+}
+
+/// A recipe or exercise a user saved for quick access
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Favorite {
+    pub kind: FavoriteKind,
+    pub item_id: String,
+    pub created_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whose data a user may see: clients their own, coaches also their linked
+/// clients', admins everyone's
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, ToSchema)]
+pub enum Role {
+    #[default]
+    Client,
+    Coach,
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum FitnessLevel {
     Beginner,
     Intermediate,
@@ -20,7 +114,7 @@ pub enum FitnessLevel {
     Elite,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum FitnessGoal {
     WeightLoss,
     MuscleGain,
@@ -30,16 +124,39 @@ pub enum FitnessGoal {
     GeneralHealth,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserPreferences {
     pub preferred_exercise_types: Vec<ExerciseType>,
     pub available_equipment: Vec<Equipment>,
     pub workout_duration_minutes: u32,
     pub workouts_per_week: u32,
     pub preferred_time_of_day: Option<String>,
+    /// How the user enters and reads weights and lengths; storage is metric
+    #[serde(default)]
+    pub unit_system: UnitSystem,
+    /// Injuries or limitations; exercises unsafe for any of them are never
+    /// recommended
+    #[serde(default)]
+    pub limitations: Vec<Limitation>,
+    /// Whether recommended sessions come with a warmup and cooldown
+    #[serde(default = "default_warmup_cooldown")]
+    pub warmup_cooldown: bool,
+}
+
+fn default_warmup_cooldown() -> bool {
+    true
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An injury or chronic limitation that rules out some movements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum Limitation {
+    Knee,
+    Shoulder,
+    LowerBack,
+    Wrist,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub enum ExerciseType {
     Cardio,
     Strength,
@@ -50,7 +167,7 @@ pub enum ExerciseType {
     Pilates,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum Equipment {
     None,
     Dumbbells,
@@ -60,4 +177,40 @@ pub enum Equipment {
     Bench,
     TreadMill,
     StationaryBike,
-}
\ No newline at end of file
+}
+/// What happened to one user of a batch registration
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistrationStatus {
+    Created,
+    /// The id is already taken, by an existing user or earlier in the batch
+    Duplicate,
+    Invalid,
+    /// Valid, but saving it failed
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct RegistrationResult {
+    /// Position of the user in the submitted batch
+    pub index: usize,
+    pub user_id: String,
+    pub status: RegistrationStatus,
+    pub errors: Vec<FieldError>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BatchRegistrationReport {
+    pub created: usize,
+    pub rejected: usize,
+    pub results: Vec<RegistrationResult>,
+}
+
+impl BatchRegistrationReport {
+    pub fn new(results: Vec<RegistrationResult>) -> Self {
+        let created = results.iter()
+            .filter(|r| r.status == RegistrationStatus::Created)
+            .count();
+        Self { created, rejected: results.len() - created, results }
+    }
+}
@@ -0,0 +1,65 @@
+// src/models/validation.rs - Field-level validation of incoming payloads
+
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A field that failed validation, named by its path in the payload
+/// (e.g. `exercises[0].exercise_id`)
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub reason: String,
+}
+
+/// Collects every failed check so a client can fix them all in one go
+#[derive(Debug, Default)]
+pub struct FieldErrors {
+    errors: Vec<FieldError>,
+}
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `reason` against `field` unless `valid` holds
+    pub fn check(&mut self, valid: bool, field: &str, reason: impl Into<String>) {
+        if !valid {
+            self.errors.push(FieldError {
+                field: field.to_string(),
+                reason: reason.into(),
+            });
+        }
+    }
+
+    /// Validate a nested value, naming its errors under `field`
+    pub fn nested<T: Validate + ?Sized>(&mut self, field: &str, value: &T) {
+        if let Err(errors) = value.validate_fields() {
+            self.errors.extend(errors.into_iter().map(|e| FieldError {
+                field: format!("{}.{}", field, e.field),
+                reason: e.reason,
+            }));
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// Payloads that can list everything wrong with them
+pub trait Validate {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>>;
+}
+
+/// Field errors as one line, for callers that report a single message
+pub fn describe_field_errors(errors: &[FieldError]) -> String {
+    errors.iter()
+        .map(|e| format!("{}: {}", e.field, e.reason))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Something integrators can be notified about
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    WorkoutLogged,
+    GoalCompleted,
+    PlanGenerated,
+}
+
+impl WebhookEvent {
+    /// Value stored with deliveries and sent in the event header
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::WorkoutLogged => "workout_logged",
+            WebhookEvent::GoalCompleted => "goal_completed",
+            WebhookEvent::PlanGenerated => "plan_generated",
+        }
+    }
+
+    pub fn parse(event: &str) -> Option<Self> {
+        match event {
+            "workout_logged" => Some(WebhookEvent::WorkoutLogged),
+            "goal_completed" => Some(WebhookEvent::GoalCompleted),
+            "plan_generated" => Some(WebhookEvent::PlanGenerated),
+            _ => None,
+        }
+    }
+}
+
+/// A URL that receives the events it subscribed to. The signing secret is
+/// write-only and never returned.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookSubscription {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Not delivered yet; retried at `next_attempt_at`
+    Pending,
+    Delivered,
+    /// Gave up after the configured number of attempts
+    DeadLettered,
+}
+
+impl DeliveryStatus {
+    /// Value stored in the webhook_deliveries table
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::DeadLettered => "dead_lettered",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(DeliveryStatus::Pending),
+            "delivered" => Some(DeliveryStatus::Delivered),
+            "dead_lettered" => Some(DeliveryStatus::DeadLettered),
+            _ => None,
+        }
+    }
+}
+
+/// One event on its way to one subscription
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub subscription_id: String,
+    pub event: WebhookEvent,
+    /// The exact JSON body sent, and signed
+    pub payload: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// UTC, "YYYY-MM-DD HH:MM:SS"
+    pub next_attempt_at: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
-use crate::models::exercise::ExerciseSet;
+use utoipa::ToSchema;
+use crate::models::activity::ActivityTrends;
+use crate::models::exercise::{Exercise, ExerciseSet, MuscleGroup};
+use crate::models::user::{ExerciseType, Limitation, User};
+use crate::models::validation::{FieldError, FieldErrors, Validate};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct WorkoutSession {
     pub id: String,
     pub user_id: String,
@@ -9,14 +13,322 @@ pub struct WorkoutSession {
     pub exercises: Vec<ExerciseSet>,
     pub total_duration_minutes: u32,
     pub calories_burned: Option<f32>,
+    /// True when `calories_burned` was estimated rather than supplied by the user
+    #[serde(default)]
+    pub calories_estimated: bool,
     pub user_rating: Option<u32>,
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Validate for WorkoutSession {
+    fn validate_fields(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = FieldErrors::new();
+        errors.check(!self.id.trim().is_empty(), "id", "must not be empty");
+        errors.check(!self.user_id.trim().is_empty(), "user_id", "must not be empty");
+        errors.check(
+            chrono::NaiveDate::parse_from_str(&self.date, "%Y-%m-%d").is_ok(),
+            "date",
+            "must be a YYYY-MM-DD date",
+        );
+        errors.check(self.total_duration_minutes <= 24 * 60, "total_duration_minutes", "must be at most a day");
+        errors.check(
+            self.calories_burned.is_none_or(|calories| calories.is_finite() && calories >= 0.0),
+            "calories_burned",
+            "must not be negative",
+        );
+        errors.check(
+            self.user_rating.is_none_or(|rating| (1..=5).contains(&rating)),
+            "user_rating",
+            "must be between 1 and 5",
+        );
+        for (i, set) in self.exercises.iter().enumerate() {
+            errors.check(
+                !set.exercise_id.trim().is_empty(),
+                &format!("exercises[{}].exercise_id", i),
+                "must not be empty",
+            );
+            errors.check(set.sets > 0, &format!("exercises[{}].sets", i), "must be positive");
+        }
+        errors.into_result()
+    }
+}
+
+/// Estimate calories burned as MET × body weight (kg) × duration (hours).
+/// The session MET is the average of its exercises' METs weighted by sets;
+/// exercises missing from `exercises` are treated as strength work.
+pub fn estimate_calories_burned(session: &WorkoutSession, user: &User, exercises: &[Exercise]) -> f32 {
+    let (weighted_met, total_sets) = session.exercises.iter()
+        .fold((0.0, 0u32), |(met, sets), set| {
+            let exercise_met = exercises.iter()
+                .find(|e| e.id == set.exercise_id)
+                .map(|e| e.exercise_type.met_value())
+                .unwrap_or_else(|| ExerciseType::Strength.met_value());
+            (met + exercise_met * set.sets as f32, sets + set.sets)
+        });
+
+    let met = if total_sets > 0 {
+        weighted_met / total_sets as f32
+    } else {
+        ExerciseType::Strength.met_value()
+    };
+
+    met * user.weight * session.total_duration_minutes as f32 / 60.0
+}
+
+/// Recommended exercise sets with the time they are expected to take
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WorkoutRecommendation {
+    pub exercises: Vec<ExerciseSet>,
+    pub estimated_duration_minutes: u32,
+    /// How to perform each exercise in the plan, once per exercise
+    #[serde(default)]
+    pub guidance: Vec<ExerciseGuidance>,
+    /// Exercises left out because of the user's limitations, and why
+    #[serde(default)]
+    pub excluded: Vec<ExcludedExercise>,
+    /// Done before the working sets; not counted in `estimated_duration_minutes`
+    #[serde(default)]
+    pub warmup: Option<SessionPhase>,
+    /// Done after the working sets; not counted in `estimated_duration_minutes`
+    #[serde(default)]
+    pub cooldown: Option<SessionPhase>,
+    /// How to load the bar for each barbell exercise with a suggested weight
+    #[serde(default)]
+    pub plate_loading: Vec<PlateLoading>,
+}
+
+/// Plates for one side of the bar, in the user's units. `total` is what
+/// they make with the bar, which may differ from the suggested weight when
+/// that can't be loaded exactly.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PlateLoading {
+    pub exercise_id: String,
+    /// "kg" or "lb"
+    pub unit: String,
+    pub bar: f32,
+    pub per_side: Vec<f32>,
+    pub total: f32,
+}
+
+/// A warmup or cooldown around a session's working sets
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SessionPhase {
+    pub movements: Vec<PhaseMovement>,
+    pub duration_seconds: u32,
+}
+
+/// One timed movement in a warmup or cooldown
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PhaseMovement {
+    pub name: String,
+    pub style: MovementStyle,
+    pub duration_seconds: u32,
+    pub muscles: Vec<MuscleGroup>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum MovementStyle {
+    /// Moving through a range of motion to prepare a joint
+    DynamicStretch,
+    /// Light work to wake up the muscles about to be loaded
+    Activation,
+    /// A stretch held still, for cooling down
+    StaticStretch,
+}
+
+/// An exercise kept out of a plan because it's unsafe for a limitation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExcludedExercise {
+    pub exercise_id: String,
+    pub limitation: Limitation,
+    pub reason: String,
+    /// The safe exercise done instead, if there was one
+    pub replaced_by: Option<String>,
+}
+
+/// Demo link and form cues for an exercise in a recommendation
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ExerciseGuidance {
+    pub exercise_id: String,
+    pub name: String,
+    pub demo_url: Option<String>,
+    pub cues: Vec<String>,
+}
+
+/// Which muscles a scheduled day trains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum DayFocus {
+    Rest,
+    FullBody,
+    LowerBody,
+    UpperBody,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum SessionIntensity {
+    Rest,
+    Moderate,
+    High,
+}
+
+/// One day of a weekly schedule; rest days have no workout
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ScheduledDay {
+    /// Monday through Sunday
+    pub day: String,
+    pub focus: DayFocus,
+    pub intensity: SessionIntensity,
+    /// The user's preferred time of day, on training days
+    pub time_of_day: Option<String>,
+    pub workout: Option<WorkoutRecommendation>,
+}
+
+/// A week of training and rest days, Monday first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WeeklySchedule {
+    pub user_id: String,
+    pub training_days: u32,
+    pub days: Vec<ScheduledDay>,
+}
+
+/// One day of a carb-cycling plan
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CarbCycleDay {
+    /// Monday through Sunday
+    pub day: String,
+    pub training: bool,
+    pub calories: f64,
+    pub protein_g: f64,
+    pub carbs_g: f64,
+    pub fat_g: f64,
+}
+
+/// Daily macro targets that follow the weekly schedule: more carbs on
+/// training days, fewer on rest days, the same weekly average
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CarbCyclePlan {
+    pub user_id: String,
+    pub days: Vec<CarbCycleDay>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProgressAnalysis {
     pub total_workouts: u32,
     pub average_duration_minutes: f32,
     pub total_calories_burned: f32,
     pub consistency_score: f32,
-}
\ No newline at end of file
+    /// Chronological weekly series for progress charts
+    #[serde(default)]
+    pub weekly_progress: Vec<WeeklyProgress>,
+    /// Change in weekly training volume (kg per week)
+    #[serde(default)]
+    pub volume_trend: f32,
+    /// Change in weekly average session rating (points per week)
+    #[serde(default)]
+    pub rating_trend: f32,
+    /// Percentage of the weekly workout target met (0-100)
+    #[serde(default)]
+    pub frequency_adherence: f32,
+    /// Present when recent training suggests the user should back off
+    #[serde(default)]
+    pub deload_recommendation: Option<DeloadRecommendation>,
+    /// Steps and heart rate, when the user has imported any from a wearable
+    #[serde(default)]
+    pub activity: Option<ActivityTrends>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeloadRecommendation {
+    pub reason: String,
+    /// Suggested cut in training volume (0-100)
+    pub volume_reduction_percent: f32,
+    pub duration_days: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WeeklyProgress {
+    /// Monday of the week (YYYY-MM-DD)
+    pub week_start: String,
+    pub workouts: u32,
+    /// Sum of sets × reps × load over completed sets
+    pub total_volume_kg: f32,
+    pub average_rating: Option<f32>,
+}
+
+/// Workout streaks and recent weekly consistency. Days are the user's local
+/// calendar days.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StreakInfo {
+    pub user_id: String,
+    /// Days from the start of the running streak to the last workout; 0 once
+    /// the gap since the last workout is longer than the streak allows
+    pub current_streak_days: u32,
+    pub longest_streak_days: u32,
+    /// Longest gap between workouts (in days) that keeps a streak going,
+    /// from the weekly target plus a grace day
+    pub max_gap_days: u32,
+    pub last_workout_date: Option<String>,
+    pub workouts_this_week: u32,
+    pub weekly_target: u32,
+    /// Percentage of the weekly target met over recent complete weeks (0-100)
+    pub weekly_consistency: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::user::{Equipment, FitnessGoal, FitnessLevel, Role, UserPreferences};
+    use crate::sample_data::create_sample_exercises;
+
+    #[test]
+    fn test_strength_session_calorie_estimate() {
+        let user = User {
+            id: "test_user".to_string(),
+            name: "Test User".to_string(),
+            age: 30,
+            height: 175.0,
+            weight: 70.0,
+            fitness_level: FitnessLevel::Intermediate,
+            goals: vec![FitnessGoal::Strength],
+            preferences: UserPreferences {
+                preferred_exercise_types: vec![ExerciseType::Strength],
+                available_equipment: vec![Equipment::Barbells],
+                workout_duration_minutes: 45,
+                workouts_per_week: 3,
+                preferred_time_of_day: None,
+                unit_system: crate::models::UnitSystem::Metric,
+                limitations: vec![],
+                warmup_cooldown: true,
+            },
+            role: Role::Client,
+            version: 0,
+        };
+        let session = WorkoutSession {
+            id: "session".to_string(),
+            user_id: "test_user".to_string(),
+            date: "2025-08-13".to_string(),
+            exercises: ["squat", "deadlift", "pushup"].iter()
+                .map(|id| ExerciseSet {
+                    exercise_id: id.to_string(),
+                    sets: 4,
+                    reps: 8,
+                    weight_kg: None,
+                    duration_seconds: None,
+                    rest_seconds: 90,
+                    completed: true,
+                    tempo: None,
+                    target_rpe: None,
+                    group_id: None,
+                })
+                .collect(),
+            total_duration_minutes: 45,
+            calories_burned: None,
+            calories_estimated: false,
+            user_rating: None,
+            notes: None,
+        };
+
+        let calories = estimate_calories_burned(&session, &user, &create_sample_exercises());
+        assert!((200.0..=350.0).contains(&calories), "estimate was {}", calories);
+    }
+}
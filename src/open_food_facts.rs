@@ -0,0 +1,176 @@
+// src/open_food_facts.rs - Barcode lookups against Open Food Facts
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::config::OpenFoodFactsConfig;
+use crate::models::food::{Allergen, Food, FoodCategory, NutritionFacts, TasteProfile};
+
+/// Client for the Open Food Facts product API. Products are cached by
+/// barcode, including barcodes Open Food Facts doesn't know, so a repeated
+/// scan never calls the API twice.
+#[derive(Debug)]
+pub struct OpenFoodFactsClient {
+    client: Client,
+    base_url: String,
+    cache: RwLock<HashMap<String, Option<Food>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProductResponse {
+    /// 1 when the product was found
+    #[serde(default)]
+    status: u8,
+    product: Option<Product>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Product {
+    #[serde(default)]
+    product_name: Option<String>,
+    #[serde(default)]
+    brands: Option<String>,
+    #[serde(default)]
+    categories_tags: Vec<String>,
+    #[serde(default)]
+    allergens_tags: Vec<String>,
+    #[serde(default)]
+    nutriments: HashMap<String, serde_json::Value>,
+}
+
+/// EAN-8, UPC-A, EAN-13 or GTIN-14
+pub fn is_valid_barcode(barcode: &str) -> bool {
+    matches!(barcode.len(), 8 | 12 | 13 | 14) && barcode.chars().all(|c| c.is_ascii_digit())
+}
+
+impl OpenFoodFactsClient {
+    pub fn from_config(config: &OpenFoodFactsConfig) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .user_agent(concat!("fitness_advisor_ai/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self {
+            client,
+            base_url: config.base_url.trim_end_matches('/').to_string(),
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The product for a barcode, `None` when Open Food Facts doesn't list it
+    pub async fn product(&self, barcode: &str) -> Result<Option<Food>> {
+        if let Some(cached) = self.cache.read().await.get(barcode) {
+            return Ok(cached.clone());
+        }
+
+        let response = self.client
+            .get(format!("{}/api/v2/product/{}.json", self.base_url, barcode))
+            .send()
+            .await
+            .map_err(|e| anyhow!("Open Food Facts request failed: {}", e))?;
+
+        // Unknown barcodes come back as 404 with a status 0 body
+        let food = if response.status() == reqwest::StatusCode::NOT_FOUND {
+            None
+        } else if response.status().is_success() {
+            let body: ProductResponse = response.json().await
+                .map_err(|e| anyhow!("Failed to parse Open Food Facts product: {}", e))?;
+            body.product
+                .filter(|_| body.status == 1)
+                .map(|product| product.into_food(barcode))
+        } else {
+            return Err(anyhow!("Open Food Facts request failed with status: {}", response.status()));
+        };
+
+        if let Some(food) = &food {
+            info!("Resolved barcode {} to {}", barcode, food.name);
+        }
+        self.cache.write().await.insert(barcode.to_string(), food.clone());
+        Ok(food)
+    }
+}
+
+impl Product {
+    /// Per-100g nutriment, 0 when missing. Open Food Facts reports these in
+    /// grams, so `scale` converts to the unit `NutritionFacts` uses.
+    fn nutriment(&self, name: &str, scale: f64) -> f64 {
+        self.nutriments.get(&format!("{}_100g", name))
+            .and_then(|value| value.as_f64().or_else(|| value.as_str()?.parse().ok()))
+            .map_or(0.0, |value| value * scale)
+    }
+
+    fn allergens(&self) -> Vec<Allergen> {
+        let mut allergens = Vec::new();
+        for tag in &self.allergens_tags {
+            let allergen = match tag.trim_start_matches("en:") {
+                "gluten" => Allergen::Gluten,
+                "milk" => Allergen::Dairy,
+                "eggs" => Allergen::Eggs,
+                "fish" => Allergen::Fish,
+                "crustaceans" | "molluscs" => Allergen::Shellfish,
+                "nuts" => Allergen::TreeNuts,
+                "peanuts" => Allergen::Peanuts,
+                "soybeans" => Allergen::Soy,
+                "sesame-seeds" => Allergen::Sesame,
+                _ => continue,
+            };
+            if !allergens.contains(&allergen) {
+                allergens.push(allergen);
+            }
+        }
+        allergens
+    }
+
+    fn into_food(self, barcode: &str) -> Food {
+        const MG_PER_G: f64 = 1_000.0;
+        const MCG_PER_G: f64 = 1_000_000.0;
+        const IU_PER_MCG_VITAMIN_D: f64 = 40.0;
+
+        let nutrition_per_100g = NutritionFacts {
+            calories: self.nutriment("energy-kcal", 1.0),
+            protein_g: self.nutriment("proteins", 1.0),
+            carbs_g: self.nutriment("carbohydrates", 1.0),
+            fat_g: self.nutriment("fat", 1.0),
+            fiber_g: self.nutriment("fiber", 1.0),
+            sugar_g: self.nutriment("sugars", 1.0),
+            sodium_mg: self.nutriment("sodium", MG_PER_G),
+            potassium_mg: self.nutriment("potassium", MG_PER_G),
+            calcium_mg: self.nutriment("calcium", MG_PER_G),
+            iron_mg: self.nutriment("iron", MG_PER_G),
+            vitamin_c_mg: self.nutriment("vitamin-c", MG_PER_G),
+            vitamin_d_iu: self.nutriment("vitamin-d", MCG_PER_G * IU_PER_MCG_VITAMIN_D),
+            vitamin_b12_mcg: self.nutriment("vitamin-b12", MCG_PER_G),
+            folate_mcg: self.nutriment("folates", MCG_PER_G),
+            omega3_g: self.nutriment("omega-3-fat", 1.0),
+            omega6_g: self.nutriment("omega-6-fat", 1.0),
+        };
+
+        let name = match (self.product_name.as_deref().map(str::trim), self.brands.as_deref()) {
+            (Some(name), Some(brands)) if !name.is_empty() && !brands.is_empty() => {
+                format!("{} ({})", name, brands)
+            }
+            (Some(name), _) if !name.is_empty() => name.to_string(),
+            _ => format!("Product {}", barcode),
+        };
+
+        Food {
+            id: format!("off_{}", barcode),
+            name,
+            category: FoodCategory::from_description(&self.categories_tags.join(" ")),
+            allergens: self.allergens(),
+            nutrition_per_100g,
+            dietary_flags: vec![],
+            seasonality: None,
+            cost_per_100g: None,
+            availability_score: 0.5,
+            glycemic_index: None,
+            taste_profile: TasteProfile::default(),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// src/openapi.rs - OpenAPI document and Swagger UI for the HTTP API
+
+use axum::response::{Html, Json};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{api, websocket};
+
+/// Name of the bearer token security scheme referenced by protected paths
+const BEARER_SCHEME: &str = "bearer";
+
+/// The API description. Paths come from the `#[utoipa::path]` annotation on
+/// each handler and schemas from the types those handlers accept and return,
+/// so adding a route means annotating its handler and listing it here.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Fitness Advisor AI"),
+    paths(
+        api::login,
+        api::create_user,
+        api::create_users_batch,
+        api::get_all_users,
+        api::get_user,
+        api::update_user,
+        api::delete_user,
+        api::link_coach,
+        api::unlink_coach,
+        api::get_clients,
+        api::list_favorites,
+        api::add_favorite,
+        api::remove_favorite,
+        api::create_goal,
+        api::list_goals,
+        api::get_goal,
+        api::list_achievements,
+        api::record_feedback,
+        api::get_feedback_summary,
+        api::get_experiment_results,
+        api::create_webhook,
+        api::list_webhooks,
+        api::delete_webhook,
+        api::list_webhook_deliveries,
+        api::record_measurement,
+        api::get_measurements,
+        api::import_health_data,
+        api::get_food_log,
+        api::import_food_diary,
+        api::sync_fitbit,
+        api::get_workout_recommendation,
+        api::get_weekly_schedule,
+        api::get_carb_cycle_plan,
+        api::get_progress_analysis,
+        api::get_streak,
+        api::get_report_pdf,
+        api::export_user_data,
+        api::get_user_workouts,
+        api::log_workout,
+        api::get_exercises,
+        api::search_exercises,
+        api::create_exercises_bulk,
+        api::get_exercise_alternatives,
+        api::search_recipes,
+        api::lookup_food,
+        api::lookup_barcode,
+        api::analyze_form,
+        api::analyze_form_image,
+        api::compare_form,
+        websocket::websocket_handler,
+        api::ml_analyze_frame,
+        api::ml_analyze_video,
+        api::ml_analyze_batch,
+        api::ml_service_status,
+        api::optimize_meal_plan,
+        api::menu_optimizer_status,
+        api::get_menu_recommendations,
+        api::suggest_recipes,
+        api::substitute_ingredient,
+        api::build_grocery_list,
+        api::diff_meal_plans,
+        api::health_check,
+        api::database_health,
+        api::readiness_check,
+        api::gpu_status,
+        api::prometheus_metrics,
+    ),
+    components(schemas(crate::models::optimization::OptimizationRequest)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "auth", description = "Bearer token login"),
+        (name = "users", description = "User profiles, measurements and coach links"),
+        (name = "workouts", description = "Workout logging, history and recommendations"),
+        (name = "exercises", description = "Exercise catalogue"),
+        (name = "ai", description = "Form analysis"),
+        (name = "ml", description = "Proxy to the Python ML service"),
+        (name = "menu", description = "Recipes and meal plan optimization"),
+        (name = "webhooks", description = "Signed event notifications for integrators"),
+        (name = "system", description = "Health and metrics"),
+    ),
+)]
+pub struct ApiDoc;
+
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Swagger UI pointed at `/api/openapi.json`. The UI assets come from a CDN
+/// so the server doesn't have to bundle them.
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI_HTML)
+}
+
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8">
+    <title>Fitness Advisor AI - API docs</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script>
+        window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+    </script>
+</body>
+</html>
+"##;
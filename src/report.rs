@@ -0,0 +1,242 @@
+// src/report.rs - Printable PDF report of a user's week: training plan,
+// nutrition targets and progress
+
+use chrono::NaiveDate;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+use crate::models::measurement::BodyMeasurement;
+use crate::models::user::User;
+use crate::models::workout::{CarbCyclePlan, DayFocus, ProgressAnalysis, WeeklySchedule};
+
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+const BODY_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 15.0;
+/// Widest a progress bar gets, in points
+const BAR_WIDTH: f32 = 250.0;
+/// Weeks of progress shown, most recent last
+const PROGRESS_WEEKS: usize = 8;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// Everything a report shows. Users without workouts or measurements still
+/// get a plan; the progress sections then say there's nothing logged.
+pub struct ReportData {
+    pub user: User,
+    pub schedule: WeeklySchedule,
+    pub nutrition: CarbCyclePlan,
+    pub progress: ProgressAnalysis,
+    pub measurements: Vec<BodyMeasurement>,
+    pub generated_on: NaiveDate,
+}
+
+/// Text laid out top to bottom, starting a new page when one fills up
+struct Layout {
+    pages: Vec<Content>,
+    y: f32,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Self { pages: vec![Content::new()], y: PAGE_HEIGHT - MARGIN }
+    }
+
+    fn page(&mut self) -> &mut Content {
+        self.pages.last_mut().expect("layout always has a page")
+    }
+
+    /// Move down by `height`, first starting a new page if it wouldn't fit
+    fn advance(&mut self, height: f32) -> f32 {
+        if self.y - height < MARGIN {
+            self.pages.push(Content::new());
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+        self.y -= height;
+        self.y
+    }
+
+    fn text_at(&mut self, x: f32, y: f32, font: Name, size: f32, text: &str) {
+        let text = pdf_text(text);
+        self.page()
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, y)
+            .show(Str(text.as_bytes()))
+            .end_text();
+    }
+
+    fn title(&mut self, text: &str) {
+        let y = self.advance(24.0);
+        self.text_at(MARGIN, y, BOLD, 18.0, text);
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.advance(LINE_HEIGHT / 2.0);
+        let y = self.advance(LINE_HEIGHT * 1.2);
+        self.text_at(MARGIN, y, BOLD, 13.0, text);
+    }
+
+    fn line(&mut self, text: &str) {
+        let y = self.advance(LINE_HEIGHT);
+        self.text_at(MARGIN, y, REGULAR, BODY_SIZE, text);
+    }
+
+    /// A row of cells starting at the given x offsets
+    fn row(&mut self, columns: &[f32], cells: &[String], font: Name) {
+        let y = self.advance(LINE_HEIGHT);
+        for (x, cell) in columns.iter().zip(cells) {
+            self.text_at(MARGIN + x, y, font, BODY_SIZE, cell);
+        }
+    }
+
+    /// A labelled horizontal bar scaled against `max`
+    fn bar(&mut self, label: &str, value: f32, max: f32, caption: &str) {
+        let y = self.advance(LINE_HEIGHT);
+        self.text_at(MARGIN, y, REGULAR, BODY_SIZE, label);
+
+        let width = if max > 0.0 { (value / max).clamp(0.0, 1.0) * BAR_WIDTH } else { 0.0 };
+        let x = MARGIN + 90.0;
+        self.page()
+            .set_fill_rgb(0.27, 0.51, 0.71)
+            .rect(x, y - 1.0, width.max(1.0), BODY_SIZE)
+            .fill_nonzero()
+            .set_fill_gray(0.0);
+        self.text_at(x + BAR_WIDTH + 10.0, y, REGULAR, BODY_SIZE, caption);
+    }
+}
+
+/// Helvetica's built-in encoding covers printable ASCII; anything else is
+/// replaced rather than rendered as garbage
+fn pdf_text(text: &str) -> String {
+    text.chars().map(|c| if c == ' ' || c.is_ascii_graphic() { c } else { '?' }).collect()
+}
+
+fn focus_label(focus: DayFocus) -> &'static str {
+    match focus {
+        DayFocus::Rest => "Rest",
+        DayFocus::FullBody => "Full body",
+        DayFocus::LowerBody => "Lower body",
+        DayFocus::UpperBody => "Upper body",
+    }
+}
+
+/// Render the report as a PDF document
+pub fn render(report: &ReportData) -> Vec<u8> {
+    let mut layout = Layout::new();
+    let user = &report.user;
+
+    layout.title(&format!("Weekly report: {}", user.name));
+    layout.line(&format!(
+        "Generated {} | Age {} | {:.0} cm | {:.1} kg | {:?}",
+        report.generated_on, user.age, user.height, user.weight, user.fitness_level,
+    ));
+    if !user.goals.is_empty() {
+        let goals: Vec<String> = user.goals.iter().map(|goal| format!("{:?}", goal)).collect();
+        layout.line(&format!("Goals: {}", goals.join(", ")));
+    }
+
+    layout.heading(&format!("Training plan ({} training days)", report.schedule.training_days));
+    for day in &report.schedule.days {
+        let Some(workout) = &day.workout else {
+            layout.line(&format!("{}: {}", day.day, focus_label(day.focus)));
+            continue;
+        };
+        layout.line(&format!(
+            "{}: {} ({:?}, about {} min)",
+            day.day, focus_label(day.focus), day.intensity, workout.estimated_duration_minutes,
+        ));
+        for set in &workout.exercises {
+            let name = workout.guidance.iter()
+                .find(|g| g.exercise_id == set.exercise_id)
+                .map_or(set.exercise_id.as_str(), |g| g.name.as_str());
+            let load = set.weight_kg.map(|kg| format!(" @ {:.1} kg", kg)).unwrap_or_default();
+            layout.line(&format!("      {} - {} x {}{}", name, set.sets, set.reps, load));
+        }
+    }
+
+    layout.heading("Daily nutrition targets");
+    let columns = [0.0, 100.0, 170.0, 250.0, 330.0, 410.0];
+    let header = ["Day", "Type", "Calories", "Protein", "Carbs", "Fat"].map(String::from);
+    layout.row(&columns, &header, BOLD);
+    for day in &report.nutrition.days {
+        layout.row(&columns, &[
+            day.day.clone(),
+            if day.training { "Training" } else { "Rest" }.to_string(),
+            format!("{:.0} kcal", day.calories),
+            format!("{:.0} g", day.protein_g),
+            format!("{:.0} g", day.carbs_g),
+            format!("{:.0} g", day.fat_g),
+        ], REGULAR);
+    }
+
+    layout.heading("Progress");
+    let progress = &report.progress;
+    if progress.total_workouts == 0 {
+        layout.line("No workouts logged yet.");
+    } else {
+        layout.line(&format!(
+            "{} workouts, {:.0} min average, {:.0} kcal burned, {:.0}% of weekly target met",
+            progress.total_workouts, progress.average_duration_minutes,
+            progress.total_calories_burned, progress.frequency_adherence,
+        ));
+        let recent = &progress.weekly_progress[progress.weekly_progress.len().saturating_sub(PROGRESS_WEEKS)..];
+        let max_volume = recent.iter().map(|week| week.total_volume_kg).fold(0.0, f32::max);
+        layout.line("Weekly training volume:");
+        for week in recent {
+            layout.bar(
+                &week.week_start,
+                week.total_volume_kg,
+                max_volume,
+                &format!("{:.0} kg ({} workouts)", week.total_volume_kg, week.workouts),
+            );
+        }
+        if let Some(deload) = &progress.deload_recommendation {
+            layout.line(&format!("Deload suggested: {}", deload.reason));
+        }
+    }
+
+    let recent_measurements = &report.measurements[report.measurements.len().saturating_sub(PROGRESS_WEEKS)..];
+    if !recent_measurements.is_empty() {
+        layout.heading("Body weight");
+        let max_weight = recent_measurements.iter().map(|m| m.weight_kg).fold(0.0, f32::max);
+        for measurement in recent_measurements {
+            layout.bar(&measurement.date, measurement.weight_kg, max_weight, &format!("{:.1} kg", measurement.weight_kg));
+        }
+    }
+
+    write_pdf(layout.pages, &format!("Weekly report: {}", user.name))
+}
+
+fn write_pdf(pages: Vec<Content>, title: &str) -> Vec<u8> {
+    let catalog_id = Ref::new(1);
+    let page_tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let info_id = Ref::new(5);
+    // Each page takes two ids: the page and its content stream
+    let page_ids: Vec<Ref> = (0..pages.len() as i32).map(|i| Ref::new(6 + 2 * i)).collect();
+
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(page_tree_id);
+    pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(pages.len() as i32);
+    pdf.type1_font(regular_id).base_font(Name(b"Helvetica"));
+    pdf.type1_font(bold_id).base_font(Name(b"Helvetica-Bold"));
+    pdf.document_info(info_id)
+        .title(TextStr(title))
+        .producer(TextStr("fitness_advisor_ai"));
+
+    for (page_id, content) in page_ids.into_iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(page_id);
+        page.media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .parent(page_tree_id)
+            .contents(content_id);
+        page.resources().fonts().pair(REGULAR, regular_id).pair(BOLD, bold_id);
+        page.finish();
+        pdf.stream(content_id, &content.finish());
+    }
+
+    pdf.finish()
+}
@@ -0,0 +1,251 @@
+// src/sample_data/exercises.rs - Sample exercise catalog seeded into the database
+
+use crate::models::exercise::{Exercise, MuscleGroup};
+use crate::models::user::{Equipment, ExerciseType};
+
+pub fn create_sample_exercises() -> Vec<Exercise> {
+    vec![
+        // === CORE MOVEMENTS ===
+        Exercise {
+            id: "pushup".to_string(),
+            name: "Push-up".to_string(),
+            description: "Classic bodyweight chest exercise".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 3,
+            primary_muscles: vec![MuscleGroup::Chest],
+            secondary_muscles: vec![MuscleGroup::Arms, MuscleGroup::Core],
+            instructions: vec![
+                "Start in plank position".to_string(),
+                "Lower body until chest nearly touches floor".to_string(),
+                "Push back up to starting position".to_string(),
+            ],
+            safety_tips: vec![
+                "Keep body straight".to_string(),
+                "Don't let hips sag".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Hands just outside shoulders".to_string(),
+                "Elbows at about 45 degrees".to_string(),
+                "Squeeze glutes to keep a straight line".to_string(),
+            ],
+        },
+        Exercise {
+            id: "squat".to_string(),
+            name: "Squat".to_string(),
+            description: "Fundamental lower body exercise".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 2,
+            primary_muscles: vec![MuscleGroup::Legs, MuscleGroup::Glutes],
+            secondary_muscles: vec![MuscleGroup::Core],
+            instructions: vec![
+                "Stand with feet shoulder-width apart".to_string(),
+                "Lower hips back and down".to_string(),
+                "Return to standing position".to_string(),
+            ],
+            safety_tips: vec![
+                "Keep knees behind toes".to_string(),
+                "Maintain neutral spine".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Brace your core before each rep".to_string(),
+                "Knees track over your toes".to_string(),
+                "Drive up through your whole foot".to_string(),
+            ],
+        },
+        Exercise {
+            id: "plank".to_string(),
+            name: "Plank".to_string(),
+            description: "Core strengthening exercise".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 3,
+            primary_muscles: vec![MuscleGroup::Core],
+            secondary_muscles: vec![MuscleGroup::Shoulders, MuscleGroup::Back],
+            instructions: vec![
+                "Start in push-up position".to_string(),
+                "Keep body straight from head to heels".to_string(),
+                "Hold position".to_string(),
+            ],
+            safety_tips: vec![
+                "Don't let hips sag or rise".to_string(),
+                "Breathe normally".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Elbows under shoulders".to_string(),
+                "Squeeze glutes and quads".to_string(),
+                "Push the floor away".to_string(),
+            ],
+        },
+        // Add more exercises
+        Exercise {
+            id: "burpee".to_string(),
+            name: "Burpee".to_string(),
+            description: "Full body explosive exercise".to_string(),
+            exercise_type: ExerciseType::Cardio,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 8,
+            primary_muscles: vec![MuscleGroup::Legs, MuscleGroup::Core],
+            secondary_muscles: vec![MuscleGroup::Chest, MuscleGroup::Arms],
+            instructions: vec![
+                "Start standing".to_string(),
+                "Drop to squat, hands on floor".to_string(),
+                "Jump feet back to plank".to_string(),
+                "Do a push-up".to_string(),
+                "Jump feet forward".to_string(),
+                "Explosive jump up".to_string(),
+            ],
+            safety_tips: vec![
+                "Land softly".to_string(),
+                "Keep core tight throughout".to_string(),
+                "Start slowly and build intensity".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Land softly with bent knees".to_string(),
+                "Keep hips in line during the plank".to_string(),
+                "Jump straight up, arms overhead".to_string(),
+            ],
+        },
+        Exercise {
+            id: "deadlift".to_string(),
+            name: "Deadlift".to_string(),
+            description: "Compound pulling exercise".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::Barbells],
+            difficulty_level: 7,
+            primary_muscles: vec![MuscleGroup::Back, MuscleGroup::Legs],
+            secondary_muscles: vec![MuscleGroup::Core, MuscleGroup::Arms],
+            instructions: vec![
+                "Stand with feet hip-width apart".to_string(),
+                "Grip barbell with hands outside legs".to_string(),
+                "Keep back straight, chest up".to_string(),
+                "Drive through heels to lift".to_string(),
+                "Lower with control".to_string(),
+            ],
+            safety_tips: vec![
+                "Never round your back".to_string(),
+                "Start with light weight".to_string(),
+                "Keep bar close to body".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Bar over mid-foot".to_string(),
+                "Chest up, lats tight".to_string(),
+                "Push the floor away, then lock out with glutes".to_string(),
+            ],
+        },
+
+        // === REGRESSIONS ===
+        // Easier variants the workout recommender swaps in when a user keeps
+        // skipping the standard movement.
+        Exercise {
+            id: "knee_pushup".to_string(),
+            name: "Knee Push-up".to_string(),
+            description: "Push-up regression performed from the knees".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 2,
+            primary_muscles: vec![MuscleGroup::Chest],
+            secondary_muscles: vec![MuscleGroup::Arms, MuscleGroup::Core],
+            instructions: vec![
+                "Start in plank position with knees on the floor".to_string(),
+                "Lower chest towards the floor".to_string(),
+                "Push back up to starting position".to_string(),
+            ],
+            safety_tips: vec![
+                "Keep a straight line from knees to head".to_string(),
+                "Use a mat to protect your knees".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Hands just outside shoulders".to_string(),
+                "Hips in line with knees and shoulders".to_string(),
+                "Lower under control".to_string(),
+            ],
+        },
+        Exercise {
+            id: "mountain_climber".to_string(),
+            name: "Mountain Climber".to_string(),
+            description: "Low-impact conditioning exercise from a plank".to_string(),
+            exercise_type: ExerciseType::Cardio,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 4,
+            primary_muscles: vec![MuscleGroup::Core, MuscleGroup::Legs],
+            secondary_muscles: vec![MuscleGroup::Shoulders],
+            instructions: vec![
+                "Start in high plank position".to_string(),
+                "Drive one knee towards your chest".to_string(),
+                "Alternate legs at a steady pace".to_string(),
+            ],
+            safety_tips: vec![
+                "Keep hips level".to_string(),
+                "Keep hands under shoulders".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Shoulders stacked over wrists".to_string(),
+                "Drive knees without bouncing hips".to_string(),
+                "Breathe steadily".to_string(),
+            ],
+        },
+        Exercise {
+            id: "glute_bridge".to_string(),
+            name: "Glute Bridge".to_string(),
+            description: "Bodyweight hip hinge for glutes and hamstrings".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::None],
+            difficulty_level: 2,
+            primary_muscles: vec![MuscleGroup::Glutes, MuscleGroup::Legs],
+            secondary_muscles: vec![MuscleGroup::Back, MuscleGroup::Core],
+            instructions: vec![
+                "Lie on your back with knees bent and feet flat".to_string(),
+                "Drive hips up by squeezing glutes".to_string(),
+                "Lower slowly back to the floor".to_string(),
+            ],
+            safety_tips: vec![
+                "Don't over-arch your lower back".to_string(),
+                "Push through your heels".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Ribs down".to_string(),
+                "Drive through heels".to_string(),
+                "Pause and squeeze at the top".to_string(),
+            ],
+        },
+
+        // === EQUIPMENT SUBSTITUTES ===
+        // Alternatives used when a user lacks the equipment for a movement.
+        Exercise {
+            id: "dumbbell_single_leg_rdl".to_string(),
+            name: "Single-Leg Romanian Deadlift (Dumbbell)".to_string(),
+            description: "Unilateral hip hinge for posterior chain".to_string(),
+            exercise_type: ExerciseType::Strength,
+            equipment_needed: vec![Equipment::Dumbbells],
+            difficulty_level: 5,
+            primary_muscles: vec![MuscleGroup::Back, MuscleGroup::Legs],
+            secondary_muscles: vec![MuscleGroup::Glutes, MuscleGroup::Core],
+            instructions: vec![
+                "Hold a dumbbell in each hand and stand on one leg".to_string(),
+                "Hinge at the hips while the free leg extends behind you".to_string(),
+                "Lower the weights along the standing leg".to_string(),
+                "Squeeze glutes to return upright".to_string(),
+            ],
+            safety_tips: vec![
+                "Keep your back flat throughout".to_string(),
+                "Keep hips square to the floor".to_string(),
+            ],
+            demo_url: None,
+            cues: vec![
+                "Soft bend in the standing knee".to_string(),
+                "Reach the free heel back".to_string(),
+                "Keep weights close to the leg".to_string(),
+            ],
+        },
+    ]
+}
@@ -36,6 +36,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(2.50),
         availability_score: 0.95,
+        glycemic_index: None, // No carbohydrate to rate
         taste_profile: TasteProfile {
             sweetness: 0.0,
             saltiness: 0.1,
@@ -74,6 +75,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(6.00),
         availability_score: 0.85,
+        glycemic_index: None, // No carbohydrate to rate
         taste_profile: TasteProfile {
             sweetness: 0.1,
             saltiness: 0.2,
@@ -112,6 +114,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(1.20),
         availability_score: 0.98,
+        glycemic_index: None, // No carbohydrate to rate
         taste_profile: TasteProfile {
             sweetness: 0.0,
             saltiness: 0.2,
@@ -152,6 +155,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(0.30),
         availability_score: 0.95,
+        glycemic_index: Some(68.0),
         taste_profile: TasteProfile {
             sweetness: 0.1,
             saltiness: 0.0,
@@ -162,6 +166,45 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         },
     });
 
+    // White Rice
+    foods.insert("white_rice".to_string(), Food {
+        id: "white_rice".to_string(),
+        name: "White Rice (cooked)".to_string(),
+        category: FoodCategory::Grains,
+        nutrition_per_100g: NutritionFacts {
+            calories: 130.0,
+            protein_g: 2.7,
+            carbs_g: 28.2,
+            fat_g: 0.3,
+            fiber_g: 0.4,
+            sugar_g: 0.1,
+            sodium_mg: 1.0,
+            potassium_mg: 35.0,
+            calcium_mg: 10.0,
+            iron_mg: 0.2,
+            vitamin_c_mg: 0.0,
+            vitamin_d_iu: 0.0,
+            vitamin_b12_mcg: 0.0,
+            folate_mcg: 3.0,
+            omega3_g: 0.0,
+            omega6_g: 0.1,
+        },
+        allergens: vec![],
+        dietary_flags: vec![DietaryFlag::GlutenFree, DietaryFlag::Vegan, DietaryFlag::Vegetarian],
+        seasonality: None,
+        cost_per_100g: Some(0.20),
+        availability_score: 0.98,
+        glycemic_index: Some(73.0),
+        taste_profile: TasteProfile {
+            sweetness: 0.1,
+            saltiness: 0.0,
+            sourness: 0.0,
+            bitterness: 0.0,
+            umami: 0.1,
+            spiciness: 0.0,
+        },
+    });
+
     // Oats
     foods.insert("oats".to_string(), Food {
         id: "oats".to_string(),
@@ -190,6 +233,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(0.25),
         availability_score: 0.98,
+        glycemic_index: Some(55.0),
         taste_profile: TasteProfile {
             sweetness: 0.1,
             saltiness: 0.0,
@@ -241,6 +285,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         }),
         cost_per_100g: Some(0.80),
         availability_score: 0.90,
+        glycemic_index: Some(15.0),
         taste_profile: TasteProfile {
             sweetness: 0.2,
             saltiness: 0.0,
@@ -290,6 +335,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         }),
         cost_per_100g: Some(1.20),
         availability_score: 0.85,
+        glycemic_index: Some(15.0),
         taste_profile: TasteProfile {
             sweetness: 0.1,
             saltiness: 0.1,
@@ -330,6 +376,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None, // Available year-round (imported)
         cost_per_100g: Some(0.40),
         availability_score: 0.98,
+        glycemic_index: Some(51.0),
         taste_profile: TasteProfile {
             sweetness: 0.8,
             saltiness: 0.0,
@@ -383,6 +430,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         }),
         cost_per_100g: Some(3.00),
         availability_score: 0.70,
+        glycemic_index: Some(53.0),
         taste_profile: TasteProfile {
             sweetness: 0.7,
             saltiness: 0.0,
@@ -423,6 +471,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(1.50),
         availability_score: 0.95,
+        glycemic_index: Some(11.0),
         taste_profile: TasteProfile {
             sweetness: 0.2,
             saltiness: 0.1,
@@ -433,6 +482,50 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         },
     });
 
+    // Coconut Yogurt - dairy-free swap for yogurt
+    foods.insert("coconut_yogurt".to_string(), Food {
+        id: "coconut_yogurt".to_string(),
+        name: "Plain Coconut Yogurt".to_string(),
+        category: FoodCategory::Dairy,
+        nutrition_per_100g: NutritionFacts {
+            calories: 120.0,
+            protein_g: 0.8,
+            carbs_g: 7.0,
+            fat_g: 10.0,
+            fiber_g: 0.5,
+            sugar_g: 4.0,
+            sodium_mg: 20.0,
+            potassium_mg: 60.0,
+            calcium_mg: 120.0,
+            iron_mg: 0.3,
+            vitamin_c_mg: 0.0,
+            vitamin_d_iu: 80.0,
+            vitamin_b12_mcg: 0.4,
+            folate_mcg: 0.0,
+            omega3_g: 0.0,
+            omega6_g: 0.1,
+        },
+        allergens: vec![],
+        dietary_flags: vec![
+            DietaryFlag::Vegan,
+            DietaryFlag::Vegetarian,
+            DietaryFlag::DairyFree,
+            DietaryFlag::GlutenFree,
+        ],
+        seasonality: None,
+        cost_per_100g: Some(2.20),
+        availability_score: 0.7,
+        glycemic_index: Some(35.0),
+        taste_profile: TasteProfile {
+            sweetness: 0.3,
+            saltiness: 0.0,
+            sourness: 0.4,
+            bitterness: 0.0,
+            umami: 0.0,
+            spiciness: 0.0,
+        },
+    });
+
     // === NUTS ===
 
     // Almonds
@@ -463,6 +556,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(8.00),
         availability_score: 0.95,
+        glycemic_index: Some(15.0),
         taste_profile: TasteProfile {
             sweetness: 0.3,
             saltiness: 0.0,
@@ -503,6 +597,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(0.40),
         availability_score: 0.95,
+        glycemic_index: Some(30.0),
         taste_profile: TasteProfile {
             sweetness: 0.1,
             saltiness: 0.0,
@@ -543,6 +638,7 @@ pub fn create_sample_foods() -> HashMap<String, Food> {
         seasonality: None,
         cost_per_100g: Some(12.00),
         availability_score: 0.95,
+        glycemic_index: None, // No carbohydrate to rate
         taste_profile: TasteProfile {
             sweetness: 0.0,
             saltiness: 0.0,
@@ -1,8 +1,10 @@
 // src/sample_data/mod.rs - Sample data management module
 
+pub mod exercises;
 pub mod foods;
 pub mod recipes;
 
+pub use exercises::create_sample_exercises;
 pub use foods::{create_sample_foods, get_sample_food_count};
 pub use recipes::{create_sample_recipes, get_sample_recipe_count};
 
@@ -0,0 +1,157 @@
+// src/shutdown.rs - Graceful server shutdown with in-flight request draining
+
+use std::future::{Future, IntoFuture};
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::Router;
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Resolves when the process receives SIGINT (Ctrl+C) or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("🛑 Received Ctrl+C"),
+        _ = terminate => info!("🛑 Received SIGTERM"),
+    }
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections and
+/// wait up to `grace_period` for in-flight requests to finish. At the deadline
+/// this returns without waiting further; requests still running are cancelled
+/// when the runtime shuts down after `main` returns.
+pub async fn serve_with_graceful_shutdown<F>(
+    listener: TcpListener,
+    app: Router,
+    signal: F,
+    grace_period: Duration,
+) -> Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (signalled_tx, signalled_rx) = tokio::sync::oneshot::channel();
+
+    let server = axum::serve(listener, app).with_graceful_shutdown(async move {
+        signal.await;
+        info!("🛑 Shutdown initiated, no longer accepting new connections");
+        let _ = signalled_tx.send(());
+    });
+    let mut server = tokio::spawn(server.into_future());
+
+    tokio::select! {
+        result = &mut server => {
+            result??;
+            return Ok(());
+        }
+        _ = signalled_rx => {}
+    }
+
+    info!("⏳ Waiting up to {}s for in-flight requests to finish", grace_period.as_secs());
+    match tokio::time::timeout(grace_period, &mut server).await {
+        Ok(result) => {
+            result??;
+            info!("✅ All in-flight requests finished, server stopped");
+        }
+        Err(_) => {
+            warn!("⚠️  Grace period elapsed, abandoning remaining requests");
+            server.abort();
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::sync::Arc;
+    use tokio::sync::{oneshot, Notify};
+
+    /// Returns the URL, the shutdown trigger, a notification for when the
+    /// handler has started, and the server task
+    async fn start_slow_server(
+        handler_delay: Duration,
+        grace_period: Duration,
+    ) -> (String, oneshot::Sender<()>, Arc<Notify>, tokio::task::JoinHandle<Result<()>>) {
+        let started = Arc::new(Notify::new());
+        let handler_started = started.clone();
+        let app = Router::new().route(
+            "/slow",
+            get(move || async move {
+                handler_started.notify_one();
+                tokio::time::sleep(handler_delay).await;
+                "done"
+            }),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+        let (trigger, triggered) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_with_graceful_shutdown(
+            listener,
+            app,
+            async move {
+                let _ = triggered.await;
+            },
+            grace_period,
+        ));
+
+        (url, trigger, started, server)
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_request_completes_during_shutdown() {
+        let (url, trigger, started, server) =
+            start_slow_server(Duration::from_millis(300), Duration::from_secs(5)).await;
+
+        let request = tokio::spawn(async move { reqwest::get(&url).await?.text().await });
+        // Shut down only once the request is in flight, not merely sent
+        started.notified().await;
+        trigger.send(()).unwrap();
+
+        assert_eq!(request.await.unwrap().unwrap(), "done");
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_returns_at_grace_deadline() {
+        let (url, trigger, started, server) =
+            start_slow_server(Duration::from_secs(10), Duration::from_millis(200)).await;
+
+        let request = tokio::spawn(async move { reqwest::get(&url).await?.text().await });
+        started.notified().await;
+        trigger.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(2), server)
+            .await
+            .expect("server should stop at the grace deadline")
+            .unwrap()
+            .unwrap();
+        assert!(!request.is_finished());
+        request.abort();
+    }
+}
@@ -0,0 +1,190 @@
+// src/webhooks.rs - Signed delivery of events to integrators' webhook URLs
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use ring::hmac;
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::config::WebhookConfig;
+use crate::database::DatabaseManager;
+use crate::events::DomainEvent;
+use crate::models::webhook::{DeliveryStatus, WebhookDelivery, WebhookEvent};
+
+/// HMAC-SHA256 of the raw body under the subscription's secret, as
+/// `sha256=<hex>`
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Deliveries sent per pass; the rest wait for the next one
+const BATCH_SIZE: i64 = 50;
+/// Longest wait between retries, however many attempts have failed
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Timestamps in the format SQLite's CURRENT_TIMESTAMP uses, so they sort
+/// and compare as text
+pub fn db_timestamp(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let tag = hmac::sign(&key, body);
+    let hex: String = tag.as_ref().iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// How receivers check a delivery: recompute the signature over the raw
+/// body and compare in constant time. The server only signs, so this lives
+/// with the tests as the reference receivers can follow.
+#[cfg(test)]
+pub fn verify(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Some(tag) = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::verify(&key, body, &tag).is_ok()
+}
+
+/// Wait before retrying after `attempts` failures: the initial backoff,
+/// doubled for every failure after the first
+pub fn backoff(config: &WebhookConfig, attempts: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempts.saturating_sub(1));
+    Duration::from_secs(config.initial_backoff_seconds.saturating_mul(factor)).min(MAX_BACKOFF)
+}
+
+/// The webhook event and signed body sent for a domain event, if
+/// subscribers can be notified of it
+pub fn payload(event: &DomainEvent) -> Option<(WebhookEvent, String)> {
+    let (webhook_event, data) = match event {
+        DomainEvent::WorkoutLogged { workout, .. } => (WebhookEvent::WorkoutLogged, serde_json::json!(workout)),
+        DomainEvent::GoalCompleted { goal, .. } => (WebhookEvent::GoalCompleted, serde_json::json!(goal)),
+        DomainEvent::PlanOptimized { plan, .. } => (WebhookEvent::PlanGenerated, plan.clone()),
+        DomainEvent::UserRegistered { .. } => return None,
+    };
+    let payload = serde_json::json!({
+        "event": webhook_event,
+        "occurred_at": event.occurred_at().to_rfc3339(),
+        "user_id": event.user_id(),
+        "data": data,
+    });
+    Some((webhook_event, payload.to_string()))
+}
+
+/// Sends queued deliveries. New events wake it straight away; retries are
+/// picked up on the poll interval once they fall due.
+pub struct WebhookDispatcher {
+    db: Arc<DatabaseManager>,
+    client: Client,
+    config: WebhookConfig,
+    wake: Arc<Notify>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(db: Arc<DatabaseManager>, config: &WebhookConfig, wake: Arc<Notify>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        Self { db, client, config: config.clone(), wake }
+    }
+
+    /// Deliver until the task is dropped
+    pub async fn run(self) {
+        let poll_interval = Duration::from_secs(self.config.poll_interval_seconds);
+        loop {
+            if let Err(e) = self.deliver_due().await {
+                warn!("Webhook delivery pass failed: {}", e);
+            }
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+    }
+
+    /// Attempt every delivery that's due. Returns how many were delivered.
+    pub async fn deliver_due(&self) -> Result<usize> {
+        let now = chrono::Utc::now();
+        let due = self.db.due_webhook_deliveries(&db_timestamp(now), BATCH_SIZE).await?;
+
+        let mut delivered = 0;
+        for (mut delivery, url, secret) in due {
+            delivery.attempts += 1;
+            match self.send(&delivery, &url, &secret).await {
+                Ok(()) => {
+                    delivery.status = DeliveryStatus::Delivered;
+                    delivery.next_attempt_at = None;
+                    delivery.last_error = None;
+                    delivered += 1;
+                }
+                Err(e) if delivery.attempts >= self.config.max_attempts => {
+                    warn!("Dead-lettering webhook delivery {} after {} attempts: {}", delivery.id, delivery.attempts, e);
+                    delivery.status = DeliveryStatus::DeadLettered;
+                    delivery.next_attempt_at = None;
+                    delivery.last_error = Some(e.to_string());
+                }
+                Err(e) => {
+                    let retry_at = now + chrono::Duration::from_std(backoff(&self.config, delivery.attempts))?;
+                    info!("Webhook delivery {} failed, retrying at {}: {}", delivery.id, retry_at, e);
+                    delivery.next_attempt_at = Some(db_timestamp(retry_at));
+                    delivery.last_error = Some(e.to_string());
+                }
+            }
+            self.db.record_webhook_attempt(&delivery).await?;
+        }
+        Ok(delivered)
+    }
+
+    async fn send(&self, delivery: &WebhookDelivery, url: &str, secret: &str) -> Result<()> {
+        let response = self.client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(secret, delivery.payload.as_bytes()))
+            .header(EVENT_HEADER, delivery.event.as_str())
+            .header(DELIVERY_HEADER, &delivery.id)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|e| anyhow!("request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(anyhow!("receiver answered {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signatures_verify_and_backoff_doubles() {
+        let signature = sign("whsec_test", b"{\"event\":\"workout_logged\"}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert!(verify("whsec_test", b"{\"event\":\"workout_logged\"}", &signature));
+        assert!(!verify("whsec_test", b"{\"event\":\"goal_completed\"}", &signature));
+        assert!(!verify("other_secret", b"{\"event\":\"workout_logged\"}", &signature));
+        assert!(!verify("whsec_test", b"{}", "sha256=zz"));
+
+        let config = WebhookConfig { initial_backoff_seconds: 30, ..WebhookConfig::default() };
+        assert_eq!(backoff(&config, 1), Duration::from_secs(30));
+        assert_eq!(backoff(&config, 2), Duration::from_secs(60));
+        assert_eq!(backoff(&config, 4), Duration::from_secs(240));
+        assert_eq!(backoff(&config, 40), MAX_BACKOFF);
+    }
+}
@@ -1,54 +1,257 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use axum::{
-    extract::{State, WebSocketUpgrade},
+    extract::{Query, State, WebSocketUpgrade},
     response::Response,
 };
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, Mutex};
 use tracing::{info, warn};
+use utoipa::IntoParams;
 use anyhow::Result;
 
 use crate::AppState;
+use crate::models::optimization::OptimizationProgress;
 
+/// Events pushed to every client connected to the same live session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RealtimeEvent {
+    RepCounted { session_id: String, exercise: String, rep_count: u32 },
+    FormCue { session_id: String, message: String },
+    SessionComplete { session_id: String, total_reps: u32 },
+    /// A meal plan optimization started with this session's id is running
+    OptimizationProgress {
+        session_id: String,
+        #[serde(flatten)]
+        progress: OptimizationProgress,
+    },
+}
+
+/// Buffered events per session before slow clients start missing some
+const SESSION_EVENT_CAPACITY: usize = 64;
+
+/// Counts reps from the stream of per-frame exercise classifications. The
+/// analyzer labels the bottom of a rep with the exercise (e.g. "squat") and
+/// the top as "standing", so a rep is a return to standing after a working
+/// frame.
+#[derive(Debug, Default)]
+struct RepCounter {
+    rep_count: u32,
+    working_exercise: Option<String>,
+    last_cue: Option<String>,
+}
+
+impl RepCounter {
+    /// Feed one frame's analysis, returning any events it triggers
+    fn observe(&mut self, session_id: &str, analysis: &serde_json::Value) -> Vec<RealtimeEvent> {
+        let mut events = Vec::new();
+
+        match analysis["exercise"].as_str() {
+            Some("standing") => {
+                if let Some(exercise) = self.working_exercise.take() {
+                    self.rep_count += 1;
+                    events.push(RealtimeEvent::RepCounted {
+                        session_id: session_id.to_string(),
+                        exercise,
+                        rep_count: self.rep_count,
+                    });
+                }
+            }
+            Some("unknown") | Some("error") | None => {}
+            Some(exercise) => self.working_exercise = Some(exercise.to_string()),
+        }
+
+        // Only announce a warning when it changes, not on every frame
+        let cue = analysis["warnings"].get(0).and_then(|w| w.as_str()).map(str::to_string);
+        if cue != self.last_cue {
+            if let Some(message) = &cue {
+                events.push(RealtimeEvent::FormCue {
+                    session_id: session_id.to_string(),
+                    message: message.clone(),
+                });
+            }
+            self.last_cue = cue;
+        }
+
+        events
+    }
+}
+
+/// A live workout that one or more clients (e.g. the athlete's phone and a
+/// trainer's screen) are connected to
+pub struct RealtimeSession {
+    id: String,
+    events: broadcast::Sender<RealtimeEvent>,
+    reps: Mutex<RepCounter>,
+}
+
+impl RealtimeSession {
+    fn new(id: String) -> Self {
+        let (events, _) = broadcast::channel(SESSION_EVENT_CAPACITY);
+        Self { id, events, reps: Mutex::new(RepCounter::default()) }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RealtimeEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, event: RealtimeEvent) {
+        // No subscribers just means every client has gone
+        let _ = self.events.send(event);
+    }
+
+    async fn observe_analysis(&self, analysis: &serde_json::Value) {
+        let events = self.reps.lock().await.observe(&self.id, analysis);
+        for event in events {
+            self.publish(event);
+        }
+    }
+
+    async fn complete(&self) {
+        let total_reps = self.reps.lock().await.rep_count;
+        self.publish(RealtimeEvent::SessionComplete { session_id: self.id.clone(), total_reps });
+    }
+}
+
+/// Live sessions by id, so clients joining the same id share one event stream
+#[derive(Default)]
+pub struct RealtimeSessions {
+    sessions: Mutex<HashMap<String, Arc<RealtimeSession>>>,
+}
+
+impl RealtimeSessions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn join(&self, session_id: &str) -> Arc<RealtimeSession> {
+        self.sessions.lock().await
+            .entry(session_id.to_string())
+            .or_insert_with(|| Arc::new(RealtimeSession::new(session_id.to_string())))
+            .clone()
+    }
+
+    /// Publish an optimization's progress to the session's clients until the
+    /// optimization drops its sender
+    pub async fn forward_optimization_progress(
+        self: Arc<Self>,
+        session_id: String,
+        mut progress: tokio::sync::mpsc::UnboundedReceiver<OptimizationProgress>,
+    ) {
+        let session = self.join(&session_id).await;
+        while let Some(progress) = progress.recv().await {
+            session.publish(RealtimeEvent::OptimizationProgress { session_id: session_id.clone(), progress });
+        }
+        drop(session);
+        self.leave(&session_id).await;
+    }
+
+    /// Forget the session once its last client has left
+    pub async fn leave(&self, session_id: &str) {
+        let mut sessions = self.sessions.lock().await;
+        if sessions.get(session_id).is_some_and(|s| s.events.receiver_count() == 0) {
+            sessions.remove(session_id);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RealtimeParams {
+    /// Clients passing the same id receive each other's rep and cue events
+    pub session_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/ai/realtime",
+    tag = "ai",
+    summary = "Open a realtime form-analysis WebSocket",
+    params(RealtimeParams),
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol"),
+    ),
+)]
 pub async fn websocket_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
+    Query(params): Query<RealtimeParams>,
 ) -> Response {
     info!("🔗 WebSocket connection established for real-time analysis");
-    ws.on_upgrade(|socket| handle_socket(socket, state))
+    let session_id = params.session_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    ws.on_upgrade(|socket| handle_socket(socket, state, session_id))
 }
 
-async fn handle_socket(socket: axum::extract::ws::WebSocket, state: Arc<AppState>) {
-    use axum::extract::ws::{Message, WebSocket};
+async fn handle_socket(socket: axum::extract::ws::WebSocket, state: Arc<AppState>, session_id: String) {
+    use axum::extract::ws::Message;
     use futures_util::{SinkExt, StreamExt};
     
-    let (mut sender, mut receiver) = socket.split();
+    let (sender, mut receiver) = socket.split();
+    let sender = Arc::new(Mutex::new(sender));
+    let session = state.realtime_sessions.join(&session_id).await;
+    let mut events = session.subscribe();
     
-    info!("🎥 Real-time analysis session started");
+    info!("🎥 Real-time analysis session {} started", session_id);
     
     let welcome = serde_json::json!({
         "type": "welcome",
         "message": "Real-time analysis ready",
+        "session_id": session_id,
         "target_latency_ms": 50
     });
     
-    if sender.send(Message::Text(welcome.to_string())).await.is_err() {
+    if sender.lock().await.send(Message::Text(welcome.to_string())).await.is_err() {
+        drop(events);
+        state.realtime_sessions.leave(&session_id).await;
         return;
     }
+
+    // Forward session events to this client alongside its own analysis results
+    let event_sender = sender.clone();
+    let forward_events = tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if event_sender.lock().await.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Realtime client fell behind and missed {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
     
     while let Some(msg) = receiver.next().await {
         match msg {
             Ok(Message::Text(text)) => {
-                if let Err(e) = process_frame_message(&text, &state, &mut sender).await {
-                    warn!("Frame processing error: {}", e);
-                    let error_msg = serde_json::json!({
-                        "type": "error",
-                        "message": format!("Processing failed: {}", e)
-                    });
-                    let _ = sender.send(Message::Text(error_msg.to_string())).await;
+                if is_end_session(&text) {
+                    session.complete().await;
+                    continue;
+                }
+
+                let result = process_frame_message(&text, &state, &mut *sender.lock().await).await;
+                match result {
+                    Ok(analysis) => session.observe_analysis(&analysis).await,
+                    Err(e) => {
+                        warn!("Frame processing error: {}", e);
+                        let error_msg = serde_json::json!({
+                            "type": "error",
+                            "message": format!("Processing failed: {}", e)
+                        });
+                        let _ = sender.lock().await.send(Message::Text(error_msg.to_string())).await;
+                    }
                 }
             }
             Ok(Message::Binary(data)) => {
-                if let Err(e) = process_binary_frame(&data, &state, &mut sender).await {
-                    warn!("Binary frame processing error: {}", e);
+                let result = process_binary_frame(&data, &state, &mut *sender.lock().await).await;
+                match result {
+                    Ok(analysis) => session.observe_analysis(&analysis).await,
+                    Err(e) => warn!("Binary frame processing error: {}", e),
                 }
             }
             Ok(Message::Close(_)) => {
@@ -56,7 +259,7 @@ async fn handle_socket(socket: axum::extract::ws::WebSocket, state: Arc<AppState
                 break;
             }
             Ok(Message::Ping(data)) => {
-                let _ = sender.send(Message::Pong(data)).await;
+                let _ = sender.lock().await.send(Message::Pong(data)).await;
             }
             Ok(Message::Pong(_)) => {
                 // Handle pong
@@ -67,15 +270,25 @@ async fn handle_socket(socket: axum::extract::ws::WebSocket, state: Arc<AppState
             }
         }
     }
+
+    forward_events.abort();
+    let _ = forward_events.await;
+    state.realtime_sessions.leave(&session_id).await;
     
-    info!("🏁 Real-time analysis session ended");
+    info!("🏁 Real-time analysis session {} ended", session_id);
+}
+
+fn is_end_session(text: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(text)
+        .map(|message| message["type"] == "end_session")
+        .unwrap_or(false)
 }
 
 async fn process_frame_message(
     text: &str,
     state: &Arc<AppState>,
     sender: &mut futures_util::stream::SplitSink<axum::extract::ws::WebSocket, axum::extract::ws::Message>,
-) -> Result<()> {
+) -> Result<serde_json::Value> {
     use axum::extract::ws::Message;
     use futures_util::SinkExt;
     
@@ -108,14 +321,14 @@ async fn process_frame_message(
         info!("⚡ Analysis completed in {}ms", total_latency);
     }
     
-    Ok(())
+    Ok(response)
 }
 
 async fn process_binary_frame(
     data: &[u8],
     state: &Arc<AppState>,
     sender: &mut futures_util::stream::SplitSink<axum::extract::ws::WebSocket, axum::extract::ws::Message>,
-) -> Result<()> {
+) -> Result<serde_json::Value> {
     use axum::extract::ws::Message;
     use futures_util::SinkExt;
     
@@ -133,5 +346,65 @@ async fn process_binary_frame(
     sender.send(Message::Text(response.to_string())).await
         .map_err(|e| anyhow::anyhow!("Failed to send response: {}", e))?;
     
-    Ok(())
-}
\ No newline at end of file
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rep_event_reaches_every_client_in_session() {
+        let sessions = RealtimeSessions::new();
+        let athlete = sessions.join("live-1").await;
+        let trainer = sessions.join("live-1").await;
+        let mut athlete_events = athlete.subscribe();
+        let mut trainer_events = trainer.subscribe();
+
+        let frame = |exercise: &str| serde_json::json!({ "exercise": exercise, "warnings": [] });
+        athlete.observe_analysis(&frame("standing")).await;
+        athlete.observe_analysis(&frame("squat")).await;
+        athlete.observe_analysis(&frame("standing")).await;
+
+        let expected = RealtimeEvent::RepCounted {
+            session_id: "live-1".to_string(),
+            exercise: "squat".to_string(),
+            rep_count: 1,
+        };
+        assert_eq!(athlete_events.recv().await.unwrap(), expected);
+        let received = trainer_events.recv().await.unwrap();
+        assert_eq!(received, expected);
+
+        // What goes over the socket parses back into the same event
+        let wire = serde_json::to_string(&received).unwrap();
+        let wire_json: serde_json::Value = serde_json::from_str(&wire).unwrap();
+        assert_eq!(wire_json["type"], "rep_counted");
+        assert_eq!(serde_json::from_str::<RealtimeEvent>(&wire).unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_optimization_progress_is_published_to_session() {
+        let sessions = Arc::new(RealtimeSessions::new());
+        let mut events = sessions.join("plan-1").await.subscribe();
+
+        let (progress, updates) = tokio::sync::mpsc::unbounded_channel();
+        let forwarder = tokio::spawn(sessions.clone().forward_optimization_progress("plan-1".to_string(), updates));
+        let report = OptimizationProgress {
+            generation: 3,
+            max_generations: 10,
+            best_fitness: 0.8,
+            elapsed_ms: 30.0,
+            estimated_remaining_ms: 70.0,
+        };
+        progress.send(report.clone()).unwrap();
+        drop(progress);
+        forwarder.await.unwrap();
+
+        let event = events.recv().await.unwrap();
+        assert_eq!(event, RealtimeEvent::OptimizationProgress { session_id: "plan-1".to_string(), progress: report });
+        let wire: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(wire["type"], "optimization_progress");
+        assert_eq!(wire["generation"], 3);
+        assert_eq!(wire["estimated_remaining_ms"], 70.0);
+    }
+}